@@ -1,8 +1,11 @@
 #![no_std]
-use shared_utils::RateLimiter;
+use commitlabs_interfaces::core::CommitmentCoreClient;
+pub use commitlabs_interfaces::core::{Commitment, CommitmentRules, CommitmentStatus};
+use commitlabs_interfaces::nft::CommitmentNftClient;
+use shared_utils::{AccessControl, PercentMath, RateLimiter, AUDITOR_ROLE};
 use soroban_sdk::{
-    contract, contracterror, contractimpl, contracttype, symbol_short, Address, Env, IntoVal, Map,
-    String, Symbol, TryIntoVal, Val, Vec,
+    contract, contracterror, contractimpl, contracttype, symbol_short, vec, Address, BytesN, Env,
+    IntoVal, Map, String, Symbol, Val, Vec,
 };
 
 // ============================================================================
@@ -30,6 +33,26 @@ pub enum AttestationError {
     CommitmentNotFound = 7,
     /// Storage operation failed
     StorageError = 8,
+    /// Commitment has not reached settled status
+    NotSettled = 9,
+    /// Final compliance score or duration does not meet the badge criteria
+    BadgeCriteriaNotMet = 10,
+    /// A badge was already issued for this commitment
+    BadgeAlreadyIssued = 11,
+    /// Badge minting has not been configured
+    BadgeNotConfigured = 12,
+    /// Invalid badge configuration (score must be 0-100)
+    InvalidBadgeConfig = 13,
+    /// Audit period ends before it starts
+    InvalidAuditPeriod = 14,
+    /// The same report hash was already recorded for this scope and period
+    DuplicateAudit = 15,
+    /// Verifier is restricted to other attestation types
+    TypeNotPermittedForVerifier = 16,
+    /// Commitment attestation data has been archived
+    CommitmentArchived = 17,
+    /// No admin transfer has been proposed
+    NoPendingAdmin = 18,
 }
 
 // ============================================================================
@@ -45,6 +68,8 @@ pub enum DataKey {
     CoreContract,
     /// Verifier whitelist (Address -> bool)
     Verifier(Address),
+    /// Attestation types a verifier may submit (Address -> Vec<String>, empty = all)
+    VerifierTypes(Address),
     /// Attestations for a commitment (commitment_id -> Vec<Attestation>)
     Attestations(String),
     /// Health metrics for a commitment (commitment_id -> HealthMetrics)
@@ -61,43 +86,65 @@ pub enum DataKey {
     TotalFees,
     /// Per-verifier analytics: attestation count by verifier
     VerifierAttestationCount(Address),
+    /// Compliance badge settings (BadgeConfig)
+    BadgeConfig,
+    /// Badge issued for a commitment (commitment_id -> badge_id)
+    BadgeIssued(String),
+    /// Number of audit reports recorded for a scope (AuditScope -> u32)
+    AuditCount(AuditScope),
+    /// Audit report by scope and index (AuditRecord)
+    Audit(AuditScope, u32),
+    /// Report hash already recorded for a scope and period (-> bool)
+    AuditHash(AuditScope, BytesN<32>, u64, u64),
+    /// Final report frozen when a commitment's data was archived (SettlementReport)
+    FinalReport(String),
+    /// Daily summaries left after archiving (commitment_id -> ArchivedAttestations)
+    Archive(String),
+    /// Dispatcher routing tag added to a commitment's event topics (-> Symbol)
+    AttestationRoute(String),
+    /// Address proposed as the next admin (two-step transfer)
+    PendingAdmin,
 }
 
+/// What an external audit report covers
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct Attestation {
-    pub commitment_id: String,
-    pub timestamp: u64,
-    pub attestation_type: String, // "health_check", "violation", "fee_generation", "drawdown"
-    pub data: Map<String, String>, // Flexible data structure
-    pub is_compliant: bool,
-    pub verified_by: Address,
+pub enum AuditScope {
+    Commitment(String),
+    Protocol,
 }
 
-// Import Commitment types from commitment_core (define locally for cross-contract calls)
+/// An external audit report anchored by its SHA-256 document hash
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct CommitmentRules {
-    pub duration_days: u32,
-    pub max_loss_percent: u32,
-    pub commitment_type: String,
-    pub early_exit_penalty: u32,
-    pub min_fee_threshold: i128,
+pub struct AuditRecord {
+    pub scope: AuditScope,
+    pub sha256: BytesN<32>,
+    pub uri: String,
+    pub period_start: u64,
+    pub period_end: u64,
+    pub recorded_by: Address,
+    pub recorded_at: u64,
 }
 
+/// Criteria and NFT contract for compliance badges
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct Commitment {
+pub struct BadgeConfig {
+    pub nft_contract: Address,
+    pub min_compliance_score: u32,
+    pub min_duration_days: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Attestation {
     pub commitment_id: String,
-    pub owner: Address,
-    pub nft_token_id: u32,
-    pub rules: CommitmentRules,
-    pub amount: i128,
-    pub asset_address: Address,
-    pub created_at: u64,
-    pub expires_at: u64,
-    pub current_value: i128,
-    pub status: String,
+    pub timestamp: u64,
+    pub attestation_type: String, // "health_check", "violation", "fee_generation", "drawdown"
+    pub data: Map<String, String>, // Flexible data structure
+    pub is_compliant: bool,
+    pub verified_by: Address,
 }
 
 #[contracttype]
@@ -113,6 +160,115 @@ pub struct HealthMetrics {
     pub compliance_score: u32, // 0-100
 }
 
+/// Attestations recorded on one UTC day
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DailySummary {
+    pub day: u64, // timestamp / 86400
+    pub attestation_count: u32,
+    pub violation_count: u32,
+    pub fees_generated: i128,
+}
+
+/// What remains of a commitment's attestation data after archiving
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ArchivedAttestations {
+    pub archived_at: u64,
+    pub archived_by: Address,
+    pub attestation_count: u64,
+    pub daily_summaries: Vec<DailySummary>,
+}
+
+/// Attestation data for a commitment: raw attestations, or daily summaries
+/// once archived
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AttestationHistory {
+    pub archived: bool,
+    pub attestations: Vec<Attestation>,
+    pub daily_summaries: Vec<DailySummary>,
+}
+
+/// Ledgers per day at ~5s close time
+const LEDGERS_PER_DAY: u32 = 17_280;
+/// TTL kept on an active commitment's attestation data
+const ACTIVE_DATA_TTL: u32 = 120 * LEDGERS_PER_DAY;
+/// Remaining TTL below which active data is extended
+const ACTIVE_DATA_TTL_THRESHOLD: u32 = 30 * LEDGERS_PER_DAY;
+/// Grace period left on a terminal commitment's raw data before it expires
+const TERMINAL_GRACE_TTL: u32 = 30 * LEDGERS_PER_DAY;
+/// TTL given to archived summaries and the frozen final report
+const ARCHIVE_TTL: u32 = 365 * LEDGERS_PER_DAY;
+
+/// Per-violation penalty when no attestation tally is stored
+const SCORE_VIOLATION_PENALTY: u32 = 20;
+/// Penalty per whole percent of drawdown beyond `max_loss_percent`
+const SCORE_DRAWDOWN_PER_PERCENT: u32 = 1;
+/// Maximum bonus for fees generated against `min_fee_threshold`
+const SCORE_FEE_BONUS_CAP: u32 = 100;
+/// Bonus while the commitment is still running its term
+const SCORE_FRESHNESS_BONUS: u32 = 10;
+
+/// Weights used to compute a compliance score
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ScoreWeights {
+    pub violation_penalty: u32,
+    pub drawdown_per_percent: u32,
+    pub fee_bonus_cap: u32,
+    pub freshness_bonus: u32,
+}
+
+/// Components of a compliance score.
+///
+/// `score = clamp(100 - violation_penalty_applied + drawdown_component
+/// + fee_component + freshness_component, 0, 100)`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ScoreBreakdown {
+    pub drawdown_component: i32,  // <= 0, drawdown beyond max_loss_percent
+    pub fee_component: i32,       // >= 0, fees generated vs min_fee_threshold
+    pub freshness_component: i32, // >= 0, commitment still running its term
+    pub violation_penalty_applied: u32,
+    pub weights_used: ScoreWeights,
+    pub score: u32, // 0-100
+}
+
+/// Final report for a commitment that is no longer active
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SettlementReport {
+    pub commitment_id: String,
+    pub owner: Address,
+    pub status: String, // "settled", "violated", "early_exit", "migrating", "migrated"
+    pub initial_value: i128,
+    pub final_value: i128,
+    pub attestation_count: u64,
+    pub score_breakdown: ScoreBreakdown,
+    pub generated_at: u64,
+}
+
+/// Label a core commitment status as it appears in `SettlementReport`.
+fn status_label(status: CommitmentStatus) -> &'static str {
+    match status {
+        CommitmentStatus::Active => "active",
+        CommitmentStatus::Settled => "settled",
+        CommitmentStatus::Violated => "violated",
+        CommitmentStatus::EarlyExit => "early_exit",
+        CommitmentStatus::Migrating => "migrating",
+        CommitmentStatus::Migrated => "migrated",
+        CommitmentStatus::Cancelled => "cancelled",
+        CommitmentStatus::Pending => "pending",
+    }
+}
+
+/// Whether a core commitment is still running: active, or scheduled but
+/// not yet started.
+fn is_open(status: &CommitmentStatus) -> bool {
+    matches!(status, CommitmentStatus::Active | CommitmentStatus::Pending)
+}
+
 #[contract]
 pub struct AttestationEngineContract;
 
@@ -220,6 +376,82 @@ impl AttestationEngineContract {
         Ok(())
     }
 
+    /// Restrict a verifier to a set of attestation types
+    ///
+    /// An empty `types` list lifts the restriction. Attestations already
+    /// recorded by the verifier are not affected.
+    ///
+    /// # Arguments
+    /// * `caller` - Must be admin
+    /// * `verifier` - Verifier to restrict
+    /// * `types` - Attestation types the verifier may submit
+    pub fn set_verifier_types(
+        e: Env,
+        caller: Address,
+        verifier: Address,
+        types: Vec<String>,
+    ) -> Result<(), AttestationError> {
+        caller.require_auth();
+
+        let admin: Address = e
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(AttestationError::NotInitialized)?;
+
+        if caller != admin {
+            return Err(AttestationError::Unauthorized);
+        }
+
+        for att_type in types.iter() {
+            if !Self::is_valid_attestation_type(&e, &att_type) {
+                return Err(AttestationError::InvalidAttestationType);
+            }
+        }
+
+        let key = DataKey::VerifierTypes(verifier.clone());
+        if types.is_empty() {
+            e.storage().instance().remove(&key);
+        } else {
+            e.storage().instance().set(&key, &types);
+        }
+
+        e.events()
+            .publish((Symbol::new(&e, "VerifierTypesSet"),), (verifier, types));
+
+        Ok(())
+    }
+
+    /// Attestation types a verifier may submit (empty = all types)
+    pub fn get_verifier_types(e: Env, verifier: Address) -> Vec<String> {
+        e.storage()
+            .instance()
+            .get(&DataKey::VerifierTypes(verifier))
+            .unwrap_or_else(|| Vec::new(&e))
+    }
+
+    /// Check a verifier's type restriction. The admin is never restricted.
+    fn is_type_permitted_for_verifier(e: &Env, verifier: &Address, att_type: &String) -> bool {
+        let types: Vec<String> = match e
+            .storage()
+            .instance()
+            .get(&DataKey::VerifierTypes(verifier.clone()))
+        {
+            Some(types) => types,
+            None => return true,
+        };
+        if let Some(admin) = e
+            .storage()
+            .instance()
+            .get::<DataKey, Address>(&DataKey::Admin)
+        {
+            if *verifier == admin {
+                return true;
+            }
+        }
+        types.contains(att_type)
+    }
+
     /// Check if an address is an authorized verifier
     fn is_authorized_verifier(e: &Env, address: &Address) -> bool {
         // Admin is always authorized
@@ -253,6 +485,80 @@ impl AttestationEngineContract {
             .ok_or(AttestationError::NotInitialized)
     }
 
+    /// Propose the next admin
+    ///
+    /// The transfer completes only when `new_admin` calls `accept_admin`;
+    /// proposing again replaces the pending address.
+    ///
+    /// # Arguments
+    /// * `caller` - Must be admin
+    /// * `new_admin` - Address proposed as the next admin
+    pub fn propose_admin(
+        e: Env,
+        caller: Address,
+        new_admin: Address,
+    ) -> Result<(), AttestationError> {
+        caller.require_auth();
+        let admin = Self::get_admin(e.clone())?;
+        if caller != admin {
+            return Err(AttestationError::Unauthorized);
+        }
+
+        e.storage()
+            .instance()
+            .set(&DataKey::PendingAdmin, &new_admin);
+        e.events().publish(
+            (Symbol::new(&e, "AdminProposed"), admin),
+            (new_admin, e.ledger().timestamp()),
+        );
+        Ok(())
+    }
+
+    /// Accept a pending admin proposal
+    ///
+    /// # Arguments
+    /// * `caller` - Must be the proposed admin
+    pub fn accept_admin(e: Env, caller: Address) -> Result<(), AttestationError> {
+        caller.require_auth();
+        let pending =
+            Self::get_pending_admin(e.clone()).ok_or(AttestationError::NoPendingAdmin)?;
+        if caller != pending {
+            return Err(AttestationError::Unauthorized);
+        }
+
+        let previous = Self::get_admin(e.clone())?;
+        e.storage().instance().set(&DataKey::Admin, &caller);
+        e.storage().instance().remove(&DataKey::PendingAdmin);
+        e.events().publish(
+            (Symbol::new(&e, "AdminChanged"), previous),
+            (caller, e.ledger().timestamp()),
+        );
+        Ok(())
+    }
+
+    /// Withdraw the pending admin proposal
+    ///
+    /// # Arguments
+    /// * `caller` - Must be admin
+    pub fn cancel_admin_proposal(e: Env, caller: Address) -> Result<(), AttestationError> {
+        caller.require_auth();
+        let admin = Self::get_admin(e.clone())?;
+        if caller != admin {
+            return Err(AttestationError::Unauthorized);
+        }
+        if !e.storage().instance().has(&DataKey::PendingAdmin) {
+            return Err(AttestationError::NoPendingAdmin);
+        }
+
+        e.storage().instance().remove(&DataKey::PendingAdmin);
+        Ok(())
+    }
+
+    /// Get the address proposed as the next admin, if any
+    pub fn get_pending_admin(e: Env) -> Option<Address> {
+        e.storage().instance().get(&DataKey::PendingAdmin)
+    }
+
     /// Get the core contract address
     pub fn get_core_contract(e: Env) -> Result<Address, AttestationError> {
         e.storage()
@@ -314,25 +620,20 @@ impl AttestationEngineContract {
 
     /// Check if commitment exists in core contract
     fn commitment_exists(e: &Env, commitment_id: &String) -> bool {
-        let commitment_core: Address = match e.storage().instance().get(&DataKey::CoreContract) {
-            Some(addr) => addr,
-            None => return false,
-        };
-
-        // Try to get commitment from core contract
-        let mut args = Vec::new(e);
-        args.push_back(commitment_id.clone().into_val(e));
+        Self::fetch_commitment(e, commitment_id).is_some()
+    }
 
-        // Use try_invoke_contract to handle potential failures
-        let result = e.try_invoke_contract::<Val, soroban_sdk::Error>(
-            &commitment_core,
-            &Symbol::new(e, "get_commitment"),
-            args,
-        );
+    /// Client for the configured core contract
+    fn core_client(e: &Env) -> Option<CommitmentCoreClient<'static>> {
+        let commitment_core: Address = e.storage().instance().get(&DataKey::CoreContract)?;
+        Some(CommitmentCoreClient::new(e, &commitment_core))
+    }
 
-        match result {
-            Ok(Ok(_)) => true,
-            _ => false,
+    /// Fetch a commitment from the core contract, if it exists
+    fn fetch_commitment(e: &Env, commitment_id: &String) -> Option<Commitment> {
+        match Self::core_client(e)?.try_get_commitment(commitment_id) {
+            Ok(Ok(commitment)) => Some(commitment),
+            _ => None,
         }
     }
 
@@ -447,9 +748,8 @@ impl AttestationEngineContract {
         }
 
         // OPTIMIZATION: Single pass parsing with early exit on invalid char
-        for i in start_idx..len as usize {
-            let b = buf[i];
-            if b < b'0' || b > b'9' {
+        for &b in buf.iter().take(len as usize).skip(start_idx) {
+            if !b.is_ascii_digit() {
                 return None; // Invalid character - early exit
             }
             result = result.checked_mul(10)?;
@@ -510,15 +810,22 @@ impl AttestationEngineContract {
         RateLimiter::check(&e, &caller, &fn_symbol);
 
         // 4. Validate commitment_id is not empty
-        if commitment_id.len() == 0 {
+        if commitment_id.is_empty() {
             e.storage().instance().remove(&DataKey::ReentrancyGuard);
             return Err(AttestationError::InvalidCommitmentId);
         }
 
         // 5. Validate commitment exists in core contract
-        if !Self::commitment_exists(&e, &commitment_id) {
+        let commitment = match Self::fetch_commitment(&e, &commitment_id) {
+            Some(commitment) => commitment,
+            None => {
+                e.storage().instance().remove(&DataKey::ReentrancyGuard);
+                return Err(AttestationError::CommitmentNotFound);
+            }
+        };
+        if Self::is_archived(&e, &commitment_id) {
             e.storage().instance().remove(&DataKey::ReentrancyGuard);
-            return Err(AttestationError::CommitmentNotFound);
+            return Err(AttestationError::CommitmentArchived);
         }
 
         // 6. Validate attestation type
@@ -527,6 +834,12 @@ impl AttestationEngineContract {
             return Err(AttestationError::InvalidAttestationType);
         }
 
+        // 6b. Enforce the verifier's permitted attestation types
+        if !Self::is_type_permitted_for_verifier(&e, &caller, &attestation_type) {
+            e.storage().instance().remove(&DataKey::ReentrancyGuard);
+            return Err(AttestationError::TypeNotPermittedForVerifier);
+        }
+
         // 7. Validate data format for the attestation type
         if !Self::validate_attestation_data(&e, &attestation_type, &data) {
             e.storage().instance().remove(&DataKey::ReentrancyGuard);
@@ -598,13 +911,20 @@ impl AttestationEngineContract {
             .instance()
             .set(&verifier_key, &(verifier_count + 1));
 
+        // 11c. Open data is kept alive; terminal data only gets the grace period
+        let is_active = is_open(&commitment.status);
+        Self::extend_commitment_data_ttl(&e, &commitment_id, is_active);
+
         // 12. Emit enhanced AttestationRecorded event
-        e.events().publish(
-            (
-                Symbol::new(&e, "AttestationRecorded"),
-                commitment_id,
-                caller,
-            ),
+        Self::publish_routed(
+            &e,
+            vec![
+                &e,
+                Symbol::new(&e, "AttestationRecorded").into_val(&e),
+                commitment_id.into_val(&e),
+                caller.into_val(&e),
+            ],
+            &commitment_id,
             (attestation_type, is_compliant, timestamp),
         );
 
@@ -626,24 +946,19 @@ impl AttestationEngineContract {
 
     /// Get attestation count for a commitment
     pub fn get_attestation_count(e: Env, commitment_id: String) -> u64 {
-        let key = DataKey::AttestationCounter(commitment_id);
-        e.storage().persistent().get(&key).unwrap_or(0)
+        let key = DataKey::AttestationCounter(commitment_id.clone());
+        if let Some(count) = e.storage().persistent().get(&key) {
+            return count;
+        }
+        Self::get_archive(e, commitment_id)
+            .map(|archive| archive.attestation_count)
+            .unwrap_or(0)
     }
 
     /// Get current health metrics for a commitment
     pub fn get_health_metrics(e: Env, commitment_id: String) -> HealthMetrics {
         // Get commitment from core contract
-        let commitment_core: Address = e.storage().instance().get(&DataKey::CoreContract).unwrap();
-
-        // Call get_commitment on commitment_core contract
-        // Using Symbol::new() for function name longer than 9 characters
-        let mut args = Vec::new(&e);
-        args.push_back(commitment_id.clone().into_val(&e));
-        let commitment_val: Val =
-            e.invoke_contract(&commitment_core, &Symbol::new(&e, "get_commitment"), args);
-
-        // Convert Val to Commitment
-        let commitment: Commitment = commitment_val.try_into_val(&e).unwrap();
+        let commitment = Self::core_client(&e).unwrap().get_commitment(&commitment_id);
 
         // Get all attestations
         let attestations = Self::get_attestations(e.clone(), commitment_id.clone());
@@ -653,38 +968,12 @@ impl AttestationEngineContract {
         let current_value = commitment.current_value;
 
         // Calculate drawdown percentage: ((initial - current) / initial) * 100
-        // Handle zero initial value to prevent division by zero
-        let drawdown_percent = if initial_value > 0 {
-            let diff = initial_value.checked_sub(current_value).unwrap_or(0);
-            diff.checked_mul(100)
-                .unwrap_or(0)
-                .checked_div(initial_value)
-                .unwrap_or(0)
-        } else {
-            0
-        };
+        // PercentMath reports 0 for zero baselines and gains, clamped to 100
+        let drawdown_percent = PercentMath::loss_percent(initial_value, current_value) as i128;
 
-        // Sum fees from fee attestations
-        // Extract fee_amount from data map where key is "fee_amount"
-        let fees_generated: i128 = 0;
-        let fee_key = String::from_str(&e, "fee_amount");
-        for att in attestations.iter() {
-            if att.attestation_type == String::from_str(&e, "fee_generation") {
-                // Try to get fee_amount from data map
-                if let Some(_fee_val) = att.data.get(fee_key.clone()) {
-                    // The value is stored as String, we need to parse it
-                    // For simplicity, we'll use a helper to extract numeric value
-                    // In a real implementation, fees would be stored as i128 directly
-                    // For now, we'll track fees in a separate storage or use a different approach
-                    // Since Map<String, String> stores strings, we'll need parsing
-                    // Simplified: assume fee is stored as string representation of number
-                }
-            }
-        }
-
-        // For now, fees_generated will be 0 until we implement proper fee tracking
-        // This is acceptable as the requirement is to sum from fee attestations
-        // which requires the attest() function to properly store fees
+        // Fees come from core's per-commitment ledger, which allocators
+        // record into and settlement leaves in place
+        let fees_generated = Self::core_client(&e).unwrap().get_fees_generated(&commitment_id);
 
         // Calculate volatility exposure from attestations
         // Simplified: use variance of price changes from attestations
@@ -727,29 +1016,12 @@ impl AttestationEngineContract {
     /// # Returns
     /// `true` if compliant, `false` otherwise
     pub fn verify_compliance(e: Env, commitment_id: String) -> bool {
-        // Get commitment from core contract
-        let commitment_core: Address = match e.storage().instance().get(&DataKey::CoreContract) {
-            Some(addr) => addr,
+        // Get commitment details from core contract
+        let commitment = match Self::fetch_commitment(&e, &commitment_id) {
+            Some(c) => c,
             None => return false,
         };
 
-        // Get commitment details
-        let mut args = Vec::new(&e);
-        args.push_back(commitment_id.clone().into_val(&e));
-        let commitment_val: Val = match e.try_invoke_contract::<Val, soroban_sdk::Error>(
-            &commitment_core,
-            &Symbol::new(&e, "get_commitment"),
-            args,
-        ) {
-            Ok(Ok(val)) => val,
-            _ => return false,
-        };
-
-        let commitment: Commitment = match commitment_val.try_into_val(&e) {
-            Ok(c) => c,
-            Err(_) => return false,
-        };
-
         // Get health metrics
         let metrics = Self::get_health_metrics(e.clone(), commitment_id);
 
@@ -801,8 +1073,14 @@ impl AttestationEngineContract {
         )?;
 
         // Emit fee event
-        e.events().publish(
-            (Symbol::new(&e, "FeeRecorded"), commitment_id),
+        Self::publish_routed(
+            &e,
+            vec![
+                &e,
+                Symbol::new(&e, "FeeRecorded").into_val(&e),
+                commitment_id.into_val(&e),
+            ],
+            &commitment_id,
             (fee_amount, e.ledger().timestamp()),
         );
 
@@ -825,20 +1103,12 @@ impl AttestationEngineContract {
         drawdown_percent: i128,
     ) -> Result<(), AttestationError> {
         // Get commitment to check max_loss_percent
-        let commitment_core: Address = e
-            .storage()
-            .instance()
-            .get(&DataKey::CoreContract)
-            .ok_or(AttestationError::NotInitialized)?;
-
-        let mut args = Vec::new(&e);
-        args.push_back(commitment_id.clone().into_val(&e));
-        let commitment_val: Val =
-            e.invoke_contract(&commitment_core, &Symbol::new(&e, "get_commitment"), args);
-
-        let commitment: Commitment = commitment_val
-            .try_into_val(&e)
-            .map_err(|_| AttestationError::CommitmentNotFound)?;
+        let commitment = Self::core_client(&e)
+            .ok_or(AttestationError::NotInitialized)?
+            .try_get_commitment(&commitment_id)
+            .ok()
+            .and_then(|c| c.ok())
+            .ok_or(AttestationError::CommitmentNotFound)?;
 
         let max_loss = commitment.rules.max_loss_percent as i128;
         let is_compliant = drawdown_percent <= max_loss;
@@ -865,8 +1135,14 @@ impl AttestationEngineContract {
         )?;
 
         // Emit drawdown event with violation warning if applicable
-        e.events().publish(
-            (Symbol::new(&e, "DrawdownRecorded"), commitment_id),
+        Self::publish_routed(
+            &e,
+            vec![
+                &e,
+                Symbol::new(&e, "DrawdownRecorded").into_val(&e),
+                commitment_id.into_val(&e),
+            ],
+            &commitment_id,
             (drawdown_percent, is_compliant, e.ledger().timestamp()),
         );
 
@@ -914,6 +1190,8 @@ impl AttestationEngineContract {
 
     /// Calculate compliance score (0-100)
     ///
+    /// Equal to `get_score_breakdown(commitment_id).score`.
+    ///
     /// # Formal Verification
     /// **Preconditions:**
     /// - `commitment_id` exists
@@ -931,137 +1209,391 @@ impl AttestationEngineContract {
     /// - SP-4: State consistency (read-only)
     /// - SP-3: Arithmetic safety
     pub fn calculate_compliance_score(e: Env, commitment_id: String) -> u32 {
-        // First check if we have stored metrics with a compliance score
-        let metrics_key = DataKey::HealthMetrics(commitment_id.clone());
-        if let Some(stored_metrics) = e
+        let score = Self::score_breakdown(&e, &commitment_id).score;
+
+        // Emit compliance score update event
+        Self::publish_routed(
+            &e,
+            vec![
+                &e,
+                symbol_short!("ScoreUpd").into_val(&e),
+                commitment_id.into_val(&e),
+            ],
+            &commitment_id,
+            (score, e.ledger().timestamp()),
+        );
+
+        score
+    }
+
+    /// Get the components behind `calculate_compliance_score`
+    ///
+    /// Read-only; the scalar score is always `get_score_breakdown(..).score`.
+    pub fn get_score_breakdown(e: Env, commitment_id: String) -> ScoreBreakdown {
+        Self::score_breakdown(&e, &commitment_id)
+    }
+
+    /// Compute the compliance score and its components
+    fn score_breakdown(e: &Env, commitment_id: &String) -> ScoreBreakdown {
+        // Archived commitments no longer have the data the score is built from
+        if let Some(report) = e
             .storage()
             .persistent()
-            .get::<DataKey, HealthMetrics>(&metrics_key)
+            .get::<DataKey, SettlementReport>(&DataKey::FinalReport(commitment_id.clone()))
         {
-            return stored_metrics.compliance_score;
+            return report.score_breakdown;
         }
 
         // Get commitment from core contract
-        let commitment_core: Address = e.storage().instance().get(&DataKey::CoreContract).unwrap();
+        let commitment = Self::core_client(e).unwrap().get_commitment(commitment_id);
 
-        // Call get_commitment on commitment_core contract
-        // Using Symbol::new() for function name longer than 9 characters
-        let mut args = Vec::new(&e);
-        args.push_back(commitment_id.clone().into_val(&e));
-        let commitment_val: Val =
-            e.invoke_contract(&commitment_core, &Symbol::new(&e, "get_commitment"), args);
-
-        // Convert Val to Commitment
-        let commitment: Commitment = commitment_val.try_into_val(&e).unwrap();
-
-        // Get all attestations
-        let attestations = Self::get_attestations(e.clone(), commitment_id.clone());
-
-        // Base score: 100
-        let mut score: i32 = 100;
-
-        // Count violations: -20 per violation
-        let violation_count = attestations
-            .iter()
-            .filter(|att| {
-                !att.is_compliant || att.attestation_type == String::from_str(&e, "violation")
-            })
-            .count() as i32;
-        score = score
-            .checked_sub(violation_count.checked_mul(20).unwrap_or(0))
-            .unwrap_or(0);
-
-        // Calculate drawdown vs threshold: -1 per % over threshold
-        let initial_value = commitment.amount;
-        let current_value = commitment.current_value;
-        let max_loss_percent = commitment.rules.max_loss_percent as i128;
-
-        if initial_value > 0 {
-            let drawdown_percent = {
-                let diff = initial_value.checked_sub(current_value).unwrap_or(0);
-                diff.checked_mul(100)
-                    .unwrap_or(0)
-                    .checked_div(initial_value)
-                    .unwrap_or(0)
-            };
+        let stored_metrics = e
+            .storage()
+            .persistent()
+            .get::<DataKey, HealthMetrics>(&DataKey::HealthMetrics(commitment_id.clone()));
+
+        // Violations: the stored attestation tally already weighs severities;
+        // without one, -20 per violation attestation
+        let violation_penalty_applied = match &stored_metrics {
+            Some(metrics) => 100u32.saturating_sub(metrics.compliance_score),
+            None => {
+                let violation = String::from_str(e, "violation");
+                let violation_count = Self::get_attestations(e.clone(), commitment_id.clone())
+                    .iter()
+                    .filter(|att| !att.is_compliant || att.attestation_type == violation)
+                    .count() as u32;
+                violation_count
+                    .saturating_mul(SCORE_VIOLATION_PENALTY)
+                    .min(100)
+            }
+        };
 
+        // Drawdown vs threshold: -1 per % over threshold
+        let mut drawdown_component: i32 = 0;
+        let max_loss_percent = commitment.rules.max_loss_percent;
+        if commitment.amount > 0 {
+            let drawdown_percent =
+                PercentMath::loss_percent(commitment.amount, commitment.current_value);
             if drawdown_percent > max_loss_percent {
-                let over_threshold = drawdown_percent.checked_sub(max_loss_percent).unwrap_or(0);
-                score = score.checked_sub(over_threshold as i32).unwrap_or(0);
+                let over_threshold = drawdown_percent - max_loss_percent;
+                drawdown_component =
+                    -(over_threshold.saturating_mul(SCORE_DRAWDOWN_PER_PERCENT).min(100) as i32);
             }
         }
 
-        // Calculate fee generation vs expectations: +1 per % of expected fees
+        // Fee generation vs expectations: +1 per % of expected fees, capped
+        let mut fee_component: i32 = 0;
         let min_fee_threshold = commitment.rules.min_fee_threshold;
-        // Get fees from health metrics (which sums from attestations)
-        // We'll calculate this from the attestations directly
-        let total_fees: i128 = 0;
-        let fee_key = String::from_str(&e, "fee_amount");
-
-        for att in attestations.iter() {
-            if att.attestation_type == String::from_str(&e, "fee_generation") {
-                // Extract fee from data map
-                // Since Map<String, String> stores strings, we need to parse
-                // For this implementation, we'll use a simplified approach:
-                // If fee_amount exists in data, we'll try to extract it
-                // In production, fees should be stored as i128 in a separate field
-                if let Some(_fee_str) = att.data.get(fee_key.clone()) {
-                    // Parse would be needed here - for now, we'll use 0
-                    // This is acceptable as fee tracking requires proper implementation
-                    // of the attest() function to store fees correctly
-                }
-            }
-        }
-
-        // Only add fee bonus if we have fees and a threshold
+        let total_fees = stored_metrics
+            .as_ref()
+            .map(|metrics| metrics.fees_generated)
+            .unwrap_or(0);
         if min_fee_threshold > 0 && total_fees > 0 {
             let fee_percent = total_fees
                 .checked_mul(100)
-                .unwrap_or(0)
+                .unwrap_or(i128::MAX)
                 .checked_div(min_fee_threshold)
                 .unwrap_or(0);
-            // Cap the bonus to prevent excessive score inflation
-            let bonus = if fee_percent > 100 { 100 } else { fee_percent };
-            score = score.checked_add(bonus as i32).unwrap_or(100);
+            fee_component = fee_percent.min(SCORE_FEE_BONUS_CAP as i128) as i32;
         }
 
-        // Duration adherence: +10 if on track
-        let current_time = e.ledger().timestamp();
-        let expires_at = commitment.expires_at;
-        let created_at = commitment.created_at;
+        // Freshness: +10 while the commitment is still running its term;
+        // final scores at or after expiry rest on the recorded data alone
+        let mut freshness_component: i32 = 0;
+        if e.ledger().timestamp() < commitment.expires_at
+            && commitment.expires_at > commitment.created_at
+        {
+            freshness_component = SCORE_FRESHNESS_BONUS as i32;
+        }
 
-        if expires_at > created_at {
-            let total_duration = expires_at.checked_sub(created_at).unwrap_or(1);
-            let elapsed = current_time.checked_sub(created_at).unwrap_or(0);
+        let score = (100 - violation_penalty_applied as i32
+            + drawdown_component
+            + fee_component
+            + freshness_component)
+            .clamp(0, 100) as u32;
+
+        ScoreBreakdown {
+            drawdown_component,
+            fee_component,
+            freshness_component,
+            violation_penalty_applied,
+            weights_used: ScoreWeights {
+                violation_penalty: SCORE_VIOLATION_PENALTY,
+                drawdown_per_percent: SCORE_DRAWDOWN_PER_PERCENT,
+                fee_bonus_cap: SCORE_FEE_BONUS_CAP,
+                freshness_bonus: SCORE_FRESHNESS_BONUS,
+            },
+            score,
+        }
+    }
 
-            // Check if we're on track (not too far behind or ahead)
-            // Simplified: if elapsed is within reasonable bounds of expected progress
-            let expected_progress = (elapsed as u128)
-                .checked_mul(100)
-                .unwrap_or(0)
-                .checked_div(total_duration as u128)
-                .unwrap_or(0);
+    /// Get the final report for a commitment that is no longer active
+    ///
+    /// # Returns
+    /// * `Err(AttestationError::NotSettled)` while the commitment is active
+    ///   or pending
+    pub fn get_settlement_report(
+        e: Env,
+        commitment_id: String,
+    ) -> Result<SettlementReport, AttestationError> {
+        if let Some(report) = e
+            .storage()
+            .persistent()
+            .get(&DataKey::FinalReport(commitment_id.clone()))
+        {
+            return Ok(report);
+        }
+        let commitment = Self::fetch_commitment(&e, &commitment_id)
+            .ok_or(AttestationError::CommitmentNotFound)?;
+        if is_open(&commitment.status) {
+            return Err(AttestationError::NotSettled);
+        }
 
-            // Consider "on track" if between 0-100% of expected time
-            if expected_progress <= 100 {
-                score = score.checked_add(10).unwrap_or(100);
-            }
+        Ok(SettlementReport {
+            commitment_id: commitment_id.clone(),
+            owner: commitment.owner,
+            status: String::from_str(&e, status_label(commitment.status)),
+            initial_value: commitment.amount,
+            final_value: commitment.current_value,
+            attestation_count: Self::get_attestation_count(e.clone(), commitment_id.clone()),
+            score_breakdown: Self::score_breakdown(&e, &commitment_id),
+            generated_at: e.ledger().timestamp(),
+        })
+    }
+
+    // ========================================================================
+    // Event routing
+    // ========================================================================
+
+    /// Tag a commitment's attestation, drawdown and compliance events with a
+    /// routing hint for off-chain dispatchers (admin or commitment owner)
+    ///
+    /// The route is appended as the last topic of those events until it is
+    /// cleared or the commitment's data is archived.
+    pub fn set_attestation_route(
+        e: Env,
+        commitment_id: String,
+        caller: Address,
+        route: Symbol,
+    ) -> Result<(), AttestationError> {
+        Self::require_route_manager(&e, &commitment_id, &caller)?;
+        e.storage()
+            .persistent()
+            .set(&DataKey::AttestationRoute(commitment_id.clone()), &route);
+        e.events().publish(
+            (Symbol::new(&e, "AttestationRouteSet"), commitment_id),
+            (caller, Some(route)),
+        );
+        Ok(())
+    }
+
+    /// Remove a commitment's routing tag (admin or commitment owner)
+    pub fn clear_attestation_route(
+        e: Env,
+        commitment_id: String,
+        caller: Address,
+    ) -> Result<(), AttestationError> {
+        Self::require_route_manager(&e, &commitment_id, &caller)?;
+        e.storage()
+            .persistent()
+            .remove(&DataKey::AttestationRoute(commitment_id.clone()));
+        e.events().publish(
+            (Symbol::new(&e, "AttestationRouteSet"), commitment_id),
+            (caller, None::<Symbol>),
+        );
+        Ok(())
+    }
+
+    /// Get a commitment's routing tag, if set
+    pub fn get_attestation_route(e: Env, commitment_id: String) -> Option<Symbol> {
+        e.storage()
+            .persistent()
+            .get(&DataKey::AttestationRoute(commitment_id))
+    }
+
+    /// Caller must be the admin or, per core, the commitment's owner
+    fn require_route_manager(
+        e: &Env,
+        commitment_id: &String,
+        caller: &Address,
+    ) -> Result<(), AttestationError> {
+        caller.require_auth();
+        if Self::is_archived(e, commitment_id) {
+            return Err(AttestationError::CommitmentArchived);
         }
+        if *caller == Self::get_admin(e.clone())? {
+            return Ok(());
+        }
+        let commitment =
+            Self::fetch_commitment(e, commitment_id).ok_or(AttestationError::CommitmentNotFound)?;
+        if commitment.owner != *caller {
+            return Err(AttestationError::Unauthorized);
+        }
+        Ok(())
+    }
 
-        // Clamp between 0 and 100
-        if score < 0 {
-            score = 0;
-        } else if score > 100 {
-            score = 100;
+    /// Publish a commitment event, appending its routing tag if one is set
+    fn publish_routed<D: IntoVal<Env, Val>>(
+        e: &Env,
+        mut topics: Vec<Val>,
+        commitment_id: &String,
+        data: D,
+    ) {
+        if let Some(route) = Self::get_attestation_route(e.clone(), commitment_id.clone()) {
+            topics.push_back(route.into_val(e));
         }
+        e.events().publish(topics, data);
+    }
+
+    // ========================================================================
+    // Archival
+    // ========================================================================
+
+    /// Collapse a terminal commitment's attestation data into its final
+    /// report and daily summaries (admin or commitment owner)
+    ///
+    /// The settlement report is frozen as it stands and later reads return
+    /// it unchanged. Raw attestations, the counter and stored health
+    /// metrics are deleted. Summaries and the report get a long TTL.
+    ///
+    /// # Returns
+    /// * `Err(AttestationError::NotSettled)` while the commitment is active
+    ///   or pending
+    /// * `Err(AttestationError::CommitmentArchived)` if already archived
+    pub fn archive_attestation_data(
+        e: Env,
+        caller: Address,
+        commitment_id: String,
+    ) -> Result<ArchivedAttestations, AttestationError> {
+        caller.require_auth();
+        if Self::is_archived(&e, &commitment_id) {
+            return Err(AttestationError::CommitmentArchived);
+        }
+        let report = Self::get_settlement_report(e.clone(), commitment_id.clone())?;
+        let admin = Self::get_admin(e.clone())?;
+        if caller != admin && caller != report.owner {
+            return Err(AttestationError::Unauthorized);
+        }
+
+        let attestations = Self::get_attestations(e.clone(), commitment_id.clone());
+        let violation = String::from_str(&e, "violation");
+        let fee_generation = String::from_str(&e, "fee_generation");
+        let fee_amount_key = String::from_str(&e, "fee_amount");
+        let mut daily_summaries: Vec<DailySummary> = Vec::new(&e);
+        // Attestations are stored in time order, so each day is one run
+        for att in attestations.iter() {
+            let day = att.timestamp / 86400;
+            let mut summary = match daily_summaries.last() {
+                Some(last) if last.day == day => daily_summaries.pop_back().unwrap(),
+                _ => DailySummary {
+                    day,
+                    attestation_count: 0,
+                    violation_count: 0,
+                    fees_generated: 0,
+                },
+            };
+            summary.attestation_count += 1;
+            if !att.is_compliant || att.attestation_type == violation {
+                summary.violation_count += 1;
+            }
+            if att.attestation_type == fee_generation {
+                if let Some(fee) = att
+                    .data
+                    .get(fee_amount_key.clone())
+                    .and_then(|fee| Self::parse_i128_from_string(&e, &fee))
+                {
+                    summary.fees_generated = summary.fees_generated.saturating_add(fee);
+                }
+            }
+            daily_summaries.push_back(summary);
+        }
+
+        let archive = ArchivedAttestations {
+            archived_at: e.ledger().timestamp(),
+            archived_by: caller.clone(),
+            attestation_count: report.attestation_count,
+            daily_summaries,
+        };
+        let report_key = DataKey::FinalReport(commitment_id.clone());
+        let archive_key = DataKey::Archive(commitment_id.clone());
+        e.storage().persistent().set(&report_key, &report);
+        e.storage().persistent().set(&archive_key, &archive);
+        e.storage()
+            .persistent()
+            .extend_ttl(&report_key, ARCHIVE_TTL, ARCHIVE_TTL);
+        e.storage()
+            .persistent()
+            .extend_ttl(&archive_key, ARCHIVE_TTL, ARCHIVE_TTL);
+
+        e.storage()
+            .persistent()
+            .remove(&DataKey::Attestations(commitment_id.clone()));
+        e.storage()
+            .persistent()
+            .remove(&DataKey::AttestationCounter(commitment_id.clone()));
+        e.storage()
+            .persistent()
+            .remove(&DataKey::HealthMetrics(commitment_id.clone()));
+        e.storage()
+            .persistent()
+            .remove(&DataKey::AttestationRoute(commitment_id.clone()));
 
-        // Emit compliance score update event
         e.events().publish(
-            (symbol_short!("ScoreUpd"), commitment_id),
-            (score as u32, e.ledger().timestamp()),
+            (Symbol::new(&e, "AttestationsArchived"), commitment_id),
+            (caller, archive.attestation_count),
         );
+        Ok(archive)
+    }
+
+    /// Get the archived summaries for a commitment, if archived
+    pub fn get_archive(e: Env, commitment_id: String) -> Option<ArchivedAttestations> {
+        e.storage()
+            .persistent()
+            .get(&DataKey::Archive(commitment_id))
+    }
+
+    /// Get a commitment's attestation data: raw attestations while live,
+    /// daily summaries with `archived` set once archived
+    pub fn get_attestation_history(e: Env, commitment_id: String) -> AttestationHistory {
+        match Self::get_archive(e.clone(), commitment_id.clone()) {
+            Some(archive) => AttestationHistory {
+                archived: true,
+                attestations: Vec::new(&e),
+                daily_summaries: archive.daily_summaries,
+            },
+            None => AttestationHistory {
+                archived: false,
+                attestations: Self::get_attestations(e.clone(), commitment_id),
+                daily_summaries: Vec::new(&e),
+            },
+        }
+    }
 
-        score as u32
+    fn is_archived(e: &Env, commitment_id: &String) -> bool {
+        e.storage()
+            .persistent()
+            .has(&DataKey::Archive(commitment_id.clone()))
+    }
+
+    /// Extend the TTL of a commitment's raw attestation data: the long active
+    /// tier while it runs, only the grace period once it is terminal
+    fn extend_commitment_data_ttl(e: &Env, commitment_id: &String, is_active: bool) {
+        let (threshold, ttl) = if is_active {
+            (ACTIVE_DATA_TTL_THRESHOLD, ACTIVE_DATA_TTL)
+        } else {
+            (TERMINAL_GRACE_TTL, TERMINAL_GRACE_TTL)
+        };
+        let keys = [
+            DataKey::Attestations(commitment_id.clone()),
+            DataKey::AttestationCounter(commitment_id.clone()),
+            DataKey::HealthMetrics(commitment_id.clone()),
+            DataKey::AttestationRoute(commitment_id.clone()),
+        ];
+        for key in keys.iter() {
+            if e.storage().persistent().has(key) {
+                e.storage().persistent().extend_ttl(key, threshold, ttl);
+            }
+        }
     }
 
     /// Get high-level protocol analytics combining commitment and attestation data.
@@ -1073,16 +1605,7 @@ impl AttestationEngineContract {
     /// - total_fees_generated
     pub fn get_protocol_statistics(e: Env) -> (u64, u64, u64, i128) {
         // Read commitment_core statistics
-        let commitment_core: Address = e.storage().instance().get(&DataKey::CoreContract).unwrap();
-
-        // get_total_commitments() on core contract
-        let args = Vec::new(&e);
-        let total_commitments_val: Val = e.invoke_contract(
-            &commitment_core,
-            &Symbol::new(&e, "get_total_commitments"),
-            args,
-        );
-        let total_commitments: u64 = total_commitments_val.try_into_val(&e).unwrap();
+        let total_commitments = Self::core_client(&e).unwrap().get_total_commitments();
 
         let total_attestations: u64 = e
             .storage()
@@ -1112,6 +1635,284 @@ impl AttestationEngineContract {
         e.storage().instance().get(&key).unwrap_or(0)
     }
 
+    // ========================================================================
+    // Compliance Badges
+    // ========================================================================
+
+    /// Configure compliance badge minting (admin only)
+    ///
+    /// # Arguments
+    /// * `nft_contract` - NFT contract exposing `mint_badge`; this contract
+    ///   must be its badge minter
+    /// * `min_compliance_score` - Final score required (0-100, inclusive)
+    /// * `min_duration_days` - Minimum commitment duration
+    pub fn set_badge_config(
+        e: Env,
+        caller: Address,
+        nft_contract: Address,
+        min_compliance_score: u32,
+        min_duration_days: u32,
+    ) -> Result<(), AttestationError> {
+        caller.require_auth();
+        let admin: Address = e
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(AttestationError::NotInitialized)?;
+        if caller != admin {
+            return Err(AttestationError::Unauthorized);
+        }
+        if !PercentMath::is_valid_percent(min_compliance_score) {
+            return Err(AttestationError::InvalidBadgeConfig);
+        }
+
+        let config = BadgeConfig {
+            nft_contract,
+            min_compliance_score,
+            min_duration_days,
+        };
+        e.storage().instance().set(&DataKey::BadgeConfig, &config);
+        e.events().publish(
+            (Symbol::new(&e, "BadgeConfigSet"),),
+            (config.nft_contract, min_compliance_score, min_duration_days),
+        );
+
+        Ok(())
+    }
+
+    /// Get the compliance badge settings, if configured
+    pub fn get_badge_config(e: Env) -> Option<BadgeConfig> {
+        e.storage().instance().get(&DataKey::BadgeConfig)
+    }
+
+    /// Get the badge issued for a commitment, if any
+    pub fn get_commitment_badge(e: Env, commitment_id: String) -> Option<u32> {
+        e.storage()
+            .persistent()
+            .get(&DataKey::BadgeIssued(commitment_id))
+    }
+
+    /// Mint a compliance badge for a settled commitment
+    ///
+    /// The commitment must be settled, meet the configured duration and have
+    /// a final compliance score at or above the configured threshold. Only
+    /// one badge is ever issued per commitment.
+    ///
+    /// # Arguments
+    /// * `caller` - The commitment owner or an authorized verifier
+    /// * `owner` - Badge recipient; must own the commitment
+    /// * `commitment_id` - The settled commitment
+    ///
+    /// # Returns
+    /// * `Ok(badge_id)` minted by the NFT contract
+    pub fn mint_badge(
+        e: Env,
+        caller: Address,
+        owner: Address,
+        commitment_id: String,
+    ) -> Result<u32, AttestationError> {
+        caller.require_auth();
+
+        let config: BadgeConfig = e
+            .storage()
+            .instance()
+            .get(&DataKey::BadgeConfig)
+            .ok_or(AttestationError::BadgeNotConfigured)?;
+
+        let commitment = Self::fetch_commitment(&e, &commitment_id)
+            .ok_or(AttestationError::CommitmentNotFound)?;
+        if commitment.owner != owner
+            || (caller != owner && !Self::is_authorized_verifier(&e, &caller))
+        {
+            return Err(AttestationError::Unauthorized);
+        }
+        if commitment.status != CommitmentStatus::Settled {
+            return Err(AttestationError::NotSettled);
+        }
+
+        let issued_key = DataKey::BadgeIssued(commitment_id.clone());
+        if e.storage().persistent().has(&issued_key) {
+            return Err(AttestationError::BadgeAlreadyIssued);
+        }
+
+        let compliance_score =
+            Self::calculate_compliance_score(e.clone(), commitment_id.clone());
+        if compliance_score < config.min_compliance_score
+            || commitment.rules.duration_days < config.min_duration_days
+        {
+            return Err(AttestationError::BadgeCriteriaNotMet);
+        }
+
+        // INTERACTIONS: mint the soulbound badge in the NFT contract
+        let badge_id = CommitmentNftClient::new(&e, &config.nft_contract).mint_badge(
+            &owner,
+            &commitment_id,
+            &compliance_score,
+            &commitment.rules.duration_days,
+        );
+
+        e.storage().persistent().set(&issued_key, &badge_id);
+
+        e.events().publish(
+            (symbol_short!("BadgeIss"), commitment_id, owner),
+            (badge_id, compliance_score),
+        );
+
+        Ok(badge_id)
+    }
+
+    // ========================================================================
+    // Audit reports
+    // ========================================================================
+
+    /// Grant the auditor role (admin only)
+    pub fn add_auditor(e: Env, caller: Address, auditor: Address) -> Result<(), AttestationError> {
+        caller.require_auth();
+        let admin: Address = e
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(AttestationError::NotInitialized)?;
+        if caller != admin {
+            return Err(AttestationError::Unauthorized);
+        }
+
+        AccessControl::grant_role(&e, &AUDITOR_ROLE, &auditor);
+        e.events()
+            .publish((Symbol::new(&e, "AuditorAdded"),), (auditor,));
+        Ok(())
+    }
+
+    /// Revoke the auditor role (admin only)
+    pub fn remove_auditor(
+        e: Env,
+        caller: Address,
+        auditor: Address,
+    ) -> Result<(), AttestationError> {
+        caller.require_auth();
+        let admin: Address = e
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(AttestationError::NotInitialized)?;
+        if caller != admin {
+            return Err(AttestationError::Unauthorized);
+        }
+
+        AccessControl::revoke_role(&e, &AUDITOR_ROLE, &auditor);
+        e.events()
+            .publish((Symbol::new(&e, "AuditorRemoved"),), (auditor,));
+        Ok(())
+    }
+
+    /// Check if an address holds the auditor role
+    pub fn is_auditor(e: Env, address: Address) -> bool {
+        AccessControl::has_role(&e, &AUDITOR_ROLE, &address)
+    }
+
+    /// Anchor an external audit report on-chain
+    ///
+    /// # Arguments
+    /// * `caller` - Admin or an address holding the auditor role
+    /// * `scope` - A single commitment or the whole protocol
+    /// * `sha256` - SHA-256 hash of the report document
+    /// * `uri` - Where the report can be retrieved
+    /// * `period_start` / `period_end` - Period covered by the report
+    ///
+    /// # Returns
+    /// * `Ok(index)` of the record within its scope
+    /// * `Err(AttestationError::DuplicateAudit)` if the hash was already
+    ///   recorded for the same scope and period
+    pub fn record_audit(
+        e: Env,
+        caller: Address,
+        scope: AuditScope,
+        sha256: BytesN<32>,
+        uri: String,
+        period_start: u64,
+        period_end: u64,
+    ) -> Result<u32, AttestationError> {
+        caller.require_auth();
+        let admin: Address = e
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(AttestationError::NotInitialized)?;
+        if caller != admin && !AccessControl::has_role(&e, &AUDITOR_ROLE, &caller) {
+            return Err(AttestationError::Unauthorized);
+        }
+
+        if let AuditScope::Commitment(commitment_id) = &scope {
+            if commitment_id.is_empty() {
+                return Err(AttestationError::InvalidCommitmentId);
+            }
+            if !Self::commitment_exists(&e, commitment_id) {
+                return Err(AttestationError::CommitmentNotFound);
+            }
+        }
+        if uri.is_empty() {
+            return Err(AttestationError::InvalidAttestationData);
+        }
+        if period_end < period_start {
+            return Err(AttestationError::InvalidAuditPeriod);
+        }
+
+        let hash_key =
+            DataKey::AuditHash(scope.clone(), sha256.clone(), period_start, period_end);
+        if e.storage().persistent().has(&hash_key) {
+            return Err(AttestationError::DuplicateAudit);
+        }
+
+        let count_key = DataKey::AuditCount(scope.clone());
+        let index: u32 = e.storage().persistent().get(&count_key).unwrap_or(0);
+        let record = AuditRecord {
+            scope: scope.clone(),
+            sha256: sha256.clone(),
+            uri,
+            period_start,
+            period_end,
+            recorded_by: caller.clone(),
+            recorded_at: e.ledger().timestamp(),
+        };
+        e.storage()
+            .persistent()
+            .set(&DataKey::Audit(scope.clone(), index), &record);
+        e.storage().persistent().set(&count_key, &(index + 1));
+        e.storage().persistent().set(&hash_key, &true);
+
+        e.events().publish(
+            (symbol_short!("AuditRec"), scope),
+            (index, sha256, period_start, period_end, caller),
+        );
+
+        Ok(index)
+    }
+
+    /// Get audit reports recorded for a scope, oldest first
+    pub fn get_audits(e: Env, scope: AuditScope, offset: u32, limit: u32) -> Vec<AuditRecord> {
+        let count = Self::get_audit_count(e.clone(), scope.clone());
+        let end = offset.saturating_add(limit).min(count);
+        let mut audits = Vec::new(&e);
+        for index in offset..end {
+            if let Some(record) = e
+                .storage()
+                .persistent()
+                .get::<_, AuditRecord>(&DataKey::Audit(scope.clone(), index))
+            {
+                audits.push_back(record);
+            }
+        }
+        audits
+    }
+
+    /// Number of audit reports recorded for a scope
+    pub fn get_audit_count(e: Env, scope: AuditScope) -> u32 {
+        e.storage()
+            .persistent()
+            .get(&DataKey::AuditCount(scope))
+            .unwrap_or(0)
+    }
+
     /// Configure rate limits for this contract's functions (e.g. `attest`).
     ///
     /// Restricted to admin.
@@ -3,13 +3,14 @@
 use super::*;
 use commitment_core::{
     Commitment as CoreCommitment, CommitmentCoreContract, CommitmentRules as CoreCommitmentRules,
-    DataKey,
+    CommitmentStatus as CoreCommitmentStatus, DataKey, RiskProfile,
 };
 use soroban_sdk::{
     symbol_short, testutils::Address as _, testutils::Events, testutils::Ledger as _, vec, Address,
     Env, IntoVal, Map, String, Symbol,
 };
 
+#[allow(clippy::too_many_arguments)]
 fn store_core_commitment(
     e: &Env,
     commitment_core_id: &Address,
@@ -29,7 +30,7 @@ fn store_core_commitment(
         rules: CoreCommitmentRules {
             duration_days,
             max_loss_percent,
-            commitment_type: String::from_str(e, "balanced"),
+            commitment_type: RiskProfile::Balanced,
             early_exit_penalty: 10,
             min_fee_threshold: 1000,
         },
@@ -38,11 +39,12 @@ fn store_core_commitment(
         created_at,
         expires_at,
         current_value,
-        status: String::from_str(e, "active"),
+        status: CoreCommitmentStatus::Active,
+        is_virtual: false,
     };
 
     e.as_contract(commitment_core_id, || {
-        e.storage().instance().set(
+        e.storage().persistent().set(
             &DataKey::Commitment(commitment.commitment_id.clone()),
             &commitment,
         );
@@ -78,7 +80,7 @@ fn setup_test_env() -> (Env, Address, Address, Address) {
 
 #[test]
 fn test_initialize() {
-    let (e, admin, commitment_core, contract_id) = setup_test_env();
+    let (e, _admin, _commitment_core, contract_id) = setup_test_env();
 
     // Verify initialization by checking that we can call other functions
     // (indirect verification through storage access)
@@ -616,6 +618,72 @@ fn test_attest_authorized_verifier() {
     assert!(result.is_ok());
 }
 
+#[test]
+fn test_verifier_types_restrict_attest() {
+    let (e, admin, commitment_core, contract_id) = setup_test_env();
+    let client = AttestationEngineContractClient::new(&e, &contract_id);
+    let verifier = Address::generate(&e);
+    let owner = Address::generate(&e);
+    let commitment_id = String::from_str(&e, "typed_commitment");
+    store_core_commitment(
+        &e,
+        &commitment_core,
+        "typed_commitment",
+        &owner,
+        1000,
+        1000,
+        10,
+        30,
+        1000,
+    );
+    client.add_verifier(&admin, &verifier);
+    assert_eq!(client.get_verifier_types(&verifier).len(), 0);
+
+    // Recorded before the restriction; must survive it
+    let mut drawdown_data = Map::new(&e);
+    drawdown_data.set(
+        String::from_str(&e, "drawdown_percent"),
+        String::from_str(&e, "5"),
+    );
+    let drawdown = String::from_str(&e, "drawdown");
+    client.attest(&verifier, &commitment_id, &drawdown, &drawdown_data, &true);
+
+    let health_check = String::from_str(&e, "health_check");
+    client.set_verifier_types(&admin, &verifier, &vec![&e, health_check.clone()]);
+    assert_eq!(
+        client.get_verifier_types(&verifier),
+        vec![&e, health_check.clone()]
+    );
+
+    let result = client.try_attest(&verifier, &commitment_id, &drawdown, &drawdown_data, &true);
+    assert_eq!(result, Err(Ok(AttestationError::TypeNotPermittedForVerifier)));
+    client.attest(&verifier, &commitment_id, &health_check, &Map::new(&e), &true);
+
+    // Admin is never restricted
+    client.attest(&admin, &commitment_id, &drawdown, &drawdown_data, &true);
+    assert_eq!(client.get_attestations(&commitment_id).len(), 3);
+
+    // Clearing the set restores access to all types
+    client.set_verifier_types(&admin, &verifier, &Vec::new(&e));
+    assert_eq!(client.get_verifier_types(&verifier).len(), 0);
+    client.attest(&verifier, &commitment_id, &drawdown, &drawdown_data, &true);
+}
+
+#[test]
+fn test_set_verifier_types_validation() {
+    let (e, admin, _commitment_core, contract_id) = setup_test_env();
+    let client = AttestationEngineContractClient::new(&e, &contract_id);
+    let verifier = Address::generate(&e);
+    let types = vec![&e, String::from_str(&e, "health_check")];
+
+    let result = client.try_set_verifier_types(&verifier, &verifier, &types);
+    assert_eq!(result, Err(Ok(AttestationError::Unauthorized)));
+
+    let bogus = vec![&e, String::from_str(&e, "bogus")];
+    let result = client.try_set_verifier_types(&admin, &verifier, &bogus);
+    assert_eq!(result, Err(Ok(AttestationError::InvalidAttestationType)));
+}
+
 // ============================================================================
 // Validation Tests
 // ============================================================================
@@ -1537,7 +1605,7 @@ fn test_record_drawdown_event() {
     let event_data: (i128, bool, u64) = last_event.2.into_val(&e);
     // (drawdown_percent, is_compliant, timestamp)
     assert_eq!(event_data.0, 5);
-    assert_eq!(event_data.1, true);
+    assert!(event_data.1);
 }
 
 #[test]
@@ -1577,3 +1645,624 @@ fn test_calculate_compliance_score_event() {
     let event_data: (u32, u64) = last_event.2.into_val(&e);
     assert_eq!(event_data.0, 100);
 }
+
+// ============================================================================
+// Compliance badges
+// ============================================================================
+
+struct BadgeEnv {
+    e: Env,
+    admin: Address,
+    owner: Address,
+    core: commitment_core::CommitmentCoreContractClient<'static>,
+    nft: commitment_nft::CommitmentNFTContractClient<'static>,
+    client: AttestationEngineContractClient<'static>,
+}
+
+/// Real core + NFT + engine wiring with one active 90-day commitment
+/// ("badge_c") whose NFT was minted in the NFT contract.
+fn setup_badge_env() -> BadgeEnv {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let owner = Address::generate(&e);
+
+    let nft_id = e.register_contract(None, commitment_nft::CommitmentNFTContract);
+    let nft = commitment_nft::CommitmentNFTContractClient::new(&e, &nft_id);
+    let core_id = e.register_contract(None, CommitmentCoreContract);
+    let core = commitment_core::CommitmentCoreContractClient::new(&e, &core_id);
+    let engine_id = e.register_contract(None, AttestationEngineContract);
+    let client = AttestationEngineContractClient::new(&e, &engine_id);
+
+    nft.initialize(&admin);
+    nft.set_core_contract(&core_id);
+    nft.set_badge_minter(&engine_id);
+    core.initialize(&admin, &nft_id);
+    client.initialize(&admin, &core_id);
+    client.set_badge_config(&admin, &nft_id, &95, &90);
+
+    let asset = e.register_stellar_asset_contract_v2(admin.clone()).address();
+    soroban_sdk::token::StellarAssetClient::new(&e, &asset).mint(&core_id, &1000);
+    let token_id = nft.mint(
+        &admin,
+        &owner,
+        &String::from_str(&e, "badge_c"),
+        &90,
+        &10,
+        &RiskProfile::Balanced,
+        &1000,
+        &asset,
+        &5,
+    );
+
+    let created_at = e.ledger().timestamp();
+    let commitment = CoreCommitment {
+        commitment_id: String::from_str(&e, "badge_c"),
+        owner: owner.clone(),
+        nft_token_id: token_id,
+        rules: CoreCommitmentRules {
+            duration_days: 90,
+            max_loss_percent: 10,
+            commitment_type: RiskProfile::Balanced,
+            early_exit_penalty: 5,
+            min_fee_threshold: 0,
+        },
+        amount: 1000,
+        asset_address: asset,
+        created_at,
+        expires_at: created_at + 90 * 86400,
+        current_value: 1000,
+        status: CoreCommitmentStatus::Active,
+        is_virtual: false,
+    };
+    e.as_contract(&core_id, || {
+        e.storage().persistent().set(
+            &DataKey::Commitment(commitment.commitment_id.clone()),
+            &commitment,
+        );
+    });
+
+    BadgeEnv {
+        e,
+        admin,
+        owner,
+        core,
+        nft,
+        client,
+    }
+}
+
+fn final_report(f: &BadgeEnv, commitment_id: &String) {
+    f.client.attest(
+        &f.admin,
+        commitment_id,
+        &String::from_str(&f.e, "health_check"),
+        &Map::new(&f.e),
+        &true,
+    );
+}
+
+#[test]
+fn test_mint_badge_after_settlement() {
+    let f = setup_badge_env();
+    let commitment_id = String::from_str(&f.e, "badge_c");
+
+    f.e.ledger().with_mut(|l| l.timestamp += 90 * 86400);
+    f.core.settle(&commitment_id);
+    final_report(&f, &commitment_id);
+
+    let badge_id = f.client.mint_badge(&f.owner, &f.owner, &commitment_id);
+
+    assert_eq!(f.client.get_commitment_badge(&commitment_id), Some(badge_id));
+    let badge = f.nft.get_badge(&badge_id);
+    assert_eq!(badge.owner, f.owner);
+    assert_eq!(badge.metadata.commitment_id, commitment_id);
+    assert_eq!(badge.metadata.compliance_score, 100);
+    assert_eq!(badge.metadata.duration_days, 90);
+
+    // A second attempt is rejected
+    assert_eq!(
+        f.client.try_mint_badge(&f.owner, &f.owner, &commitment_id),
+        Err(Ok(AttestationError::BadgeAlreadyIssued))
+    );
+}
+
+#[test]
+fn test_mint_badge_requires_settled_commitment() {
+    let f = setup_badge_env();
+    let commitment_id = String::from_str(&f.e, "badge_c");
+    final_report(&f, &commitment_id);
+
+    assert_eq!(
+        f.client.try_mint_badge(&f.owner, &f.owner, &commitment_id),
+        Err(Ok(AttestationError::NotSettled))
+    );
+}
+
+#[test]
+fn test_mint_badge_below_threshold_rejected() {
+    let f = setup_badge_env();
+    let commitment_id = String::from_str(&f.e, "badge_c");
+
+    let mut data = Map::new(&f.e);
+    data.set(
+        String::from_str(&f.e, "violation_type"),
+        String::from_str(&f.e, "max_loss"),
+    );
+    data.set(
+        String::from_str(&f.e, "severity"),
+        String::from_str(&f.e, "low"),
+    );
+    f.client.attest(
+        &f.admin,
+        &commitment_id,
+        &String::from_str(&f.e, "violation"),
+        &data,
+        &false,
+    );
+
+    f.e.ledger().with_mut(|l| l.timestamp += 90 * 86400);
+    f.core.settle(&commitment_id);
+    final_report(&f, &commitment_id);
+
+    assert_eq!(
+        f.client.try_mint_badge(&f.owner, &f.owner, &commitment_id),
+        Err(Ok(AttestationError::BadgeCriteriaNotMet))
+    );
+    assert_eq!(f.client.get_commitment_badge(&commitment_id), None);
+}
+
+#[test]
+fn test_mint_badge_for_other_owner_rejected() {
+    let f = setup_badge_env();
+    let commitment_id = String::from_str(&f.e, "badge_c");
+    f.e.ledger().with_mut(|l| l.timestamp += 90 * 86400);
+    f.core.settle(&commitment_id);
+
+    let stranger = Address::generate(&f.e);
+    assert_eq!(
+        f.client.try_mint_badge(&stranger, &stranger, &commitment_id),
+        Err(Ok(AttestationError::Unauthorized))
+    );
+}
+
+#[test]
+fn test_set_badge_config_rejects_invalid_score() {
+    let f = setup_badge_env();
+    assert_eq!(
+        f.client
+            .try_set_badge_config(&f.admin, &f.nft.address, &101, &90),
+        Err(Ok(AttestationError::InvalidBadgeConfig))
+    );
+}
+
+// ============================================================================
+// Audit reports
+// ============================================================================
+
+#[test]
+fn test_record_audit_for_commitment() {
+    let (e, admin, commitment_core_id, contract_id) = setup_test_env();
+    let client = AttestationEngineContractClient::new(&e, &contract_id);
+    let owner = Address::generate(&e);
+    store_core_commitment(
+        &e,
+        &commitment_core_id,
+        "audit_c",
+        &owner,
+        1000,
+        1000,
+        10,
+        30,
+        0,
+    );
+
+    let auditor = Address::generate(&e);
+    client.add_auditor(&admin, &auditor);
+    assert!(client.is_auditor(&auditor));
+
+    let scope = AuditScope::Commitment(String::from_str(&e, "audit_c"));
+    let hash = BytesN::from_array(&e, &[7u8; 32]);
+    let uri = String::from_str(&e, "ipfs://report-q1");
+    assert_eq!(client.record_audit(&auditor, &scope, &hash, &uri, &0, &100), 0);
+    let next_hash = BytesN::from_array(&e, &[8u8; 32]);
+    assert_eq!(
+        client.record_audit(&auditor, &scope, &next_hash, &uri, &100, &200),
+        1
+    );
+
+    let audits = client.get_audits(&scope, &0, &10);
+    assert_eq!(audits.len(), 2);
+    let first = audits.get(0).unwrap();
+    assert_eq!(first.sha256, hash);
+    assert_eq!(first.recorded_by, auditor);
+    assert_eq!((first.period_start, first.period_end), (0, 100));
+    assert_eq!(client.get_audits(&scope, &1, &10).len(), 1);
+    assert_eq!(client.get_audits(&AuditScope::Protocol, &0, &10).len(), 0);
+
+    let missing = AuditScope::Commitment(String::from_str(&e, "missing"));
+    assert_eq!(
+        client.try_record_audit(&auditor, &missing, &hash, &uri, &0, &100),
+        Err(Ok(AttestationError::CommitmentNotFound))
+    );
+}
+
+#[test]
+fn test_record_protocol_wide_audit() {
+    let (e, admin, _, contract_id) = setup_test_env();
+    let client = AttestationEngineContractClient::new(&e, &contract_id);
+    let hash = BytesN::from_array(&e, &[1u8; 32]);
+    let uri = String::from_str(&e, "https://audits.example/protocol-2025.pdf");
+
+    client.record_audit(&admin, &AuditScope::Protocol, &hash, &uri, &10, &20);
+    assert_eq!(client.get_audit_count(&AuditScope::Protocol), 1);
+    assert_eq!(
+        client.get_audits(&AuditScope::Protocol, &0, &10).get(0).unwrap().uri,
+        uri
+    );
+
+    assert_eq!(
+        client.try_record_audit(&admin, &AuditScope::Protocol, &hash, &uri, &20, &10),
+        Err(Ok(AttestationError::InvalidAuditPeriod))
+    );
+    let stranger = Address::generate(&e);
+    assert_eq!(
+        client.try_record_audit(&stranger, &AuditScope::Protocol, &hash, &uri, &30, &40),
+        Err(Ok(AttestationError::Unauthorized))
+    );
+}
+
+#[test]
+fn test_duplicate_audit_hash_rejected() {
+    let (e, admin, _, contract_id) = setup_test_env();
+    let client = AttestationEngineContractClient::new(&e, &contract_id);
+    let hash = BytesN::from_array(&e, &[9u8; 32]);
+    let uri = String::from_str(&e, "ipfs://report");
+
+    client.record_audit(&admin, &AuditScope::Protocol, &hash, &uri, &0, &100);
+    assert_eq!(
+        client.try_record_audit(&admin, &AuditScope::Protocol, &hash, &uri, &0, &100),
+        Err(Ok(AttestationError::DuplicateAudit))
+    );
+    // The same document may be anchored for a different period
+    client.record_audit(&admin, &AuditScope::Protocol, &hash, &uri, &100, &200);
+    assert_eq!(client.get_audit_count(&AuditScope::Protocol), 2);
+}
+
+// ============================================================================
+// Score breakdown
+// ============================================================================
+
+#[test]
+fn test_score_breakdown_freshness_changes_with_time_only() {
+    let (e, admin, commitment_core_id, contract_id) = setup_test_env();
+    let client = AttestationEngineContractClient::new(&e, &contract_id);
+    let owner = Address::generate(&e);
+    e.ledger().with_mut(|l| l.timestamp = 10_000);
+    store_core_commitment(&e, &commitment_core_id, "c_1", &owner, 1000, 1000, 10, 30, 1000);
+    let commitment_id = String::from_str(&e, "c_1");
+
+    let mut data = Map::new(&e);
+    data.set(
+        String::from_str(&e, "violation_type"),
+        String::from_str(&e, "max_loss"),
+    );
+    data.set(String::from_str(&e, "severity"), String::from_str(&e, "low"));
+    client.attest(
+        &admin,
+        &commitment_id,
+        &String::from_str(&e, "violation"),
+        &data,
+        &false,
+    );
+
+    let live = client.get_score_breakdown(&commitment_id);
+    assert_eq!(live.violation_penalty_applied, 10);
+    assert_eq!(live.drawdown_component, 0);
+    assert_eq!(live.fee_component, 0);
+    assert_eq!(live.freshness_component, 10);
+    assert_eq!(live.score, 100);
+    assert_eq!(client.calculate_compliance_score(&commitment_id), live.score);
+
+    // Past expiry with no new data only the freshness bonus goes away
+    e.ledger().with_mut(|l| l.timestamp = 1000 + 31 * 86400);
+    let expired = client.get_score_breakdown(&commitment_id);
+    assert_eq!(expired.freshness_component, 0);
+    assert_eq!(expired.violation_penalty_applied, live.violation_penalty_applied);
+    assert_eq!(expired.drawdown_component, live.drawdown_component);
+    assert_eq!(expired.fee_component, live.fee_component);
+    assert_eq!(expired.score, 90);
+    assert_eq!(client.calculate_compliance_score(&commitment_id), expired.score);
+}
+
+#[test]
+fn test_score_breakdown_drawdown_and_fee_components() {
+    let (e, admin, commitment_core_id, contract_id) = setup_test_env();
+    let client = AttestationEngineContractClient::new(&e, &contract_id);
+    let owner = Address::generate(&e);
+    e.ledger().with_mut(|l| l.timestamp = 10_000);
+    // 25% drawdown against a 10% limit
+    store_core_commitment(&e, &commitment_core_id, "c_1", &owner, 1000, 750, 10, 30, 1000);
+    let commitment_id = String::from_str(&e, "c_1");
+
+    let before = client.get_score_breakdown(&commitment_id);
+    assert_eq!(before.drawdown_component, -15);
+    assert_eq!(before.fee_component, 0);
+    assert_eq!(before.violation_penalty_applied, 0);
+    assert_eq!(before.score, 95);
+    assert_eq!(before.weights_used.drawdown_per_percent, 1);
+
+    // Half of the 1000 min_fee_threshold earns a 50 point bonus
+    client.record_fees(&admin, &commitment_id, &500);
+    let after = client.get_score_breakdown(&commitment_id);
+    assert_eq!(after.fee_component, 50);
+    assert_eq!(after.drawdown_component, before.drawdown_component);
+    assert_eq!(after.freshness_component, before.freshness_component);
+    assert_eq!(after.score, 100);
+}
+
+#[test]
+fn test_settlement_report_includes_score_breakdown() {
+    let f = setup_badge_env();
+    let commitment_id = String::from_str(&f.e, "badge_c");
+
+    assert_eq!(
+        f.client.try_get_settlement_report(&commitment_id),
+        Err(Ok(AttestationError::NotSettled))
+    );
+
+    f.e.ledger().with_mut(|l| l.timestamp += 90 * 86400);
+    f.core.settle(&commitment_id);
+    final_report(&f, &commitment_id);
+
+    let report = f.client.get_settlement_report(&commitment_id);
+    assert_eq!(report.owner, f.owner);
+    assert_eq!(report.status, String::from_str(&f.e, "settled"));
+    assert_eq!(report.attestation_count, 1);
+    assert_eq!(report.score_breakdown, f.client.get_score_breakdown(&commitment_id));
+    assert_eq!(report.score_breakdown.freshness_component, 0);
+    assert_eq!(
+        report.score_breakdown.score,
+        f.client.calculate_compliance_score(&commitment_id)
+    );
+}
+
+#[test]
+fn test_archive_attestation_data_keeps_report_and_summaries() {
+    let f = setup_badge_env();
+    let commitment_id = String::from_str(&f.e, "badge_c");
+    let mut fee_data = Map::new(&f.e);
+    fee_data.set(String::from_str(&f.e, "fee_amount"), String::from_str(&f.e, "40"));
+    f.client.attest(
+        &f.admin,
+        &commitment_id,
+        &String::from_str(&f.e, "fee_generation"),
+        &fee_data,
+        &true,
+    );
+
+    // Still active: nothing to archive yet
+    assert_eq!(
+        f.client.try_archive_attestation_data(&f.owner, &commitment_id),
+        Err(Ok(AttestationError::NotSettled))
+    );
+
+    f.e.ledger().with_mut(|l| l.timestamp += 90 * 86400);
+    f.core.settle(&commitment_id);
+    final_report(&f, &commitment_id);
+    let report = f.client.get_settlement_report(&commitment_id);
+    assert_eq!(report.attestation_count, 2);
+
+    assert_eq!(
+        f.client.try_archive_attestation_data(&Address::generate(&f.e), &commitment_id),
+        Err(Ok(AttestationError::Unauthorized))
+    );
+    let archive = f.client.archive_attestation_data(&f.owner, &commitment_id);
+    assert_eq!(archive.attestation_count, 2);
+    assert_eq!(archive.daily_summaries.len(), 2);
+    let first_day = archive.daily_summaries.get(0).unwrap();
+    assert_eq!(first_day.attestation_count, 1);
+    assert_eq!(first_day.fees_generated, 40);
+    assert_eq!(archive.daily_summaries.get(1).unwrap().fees_generated, 0);
+
+    // Raw entries are gone, the report and count are unchanged
+    assert_eq!(f.client.get_attestations(&commitment_id).len(), 0);
+    assert_eq!(f.client.get_stored_health_metrics(&commitment_id), None);
+    assert_eq!(f.client.get_attestation_count(&commitment_id), 2);
+    assert_eq!(f.client.get_settlement_report(&commitment_id), report);
+    assert_eq!(f.client.get_score_breakdown(&commitment_id), report.score_breakdown);
+
+    let history = f.client.get_attestation_history(&commitment_id);
+    assert!(history.archived);
+    assert_eq!(history.daily_summaries, archive.daily_summaries);
+    assert_eq!(f.client.get_archive(&commitment_id), Some(archive));
+
+    assert_eq!(
+        f.client.try_archive_attestation_data(&f.admin, &commitment_id),
+        Err(Ok(AttestationError::CommitmentArchived))
+    );
+    assert_eq!(
+        f.client.try_attest(
+            &f.admin,
+            &commitment_id,
+            &String::from_str(&f.e, "health_check"),
+            &Map::new(&f.e),
+            &true,
+        ),
+        Err(Ok(AttestationError::CommitmentArchived))
+    );
+}
+
+#[test]
+fn test_archive_pending_commitment_not_settled() {
+    let f = setup_badge_env();
+    let commitment_id = String::from_str(&f.e, "badge_c");
+    let core_id = f.core.address.clone();
+    f.e.as_contract(&core_id, || {
+        let key = DataKey::Commitment(commitment_id.clone());
+        let mut commitment: CoreCommitment = f.e.storage().persistent().get(&key).unwrap();
+        commitment.status = CoreCommitmentStatus::Pending;
+        f.e.storage().persistent().set(&key, &commitment);
+    });
+
+    // Scheduled but not started: still open, so there is no report to freeze
+    assert_eq!(
+        f.client.try_get_settlement_report(&commitment_id),
+        Err(Ok(AttestationError::NotSettled))
+    );
+    assert_eq!(
+        f.client.try_archive_attestation_data(&f.owner, &commitment_id),
+        Err(Ok(AttestationError::NotSettled))
+    );
+}
+
+// ============================================================================
+// Interface crate drift
+// ============================================================================
+
+#[test]
+fn test_interface_crate_matches_attestation_errors() {
+    extern crate std;
+    use std::format;
+
+    for code in 1..=18u32 {
+        let error = soroban_sdk::Error::from_contract_error(code);
+        let engine_error = AttestationError::try_from(error).expect("engine error code");
+        let iface_error = commitlabs_interfaces::attestation::AttestationError::try_from(error)
+            .expect("interface error code");
+        assert_eq!(format!("{:?}", engine_error), format!("{:?}", iface_error));
+    }
+    assert!(AttestationError::try_from(soroban_sdk::Error::from_contract_error(19)).is_err());
+}
+
+#[test]
+fn test_attestation_route_added_to_event_topics() {
+    let f = setup_badge_env();
+    let commitment_id = String::from_str(&f.e, "badge_c");
+    let route = symbol_short!("ops_pager");
+    let health_check = String::from_str(&f.e, "health_check");
+
+    assert_eq!(
+        f.client.try_set_attestation_route(
+            &commitment_id,
+            &Address::generate(&f.e),
+            &route
+        ),
+        Err(Ok(AttestationError::Unauthorized))
+    );
+    f.client
+        .set_attestation_route(&commitment_id, &f.owner, &route);
+    assert_eq!(f.client.get_attestation_route(&commitment_id), Some(route.clone()));
+
+    f.client
+        .attest(&f.admin, &commitment_id, &health_check, &Map::new(&f.e), &true);
+    assert_eq!(
+        f.e.events().all().last().unwrap().1,
+        vec![
+            &f.e,
+            Symbol::new(&f.e, "AttestationRecorded").into_val(&f.e),
+            commitment_id.into_val(&f.e),
+            f.admin.into_val(&f.e),
+            route.into_val(&f.e)
+        ]
+    );
+    f.client.calculate_compliance_score(&commitment_id);
+    assert_eq!(
+        f.e.events().all().last().unwrap().1,
+        vec![
+            &f.e,
+            symbol_short!("ScoreUpd").into_val(&f.e),
+            commitment_id.into_val(&f.e),
+            route.into_val(&f.e)
+        ]
+    );
+
+    f.client.clear_attestation_route(&commitment_id, &f.admin);
+    assert_eq!(f.client.get_attestation_route(&commitment_id), None);
+    f.client
+        .attest(&f.admin, &commitment_id, &health_check, &Map::new(&f.e), &true);
+    assert_eq!(
+        f.e.events().all().last().unwrap().1,
+        vec![
+            &f.e,
+            Symbol::new(&f.e, "AttestationRecorded").into_val(&f.e),
+            commitment_id.into_val(&f.e),
+            f.admin.into_val(&f.e)
+        ]
+    );
+
+    // Archiving drops the route along with the raw data
+    f.client
+        .set_attestation_route(&commitment_id, &f.admin, &route);
+    f.e.ledger().with_mut(|l| l.timestamp += 90 * 86400);
+    f.core.settle(&commitment_id);
+    final_report(&f, &commitment_id);
+    f.client.archive_attestation_data(&f.owner, &commitment_id);
+    assert_eq!(f.client.get_attestation_route(&commitment_id), None);
+}
+
+#[test]
+fn test_two_step_admin_transfer() {
+    let (e, admin, _commitment_core, contract_id) = setup_test_env();
+    let client = AttestationEngineContractClient::new(&e, &contract_id);
+    let new_admin = Address::generate(&e);
+    let stranger = Address::generate(&e);
+    assert_eq!(
+        client.try_accept_admin(&new_admin),
+        Err(Ok(AttestationError::NoPendingAdmin))
+    );
+
+    client.propose_admin(&admin, &new_admin);
+    assert_eq!(client.get_pending_admin(), Some(new_admin.clone()));
+    assert_eq!(
+        client.try_accept_admin(&stranger),
+        Err(Ok(AttestationError::Unauthorized))
+    );
+    assert_eq!(client.get_admin(), admin);
+
+    client.accept_admin(&new_admin);
+    assert_eq!(client.get_admin(), new_admin);
+    assert_eq!(client.get_pending_admin(), None);
+    assert_eq!(
+        client.try_add_verifier(&admin, &stranger),
+        Err(Ok(AttestationError::Unauthorized))
+    );
+    client.add_verifier(&new_admin, &stranger);
+}
+
+#[test]
+fn test_cancel_admin_proposal() {
+    let (e, admin, _commitment_core, contract_id) = setup_test_env();
+    let client = AttestationEngineContractClient::new(&e, &contract_id);
+    let new_admin = Address::generate(&e);
+    client.propose_admin(&admin, &new_admin);
+    assert_eq!(
+        client.try_cancel_admin_proposal(&new_admin),
+        Err(Ok(AttestationError::Unauthorized))
+    );
+
+    client.cancel_admin_proposal(&admin);
+    assert_eq!(client.get_pending_admin(), None);
+    assert_eq!(
+        client.try_accept_admin(&new_admin),
+        Err(Ok(AttestationError::NoPendingAdmin))
+    );
+}
+
+#[test]
+fn test_health_metrics_read_fees_from_core() {
+    let f = setup_badge_env();
+    let commitment_id = String::from_str(&f.e, "badge_c");
+    let allocator = Address::generate(&f.e);
+    f.core.add_allocator(&f.admin, &allocator);
+    assert_eq!(f.client.get_health_metrics(&commitment_id).fees_generated, 0);
+
+    f.core.record_commitment_fees(&allocator, &commitment_id, &120);
+    f.core.record_commitment_fees(&allocator, &commitment_id, &30);
+    assert_eq!(f.client.get_health_metrics(&commitment_id).fees_generated, 150);
+
+    // Settlement leaves the fee ledger in place
+    f.e.ledger().with_mut(|l| l.timestamp += 90 * 86400);
+    f.core.settle(&commitment_id);
+    assert_eq!(f.client.get_health_metrics(&commitment_id).fees_generated, 150);
+}
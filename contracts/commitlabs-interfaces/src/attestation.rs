@@ -0,0 +1,110 @@
+//! attestation_engine types and client
+
+use soroban_sdk::{contractclient, contracterror, contracttype, Address, Env, Map, String, Vec};
+
+#[contracterror(export = false)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum AttestationError {
+    NotInitialized = 1,
+    AlreadyInitialized = 2,
+    Unauthorized = 3,
+    InvalidCommitmentId = 4,
+    InvalidAttestationType = 5,
+    InvalidAttestationData = 6,
+    CommitmentNotFound = 7,
+    StorageError = 8,
+    NotSettled = 9,
+    BadgeCriteriaNotMet = 10,
+    BadgeAlreadyIssued = 11,
+    BadgeNotConfigured = 12,
+    InvalidBadgeConfig = 13,
+    InvalidAuditPeriod = 14,
+    DuplicateAudit = 15,
+    TypeNotPermittedForVerifier = 16,
+    CommitmentArchived = 17,
+    NoPendingAdmin = 18,
+}
+
+#[contracttype(export = false)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Attestation {
+    pub commitment_id: String,
+    pub timestamp: u64,
+    pub attestation_type: String, // "health_check", "violation", "fee_generation", "drawdown"
+    pub data: Map<String, String>,
+    pub is_compliant: bool,
+    pub verified_by: Address,
+}
+
+#[contracttype(export = false)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HealthMetrics {
+    pub commitment_id: String,
+    pub current_value: i128,
+    pub initial_value: i128,
+    pub drawdown_percent: i128,
+    pub fees_generated: i128,
+    pub volatility_exposure: i128,
+    pub last_attestation: u64,
+    pub compliance_score: u32, // 0-100
+}
+
+/// Weights used to compute a compliance score
+#[contracttype(export = false)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ScoreWeights {
+    pub violation_penalty: u32,
+    pub drawdown_per_percent: u32,
+    pub fee_bonus_cap: u32,
+    pub freshness_bonus: u32,
+}
+
+/// Components of a compliance score
+#[contracttype(export = false)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ScoreBreakdown {
+    pub drawdown_component: i32,
+    pub fee_component: i32,
+    pub freshness_component: i32,
+    pub violation_penalty_applied: u32,
+    pub weights_used: ScoreWeights,
+    pub score: u32,
+}
+
+/// Final report for a commitment that is no longer active
+#[contracttype(export = false)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SettlementReport {
+    pub commitment_id: String,
+    pub owner: Address,
+    pub status: String,
+    pub initial_value: i128,
+    pub final_value: i128,
+    pub attestation_count: u64,
+    pub score_breakdown: ScoreBreakdown,
+    pub generated_at: u64,
+}
+
+/// Integrator-facing subset of the attestation_engine interface
+#[contractclient(name = "AttestationEngineClient")]
+pub trait AttestationEngineInterface {
+    /// Record an attestation (authorized verifiers only)
+    fn attest(
+        env: Env,
+        caller: Address,
+        commitment_id: String,
+        attestation_type: String,
+        data: Map<String, String>,
+        is_compliant: bool,
+    ) -> Result<(), AttestationError>;
+    fn get_attestations(env: Env, commitment_id: String) -> Vec<Attestation>;
+    fn get_health_metrics(env: Env, commitment_id: String) -> HealthMetrics;
+    fn verify_compliance(env: Env, commitment_id: String) -> bool;
+    fn calculate_compliance_score(env: Env, commitment_id: String) -> u32;
+    fn get_score_breakdown(env: Env, commitment_id: String) -> ScoreBreakdown;
+    fn get_settlement_report(
+        env: Env,
+        commitment_id: String,
+    ) -> Result<SettlementReport, AttestationError>;
+}
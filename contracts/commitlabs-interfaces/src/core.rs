@@ -0,0 +1,361 @@
+//! commitment_core types and client
+
+pub use shared_utils::RiskProfile;
+use soroban_sdk::{
+    contractclient, contracterror, contracttype, Address, BytesN, Env, String, Symbol, Vec,
+};
+
+#[contracterror(export = false)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum CommitmentError {
+    InvalidDuration = 1,
+    InvalidMaxLossPercent = 2,
+    InvalidCommitmentType = 3,
+    InvalidAmount = 4,
+    InsufficientBalance = 5,
+    TransferFailed = 6,
+    MintingFailed = 7,
+    CommitmentNotFound = 8,
+    Unauthorized = 9,
+    AlreadyInitialized = 10,
+    ReentrancyDetected = 11,
+    NotActive = 12,
+    InvalidStatus = 13,
+    NotInitialized = 14,
+    NotExpired = 15,
+    AssetNotSupported = 16,
+    InvalidTag = 17,
+    TooManyTags = 18,
+    DuplicateTag = 19,
+    TagNotFound = 20,
+    RateLimited = 21,
+    VirtualNotAllowed = 22,
+    InvalidTwaWindow = 23,
+    CollectionNotFound = 24,
+    VoidWindowExpired = 25,
+    HasAllocations = 26,
+    NothingPending = 27,
+    TimelockNotElapsed = 28,
+    DefaultCollectionTimelocked = 29,
+    InvalidFee = 30,
+    DurationTooLong = 31,
+    AmountBelowMinimum = 32,
+    CommitmentFrozen = 33,
+    AmountAboveMaximum = 34,
+    GracePeriodExpired = 35,
+    TooManyCommitments = 36,
+    MigrationRecordMismatch = 37,
+    NotExported = 38,
+    AlreadyMigrated = 39,
+    MigrationFundsMissing = 40,
+    AssetQuarantined = 41,
+    LabelTooLong = 42,
+    UpdateTooFrequent = 43,
+    ExitRequestRequired = 44,
+    ExitRequestPending = 45,
+    IdempotencyKeyReused = 46,
+    DepositCapExceeded = 47,
+    BatchTooLarge = 48,
+    CommitmentExpired = 49,
+    AllocationExceeded = 50,
+}
+
+#[contracttype(export = false)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommitmentRules {
+    pub duration_days: u32,
+    pub max_loss_percent: u32,
+    pub commitment_type: RiskProfile,
+    pub early_exit_penalty: u32,
+    pub min_fee_threshold: i128,
+}
+
+#[contracttype(export = false)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CommitmentStatus {
+    Active,
+    Settled,
+    Violated,
+    EarlyExit,
+    Migrating,
+    Migrated,
+    Cancelled,
+    Pending,
+}
+
+#[contracttype(export = false)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Commitment {
+    pub commitment_id: String,
+    pub owner: Address,
+    pub nft_token_id: u32,
+    pub rules: CommitmentRules,
+    pub amount: i128,
+    pub asset_address: Address,
+    pub created_at: u64,
+    pub expires_at: u64,
+    pub current_value: i128,
+    pub status: CommitmentStatus,
+    pub is_virtual: bool,
+}
+
+/// What settling a commitment would pay out right now
+#[contracttype(export = false)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SettlementPreview {
+    pub commitment_id: String,
+    pub gross_value: i128,
+    pub accrued_management_fee: i128,
+    pub net_payout: i128,
+    pub expires_at: u64,
+    pub is_expired: bool,
+}
+
+/// What `settle` would pay out right now
+#[contracttype(export = false)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SettlementQuote {
+    pub commitment_id: String,
+    pub gross_value: i128,
+    pub penalty: i128,
+    pub protocol_fee: i128,
+    pub net_payout: i128,
+}
+
+/// What `early_exit` would pay out right now
+#[contracttype(export = false)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EarlyExitQuote {
+    pub commitment_id: String,
+    pub gross_value: i128,
+    pub penalty: i128,
+    pub protocol_fee: i128,
+    pub net_payout: i128,
+    pub fee_threshold_met: bool,
+}
+
+/// Penalty, fee and payout of exiting a quoted commitment at one point in its term
+#[contracttype(export = false)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExitQuote {
+    pub elapsed_percent: u32,
+    pub exit_at: u64,
+    pub penalty: i128,
+    pub management_fee: i128,
+    pub payout: i128,
+}
+
+/// What creating a commitment would cost and pay out
+#[contracttype(export = false)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommitmentQuote {
+    pub amount: i128,
+    pub expires_at: u64,
+    pub management_fee_bps: u32,
+    pub early_exit_samples: Vec<ExitQuote>,
+    pub full_term_management_fee: i128,
+    pub full_term_payout: i128,
+    pub worst_case_value: i128,
+    pub worst_case_payout: i128,
+    pub asset_supported: bool,
+    pub within_rate_limit: bool,
+    pub emergency_mode: bool,
+    pub validation_error: u32,
+    pub can_create: bool,
+}
+
+/// A pending two-phase early exit
+#[contracttype(export = false)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExitRequest {
+    pub requested_at: u64,
+    pub executable_at: u64,
+}
+
+/// Inclusive bounds on the amount of a new commitment in one asset
+#[contracttype(export = false)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AssetLimits {
+    pub min_amount: i128,
+    pub max_amount: i128,
+}
+
+/// Total TVL at one point in time
+#[contracttype(export = false)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TvlSnapshot {
+    pub timestamp: u64,
+    pub total_value_locked: i128,
+}
+
+/// Admin-set flags on a commitment
+#[contracttype(export = false)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommitmentFlags {
+    pub frozen: bool,
+    pub frozen_at: u64,
+    pub frozen_reason: Option<Symbol>,
+}
+
+/// Integrator-facing subset of the commitment_core interface
+#[contractclient(name = "CommitmentCoreClient")]
+pub trait CommitmentCoreInterface {
+    /// Lock `amount` of `asset_address` from `owner`; returns the commitment id
+    fn create_commitment(
+        env: Env,
+        owner: Address,
+        amount: i128,
+        asset_address: Address,
+        rules: CommitmentRules,
+    ) -> Result<String, CommitmentError>;
+    /// Like `create_commitment`, but the commitment stays `Pending` until
+    /// `start_at` (at most 30 days ahead) and its term runs from then
+    fn create_commitment_scheduled(
+        env: Env,
+        owner: Address,
+        amount: i128,
+        asset_address: Address,
+        rules: CommitmentRules,
+        start_at: u64,
+    ) -> Result<String, CommitmentError>;
+    /// Like `create_commitment`, but expiring `duration_seconds` (at least an
+    /// hour) from now; `rules.duration_days` becomes the term rounded up
+    fn create_commitment_with_seconds(
+        env: Env,
+        owner: Address,
+        amount: i128,
+        asset_address: Address,
+        rules: CommitmentRules,
+        duration_seconds: u64,
+    ) -> Result<String, CommitmentError>;
+    /// Like `create_commitment`, with an informational label of at most 64 bytes
+    /// (only on builds with the `labels` feature)
+    fn create_commitment_with_label(
+        env: Env,
+        owner: Address,
+        amount: i128,
+        asset_address: Address,
+        rules: CommitmentRules,
+        label: String,
+    ) -> Result<String, CommitmentError>;
+    /// Like `create_commitment`, but a retry with the same owner and key
+    /// returns the first commitment's id instead of creating another
+    fn create_commitment_idempotent(
+        env: Env,
+        owner: Address,
+        amount: i128,
+        asset_address: Address,
+        rules: CommitmentRules,
+        idempotency_key: BytesN<32>,
+    ) -> Result<String, CommitmentError>;
+    /// Make a pending commitment active once its start time has passed
+    fn activate(env: Env, commitment_id: String);
+    /// Costs and outcomes of creating a commitment now, without creating it
+    fn quote_commitment(
+        env: Env,
+        owner: Address,
+        amount: i128,
+        asset: Address,
+        rules: CommitmentRules,
+    ) -> CommitmentQuote;
+    fn get_commitment(env: Env, commitment_id: String) -> Result<Commitment, CommitmentError>;
+    fn get_commitment_label(env: Env, commitment_id: String) -> Option<String>;
+    /// Replace the label (owner only); an empty label removes it
+    fn set_label(env: Env, caller: Address, commitment_id: String, label: String);
+    /// `(status code, expires_at, current_value)`; code 0 = not found
+    fn get_status_light(env: Env, commitment_id: String) -> (u32, u64, i128);
+    fn get_status_light_batch(env: Env, commitment_ids: Vec<String>) -> Vec<(u32, u64, i128)>;
+    fn get_expiring_commitments(
+        env: Env,
+        within_seconds: u64,
+        start: u32,
+        limit: u32,
+    ) -> Vec<String>;
+    fn get_owner_commitments(env: Env, owner: Address) -> Vec<String>;
+    /// Commitment whose NFT in the default collection is `token_id`
+    fn get_commitment_by_token(env: Env, token_id: u32) -> Result<Commitment, CommitmentError>;
+    fn get_commitment_id_by_token(env: Env, token_id: u32) -> String;
+    fn get_total_commitments(env: Env) -> u64;
+    fn get_total_value_locked(env: Env) -> i128;
+    fn get_total_value_locked_by_asset(env: Env, asset: Address) -> i128;
+    /// Gain or loss versus principal realized on settled and exited commitments
+    fn get_realized_pnl(env: Env, asset: Address) -> i128;
+    /// What an owner has been paid out, after penalties and fees, less principal
+    fn get_owner_realized_pnl(env: Env, owner: Address) -> i128;
+    /// `(asset, tvl)` pages; zero-TVL assets only with `include_zero`
+    fn get_tvl_report(env: Env, start: u32, limit: u32, include_zero: bool)
+        -> Vec<(Address, i128)>;
+    /// Daily total-TVL snapshots, oldest first (the newest 90 are kept)
+    fn get_tvl_history(env: Env, start: u32, limit: u32) -> Vec<TvlSnapshot>;
+    fn get_nft_contract(env: Env) -> Address;
+    /// NFT contract that minted the commitment's token
+    fn get_commitment_nft_contract(env: Env, commitment_id: String) -> Address;
+    fn is_asset_supported(env: Env, asset: Address) -> bool;
+    fn get_asset_limits(env: Env, asset: Address) -> Option<AssetLimits>;
+    /// Active commitments allowed per owner (0 = unlimited)
+    fn get_max_commitments_per_owner(env: Env) -> u32;
+    fn get_active_commitment_count(env: Env, owner: Address) -> u32;
+    /// What `owner` may still deposit in `asset` this window (`i128::MAX`
+    /// when uncapped)
+    fn get_remaining_deposit_allowance(env: Env, owner: Address, asset: Address) -> i128;
+    /// Percent of a commitment's value its type may have deployed to pools
+    fn get_allocation_cap(env: Env, profile: RiskProfile) -> u32;
+    fn get_allocated_total(env: Env, commitment_id: String) -> i128;
+    /// False while the commitment is frozen by the admin, or has
+    /// outstanding allocations unless the admin overrides
+    fn is_transferable(env: Env, commitment_id: String) -> bool;
+    /// Whether the admin has frozen the commitment, and why
+    fn get_commitment_flags(env: Env, commitment_id: String) -> CommitmentFlags;
+    /// Returns the new status (`Violated` once the loss limit is exceeded)
+    fn update_value(
+        env: Env,
+        caller: Address,
+        commitment_id: String,
+        new_value: i128,
+    ) -> CommitmentStatus;
+    /// Marks a loss violation `Violated`; expiry alone leaves it active
+    fn check_violations(env: Env, commitment_id: String) -> bool;
+    /// When a loss breach still inside the violation grace period was first seen
+    fn get_pending_violation(env: Env, commitment_id: String) -> Option<u64>;
+    fn preview_settlement(env: Env, commitment_id: String) -> SettlementPreview;
+    /// Payout of settling now; fails where `settle` would
+    fn quote_settlement(
+        env: Env,
+        commitment_id: String,
+    ) -> Result<SettlementQuote, CommitmentError>;
+    /// Payout of exiting early now; fails where `early_exit` would
+    fn quote_early_exit(env: Env, commitment_id: String)
+        -> Result<EarlyExitQuote, CommitmentError>;
+    /// Pay out an expired commitment to its owner, or renew it if auto-renew is on
+    fn settle(env: Env, commitment_id: String);
+    /// Renew instead of paying out at settlement (owner or operator, before expiry)
+    fn set_auto_renew(env: Env, caller: Address, commitment_id: String, enabled: bool);
+    fn is_auto_renew(env: Env, commitment_id: String) -> bool;
+    /// Force-settle a violated commitment net of the early-exit penalty (admin or
+    /// allocator); returns the payout
+    fn liquidate(env: Env, caller: Address, commitment_id: String) -> i128;
+    /// Settle several commitments; returns a success flag per id
+    fn settle_batch(env: Env, commitment_ids: Vec<String>) -> Vec<(String, bool)>;
+    /// Exit before expiry, paying the early-exit penalty (halved once
+    /// allocation fees reach `min_fee_threshold`)
+    fn early_exit(env: Env, commitment_id: String, caller: Address);
+    /// Fees the commitment's allocations have earned
+    fn get_fees_generated(env: Env, commitment_id: String) -> i128;
+    /// Push an active commitment's expiry out (owner only); returns the new expiry
+    fn extend_commitment(env: Env, caller: Address, commitment_id: String, extra_days: u32) -> u64;
+    /// Add capital to an active commitment (owner only); returns the new principal
+    fn top_up(env: Env, caller: Address, commitment_id: String, additional_amount: i128) -> i128;
+    /// Refund the full principal within the grace period after creation (owner or
+    /// operator)
+    fn cancel_commitment(env: Env, caller: Address, commitment_id: String);
+    /// Start a two-phase early exit (types with an exit cooling-off period)
+    fn request_early_exit(env: Env, commitment_id: String, caller: Address) -> ExitRequest;
+    /// Complete a requested early exit after the cooling-off period
+    fn execute_early_exit(env: Env, commitment_id: String, caller: Address);
+    fn cancel_exit_request(env: Env, commitment_id: String, caller: Address);
+    /// Let an operator exit, cancel and toggle auto-renew for the owner (owner only)
+    fn approve_operator(env: Env, owner: Address, operator: Address, commitment_id: String);
+    fn revoke_operator(env: Env, owner: Address, commitment_id: String);
+    fn get_operator(env: Env, commitment_id: String) -> Option<Address>;
+}
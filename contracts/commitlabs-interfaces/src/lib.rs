@@ -0,0 +1,20 @@
+#![no_std]
+
+//! Typed interfaces for calling CommitLabs contracts
+//!
+//! Contains only the contract types, error enums and `contractclient`
+//! generated clients for:
+//! - `core` — commitment_core
+//! - `nft` — commitment_nft
+//! - `attestation` — attestation_engine
+//!
+//! No contract implementations are included, so integrators (and the
+//! CommitLabs contracts themselves) can make cross-contract calls without
+//! depending on the contract crates. Types are declared with
+//! `export = false`: they describe another contract's ABI and do not add
+//! entries to the spec of the contract that uses them. `RiskProfile` is
+//! re-exported from `shared_utils`, where both contracts take it from.
+
+pub mod attestation;
+pub mod core;
+pub mod nft;
@@ -0,0 +1,116 @@
+//! commitment_nft types and client
+
+pub use shared_utils::RiskProfile;
+use soroban_sdk::{contractclient, contracterror, contracttype, Address, Env, String, Vec};
+
+#[contracterror(export = false)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum ContractError {
+    NotInitialized = 1,
+    AlreadyInitialized = 2,
+    TokenNotFound = 3,
+    InvalidTokenId = 4,
+    NotOwner = 5,
+    NotAuthorized = 6,
+    TransferNotAllowed = 7,
+    AlreadySettled = 8,
+    NotExpired = 9,
+    InvalidDuration = 10,
+    InvalidMaxLoss = 11,
+    InvalidCommitmentType = 12,
+    InvalidAmount = 13,
+    ReentrancyDetected = 14,
+    BadgeAlreadyIssued = 15,
+    BadgeNotFound = 16,
+    AssetNotSupported = 17,
+    InvalidAsset = 18,
+    NoPendingAdmin = 19,
+}
+
+/// Metadata associated with a commitment NFT
+#[contracttype(export = false)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommitmentMetadata {
+    pub commitment_id: String,
+    pub duration_days: u32,
+    pub max_loss_percent: u32,
+    pub commitment_type: RiskProfile,
+    pub created_at: u64,
+    pub expires_at: u64,
+    pub initial_amount: i128,
+    pub asset_address: Address,
+}
+
+#[contracttype(export = false)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommitmentNFT {
+    pub owner: Address,
+    pub token_id: u32,
+    pub metadata: CommitmentMetadata,
+    pub is_active: bool,
+    pub early_exit_penalty: u32,
+}
+
+/// Metadata recorded on a compliance badge
+#[contracttype(export = false)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BadgeMetadata {
+    pub commitment_id: String,
+    pub compliance_score: u32,
+    pub duration_days: u32,
+    pub issued_at: u64,
+}
+
+#[contracttype(export = false)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommitmentBadge {
+    pub owner: Address,
+    pub badge_id: u32,
+    pub metadata: BadgeMetadata,
+}
+
+/// Integrator-facing subset of the commitment_nft interface
+#[contractclient(name = "CommitmentNftClient")]
+pub trait CommitmentNftInterface {
+    /// Mint the NFT for a new commitment; returns the token id
+    #[allow(clippy::too_many_arguments)]
+    fn mint(
+        env: Env,
+        caller: Address,
+        owner: Address,
+        commitment_id: String,
+        duration_days: u32,
+        max_loss_percent: u32,
+        commitment_type: RiskProfile,
+        initial_amount: i128,
+        asset_address: Address,
+        early_exit_penalty: u32,
+    ) -> Result<u32, ContractError>;
+    /// Mark an expired token settled
+    fn settle(env: Env, caller: Address, token_id: u32) -> Result<(), ContractError>;
+    /// Remove a token (core contract only)
+    fn burn(env: Env, token_id: u32) -> Result<(), ContractError>;
+    /// Mirror a commitment extension (core contract only)
+    fn extend(
+        env: Env,
+        token_id: u32,
+        duration_days: u32,
+        expires_at: u64,
+    ) -> Result<(), ContractError>;
+    /// Mirror core's maximum commitment duration (core contract only)
+    fn set_max_duration_days(env: Env, max_duration_days: u32) -> Result<(), ContractError>;
+    fn get_metadata(env: Env, token_id: u32) -> Result<CommitmentNFT, ContractError>;
+    fn owner_of(env: Env, token_id: u32) -> Result<Address, ContractError>;
+    fn is_active(env: Env, token_id: u32) -> Result<bool, ContractError>;
+    fn get_owner_tokens(env: Env, owner: Address, offset: u32, limit: u32) -> Vec<u32>;
+    /// Mint a soulbound compliance badge (badge minter only)
+    fn mint_badge(
+        env: Env,
+        owner: Address,
+        commitment_id: String,
+        compliance_score: u32,
+        duration_days: u32,
+    ) -> Result<u32, ContractError>;
+    fn get_badge(env: Env, badge_id: u32) -> Result<CommitmentBadge, ContractError>;
+}
@@ -0,0 +1,68 @@
+//! Builds the default-features release wasm for `wasm_size_tests`.
+//!
+//! Host builds (tests, clippy) run a nested
+//! `cargo build --release --target wasm32v1-none` of this crate into its own
+//! target directory and pass the artifact's path to the tests as
+//! `COMMITMENT_CORE_WASM`. The path is left empty, with a warning, when the
+//! wasm cannot be built (for example without the `wasm32v1-none` target), so
+//! that only the size test fails.
+
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+fn main() {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    for path in [
+        "src",
+        "Cargo.toml",
+        "../shared_utils/src",
+        "../commitlabs-interfaces/src",
+    ] {
+        println!(
+            "cargo:rerun-if-changed={}",
+            manifest_dir.join(path).display()
+        );
+    }
+
+    // The nested build itself, and any other wasm build, needs no artifact
+    if env::var("CARGO_CFG_TARGET_ARCH").as_deref() == Ok("wasm32") {
+        println!("cargo:rustc-env=COMMITMENT_CORE_WASM=");
+        return;
+    }
+
+    // <target>/<profile>/build/commitment_core-<hash>/out
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let target_dir = out_dir
+        .ancestors()
+        .nth(3)
+        .unwrap()
+        .join("commitment_core-wasm");
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".into());
+    let status = Command::new(cargo)
+        .args([
+            "build",
+            "--release",
+            "--target",
+            "wasm32v1-none",
+            "--manifest-path",
+        ])
+        .arg(manifest_dir.join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(&target_dir)
+        // Flags and wrappers of the outer (host or clippy) build do not apply
+        .env_remove("CARGO_ENCODED_RUSTFLAGS")
+        .env_remove("RUSTC_WORKSPACE_WRAPPER")
+        .status();
+
+    let wasm = target_dir.join("wasm32v1-none/release/commitment_core.wasm");
+    match status {
+        Ok(status) if status.success() && wasm.exists() => {
+            println!("cargo:rustc-env=COMMITMENT_CORE_WASM={}", wasm.display());
+        }
+        _ => {
+            println!("cargo:warning=could not build the commitment_core wasm for the size test");
+            println!("cargo:rustc-env=COMMITMENT_CORE_WASM=");
+        }
+    }
+}
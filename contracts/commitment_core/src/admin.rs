@@ -0,0 +1,68 @@
+//! Two-step admin transfer.
+//!
+//! The admin proposes a successor, who takes over only by calling
+//! `accept_admin` with their own key, so a mistyped address can never
+//! receive the contract. Until then the proposal can be replaced or
+//! cancelled. Proposals and handovers are recorded in the audit log (see
+//! `audit`).
+
+use crate::{
+    audit, fail, require_admin, CommitmentCoreContract, CommitmentCoreContractClient,
+    CommitmentError, DataKey,
+};
+use soroban_sdk::{contractimpl, contracttype, symbol_short, Address, Env, Symbol};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum AdminDataKey {
+    PendingAdmin, // Address proposed by the current admin
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Propose `new_admin` as the next admin (admin only), replacing any
+    /// earlier proposal. Emits `AdminProposed`.
+    pub fn propose_admin(e: Env, caller: Address, new_admin: Address) {
+        require_admin(&e, &caller);
+        e.storage()
+            .instance()
+            .set(&AdminDataKey::PendingAdmin, &new_admin);
+        audit::record(&e, &caller, symbol_short!("PropAdmin"), &new_admin);
+        e.events().publish(
+            (Symbol::new(&e, "AdminProposed"), caller),
+            (new_admin, e.ledger().timestamp()),
+        );
+    }
+
+    /// Become admin as the proposed address. Emits `AdminChanged`.
+    pub fn accept_admin(e: Env, caller: Address) {
+        caller.require_auth();
+        let pending = Self::get_pending_admin(e.clone())
+            .unwrap_or_else(|| fail(&e, CommitmentError::NothingPending, "accept_admin"));
+        if caller != pending {
+            fail(&e, CommitmentError::Unauthorized, "accept_admin");
+        }
+        let previous = Self::get_admin(e.clone());
+        e.storage().instance().set(&DataKey::Admin, &caller);
+        e.storage().instance().remove(&AdminDataKey::PendingAdmin);
+        audit::record(&e, &caller, symbol_short!("AcceptAdm"), &previous);
+        e.events().publish(
+            (Symbol::new(&e, "AdminChanged"), previous),
+            (caller, e.ledger().timestamp()),
+        );
+    }
+
+    /// Withdraw the pending admin proposal (admin only)
+    pub fn cancel_admin_proposal(e: Env, caller: Address) {
+        require_admin(&e, &caller);
+        if Self::get_pending_admin(e.clone()).is_none() {
+            fail(&e, CommitmentError::NothingPending, "cancel_admin_proposal");
+        }
+        e.storage().instance().remove(&AdminDataKey::PendingAdmin);
+    }
+
+    /// Address proposed as the next admin, if any
+    pub fn get_pending_admin(e: Env) -> Option<Address> {
+        e.storage().instance().get(&AdminDataKey::PendingAdmin)
+    }
+}
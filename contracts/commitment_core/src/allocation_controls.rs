@@ -0,0 +1,87 @@
+//! Admin controls over pool allocations.
+//!
+//! Per commitment type caps bound the share of a commitment's value that
+//! may be deployed at once (100% by default), and the admin may let a
+//! commitment's NFT change hands while it has outstanding allocations.
+
+use crate::{
+    fail, require_admin, storage_layout, ttl, CommitmentCoreContract, CommitmentCoreContractClient,
+    CommitmentError,
+};
+use shared_utils::RiskProfile;
+use soroban_sdk::{contractimpl, contracttype, symbol_short, Address, Env, String};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum AllocationControlDataKey {
+    AllocationCap(RiskProfile), // commitment_type -> max percent of value deployed
+    TransferOverride(String),   // commitment_id -> true when transfers are allowed while allocated
+}
+
+/// Percent of a commitment's value its type may have deployed.
+pub(crate) fn cap(e: &Env, profile: RiskProfile) -> u32 {
+    CommitmentCoreContract::get_allocation_cap(e.clone(), profile)
+}
+
+/// Whether the admin allows transfers while the commitment is allocated.
+pub(crate) fn transfer_override(e: &Env, commitment_id: &String) -> bool {
+    CommitmentCoreContract::get_transfer_override(e.clone(), commitment_id.clone())
+}
+
+/// Extend a commitment's transfer override (see `ttl::extend`).
+pub(crate) fn extend_ttl(e: &Env, commitment_id: &String, extend_to: u32) {
+    ttl::extend_persistent(
+        e,
+        &AllocationControlDataKey::TransferOverride(commitment_id.clone()),
+        extend_to,
+    );
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Allow (or stop allowing) transfers of a commitment's NFT while it
+    /// has outstanding allocations (admin only).
+    pub fn set_transfer_override(e: Env, caller: Address, commitment_id: String, allowed: bool) {
+        require_admin(&e, &caller);
+        let key = AllocationControlDataKey::TransferOverride(commitment_id.clone());
+        if allowed {
+            storage_layout::set(&e, &key, &true);
+        } else {
+            storage_layout::remove(&e, &key);
+        }
+        e.events()
+            .publish((symbol_short!("XferOvr"), commitment_id), allowed);
+    }
+
+    /// Whether the admin allows transfers while the commitment is allocated
+    pub fn get_transfer_override(e: Env, commitment_id: String) -> bool {
+        storage_layout::get::<_, bool>(
+            &e,
+            &AllocationControlDataKey::TransferOverride(commitment_id),
+        )
+        .unwrap_or(false)
+    }
+
+    /// Cap a commitment type's outstanding allocations at `cap_percent` of a
+    /// commitment's value (admin only). Fails with InvalidAmount above 100.
+    pub fn set_allocation_cap(e: Env, caller: Address, profile: RiskProfile, cap_percent: u32) {
+        require_admin(&e, &caller);
+        if cap_percent > 100 {
+            fail(&e, CommitmentError::InvalidAmount, "set_allocation_cap");
+        }
+        e.storage().instance().set(
+            &AllocationControlDataKey::AllocationCap(profile),
+            &cap_percent,
+        );
+        e.events()
+            .publish((symbol_short!("AllocCap"), profile), cap_percent);
+    }
+
+    /// Percent of a commitment's value its type may have deployed (default 100)
+    pub fn get_allocation_cap(e: Env, profile: RiskProfile) -> u32 {
+        e.storage()
+            .instance()
+            .get::<_, u32>(&AllocationControlDataKey::AllocationCap(profile))
+            .unwrap_or(100)
+    }
+}
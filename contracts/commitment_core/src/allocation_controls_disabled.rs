@@ -0,0 +1,15 @@
+//! Uncapped allocations and no transfer overrides, used when the
+//! `allocation_controls` feature is compiled out.
+
+use shared_utils::RiskProfile;
+use soroban_sdk::{Env, String};
+
+pub(crate) fn cap(_e: &Env, _profile: RiskProfile) -> u32 {
+    100
+}
+
+pub(crate) fn transfer_override(_e: &Env, _commitment_id: &String) -> bool {
+    false
+}
+
+pub(crate) fn extend_ttl(_e: &Env, _commitment_id: &String, _extend_to: u32) {}
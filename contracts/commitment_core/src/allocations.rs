@@ -0,0 +1,190 @@
+//! Per-commitment record of funds deployed to pools.
+//!
+//! `allocate` records each deployment through `record_allocation` and
+//! `deallocate` draws them down oldest first through `release_allocation`;
+//! `get_allocations` lists what is still outstanding. Allocators report the
+//! fees those pools earn through `record_commitment_fees`; once they reach
+//! the commitment's `min_fee_threshold` its early-exit penalty is halved.
+//! Per commitment type caps (see `allocation_controls`) bound the share of a
+//! commitment's value that may be deployed at once. While any allocation is
+//! outstanding the commitment's NFT cannot change hands (`is_transferable`),
+//! so a buyer never inherits funds still out at a pool; the admin may lift
+//! the lock per commitment. Nor can it settle, be liquidated, exit early or migrate
+//! to another deployment (HasAllocations): the owner would be paid only the
+//! undeployed value, so the allocator first deallocates, which a violated
+//! commitment also allows.
+
+use crate::{
+    allocation_controls, disputes, fail, read_commitment, require_allocator, storage_layout,
+    Commitment, CommitmentCoreContract, CommitmentCoreContractClient, CommitmentError,
+    CommitmentStatus,
+};
+use shared_utils::PercentMath;
+use soroban_sdk::{contractimpl, contracttype, symbol_short, Address, Env, String, Vec};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum AllocationDataKey {
+    Allocations(String),   // commitment_id -> Vec<Allocation>
+    FeesGenerated(String), // commitment_id -> i128 fees earned by its allocations
+}
+
+/// One deployment of a commitment's funds to a pool.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Allocation {
+    pub pool: Address,
+    pub amount: i128,
+    pub timestamp: u64,
+}
+
+/// Append an allocation of `amount` to `pool` to the commitment's record.
+pub(crate) fn record_allocation(e: &Env, commitment_id: &String, pool: &Address, amount: i128) {
+    let key = AllocationDataKey::Allocations(commitment_id.clone());
+    let mut allocations = storage_layout::get::<_, Vec<Allocation>>(e, &key).unwrap_or(Vec::new(e));
+    allocations.push_back(Allocation {
+        pool: pool.clone(),
+        amount,
+        timestamp: e.ledger().timestamp(),
+    });
+    storage_layout::set(e, &key, &allocations);
+}
+
+/// Release `amount` of the commitment's outstanding allocations to `pool`,
+/// oldest first. Fails if less than `amount` is allocated to the pool.
+pub(crate) fn release_allocation(e: &Env, commitment_id: &String, pool: &Address, amount: i128) {
+    let key = AllocationDataKey::Allocations(commitment_id.clone());
+    let allocations = storage_layout::get::<_, Vec<Allocation>>(e, &key).unwrap_or(Vec::new(e));
+    let outstanding: i128 = allocations
+        .iter()
+        .filter(|allocation| allocation.pool == *pool)
+        .map(|allocation| allocation.amount)
+        .sum();
+    if outstanding < amount {
+        fail(e, CommitmentError::AllocationExceeded, "deallocate");
+    }
+
+    let mut remaining = amount;
+    let mut kept = Vec::new(e);
+    for mut allocation in allocations.iter() {
+        if remaining > 0 && allocation.pool == *pool {
+            let released = remaining.min(allocation.amount);
+            allocation.amount -= released;
+            remaining -= released;
+            if allocation.amount == 0 {
+                continue;
+            }
+        }
+        kept.push_back(allocation);
+    }
+    if kept.is_empty() {
+        storage_layout::remove(e, &key);
+    } else {
+        storage_layout::set(e, &key, &kept);
+    }
+}
+
+/// `HasAllocations` while any of the commitment's funds are out at a pool.
+pub(crate) fn check_unallocated(e: &Env, commitment_id: &String) -> Result<(), CommitmentError> {
+    if CommitmentCoreContract::get_allocated_total(e.clone(), commitment_id.clone()) > 0 {
+        return Err(CommitmentError::HasAllocations);
+    }
+    Ok(())
+}
+
+/// Whether the commitment's allocations have earned at least its
+/// `min_fee_threshold`. A threshold of 0 never counts as met.
+pub(crate) fn fee_threshold_met(e: &Env, commitment: &Commitment) -> bool {
+    let threshold = commitment.rules.min_fee_threshold;
+    threshold > 0
+        && CommitmentCoreContract::get_fees_generated(e.clone(), commitment.commitment_id.clone())
+            >= threshold
+}
+
+/// Whether deploying `amount` more keeps the commitment's outstanding
+/// allocations within its type's cap of its value (undeployed plus
+/// outstanding).
+pub(crate) fn within_cap(e: &Env, commitment: &Commitment, amount: i128) -> bool {
+    let allocated =
+        CommitmentCoreContract::get_allocated_total(e.clone(), commitment.commitment_id.clone());
+    let cap = allocation_controls::cap(e, commitment.rules.commitment_type);
+    let limit = PercentMath::apply_bps(
+        commitment.current_value + allocated,
+        PercentMath::percent_to_bps(cap),
+    );
+    allocated + amount <= limit
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Outstanding allocations of a commitment, oldest first (empty if none).
+    pub fn get_allocations(e: Env, commitment_id: String) -> Vec<Allocation> {
+        storage_layout::get::<_, Vec<Allocation>>(
+            &e,
+            &AllocationDataKey::Allocations(commitment_id),
+        )
+        .unwrap_or(Vec::new(&e))
+    }
+
+    /// Total of the commitment's outstanding allocations.
+    pub fn get_allocated_total(e: Env, commitment_id: String) -> i128 {
+        Self::get_allocations(e, commitment_id)
+            .iter()
+            .map(|allocation| allocation.amount)
+            .sum()
+    }
+
+    /// Whether the commitment's NFT may be transferred: false while the
+    /// admin has it frozen, otherwise true unless it has outstanding
+    /// allocations and no admin override. Unknown ids are transferable, so
+    /// tokens minted outside core are unaffected.
+    pub fn is_transferable(e: Env, commitment_id: String) -> bool {
+        if disputes::is_frozen(&e, &commitment_id) {
+            return false;
+        }
+        Self::get_allocated_total(e.clone(), commitment_id.clone()) == 0
+            || allocation_controls::transfer_override(&e, &commitment_id)
+    }
+
+    /// Fees (in asset base units) the commitment's allocations have earned.
+    pub fn get_fees_generated(e: Env, commitment_id: String) -> i128 {
+        storage_layout::get::<_, i128>(&e, &AllocationDataKey::FeesGenerated(commitment_id))
+            .unwrap_or(0)
+    }
+
+    /// Record `amount` of fees earned by an active commitment's allocations
+    /// (admin or allocator role only). Returns the new total.
+    pub fn record_commitment_fees(
+        e: Env,
+        caller: Address,
+        commitment_id: String,
+        amount: i128,
+    ) -> i128 {
+        require_allocator(&e, &caller);
+        if amount <= 0 {
+            fail(&e, CommitmentError::InvalidAmount, "record_commitment_fees");
+        }
+        let commitment = read_commitment(&e, &commitment_id).unwrap_or_else(|| {
+            fail(
+                &e,
+                CommitmentError::CommitmentNotFound,
+                "record_commitment_fees",
+            )
+        });
+        if commitment.status != CommitmentStatus::Active {
+            fail(&e, CommitmentError::NotActive, "record_commitment_fees");
+        }
+        let total =
+            Self::get_fees_generated(e.clone(), commitment_id.clone()).saturating_add(amount);
+        storage_layout::set(
+            &e,
+            &AllocationDataKey::FeesGenerated(commitment_id.clone()),
+            &total,
+        );
+        e.events().publish(
+            (symbol_short!("AllocFees"), commitment_id),
+            (amount, total, e.ledger().timestamp()),
+        );
+        total
+    }
+}
@@ -0,0 +1,175 @@
+//! Changes to an active commitment's terms: `extend_commitment` lengthens
+//! the term and `top_up` adds principal, instead of settling and recreating
+//! the commitment.
+
+use crate::{
+    adjust_tvl, asset_limits, commitment_nft_contract, deposit_limits, disputes, duration_limits,
+    exit_requests, expiry_index, fail, publish_event, read_commitment, require_no_reentrancy,
+    set_commitment, set_reentrancy_guard, statement, try_pull_assets, ttl, CommitmentCoreContract,
+    CommitmentCoreContractClient, CommitmentError, CommitmentNftClient, CommitmentStatus,
+    LedgerEntryKind,
+};
+use shared_utils::{EmergencyControl, TimeUtils};
+use soroban_sdk::{contractimpl, symbol_short, Address, Env, String};
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Extend an active commitment by `extra_days` instead of settling and
+    /// recreating it (owner only).
+    ///
+    /// Pushes `expires_at` and `rules.duration_days` forward and mirrors them
+    /// on the NFT. The total duration is capped by `get_max_duration_days`.
+    /// Expired commitments must settle (CommitmentExpired); violated ones
+    /// fail with NotActive. Emits `Extended` with the old and new expiry.
+    pub fn extend_commitment(
+        e: Env,
+        caller: Address,
+        commitment_id: String,
+        extra_days: u32,
+    ) -> u64 {
+        caller.require_auth();
+        EmergencyControl::require_not_emergency(&e);
+        let mut commitment = read_commitment(&e, &commitment_id)
+            .unwrap_or_else(|| fail(&e, CommitmentError::CommitmentNotFound, "extend_commitment"));
+        if commitment.owner != caller {
+            fail(&e, CommitmentError::Unauthorized, "extend_commitment");
+        }
+        if commitment.status != CommitmentStatus::Active {
+            fail(&e, CommitmentError::NotActive, "extend_commitment");
+        }
+        if TimeUtils::now(&e) >= commitment.expires_at {
+            fail(&e, CommitmentError::CommitmentExpired, "extend_commitment");
+        }
+        exit_requests::require_none(&e, &commitment_id, "extend_commitment");
+        if extra_days == 0 {
+            fail(&e, CommitmentError::InvalidDuration, "extend_commitment");
+        }
+
+        // The cap applies to the whole term, so only what is left of it can
+        // be added
+        let remaining_days =
+            duration_limits::max_days(&e).saturating_sub(commitment.rules.duration_days);
+        let old_expires_at = commitment.expires_at;
+        let new_expires_at =
+            TimeUtils::checked_expiration_bounded(old_expires_at, extra_days, remaining_days)
+                .unwrap_or_else(|_| {
+                    fail(&e, CommitmentError::DurationTooLong, "extend_commitment")
+                });
+
+        // EFFECTS
+        commitment.rules.duration_days += extra_days;
+        commitment.expires_at = new_expires_at;
+        set_commitment(&e, &commitment);
+        expiry_index::remove(&e, &commitment_id, old_expires_at);
+        expiry_index::insert(&e, &commitment_id, new_expires_at);
+        ttl::extend(&e, &commitment, ttl::ledgers_until(&e, new_expires_at));
+
+        // INTERACTIONS: the token must not become settleable at the old expiry
+        if !commitment.is_virtual {
+            if let Some(nft_contract) = commitment_nft_contract(&e, &commitment_id) {
+                CommitmentNftClient::new(&e, &nft_contract).extend(
+                    &commitment.nft_token_id,
+                    &commitment.rules.duration_days,
+                    &new_expires_at,
+                );
+            }
+        }
+
+        publish_event(
+            &e,
+            commitment.is_virtual,
+            (symbol_short!("Extended"), commitment_id, caller),
+            (old_expires_at, new_expires_at, e.ledger().timestamp()),
+        );
+        new_expires_at
+    }
+
+    /// Add `additional_amount` to an active commitment (owner only) instead
+    /// of opening a second one. Returns the new principal.
+    ///
+    /// Raises `amount` and `current_value` alike by the amount actually
+    /// received (less than `additional_amount` for a fee-on-transfer token),
+    /// so the loss limit applies to the new principal; TWA mode restates
+    /// recorded values against it. The new principal must stay within the
+    /// asset's limits (AmountBelowMinimum/AmountAboveMaximum) and the top-up
+    /// within the owner's deposit cap (DepositCapExceeded). Settled, violated
+    /// and expired commitments cannot be topped up. Emits `ToppedUp`.
+    pub fn top_up(e: Env, caller: Address, commitment_id: String, additional_amount: i128) -> i128 {
+        require_no_reentrancy(&e);
+        set_reentrancy_guard(&e, true);
+        EmergencyControl::require_not_emergency(&e);
+        caller.require_auth();
+        let mut commitment = read_commitment(&e, &commitment_id).unwrap_or_else(|| {
+            set_reentrancy_guard(&e, false);
+            fail(&e, CommitmentError::CommitmentNotFound, "top_up")
+        });
+        if commitment.owner != caller {
+            set_reentrancy_guard(&e, false);
+            fail(&e, CommitmentError::Unauthorized, "top_up");
+        }
+        if commitment.status != CommitmentStatus::Active {
+            set_reentrancy_guard(&e, false);
+            fail(&e, CommitmentError::NotActive, "top_up");
+        }
+        if TimeUtils::now(&e) >= commitment.expires_at {
+            set_reentrancy_guard(&e, false);
+            fail(&e, CommitmentError::CommitmentExpired, "top_up");
+        }
+        if let Err(err) = disputes::check_not_frozen(&e, &commitment_id) {
+            set_reentrancy_guard(&e, false);
+            fail(&e, err, "top_up");
+        }
+        exit_requests::require_none(&e, &commitment_id, "top_up");
+        let asset = commitment.asset_address.clone();
+        if let Err(err) = commitment
+            .amount
+            .checked_add(additional_amount)
+            .filter(|_| additional_amount > 0)
+            .ok_or(CommitmentError::InvalidAmount)
+            .and_then(|new_amount| asset_limits::check_amount(&e, &asset, new_amount))
+        {
+            set_reentrancy_guard(&e, false);
+            fail(&e, err, "top_up");
+        }
+        if !commitment.is_virtual {
+            if let Err(err) =
+                deposit_limits::check(&e, &commitment.owner, &asset, additional_amount)
+            {
+                set_reentrancy_guard(&e, false);
+                fail(&e, err, "top_up");
+            }
+        }
+
+        // INTERACTIONS: pull the funds first and credit what arrived, as
+        // create_commitment does, so a fee-on-transfer token cannot leave
+        // the principal above the contract's holdings
+        let received = if commitment.is_virtual {
+            additional_amount
+        } else {
+            try_pull_assets(&e, &caller, &asset, additional_amount).unwrap_or_else(|err| {
+                set_reentrancy_guard(&e, false);
+                fail(&e, err, "top_up")
+            })
+        };
+
+        // EFFECTS
+        let new_amount = commitment.amount + received;
+        commitment.amount = new_amount;
+        commitment.current_value = commitment.current_value.saturating_add(received);
+        set_commitment(&e, &commitment);
+        statement::ledger_entry(&e, &commitment, LedgerEntryKind::TopUp, received);
+        if !commitment.is_virtual {
+            adjust_tvl(&e, &asset, received);
+            deposit_limits::record(&e, &commitment.owner, &asset, additional_amount);
+        }
+        set_reentrancy_guard(&e, false);
+
+        publish_event(
+            &e,
+            commitment.is_virtual,
+            (symbol_short!("ToppedUp"), commitment_id, caller),
+            (additional_amount, new_amount, e.ledger().timestamp()),
+        );
+        new_amount
+    }
+}
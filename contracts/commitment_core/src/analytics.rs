@@ -0,0 +1,276 @@
+//! Protocol analytics: reader-gated exports, the metrics heartbeat and the
+//! TVL report.
+//!
+//! Exports are limited to the admin and holders of the reader role, which
+//! the admin grants here. The heartbeat and the TVL report are public; the
+//! heartbeat may be emitted at most once per configured interval. The TVL
+//! history (`tvl_history`) is compiled in with this module.
+
+use crate::{
+    commitments_created, fail, read_commitment, require_admin, CommitmentCoreContract,
+    CommitmentCoreContractClient, CommitmentError, CommitmentStatus, DataKey, MAX_SCAN_PAGE,
+};
+use shared_utils::{AccessControl, EmergencyControl, READER_ROLE};
+use soroban_sdk::{contractimpl, contracttype, symbol_short, Address, Env, Map, Vec};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum AnalyticsDataKey {
+    HeartbeatInterval, // u64 — minimum seconds between heartbeats
+    LastHeartbeat,     // u64 — timestamp of the last heartbeat
+}
+
+/// Default minimum seconds between metrics heartbeats
+pub const DEFAULT_HEARTBEAT_INTERVAL_SECONDS: u64 = 3600;
+
+/// Number of assets reported in a metrics heartbeat
+pub const HEARTBEAT_TOP_ASSETS: u32 = 5;
+
+/// Protocol-wide counters exported for analytics (reader role).
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProtocolSnapshot {
+    pub total_commitments: u64,
+    pub commitments_created: u64,
+    pub total_value_locked: i128,
+    pub tvl_by_asset: Map<Address, i128>, // supported assets only
+    pub timestamp: u64,
+}
+
+/// Coarse protocol metrics emitted by `emit_metrics_heartbeat`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MetricsHeartbeat {
+    pub total_value_locked: i128,
+    pub total_commitments: u64,
+    pub active_commitments: u64,
+    pub top_assets: Vec<(Address, i128)>, // highest per-asset TVL first
+    pub emergency_mode: bool,
+    pub timestamp: u64,
+}
+
+/// Active commitments held by one owner (reader role).
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HolderSummary {
+    pub owner: Address,
+    pub active_commitments: u32,
+    pub value_locked: i128,
+}
+
+/// Commitment status breakdown across the protocol (reader role).
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProtocolHealth {
+    pub active: u64,
+    pub settled: u64,
+    pub violated: u64,
+    pub early_exit: u64,
+    pub total_value_locked: i128,
+    pub timestamp: u64,
+}
+
+/// Gate read-heavy views: the admin or holders of the reader role.
+fn require_reader(e: &Env, caller: &Address, context: &str) {
+    caller.require_auth();
+    let admin = e.storage().instance().get::<_, Address>(&DataKey::Admin);
+    if admin.as_ref() == Some(caller) {
+        return;
+    }
+    if !AccessControl::has_role(e, &READER_ROLE, caller) {
+        fail(e, CommitmentError::Unauthorized, context);
+    }
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Grant the reader role to `reader` (admin only)
+    pub fn add_reader(e: Env, caller: Address, reader: Address) {
+        require_admin(&e, &caller);
+        AccessControl::grant_role(&e, &READER_ROLE, &reader);
+        e.events()
+            .publish((symbol_short!("RoleGrant"), READER_ROLE), reader);
+    }
+
+    /// Revoke the reader role from `reader` (admin only)
+    pub fn remove_reader(e: Env, caller: Address, reader: Address) {
+        require_admin(&e, &caller);
+        AccessControl::revoke_role(&e, &READER_ROLE, &reader);
+        e.events()
+            .publish((symbol_short!("RoleRevok"), READER_ROLE), reader);
+    }
+
+    /// Check whether an address holds the reader role
+    pub fn is_reader(e: Env, address: Address) -> bool {
+        AccessControl::has_role(&e, &READER_ROLE, &address)
+    }
+
+    /// Export protocol-wide counters (admin or reader only)
+    pub fn export_protocol_snapshot(e: Env, caller: Address) -> ProtocolSnapshot {
+        require_reader(&e, &caller, "export_protocol_snapshot");
+        let mut tvl_by_asset = Map::new(&e);
+        for asset in Self::get_supported_assets(e.clone()).iter() {
+            let tvl = Self::get_total_value_locked_by_asset(e.clone(), asset.clone());
+            tvl_by_asset.set(asset, tvl);
+        }
+        ProtocolSnapshot {
+            total_commitments: Self::get_total_commitments(e.clone()),
+            commitments_created: commitments_created(&e),
+            total_value_locked: Self::get_total_value_locked(e.clone()),
+            tvl_by_asset,
+            timestamp: e.ledger().timestamp(),
+        }
+    }
+
+    /// Summarize active commitments per owner (admin or reader only).
+    ///
+    /// Scans commitment ids `c_{offset}..c_{offset + limit}` (at most
+    /// `MAX_SCAN_PAGE`); callers page through `commitments_created` and merge
+    /// the results.
+    pub fn export_holders(e: Env, caller: Address, offset: u64, limit: u64) -> Vec<HolderSummary> {
+        require_reader(&e, &caller, "export_holders");
+        let end = offset
+            .saturating_add(limit.min(MAX_SCAN_PAGE))
+            .min(commitments_created(&e));
+        let mut holders: Map<Address, (u32, i128)> = Map::new(&e);
+        for i in offset..end {
+            let id = Self::generate_commitment_id(&e, i);
+            if let Some(commitment) = read_commitment(&e, &id) {
+                if commitment.status != CommitmentStatus::Active || commitment.is_virtual {
+                    continue;
+                }
+                let (count, value) = holders.get(commitment.owner.clone()).unwrap_or((0, 0));
+                holders.set(commitment.owner, (count + 1, value + commitment.amount));
+            }
+        }
+        let mut summaries = Vec::new(&e);
+        for (owner, (active_commitments, value_locked)) in holders.iter() {
+            summaries.push_back(HolderSummary {
+                owner,
+                active_commitments,
+                value_locked,
+            });
+        }
+        summaries
+    }
+
+    /// Count commitments by status across the protocol (admin or reader
+    /// only).
+    ///
+    /// Read from the `get_stats` counters rather than a storage scan, so
+    /// active includes pending and early exits include cancellations;
+    /// migrating and migrated commitments are not counted.
+    pub fn get_protocol_health(e: Env, caller: Address) -> ProtocolHealth {
+        require_reader(&e, &caller, "get_protocol_health");
+        let stats = Self::get_stats(e.clone());
+        ProtocolHealth {
+            active: stats.active_count,
+            settled: stats.settled_count,
+            violated: stats.violated_count,
+            early_exit: stats.early_exit_count,
+            total_value_locked: stats.total_value_locked,
+            timestamp: e.ledger().timestamp(),
+        }
+    }
+
+    /// Emit one event with coarse protocol metrics for lightweight indexers.
+    ///
+    /// Callable by anyone, at most once per heartbeat interval. Reports the
+    /// `HEARTBEAT_TOP_ASSETS` tracked assets with the highest TVL.
+    pub fn emit_metrics_heartbeat(e: Env) -> MetricsHeartbeat {
+        let now = e.ledger().timestamp();
+        if let Some(last) = e
+            .storage()
+            .instance()
+            .get::<_, u64>(&AnalyticsDataKey::LastHeartbeat)
+        {
+            let interval = Self::get_heartbeat_interval(e.clone());
+            if now < last.saturating_add(interval) {
+                fail(
+                    &e,
+                    CommitmentError::UpdateTooFrequent,
+                    "emit_metrics_heartbeat",
+                );
+            }
+        }
+        e.storage()
+            .instance()
+            .set(&AnalyticsDataKey::LastHeartbeat, &now);
+
+        // Selection sort over the tracked set; only the top N are kept
+        let mut remaining = Vec::new(&e);
+        for asset in Self::get_tracked_assets(e.clone()).iter() {
+            let tvl = Self::get_total_value_locked_by_asset(e.clone(), asset.clone());
+            remaining.push_back((asset, tvl));
+        }
+        let mut top_assets = Vec::new(&e);
+        while top_assets.len() < HEARTBEAT_TOP_ASSETS && !remaining.is_empty() {
+            let mut best = 0;
+            for i in 1..remaining.len() {
+                if remaining.get(i).unwrap().1 > remaining.get(best).unwrap().1 {
+                    best = i;
+                }
+            }
+            top_assets.push_back(remaining.get(best).unwrap());
+            remaining.remove(best);
+        }
+
+        let heartbeat = MetricsHeartbeat {
+            total_value_locked: Self::get_total_value_locked(e.clone()),
+            total_commitments: Self::get_total_commitments(e.clone()),
+            active_commitments: Self::get_active_commitments(e.clone()),
+            top_assets,
+            emergency_mode: EmergencyControl::is_emergency_mode(&e),
+            timestamp: now,
+        };
+        e.events()
+            .publish((symbol_short!("Heartbeat"),), heartbeat.clone());
+        heartbeat
+    }
+
+    /// Set the minimum seconds between metrics heartbeats (admin only)
+    pub fn set_heartbeat_interval(e: Env, caller: Address, interval_seconds: u64) {
+        require_admin(&e, &caller);
+        e.storage()
+            .instance()
+            .set(&AnalyticsDataKey::HeartbeatInterval, &interval_seconds);
+    }
+
+    /// Get the minimum seconds between metrics heartbeats
+    pub fn get_heartbeat_interval(e: Env) -> u64 {
+        e.storage()
+            .instance()
+            .get::<_, u64>(&AnalyticsDataKey::HeartbeatInterval)
+            .unwrap_or(DEFAULT_HEARTBEAT_INTERVAL_SECONDS)
+    }
+
+    /// Up to `limit` `(asset, tvl)` pairs from position `start` of the
+    /// tracked assets, in the order they were first committed.
+    ///
+    /// Assets whose TVL is back to zero are skipped (and not counted by
+    /// `start`) unless `include_zero` is set.
+    pub fn get_tvl_report(
+        e: Env,
+        start: u32,
+        limit: u32,
+        include_zero: bool,
+    ) -> Vec<(Address, i128)> {
+        let end = start.saturating_add(limit);
+        let mut report = Vec::new(&e);
+        let mut matched = 0u32;
+        for asset in Self::get_tracked_assets(e.clone()).iter() {
+            if matched >= end {
+                break;
+            }
+            let tvl = Self::get_total_value_locked_by_asset(e.clone(), asset.clone());
+            if tvl == 0 && !include_zero {
+                continue;
+            }
+            if matched >= start {
+                report.push_back((asset, tvl));
+            }
+            matched += 1;
+        }
+        report
+    }
+}
@@ -0,0 +1,89 @@
+//! Per-asset minimum and maximum commitment amounts.
+//!
+//! `create_commitment` (and `quote_commitment`) check the amount against the
+//! asset's limits through `check_amount`, right after the positive-amount
+//! check. Assets without limits accept any positive amount. Kept under its
+//! own storage key enum like `fees`.
+
+use crate::{
+    fail, require_admin, CommitmentCoreContract, CommitmentCoreContractClient, CommitmentError,
+};
+use soroban_sdk::{contractimpl, contracttype, symbol_short, Address, Env, Symbol};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum AssetLimitsDataKey {
+    Limits(Address), // asset -> AssetLimits
+}
+
+/// Inclusive bounds on the amount of a new commitment in one asset.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AssetLimits {
+    pub min_amount: i128,
+    pub max_amount: i128,
+}
+
+/// `AmountBelowMinimum` or `AmountAboveMaximum` if `amount` is outside the
+/// asset's limits.
+pub(crate) fn check_amount(e: &Env, asset: &Address, amount: i128) -> Result<(), CommitmentError> {
+    match e
+        .storage()
+        .instance()
+        .get::<_, AssetLimits>(&AssetLimitsDataKey::Limits(asset.clone()))
+    {
+        Some(limits) if amount < limits.min_amount => Err(CommitmentError::AmountBelowMinimum),
+        Some(limits) if amount > limits.max_amount => Err(CommitmentError::AmountAboveMaximum),
+        _ => Ok(()),
+    }
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Bound new commitments in `asset` to `min_amount..=max_amount`. Admin only.
+    ///
+    /// Fails with InvalidAmount unless 0 <= min_amount <= max_amount. Existing
+    /// commitments are not affected.
+    pub fn set_asset_limits(
+        e: Env,
+        caller: Address,
+        asset: Address,
+        min_amount: i128,
+        max_amount: i128,
+    ) {
+        require_admin(&e, &caller);
+        if min_amount < 0 || min_amount > max_amount {
+            fail(&e, CommitmentError::InvalidAmount, "set_asset_limits");
+        }
+        e.storage().instance().set(
+            &AssetLimitsDataKey::Limits(asset.clone()),
+            &AssetLimits {
+                min_amount,
+                max_amount,
+            },
+        );
+        e.events().publish(
+            (symbol_short!("AssetLim"), asset),
+            (min_amount, max_amount, e.ledger().timestamp()),
+        );
+    }
+
+    /// Drop the limits on `asset`. Admin only.
+    pub fn remove_asset_limits(e: Env, caller: Address, asset: Address) {
+        require_admin(&e, &caller);
+        e.storage()
+            .instance()
+            .remove(&AssetLimitsDataKey::Limits(asset.clone()));
+        e.events().publish(
+            (Symbol::new(&e, "AssetLimRm"), asset),
+            e.ledger().timestamp(),
+        );
+    }
+
+    /// Limits on new commitments in `asset`, if set.
+    pub fn get_asset_limits(e: Env, asset: Address) -> Option<AssetLimits> {
+        e.storage()
+            .instance()
+            .get::<_, AssetLimits>(&AssetLimitsDataKey::Limits(asset))
+    }
+}
@@ -0,0 +1,45 @@
+//! Display metadata (symbol, decimals) for supported assets.
+//!
+//! Purely informational: the contract never reads it, front ends do.
+
+use crate::{require_admin, CommitmentCoreContract, CommitmentCoreContractClient};
+use soroban_sdk::{contractimpl, contracttype, Address, Env, String};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum AssetMetadataDataKey {
+    AssetMetadata(Address), // asset -> AssetMetadata (optional)
+}
+
+/// Metadata for a supported asset (symbol, decimals).
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AssetMetadata {
+    pub symbol: String,
+    pub decimals: u32,
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Set optional metadata for an asset (symbol, decimals). Admin only.
+    pub fn set_asset_metadata(
+        e: Env,
+        caller: Address,
+        asset: Address,
+        symbol: String,
+        decimals: u32,
+    ) {
+        require_admin(&e, &caller);
+        let meta = AssetMetadata { symbol, decimals };
+        e.storage()
+            .instance()
+            .set(&AssetMetadataDataKey::AssetMetadata(asset), &meta);
+    }
+
+    /// Get metadata for an asset, if set.
+    pub fn get_asset_metadata(e: Env, asset: Address) -> Option<AssetMetadata> {
+        e.storage()
+            .instance()
+            .get::<_, AssetMetadata>(&AssetMetadataDataKey::AssetMetadata(asset))
+    }
+}
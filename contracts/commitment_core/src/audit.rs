@@ -0,0 +1,27 @@
+//! Audit log of privileged actions.
+//!
+//! Allocator and oracle grants and revocations, admin proposals and
+//! handovers, fee changes, emergency mode and function pauses, freezes and
+//! supported-asset changes are recorded in the shared `AuditLog`, read back
+//! through `get_audit_log`.
+
+use crate::{CommitmentCoreContract, CommitmentCoreContractClient};
+use shared_utils::{AuditEntry, AuditLog};
+use soroban_sdk::{contractimpl, Address, Env, Symbol, Vec};
+
+/// Record `actor` taking `action` on `subject`.
+pub(crate) fn record(e: &Env, actor: &Address, action: Symbol, subject: &Address) {
+    AuditLog::record(e, actor, action, subject);
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Up to `limit` audit log entries from position `start`, oldest first.
+    ///
+    /// Covers allocator grants and revocations, admin proposals and
+    /// handovers, fee changes, emergency mode and function pauses, and
+    /// supported-asset changes. The newest `AuditLog::MAX_ENTRIES` are kept.
+    pub fn get_audit_log(e: Env, start: u32, limit: u32) -> Vec<AuditEntry> {
+        AuditLog::entries(&e, start, limit)
+    }
+}
@@ -0,0 +1,103 @@
+//! Baskets: an owner's commitments grouped and valued as one position.
+//!
+//! A basket only references commitments; they keep settling, exiting and
+//! transferring on their own. Its value counts the members still active.
+
+use crate::{
+    fail, read_commitment, storage_layout, CommitmentCoreContract, CommitmentCoreContractClient,
+    CommitmentError, CommitmentStatus,
+};
+use soroban_sdk::{contractimpl, contracttype, Address, Env, String, Vec};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum BasketDataKey {
+    NextBasketId, // u32 id the next basket gets
+    Basket(u32),  // basket_id -> Basket until dissolved
+}
+
+/// Most commitments one basket may hold
+pub const MAX_BASKET_SIZE: u32 = 10;
+
+/// A named group of one owner's commitments.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Basket {
+    pub basket_id: u32,
+    pub owner: Address,
+    pub commitment_ids: Vec<String>,
+    pub created_at: u64,
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Group up to `MAX_BASKET_SIZE` of `owner`'s commitments into a basket.
+    /// Returns the new basket's id.
+    ///
+    /// Fails with BatchTooLarge for an empty or oversized list,
+    /// CommitmentNotFound for an unknown id and Unauthorized for a
+    /// commitment `owner` does not own.
+    pub fn create_basket(e: Env, owner: Address, commitment_ids: Vec<String>) -> u32 {
+        owner.require_auth();
+        if commitment_ids.is_empty() || commitment_ids.len() > MAX_BASKET_SIZE {
+            fail(&e, CommitmentError::BatchTooLarge, "create_basket");
+        }
+        let mut ids = Vec::new(&e);
+        for id in commitment_ids.iter() {
+            let commitment = read_commitment(&e, &id)
+                .unwrap_or_else(|| fail(&e, CommitmentError::CommitmentNotFound, "create_basket"));
+            if commitment.owner != owner {
+                fail(&e, CommitmentError::Unauthorized, "create_basket");
+            }
+            if !ids.contains(&id) {
+                ids.push_back(id);
+            }
+        }
+
+        let basket_id = e
+            .storage()
+            .instance()
+            .get::<_, u32>(&BasketDataKey::NextBasketId)
+            .unwrap_or(0);
+        e.storage()
+            .instance()
+            .set(&BasketDataKey::NextBasketId, &(basket_id + 1));
+        let basket = Basket {
+            basket_id,
+            owner,
+            commitment_ids: ids,
+            created_at: e.ledger().timestamp(),
+        };
+        storage_layout::set(&e, &BasketDataKey::Basket(basket_id), &basket);
+        basket_id
+    }
+
+    /// Get a basket, if it exists
+    pub fn get_basket(e: Env, basket_id: u32) -> Option<Basket> {
+        storage_layout::get(&e, &BasketDataKey::Basket(basket_id))
+    }
+
+    /// Current value of a basket's active commitments (0 for an unknown basket)
+    pub fn get_basket_value(e: Env, basket_id: u32) -> i128 {
+        let Some(basket) = Self::get_basket(e.clone(), basket_id) else {
+            return 0;
+        };
+        let mut value = 0i128;
+        for id in basket.commitment_ids.iter() {
+            if let Some(commitment) = read_commitment(&e, &id) {
+                if commitment.status == CommitmentStatus::Active {
+                    value += commitment.current_value;
+                }
+            }
+        }
+        value
+    }
+
+    /// Remove a basket; its commitments are unaffected. Owner only.
+    pub fn dissolve_basket(e: Env, basket_id: u32) {
+        let basket = Self::get_basket(e.clone(), basket_id)
+            .unwrap_or_else(|| fail(&e, CommitmentError::NothingPending, "dissolve_basket"));
+        basket.owner.require_auth();
+        storage_layout::remove(&e, &BasketDataKey::Basket(basket_id));
+    }
+}
@@ -0,0 +1,64 @@
+//! Batched settlement and status reads.
+//!
+//! Keepers settle up to `MAX_SETTLE_BATCH` matured commitments in one call;
+//! indexers read the compact status of up to `MAX_STATUS_BATCH` at once.
+
+use crate::{
+    fail, read_status_light, require_no_reentrancy, set_reentrancy_guard, settle_commitment,
+    CommitmentCoreContract, CommitmentCoreContractClient, CommitmentError, ReceiptKind,
+    MAX_STATUS_BATCH,
+};
+use shared_utils::EmergencyControl;
+use soroban_sdk::{contractimpl, symbol_short, Env, String, Symbol, Vec};
+
+/// Maximum number of ids accepted by `settle_batch`
+pub const MAX_SETTLE_BATCH: u32 = 25;
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// `get_status_light` for up to `MAX_STATUS_BATCH` ids, in order
+    pub fn get_status_light_batch(e: Env, commitment_ids: Vec<String>) -> Vec<(u32, u64, i128)> {
+        if commitment_ids.len() > MAX_STATUS_BATCH {
+            fail(&e, CommitmentError::BatchTooLarge, "get_status_light_batch");
+        }
+        let mut statuses = Vec::new(&e);
+        for commitment_id in commitment_ids.iter() {
+            statuses.push_back(read_status_light(&e, &commitment_id));
+        }
+        statuses
+    }
+
+    /// Settle up to `MAX_SETTLE_BATCH` commitments in one call.
+    ///
+    /// Each id is attempted independently: one that cannot be settled (not
+    /// expired, not active, unknown) is reported as `false` and leaves no
+    /// changes, while the rest still settle. Emits a `BatchSettled` event
+    /// with the number settled and the total paid out.
+    pub fn settle_batch(e: Env, commitment_ids: Vec<String>) -> Vec<(String, bool)> {
+        if commitment_ids.len() > MAX_SETTLE_BATCH {
+            fail(&e, CommitmentError::BatchTooLarge, "settle_batch");
+        }
+        require_no_reentrancy(&e);
+        set_reentrancy_guard(&e, true);
+        EmergencyControl::require_not_paused(&e, &symbol_short!("settle"));
+
+        let mut results = Vec::new(&e);
+        let mut settled = 0u32;
+        let mut total_returned = 0i128;
+        for commitment_id in commitment_ids.iter() {
+            let result = settle_commitment(&e, &commitment_id, ReceiptKind::Settled);
+            if let Ok(returned) = result {
+                settled += 1;
+                total_returned += returned;
+            }
+            results.push_back((commitment_id, result.is_ok()));
+        }
+
+        set_reentrancy_guard(&e, false);
+        e.events().publish(
+            (Symbol::new(&e, "BatchSettled"),),
+            (settled, total_returned, e.ledger().timestamp()),
+        );
+        results
+    }
+}
@@ -83,7 +83,7 @@ fn benchmark_create_commitment() {
     let rules = CommitmentRules {
         duration_days: 30,
         max_loss_percent: 20,
-        commitment_type: String::from_str(&e, "balanced"),
+        commitment_type: RiskProfile::Balanced,
         early_exit_penalty: 10,
         min_fee_threshold: 1000,
     };
@@ -98,7 +98,8 @@ fn benchmark_create_commitment() {
             1000_0000000,
             asset_address.clone(),
             rules.clone(),
-        );
+        )
+        .unwrap();
         let end = e.ledger().sequence();
         metrics.record_gas(start, end);
     });
@@ -116,7 +117,7 @@ fn benchmark_get_commitment() {
     let rules = CommitmentRules {
         duration_days: 30,
         max_loss_percent: 20,
-        commitment_type: String::from_str(&e, "balanced"),
+        commitment_type: RiskProfile::Balanced,
         early_exit_penalty: 10,
         min_fee_threshold: 1000,
     };
@@ -129,13 +130,14 @@ fn benchmark_get_commitment() {
             asset_address.clone(),
             rules.clone(),
         )
+        .unwrap()
     });
 
     let mut metrics = BenchmarkMetrics::new("get_commitment");
 
     e.as_contract(&contract_id, || {
         let start = e.ledger().sequence();
-        CommitmentCoreContract::get_commitment(e.clone(), commitment_id.clone());
+        CommitmentCoreContract::get_commitment(e.clone(), commitment_id.clone()).unwrap();
         let end = e.ledger().sequence();
         metrics.record_gas(start, end);
     });
@@ -153,7 +155,7 @@ fn benchmark_check_violations() {
     let rules = CommitmentRules {
         duration_days: 30,
         max_loss_percent: 20,
-        commitment_type: String::from_str(&e, "balanced"),
+        commitment_type: RiskProfile::Balanced,
         early_exit_penalty: 10,
         min_fee_threshold: 1000,
     };
@@ -166,6 +168,7 @@ fn benchmark_check_violations() {
             asset_address.clone(),
             rules.clone(),
         )
+        .unwrap()
     });
 
     let mut metrics = BenchmarkMetrics::new("check_violations");
@@ -223,7 +226,7 @@ fn benchmark_batch_create_commitments() {
     let rules = CommitmentRules {
         duration_days: 30,
         max_loss_percent: 20,
-        commitment_type: String::from_str(&e, "balanced"),
+        commitment_type: RiskProfile::Balanced,
         early_exit_penalty: 10,
         min_fee_threshold: 1000,
     };
@@ -247,3 +250,47 @@ fn benchmark_batch_create_commitments() {
 
     metrics.print_summary();
 }
+
+#[test]
+fn benchmark_status_light_vs_get_commitment() {
+    let e = Env::default();
+    let (contract_id, _admin, owner) = setup_test_env(&e);
+    let client = CommitmentCoreContractClient::new(&e, &contract_id);
+
+    let commitment_id = String::from_str(&e, "c_0");
+    let commitment = Commitment {
+        commitment_id: commitment_id.clone(),
+        owner,
+        nft_token_id: 1,
+        rules: CommitmentRules {
+            duration_days: 30,
+            max_loss_percent: 20,
+            commitment_type: RiskProfile::Balanced,
+            early_exit_penalty: 10,
+            min_fee_threshold: 1000,
+        },
+        amount: 1000_0000000,
+        asset_address: Address::generate(&e),
+        created_at: 0,
+        expires_at: 30 * 86400,
+        current_value: 1000_0000000,
+        status: CommitmentStatus::Active,
+        is_virtual: false,
+    };
+    e.as_contract(&contract_id, || set_commitment(&e, &commitment));
+
+    // Both calls pay the same instance storage load; the difference is the
+    // record decode and return value size
+    e.budget().reset_default();
+    client.get_commitment(&commitment_id);
+    let full_cpu = e.budget().cpu_instruction_cost();
+    e.budget().reset_default();
+    client.get_status_light(&commitment_id);
+    let light_cpu = e.budget().cpu_instruction_cost();
+    assert!(light_cpu < full_cpu, "light {} vs full {}", light_cpu, full_cpu);
+    assert_eq!(client.get_status_light(&commitment_id).0, STATUS_CODE_ACTIVE);
+
+    let mut metrics = BenchmarkMetrics::new("get_status_light");
+    metrics.record_gas(full_cpu as u32, light_cpu as u32);
+    metrics.print_summary();
+}
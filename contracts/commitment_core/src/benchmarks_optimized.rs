@@ -36,7 +36,7 @@ fn benchmark_create_commitment_storage_reads() {
     let rules = CommitmentRules {
         duration_days: 30,
         max_loss_percent: 20,
-        commitment_type: String::from_str(&env, "balanced"),
+        commitment_type: RiskProfile::Balanced,
         early_exit_penalty: 10,
         min_fee_threshold: 1000,
     };
@@ -70,7 +70,7 @@ fn benchmark_batch_counter_updates() {
     let rules = CommitmentRules {
         duration_days: 30,
         max_loss_percent: 20,
-        commitment_type: String::from_str(&env, "balanced"),
+        commitment_type: RiskProfile::Balanced,
         early_exit_penalty: 10,
         min_fee_threshold: 1000,
     };
@@ -103,7 +103,7 @@ fn benchmark_commitment_id_generation() {
     
     // Generate 100 commitment IDs
     for i in 0..100 {
-        let _id = CommitmentCoreContract::generate_commitment_id(&env, i);
+        let _id = crate::ids::format(&env, Some(1), i);
     }
     
     let cpu_after = env.budget().cpu_instruction_cost();
@@ -126,7 +126,7 @@ fn benchmark_check_violations() {
     let rules = CommitmentRules {
         duration_days: 30,
         max_loss_percent: 20,
-        commitment_type: String::from_str(&env, "balanced"),
+        commitment_type: RiskProfile::Balanced,
         early_exit_penalty: 10,
         min_fee_threshold: 1000,
     };
@@ -211,7 +211,7 @@ fn benchmark_settle_function() {
     let rules = CommitmentRules {
         duration_days: 1, // Short duration for testing
         max_loss_percent: 20,
-        commitment_type: String::from_str(&env, "balanced"),
+        commitment_type: RiskProfile::Balanced,
         early_exit_penalty: 10,
         min_fee_threshold: 1000,
     };
@@ -250,7 +250,7 @@ fn benchmark_memory_usage() {
     let rules = CommitmentRules {
         duration_days: 30,
         max_loss_percent: 20,
-        commitment_type: String::from_str(&env, "balanced"),
+        commitment_type: RiskProfile::Balanced,
         early_exit_penalty: 10,
         min_fee_threshold: 1000,
     };
@@ -0,0 +1,131 @@
+//! Penalty-free cancellation within a grace period after creation.
+//!
+//! For `grace_period` seconds after `created_at` (default 24 hours) the owner
+//! may cancel a commitment and get its full principal back, with no
+//! early-exit penalty or management fee. Later exits go through
+//! `early_exit`.
+
+use crate::{
+    adjust_active_commitments, allocations, clear_value_update_state, disputes, exit_requests,
+    fail, operators, pay_out, publish_event, read_commitment, receipts, require_admin,
+    require_no_reentrancy, set_commitment, set_reentrancy_guard, statement, sync_nft_settled, tags,
+    tvl, CommitmentCoreContract, CommitmentCoreContractClient, CommitmentError, CommitmentStatus,
+    LedgerEntryKind, ReceiptKind,
+};
+use shared_utils::{EmergencyControl, TimeUtils};
+use soroban_sdk::{contractimpl, contracttype, symbol_short, Address, Env, String};
+
+/// Default cancellation grace period: 24 hours
+pub const DEFAULT_GRACE_PERIOD_SECONDS: u64 = 24 * 60 * 60;
+
+#[contracttype]
+#[derive(Clone)]
+pub enum CancellationDataKey {
+    GracePeriod, // u64 seconds after creation during which cancel_commitment is allowed
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Set how long after creation owners may cancel without penalty
+    /// (admin only). `0` disables cancellation.
+    pub fn set_grace_period(e: Env, caller: Address, seconds: u64) {
+        require_admin(&e, &caller);
+        e.storage()
+            .instance()
+            .set(&CancellationDataKey::GracePeriod, &seconds);
+        e.events().publish(
+            (symbol_short!("GraceSet"),),
+            (seconds, e.ledger().timestamp()),
+        );
+    }
+
+    /// Seconds after creation during which `cancel_commitment` is allowed
+    pub fn get_grace_period(e: Env) -> u64 {
+        e.storage()
+            .instance()
+            .get::<_, u64>(&CancellationDataKey::GracePeriod)
+            .unwrap_or(DEFAULT_GRACE_PERIOD_SECONDS)
+    }
+
+    /// Cancel an active or pending commitment within the grace period and
+    /// return its full principal to the owner (owner or operator).
+    ///
+    /// Allowed while `now - created_at <= grace_period`; afterwards fails with
+    /// GracePeriodExpired. Commitments with outstanding allocations fail
+    /// with HasAllocations. Marks the commitment `Cancelled`, releases its
+    /// principal from TVL and deactivates the NFT. Emits `Cancelled`.
+    pub fn cancel_commitment(e: Env, caller: Address, commitment_id: String) {
+        require_no_reentrancy(&e);
+        set_reentrancy_guard(&e, true);
+        EmergencyControl::require_not_emergency(&e);
+        caller.require_auth();
+        let mut commitment = read_commitment(&e, &commitment_id).unwrap_or_else(|| {
+            set_reentrancy_guard(&e, false);
+            fail(&e, CommitmentError::CommitmentNotFound, "cancel_commitment")
+        });
+        if !operators::is_owner_or_operator(&e, &commitment, &caller) {
+            set_reentrancy_guard(&e, false);
+            fail(&e, CommitmentError::Unauthorized, "cancel_commitment");
+        }
+        let open = matches!(
+            commitment.status,
+            CommitmentStatus::Active | CommitmentStatus::Pending
+        );
+        if !open {
+            set_reentrancy_guard(&e, false);
+            fail(&e, CommitmentError::NotActive, "cancel_commitment");
+        }
+        if let Err(err) = disputes::check_not_frozen(&e, &commitment_id) {
+            set_reentrancy_guard(&e, false);
+            fail(&e, err, "cancel_commitment");
+        }
+        let grace_period = Self::get_grace_period(e.clone());
+        let elapsed = TimeUtils::now(&e).saturating_sub(commitment.created_at);
+        if grace_period == 0 || elapsed > grace_period {
+            set_reentrancy_guard(&e, false);
+            fail(&e, CommitmentError::GracePeriodExpired, "cancel_commitment");
+        }
+        if let Err(err) = allocations::check_unallocated(&e, &commitment_id) {
+            set_reentrancy_guard(&e, false);
+            fail(&e, err, "cancel_commitment");
+        }
+
+        // EFFECTS: the principal comes back whatever the value did meanwhile
+        let amount = commitment.amount;
+        let value_change = amount - commitment.current_value;
+        commitment.status = CommitmentStatus::Cancelled;
+        commitment.current_value = 0;
+        set_commitment(&e, &commitment);
+        tags::clear(&e, &commitment);
+        clear_value_update_state(&e, &commitment_id);
+        exit_requests::clear(&e, &commitment_id);
+        adjust_active_commitments(&e, -1);
+        receipts::record(&e, &commitment, ReceiptKind::Cancelled, amount, 0, 0, 0);
+
+        // Virtual commitments hold no funds and have no NFT
+        if !commitment.is_virtual {
+            tvl::release(&e, &commitment, amount);
+            statement::ledger_entry(&e, &commitment, LedgerEntryKind::Adjusted, value_change);
+
+            // INTERACTIONS: refund the principal and deactivate the NFT
+            if pay_out(
+                &e,
+                &commitment_id,
+                &commitment.asset_address,
+                &commitment.owner,
+                amount,
+            ) {
+                statement::ledger_entry(&e, &commitment, LedgerEntryKind::Payout, -amount);
+            }
+            sync_nft_settled(&e, &commitment_id, commitment.nft_token_id);
+        }
+        set_reentrancy_guard(&e, false);
+
+        publish_event(
+            &e,
+            commitment.is_virtual,
+            (symbol_short!("Cancelled"), commitment_id, caller),
+            (amount, e.ledger().timestamp()),
+        );
+    }
+}
@@ -0,0 +1,199 @@
+//! Named NFT collections.
+//!
+//! Besides the default NFT contract, the admin may register collections,
+//! each minted by its own NFT contract. A commitment created in a collection
+//! records it, and settlement and other NFT calls for the commitment are
+//! routed to that collection's contract.
+
+use crate::{
+    duration_limits, fail, has_commitment, require_admin, storage_layout, ttl,
+    CommitmentCoreContract, CommitmentCoreContractClient, CommitmentError, CommitmentRules,
+    DataKey,
+};
+use soroban_sdk::{contractimpl, contracttype, symbol_short, Address, Env, String, Symbol, Vec};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum CollectionDataKey {
+    NftCollection(Symbol),        // collection_id -> NFT contract address
+    NftCollectionIds,             // Vec<Symbol> of registered non-default collections
+    CommitmentCollection(String), // commitment_id -> collection_id (absent = default)
+}
+
+/// Collection id that always resolves to the contract set at initialization
+pub const DEFAULT_COLLECTION: &str = "default";
+
+/// Id of the collection minted through the default NFT contract.
+fn default_collection(e: &Env) -> Symbol {
+    Symbol::new(e, DEFAULT_COLLECTION)
+}
+
+/// Resolve a collection id to its NFT contract. The default collection is the
+/// contract configured at initialization.
+/// NFT contract of a collection; the default collection resolves to the
+/// default NFT contract.
+fn resolve(e: &Env, collection_id: &Symbol) -> Option<Address> {
+    if *collection_id == default_collection(e) {
+        e.storage()
+            .instance()
+            .get::<_, Address>(&DataKey::NftContract)
+    } else {
+        e.storage()
+            .instance()
+            .get::<_, Address>(&CollectionDataKey::NftCollection(collection_id.clone()))
+    }
+}
+
+/// The named collection a new commitment is minted in and its NFT
+/// contract, or None for the default collection. CollectionNotFound for an
+/// unregistered id.
+pub(crate) fn route(
+    e: &Env,
+    collection: Option<Symbol>,
+) -> Result<Option<(Symbol, Address)>, CommitmentError> {
+    match collection.filter(|id| *id != default_collection(e)) {
+        Some(id) => {
+            let nft_contract = resolve(e, &id).ok_or(CommitmentError::CollectionNotFound)?;
+            Ok(Some((id, nft_contract)))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Remember the named collection a commitment was minted in.
+pub(crate) fn record(e: &Env, commitment_id: &String, collection_id: &Symbol) {
+    storage_layout::set(
+        e,
+        &CollectionDataKey::CommitmentCollection(commitment_id.clone()),
+        collection_id,
+    );
+}
+
+/// NFT contract of the named collection a commitment was minted in; None
+/// for the default collection.
+pub(crate) fn nft_contract_of(e: &Env, commitment_id: &String) -> Option<Address> {
+    storage_layout::get::<_, Symbol>(
+        e,
+        &CollectionDataKey::CommitmentCollection(commitment_id.clone()),
+    )
+    .and_then(|collection_id| resolve(e, &collection_id))
+}
+
+/// Drop a commitment's collection (used when a commitment is voided).
+#[cfg(feature = "recovery")]
+pub(crate) fn clear(e: &Env, commitment_id: &String) {
+    storage_layout::remove(
+        e,
+        &CollectionDataKey::CommitmentCollection(commitment_id.clone()),
+    );
+}
+
+/// Extend a commitment's collection entry (see `ttl::extend`).
+pub(crate) fn extend_ttl(e: &Env, commitment_id: &String, extend_to: u32) {
+    ttl::extend_persistent(
+        e,
+        &CollectionDataKey::CommitmentCollection(commitment_id.clone()),
+        extend_to,
+    );
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Create a commitment whose NFT is minted in a registered collection.
+    ///
+    /// `None` (or the default collection id) behaves like `create_commitment`.
+    /// Settlement and other NFT calls for the commitment are routed to the
+    /// collection recorded here.
+    pub fn create_commitment_in_collection(
+        e: Env,
+        owner: Address,
+        amount: i128,
+        asset_address: Address,
+        rules: CommitmentRules,
+        collection: Option<Symbol>,
+    ) -> Result<String, CommitmentError> {
+        Self::create_commitment_internal(
+            e,
+            owner,
+            amount,
+            asset_address,
+            rules,
+            false,
+            collection,
+            None,
+            None,
+        )
+    }
+
+    /// Register (or re-point) an NFT collection (admin only).
+    ///
+    /// The default collection cannot be registered here; it is re-pointed
+    /// through the timelocked `set_nft_contract`. The contract receives the
+    /// current maximum duration if the admin has changed it.
+    pub fn register_nft_collection(
+        e: Env,
+        caller: Address,
+        collection_id: Symbol,
+        nft_contract: Address,
+    ) {
+        require_admin(&e, &caller);
+        if collection_id == default_collection(&e) {
+            fail(
+                &e,
+                CommitmentError::DefaultCollectionTimelocked,
+                "register_nft_collection",
+            );
+        }
+        let mut ids = Self::get_nft_collections(e.clone());
+        if !ids.contains(&collection_id) {
+            ids.push_back(collection_id.clone());
+            e.storage()
+                .instance()
+                .set(&CollectionDataKey::NftCollectionIds, &ids);
+        }
+        e.storage().instance().set(
+            &CollectionDataKey::NftCollection(collection_id.clone()),
+            &nft_contract,
+        );
+        duration_limits::sync_nft(&e, &nft_contract, "register_nft_collection");
+        e.events().publish(
+            (symbol_short!("NftColReg"), collection_id),
+            (nft_contract, e.ledger().timestamp()),
+        );
+    }
+
+    /// Get the NFT contract for a collection id
+    pub fn get_nft_collection(e: Env, collection_id: Symbol) -> Address {
+        resolve(&e, &collection_id).unwrap_or_else(|| {
+            fail(
+                &e,
+                CommitmentError::CollectionNotFound,
+                "get_nft_collection",
+            )
+        })
+    }
+
+    /// List registered collection ids (excluding the default collection)
+    pub fn get_nft_collections(e: Env) -> Vec<Symbol> {
+        e.storage()
+            .instance()
+            .get::<_, Vec<Symbol>>(&CollectionDataKey::NftCollectionIds)
+            .unwrap_or(Vec::new(&e))
+    }
+
+    /// Get the collection a commitment's NFT was minted in
+    pub fn get_commitment_collection(e: Env, commitment_id: String) -> Symbol {
+        if !has_commitment(&e, &commitment_id) {
+            fail(
+                &e,
+                CommitmentError::CommitmentNotFound,
+                "get_commitment_collection",
+            );
+        }
+        storage_layout::get::<_, Symbol>(
+            &e,
+            &CollectionDataKey::CommitmentCollection(commitment_id),
+        )
+        .unwrap_or(default_collection(&e))
+    }
+}
@@ -0,0 +1,26 @@
+//! Collection helpers used when the `collections` feature is compiled out:
+//! every commitment is minted by the default NFT contract.
+
+use crate::CommitmentError;
+use soroban_sdk::{Address, Env, String, Symbol};
+
+pub(crate) fn route(
+    _e: &Env,
+    collection: Option<Symbol>,
+) -> Result<Option<(Symbol, Address)>, CommitmentError> {
+    match collection {
+        Some(_) => Err(CommitmentError::CollectionNotFound),
+        None => Ok(None),
+    }
+}
+
+pub(crate) fn record(_e: &Env, _commitment_id: &String, _collection_id: &Symbol) {}
+
+pub(crate) fn nft_contract_of(_e: &Env, _commitment_id: &String) -> Option<Address> {
+    None
+}
+
+#[cfg(feature = "recovery")]
+pub(crate) fn clear(_e: &Env, _commitment_id: &String) {}
+
+pub(crate) fn extend_ttl(_e: &Env, _commitment_id: &String, _extend_to: u32) {}
@@ -0,0 +1,199 @@
+//! Chunked commitment id lists: one per owner and one protocol-wide.
+//!
+//! Ids are kept in persistent chunks of `COMMITMENT_ID_CHUNK_SIZE` so no
+//! single ledger entry grows with the number of commitments. An owner's
+//! legacy `OwnerCommitments` vec (and, protocol-wide, the ids implied by the
+//! id counter) is moved into chunks on the first write to that list; reads
+//! fall back to the legacy layout until then.
+
+use crate::{
+    commitments_created, has_commitment, CommitmentCoreContract, CommitmentCoreContractClient,
+    DataKey,
+};
+use soroban_sdk::{contractimpl, contracttype, Address, Env, String, Vec};
+
+/// Commitment ids stored per chunk
+pub const COMMITMENT_ID_CHUNK_SIZE: u32 = 100;
+
+/// A chunked list of commitment ids, in creation order.
+#[contracttype]
+#[derive(Clone)]
+pub enum CommitmentList {
+    Owner(Address),
+    All,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum IndexDataKey {
+    Chunk(CommitmentList, u32), // (list, chunk) -> Vec<commitment_id>
+    Count(CommitmentList),      // list -> number of ids; absent until chunked
+}
+
+fn chunked_len(e: &Env, list: &CommitmentList) -> Option<u32> {
+    e.storage()
+        .persistent()
+        .get::<_, u32>(&IndexDataKey::Count(list.clone()))
+}
+
+fn read_chunk(e: &Env, list: &CommitmentList, chunk: u32) -> Vec<String> {
+    e.storage()
+        .persistent()
+        .get::<_, Vec<String>>(&IndexDataKey::Chunk(list.clone(), chunk))
+        .unwrap_or(Vec::new(e))
+}
+
+/// Ids of a list that has not been chunked yet.
+fn legacy_ids(e: &Env, list: &CommitmentList) -> Vec<String> {
+    match list {
+        CommitmentList::Owner(owner) => e
+            .storage()
+            .instance()
+            .get::<_, Vec<String>>(&DataKey::OwnerCommitments(owner.clone()))
+            .unwrap_or(Vec::new(e)),
+        CommitmentList::All => {
+            let mut ids = Vec::new(e);
+            for i in 0..commitments_created(e) {
+                let commitment_id = CommitmentCoreContract::generate_commitment_id(e, i);
+                if has_commitment(e, &commitment_id) {
+                    ids.push_back(commitment_id);
+                }
+            }
+            ids
+        }
+    }
+}
+
+/// Write `ids` as the contents of the list from chunk `first_chunk` on,
+/// dropping chunks left over from the previous `old_len` ids.
+fn write_from(e: &Env, list: &CommitmentList, first_chunk: u32, ids: &Vec<String>, old_len: u32) {
+    let mut chunk = first_chunk;
+    let mut start = 0;
+    while start < ids.len() {
+        let end = (start + COMMITMENT_ID_CHUNK_SIZE).min(ids.len());
+        e.storage().persistent().set(
+            &IndexDataKey::Chunk(list.clone(), chunk),
+            &ids.slice(start..end),
+        );
+        chunk += 1;
+        start = end;
+    }
+    for stale in chunk..old_len.div_ceil(COMMITMENT_ID_CHUNK_SIZE) {
+        e.storage()
+            .persistent()
+            .remove(&IndexDataKey::Chunk(list.clone(), stale));
+    }
+    e.storage().persistent().set(
+        &IndexDataKey::Count(list.clone()),
+        &(first_chunk * COMMITMENT_ID_CHUNK_SIZE + ids.len()),
+    );
+}
+
+/// Move a list into chunks if it is still in the legacy layout; returns its length.
+fn ensure_chunked(e: &Env, list: &CommitmentList) -> u32 {
+    if let Some(len) = chunked_len(e, list) {
+        return len;
+    }
+    let ids = legacy_ids(e, list);
+    write_from(e, list, 0, &ids, 0);
+    if let CommitmentList::Owner(owner) = list {
+        e.storage()
+            .instance()
+            .remove(&DataKey::OwnerCommitments(owner.clone()));
+    }
+    ids.len()
+}
+
+/// Append `ids` to the end of a list.
+pub(crate) fn append(e: &Env, list: &CommitmentList, ids: &Vec<String>) {
+    let len = ensure_chunked(e, list);
+    let last = len / COMMITMENT_ID_CHUNK_SIZE;
+    let mut tail = read_chunk(e, list, last);
+    tail.append(ids);
+    write_from(e, list, last, &tail, len);
+}
+
+/// Remove `commitment_id` from a list, keeping the order of the rest.
+///
+/// Searches from the newest chunk back, so removing a recent id only
+/// rewrites the last chunks.
+pub(crate) fn remove(e: &Env, list: &CommitmentList, commitment_id: &String) {
+    let len = ensure_chunked(e, list);
+    let mut chunk = len.div_ceil(COMMITMENT_ID_CHUNK_SIZE);
+    while chunk > 0 {
+        chunk -= 1;
+        let mut ids = read_chunk(e, list, chunk);
+        if let Some(pos) = ids.first_index_of(commitment_id) {
+            ids.remove(pos);
+            for later in chunk + 1..len.div_ceil(COMMITMENT_ID_CHUNK_SIZE) {
+                ids.append(&read_chunk(e, list, later));
+            }
+            write_from(e, list, chunk, &ids, len);
+            return;
+        }
+    }
+}
+
+/// Replace the whole contents of a list.
+#[cfg(feature = "recovery")]
+pub(crate) fn replace(e: &Env, list: &CommitmentList, ids: &Vec<String>) {
+    let len = ensure_chunked(e, list);
+    write_from(e, list, 0, ids, len);
+}
+
+/// Number of ids in a list.
+pub(crate) fn len(e: &Env, list: &CommitmentList) -> u32 {
+    chunked_len(e, list).unwrap_or_else(|| legacy_ids(e, list).len())
+}
+
+/// Up to `limit` ids of a list from `start`, reading only the chunks that
+/// overlap the window.
+pub(crate) fn page(e: &Env, list: &CommitmentList, start: u32, limit: u32) -> Vec<String> {
+    let Some(len) = chunked_len(e, list) else {
+        let ids = legacy_ids(e, list);
+        let end = start.saturating_add(limit).min(ids.len());
+        return if start < end {
+            ids.slice(start..end)
+        } else {
+            Vec::new(e)
+        };
+    };
+    let end = start.saturating_add(limit).min(len);
+    let mut ids = Vec::new(e);
+    let mut index = start;
+    while index < end {
+        let chunk_start = index / COMMITMENT_ID_CHUNK_SIZE * COMMITMENT_ID_CHUNK_SIZE;
+        let chunk = read_chunk(e, list, index / COMMITMENT_ID_CHUNK_SIZE);
+        let chunk_end = (chunk_start + chunk.len()).min(end);
+        for i in index..chunk_end {
+            ids.push_back(chunk.get_unchecked(i - chunk_start));
+        }
+        index = chunk_start + COMMITMENT_ID_CHUNK_SIZE;
+    }
+    ids
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Up to `limit` of an owner's commitment ids from position `start`, in
+    /// creation order.
+    pub fn get_owner_commitments_page(
+        e: Env,
+        owner: Address,
+        start: u32,
+        limit: u32,
+    ) -> Vec<String> {
+        page(&e, &CommitmentList::Owner(owner), start, limit)
+    }
+
+    /// Number of commitments listed for an owner
+    pub fn get_owner_commitment_count(e: Env, owner: Address) -> u32 {
+        len(&e, &CommitmentList::Owner(owner))
+    }
+
+    /// Up to `limit` commitment ids protocol-wide from position `start`, in
+    /// creation order. Voided commitments are not listed.
+    pub fn get_commitment_ids_page(e: Env, start: u32, limit: u32) -> Vec<String> {
+        page(&e, &CommitmentList::All, start, limit)
+    }
+}
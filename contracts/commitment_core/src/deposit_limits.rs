@@ -0,0 +1,164 @@
+//! Per-owner cap on the value deposited in an asset per window.
+//!
+//! Unlike the `create` rate limit, which counts calls, this bounds value:
+//! with a cap set for an asset, `create_commitment` and `top_up` add each
+//! deposit to the owner's running sum for the current window and refuse
+//! one that would take it over the cap. A window opens with the owner's
+//! first deposit after the previous one ended, and the sum starts again
+//! from zero; there is no sliding average. Virtual commitments deposit
+//! nothing and are not counted. A refused deposit fails with
+//! `DepositCapExceeded`.
+
+use crate::{
+    fail, require_admin, storage_layout, CommitmentCoreContract, CommitmentCoreContractClient,
+    CommitmentError,
+};
+use soroban_sdk::{contractimpl, contracttype, symbol_short, Address, Env};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DepositLimitsDataKey {
+    Cap(Address),             // asset -> DepositCap
+    Window(Address, Address), // (owner, asset) -> DepositWindow
+}
+
+/// Most an owner may deposit in an asset within `window_seconds`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DepositCap {
+    pub amount: i128,
+    pub window_seconds: u64,
+}
+
+/// An owner's deposits in the current window.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DepositWindow {
+    pub start: u64,
+    pub deposited: i128,
+}
+
+/// The owner's window for `asset` as of now: a lapsed window reads as empty.
+fn current_window(e: &Env, cap: &DepositCap, owner: &Address, asset: &Address) -> DepositWindow {
+    let now = e.ledger().timestamp();
+    let key = DepositLimitsDataKey::Window(owner.clone(), asset.clone());
+    match storage_layout::get::<_, DepositWindow>(e, &key) {
+        Some(window) if now < window.start.saturating_add(cap.window_seconds) => window,
+        _ => DepositWindow {
+            start: now,
+            deposited: 0,
+        },
+    }
+}
+
+/// `DepositCapExceeded` if depositing `amount` would take `owner` over the
+/// asset's cap for the current window.
+pub(crate) fn check(
+    e: &Env,
+    owner: &Address,
+    asset: &Address,
+    amount: i128,
+) -> Result<(), CommitmentError> {
+    let remaining = CommitmentCoreContract::get_remaining_deposit_allowance(
+        e.clone(),
+        owner.clone(),
+        asset.clone(),
+    );
+    if amount > remaining {
+        return Err(CommitmentError::DepositCapExceeded);
+    }
+    Ok(())
+}
+
+/// Add `amount` to the owner's sum for the current window (after `check`).
+pub(crate) fn record(e: &Env, owner: &Address, asset: &Address, amount: i128) {
+    let Some(cap) = CommitmentCoreContract::get_max_deposit_per_window(e.clone(), asset.clone())
+    else {
+        return;
+    };
+    let mut window = current_window(e, &cap, owner, asset);
+    window.deposited = window.deposited.saturating_add(amount);
+    storage_layout::set(
+        e,
+        &DepositLimitsDataKey::Window(owner.clone(), asset.clone()),
+        &window,
+    );
+}
+
+/// Take `amount` back off the owner's sum for the current window (used when
+/// a commitment is voided); a lapsed window is left alone.
+#[cfg(feature = "recovery")]
+pub(crate) fn release(e: &Env, owner: &Address, asset: &Address, amount: i128) {
+    let Some(cap) = CommitmentCoreContract::get_max_deposit_per_window(e.clone(), asset.clone())
+    else {
+        return;
+    };
+    let mut window = current_window(e, &cap, owner, asset);
+    if window.deposited == 0 {
+        return;
+    }
+    window.deposited = window.deposited.saturating_sub(amount).max(0);
+    storage_layout::set(
+        e,
+        &DepositLimitsDataKey::Window(owner.clone(), asset.clone()),
+        &window,
+    );
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Cap what each owner may deposit in `asset` to `amount` per
+    /// `window_seconds` (admin only); an `amount` of 0 removes the cap.
+    ///
+    /// Fails with InvalidAmount for a negative amount or a zero window.
+    /// Deposits already counted in open windows still count.
+    pub fn set_max_deposit_per_window(
+        e: Env,
+        caller: Address,
+        asset: Address,
+        amount: i128,
+        window_seconds: u64,
+    ) {
+        require_admin(&e, &caller);
+        let key = DepositLimitsDataKey::Cap(asset.clone());
+        if amount == 0 {
+            e.storage().instance().remove(&key);
+        } else {
+            if amount < 0 || window_seconds == 0 {
+                fail(
+                    &e,
+                    CommitmentError::InvalidAmount,
+                    "set_max_deposit_per_window",
+                );
+            }
+            e.storage().instance().set(
+                &key,
+                &DepositCap {
+                    amount,
+                    window_seconds,
+                },
+            );
+        }
+        e.events().publish(
+            (symbol_short!("DepCap"), asset),
+            (amount, window_seconds, e.ledger().timestamp()),
+        );
+    }
+
+    /// Per-owner deposit cap on `asset`, if set
+    pub fn get_max_deposit_per_window(e: Env, asset: Address) -> Option<DepositCap> {
+        e.storage()
+            .instance()
+            .get(&DepositLimitsDataKey::Cap(asset))
+    }
+
+    /// What `owner` may still deposit in `asset` in the current window
+    /// (`i128::MAX` when the asset has no cap).
+    pub fn get_remaining_deposit_allowance(e: Env, owner: Address, asset: Address) -> i128 {
+        let Some(cap) = Self::get_max_deposit_per_window(e.clone(), asset.clone()) else {
+            return i128::MAX;
+        };
+        let window = current_window(&e, &cap, &owner, &asset);
+        cap.amount.saturating_sub(window.deposited).max(0)
+    }
+}
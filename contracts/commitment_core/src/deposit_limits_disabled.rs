@@ -0,0 +1,18 @@
+//! No-op deposit cap helpers used when the `limits` feature is compiled out.
+
+use crate::CommitmentError;
+use soroban_sdk::{Address, Env};
+
+pub(crate) fn check(
+    _e: &Env,
+    _owner: &Address,
+    _asset: &Address,
+    _amount: i128,
+) -> Result<(), CommitmentError> {
+    Ok(())
+}
+
+pub(crate) fn record(_e: &Env, _owner: &Address, _asset: &Address, _amount: i128) {}
+
+#[cfg(feature = "recovery")]
+pub(crate) fn release(_e: &Env, _owner: &Address, _asset: &Address, _amount: i128) {}
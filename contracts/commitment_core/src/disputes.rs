@@ -0,0 +1,137 @@
+//! Admin freeze of a single commitment, e.g. while a dispute is investigated.
+//!
+//! Unlike `freeze_updates`, which only holds back value updates and lapses
+//! on its own, this freeze stops everything that moves the position's funds
+//! or ownership until the admin lifts it: settlement, liquidation, early
+//! exit (and requesting one), cancellation, top-ups, allocation and
+//! deallocation, emergency withdrawal, migration release and settlement
+//! claims fail with CommitmentFrozen, and `is_transferable` reports
+//! false so the NFT cannot change hands. Value updates still run, so a
+//! frozen commitment that breaches its loss limit is still marked violated.
+//! The rest of the protocol is unaffected.
+
+use crate::{
+    audit, fail, read_commitment, require_admin, storage_layout, ttl, CommitmentCoreContract,
+    CommitmentCoreContractClient, CommitmentError,
+};
+use soroban_sdk::{contractimpl, contracttype, symbol_short, Address, Env, String, Symbol};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DisputeDataKey {
+    Freeze(String), // commitment_id -> CommitmentFreeze
+}
+
+/// An admin freeze in force on a commitment.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommitmentFreeze {
+    pub frozen_by: Address,
+    pub frozen_at: u64,
+    pub reason: Symbol,
+}
+
+/// Admin-set flags on a commitment.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommitmentFlags {
+    pub frozen: bool,
+    pub frozen_at: u64,                // 0 unless frozen
+    pub frozen_reason: Option<Symbol>, // None unless frozen
+}
+
+/// Whether the admin has frozen the commitment.
+pub(crate) fn is_frozen(e: &Env, commitment_id: &String) -> bool {
+    storage_layout::has(e, &DisputeDataKey::Freeze(commitment_id.clone()))
+}
+
+/// `CommitmentFrozen` if the admin has frozen the commitment.
+pub(crate) fn check_not_frozen(e: &Env, commitment_id: &String) -> Result<(), CommitmentError> {
+    if is_frozen(e, commitment_id) {
+        return Err(CommitmentError::CommitmentFrozen);
+    }
+    Ok(())
+}
+
+/// Drop a commitment's freeze (used when a commitment is voided).
+#[cfg(feature = "recovery")]
+pub(crate) fn clear(e: &Env, commitment_id: &String) {
+    storage_layout::remove(e, &DisputeDataKey::Freeze(commitment_id.clone()));
+}
+
+/// Extend a commitment's freeze entry (see `ttl::extend`).
+pub(crate) fn extend_ttl(e: &Env, commitment_id: &String, extend_to: u32) {
+    ttl::extend_persistent(e, &DisputeDataKey::Freeze(commitment_id.clone()), extend_to);
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Freeze a commitment pending investigation (admin only).
+    ///
+    /// Every path that moves its funds, and NFT transfers, is refused until
+    /// `unfreeze_commitment`. Fails with CommitmentFrozen if it is
+    /// already frozen. Emits `CmtFrozen` with the reason.
+    pub fn freeze_commitment(e: Env, caller: Address, commitment_id: String, reason: Symbol) {
+        require_admin(&e, &caller);
+        let commitment = read_commitment(&e, &commitment_id)
+            .unwrap_or_else(|| fail(&e, CommitmentError::CommitmentNotFound, "freeze_commitment"));
+        if let Err(err) = check_not_frozen(&e, &commitment_id) {
+            fail(&e, err, "freeze_commitment");
+        }
+        let now = e.ledger().timestamp();
+        storage_layout::set(
+            &e,
+            &DisputeDataKey::Freeze(commitment_id.clone()),
+            &CommitmentFreeze {
+                frozen_by: caller.clone(),
+                frozen_at: now,
+                reason: reason.clone(),
+            },
+        );
+        audit::record(&e, &caller, symbol_short!("Freeze"), &commitment.owner);
+        e.events().publish(
+            (symbol_short!("CmtFrozen"), commitment_id, caller),
+            (reason, now),
+        );
+    }
+
+    /// Lift an admin freeze (admin only). Fails with InvalidStatus if the
+    /// commitment is not frozen. Emits `CmtUnfrz` with the reason it was
+    /// frozen for.
+    pub fn unfreeze_commitment(e: Env, caller: Address, commitment_id: String) {
+        require_admin(&e, &caller);
+        let commitment = read_commitment(&e, &commitment_id).unwrap_or_else(|| {
+            fail(
+                &e,
+                CommitmentError::CommitmentNotFound,
+                "unfreeze_commitment",
+            )
+        });
+        let key = DisputeDataKey::Freeze(commitment_id.clone());
+        let freeze = storage_layout::get::<_, CommitmentFreeze>(&e, &key)
+            .unwrap_or_else(|| fail(&e, CommitmentError::InvalidStatus, "unfreeze_commitment"));
+        storage_layout::remove(&e, &key);
+        audit::record(&e, &caller, symbol_short!("Unfreeze"), &commitment.owner);
+        e.events().publish(
+            (symbol_short!("CmtUnfrz"), commitment_id, caller),
+            (freeze.reason, e.ledger().timestamp()),
+        );
+    }
+
+    /// Admin-set flags on a commitment; all clear for unknown ids.
+    pub fn get_commitment_flags(e: Env, commitment_id: String) -> CommitmentFlags {
+        match storage_layout::get::<_, CommitmentFreeze>(&e, &DisputeDataKey::Freeze(commitment_id))
+        {
+            Some(freeze) => CommitmentFlags {
+                frozen: true,
+                frozen_at: freeze.frozen_at,
+                frozen_reason: Some(freeze.reason),
+            },
+            None => CommitmentFlags {
+                frozen: false,
+                frozen_at: 0,
+                frozen_reason: None,
+            },
+        }
+    }
+}
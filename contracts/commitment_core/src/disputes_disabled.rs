@@ -0,0 +1,17 @@
+//! No-op freeze helpers used when the `disputes` feature is compiled out.
+
+use crate::CommitmentError;
+use soroban_sdk::{Env, String};
+
+pub(crate) fn is_frozen(_e: &Env, _commitment_id: &String) -> bool {
+    false
+}
+
+pub(crate) fn check_not_frozen(_e: &Env, _commitment_id: &String) -> Result<(), CommitmentError> {
+    Ok(())
+}
+
+#[cfg(feature = "recovery")]
+pub(crate) fn clear(_e: &Env, _commitment_id: &String) {}
+
+pub(crate) fn extend_ttl(_e: &Env, _commitment_id: &String, _extend_to: u32) {}
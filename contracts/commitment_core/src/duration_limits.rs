@@ -0,0 +1,102 @@
+//! Protocol maximum commitment duration.
+//!
+//! `create_commitment`, renewals, extensions and quotes bound a
+//! commitment's term by `max_days`; the admin may lower or raise it from
+//! the default of `TimeUtils::DEFAULT_MAX_DURATION_DAYS`. Core owns the
+//! limit: every change is pushed to the NFT contracts core mints into, and
+//! a contract added later receives the current value, so their mint checks
+//! never disagree with core's.
+
+use crate::{
+    fail, require_admin, CommitmentCoreContract, CommitmentCoreContractClient, CommitmentError,
+    DataKey,
+};
+use commitlabs_interfaces::nft::CommitmentNftClient;
+use shared_utils::TimeUtils;
+use soroban_sdk::{contractimpl, contracttype, symbol_short, Address, Env};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DurationLimitsDataKey {
+    MaxDurationDays, // u32 — protocol maximum duration (default 3650)
+}
+
+/// Protocol maximum commitment duration in days.
+pub(crate) fn max_days(e: &Env) -> u32 {
+    CommitmentCoreContract::get_max_duration_days(e.clone())
+}
+
+/// Push core's limit to `nft_contract`. Fails with NotInitialized if the
+/// NFT contract does not accept the call from this core.
+fn push_to_nft(e: &Env, nft_contract: &Address, max_duration_days: u32, context: &str) {
+    let nft = CommitmentNftClient::new(e, nft_contract);
+    if !matches!(
+        nft.try_set_max_duration_days(&max_duration_days),
+        Ok(Ok(()))
+    ) {
+        fail(e, CommitmentError::NotInitialized, context);
+    }
+}
+
+/// Bring an NFT contract core starts minting into up to date with an
+/// admin-set limit; contracts still on the default need nothing.
+pub(crate) fn sync_nft(e: &Env, nft_contract: &Address, context: &str) {
+    if let Some(max_duration_days) = e
+        .storage()
+        .instance()
+        .get::<_, u32>(&DurationLimitsDataKey::MaxDurationDays)
+    {
+        push_to_nft(e, nft_contract, max_duration_days, context);
+    }
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Set the protocol maximum commitment duration in days (admin only).
+    ///
+    /// Pushed to the default NFT contract and every registered collection.
+    pub fn set_max_duration_days(e: Env, caller: Address, max_duration_days: u32) {
+        require_admin(&e, &caller);
+        if max_duration_days == 0 {
+            fail(
+                &e,
+                CommitmentError::InvalidDuration,
+                "set_max_duration_days",
+            );
+        }
+        e.storage()
+            .instance()
+            .set(&DurationLimitsDataKey::MaxDurationDays, &max_duration_days);
+        if let Some(nft_contract) = e
+            .storage()
+            .instance()
+            .get::<_, Address>(&DataKey::NftContract)
+        {
+            push_to_nft(
+                &e,
+                &nft_contract,
+                max_duration_days,
+                "set_max_duration_days",
+            );
+        }
+        for collection_id in Self::get_nft_collections(e.clone()).iter() {
+            let nft_contract = Self::get_nft_collection(e.clone(), collection_id);
+            push_to_nft(
+                &e,
+                &nft_contract,
+                max_duration_days,
+                "set_max_duration_days",
+            );
+        }
+        e.events()
+            .publish((symbol_short!("MaxDur"),), max_duration_days);
+    }
+
+    /// Get the protocol maximum commitment duration in days
+    pub fn get_max_duration_days(e: Env) -> u32 {
+        e.storage()
+            .instance()
+            .get::<_, u32>(&DurationLimitsDataKey::MaxDurationDays)
+            .unwrap_or(TimeUtils::DEFAULT_MAX_DURATION_DAYS)
+    }
+}
@@ -0,0 +1,12 @@
+//! Default maximum duration used when the `limits` feature is compiled out.
+
+use shared_utils::TimeUtils;
+use soroban_sdk::{Address, Env};
+
+pub(crate) fn max_days(_e: &Env) -> u32 {
+    TimeUtils::DEFAULT_MAX_DURATION_DAYS
+}
+
+/// Nothing to push: NFT contracts already default to the same limit
+#[allow(dead_code)]
+pub(crate) fn sync_nft(_e: &Env, _nft_contract: &Address, _context: &str) {}
@@ -0,0 +1,55 @@
+//! Commitments with a term given in seconds.
+//!
+//! `create_commitment_with_seconds` takes the term as a raw number of
+//! seconds (at least `MIN_DURATION_SECONDS`), for sub-day commitments in
+//! demos and tests. `rules.duration_days` is set to the term rounded up to
+//! whole days, as the nominal duration used by renewal and extension;
+//! `expires_at` is the exact expiry and is mirrored on the NFT.
+
+use crate::{
+    CommitmentCoreContract, CommitmentCoreContractClient, CommitmentError, CommitmentRules,
+};
+use soroban_sdk::{contractimpl, Address, Env, String};
+
+/// Shortest term `create_commitment_with_seconds` accepts: 1 hour
+pub const MIN_DURATION_SECONDS: u64 = 60 * 60;
+
+const SECONDS_PER_DAY: u64 = 86400;
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Create a commitment expiring `duration_seconds` from now.
+    ///
+    /// Otherwise like `create_commitment`; `rules.duration_days` is replaced
+    /// by the term rounded up to whole days. Fails with InvalidDuration under
+    /// `MIN_DURATION_SECONDS` and DurationTooLong beyond
+    /// `get_max_duration_days`.
+    pub fn create_commitment_with_seconds(
+        e: Env,
+        owner: Address,
+        amount: i128,
+        asset_address: Address,
+        rules: CommitmentRules,
+        duration_seconds: u64,
+    ) -> Result<String, CommitmentError> {
+        if duration_seconds < MIN_DURATION_SECONDS {
+            return Err(CommitmentError::InvalidDuration);
+        }
+        let duration_days = u32::try_from(duration_seconds.div_ceil(SECONDS_PER_DAY))
+            .map_err(|_| CommitmentError::DurationTooLong)?;
+        Self::create_commitment_internal(
+            e,
+            owner,
+            amount,
+            asset_address,
+            CommitmentRules {
+                duration_days,
+                ..rules
+            },
+            false,
+            None,
+            None,
+            Some(duration_seconds),
+        )
+    }
+}
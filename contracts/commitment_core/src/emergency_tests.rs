@@ -1,7 +1,7 @@
 #![cfg(test)]
 
 use super::*;
-use soroban_sdk::{testutils::Address as _, Address, Env, String};
+use soroban_sdk::{testutils::Address as _, Address, Env};
 
 #[test]
 fn test_emergency_mode_toggle() {
@@ -43,7 +43,7 @@ fn test_create_commitment_forbidden_in_emergency() {
     let rules = CommitmentRules {
         duration_days: 30,
         max_loss_percent: 10,
-        commitment_type: String::from_str(&e, "safe"),
+        commitment_type: RiskProfile::Safe,
         early_exit_penalty: 5,
         min_fee_threshold: 100,
     };
@@ -71,7 +71,7 @@ fn test_emergency_withdraw_forbidden_in_normal_mode() {
 }
 
 #[test]
-#[should_panic(expected = "Unauthorized: caller not allowed")]
+#[should_panic(expected = "Error(Contract, #9)")]
 fn test_set_emergency_mode_unauthorized() {
     let e = Env::default();
     e.mock_all_auths();
@@ -0,0 +1,70 @@
+//! Typed payloads for the commitment lifecycle events.
+//!
+//! Each is published with `(symbol, commitment_id)` topics (plus the
+//! `virtual` marker for virtual commitments), so indexers can filter on the
+//! commitment and decode the data into one shape per event.
+
+use crate::CommitmentRules;
+use soroban_sdk::{contracttype, Address, String, Symbol};
+
+/// `Created` event data
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommitmentCreatedEvent {
+    pub commitment_id: String,
+    pub owner: Address,
+    pub amount: i128,          // requested
+    pub received_amount: i128, // gross: what arrived, net of any transfer fee
+    pub net_amount: i128,      // committed: received less the insurance contribution
+    pub asset_address: Address,
+    pub nft_token_id: u32,
+    pub rules: CommitmentRules,
+    pub timestamp: u64,
+}
+
+/// `Settled` event data (settlement and liquidation)
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommitmentSettledEvent {
+    pub commitment_id: String,
+    pub owner: Address,
+    pub payout: i128,
+    pub management_fee: i128,
+    pub settlement_fee: i128,
+    pub timestamp: u64,
+}
+
+/// `Violated` event data
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommitmentViolatedEvent {
+    pub commitment_id: String,
+    pub owner: Address,
+    pub reason: Symbol, // LossViol
+    pub loss_bps: u32,
+    pub timestamp: u64,
+}
+
+/// `EarlyExt` event data
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EarlyExitEvent {
+    pub commitment_id: String,
+    pub owner: Address,
+    pub caller: Address, // owner or operator
+    pub penalty: i128,
+    pub returned_amount: i128,
+    pub management_fee: i128,
+    pub fee_threshold_met: bool,
+    pub timestamp: u64,
+}
+
+/// `ValUpd` event data
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ValueUpdatedEvent {
+    pub commitment_id: String,
+    pub old_value: i128,
+    pub new_value: i128,
+    pub timestamp: u64,
+}
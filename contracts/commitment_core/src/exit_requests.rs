@@ -0,0 +1,180 @@
+//! Two-phase early exits with a cooling-off period.
+//!
+//! With a cooling-off period set for a commitment type, its commitments
+//! can only exit early through `request_early_exit` followed, once the
+//! period has passed, by `execute_early_exit`. A pending request locks the
+//! commitment against allocations, top-ups, extension and migration export
+//! until it is executed or cancelled.
+
+use crate::{
+    disputes, fail, operators, read_commitment, require_admin, set_reentrancy_guard,
+    storage_layout, ttl, CommitmentCoreContract, CommitmentCoreContractClient, CommitmentError,
+    CommitmentStatus, RiskProfile,
+};
+use soroban_sdk::{contractimpl, contracttype, symbol_short, Address, Env, String};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum ExitDataKey {
+    ExitCooloff(RiskProfile), // commitment_type -> early-exit cooling-off seconds (0 = off)
+    ExitRequest(String),      // commitment_id -> ExitRequest
+}
+
+/// A pending two-phase early exit (see `request_early_exit`).
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExitRequest {
+    pub requested_at: u64,
+    pub executable_at: u64,
+}
+
+/// Fail if the commitment is locked by a pending exit request.
+pub(crate) fn require_none(e: &Env, commitment_id: &String, context: &str) {
+    if storage_layout::has(e, &ExitDataKey::ExitRequest(commitment_id.clone())) {
+        set_reentrancy_guard(e, false);
+        fail(e, CommitmentError::ExitRequestPending, context);
+    }
+}
+
+/// Drop a commitment's exit request (terminal transitions).
+pub(crate) fn clear(e: &Env, commitment_id: &String) {
+    storage_layout::remove(e, &ExitDataKey::ExitRequest(commitment_id.clone()));
+}
+
+/// Early-exit cooling-off period of a commitment type (0 = none).
+pub(crate) fn cooloff(e: &Env, commitment_type: RiskProfile) -> u64 {
+    e.storage()
+        .instance()
+        .get::<_, u64>(&ExitDataKey::ExitCooloff(commitment_type))
+        .unwrap_or(0)
+}
+
+/// Extend a commitment's exit request (see `ttl::extend`).
+pub(crate) fn extend_ttl(e: &Env, commitment_id: &String, extend_to: u32) {
+    ttl::extend_persistent(
+        e,
+        &ExitDataKey::ExitRequest(commitment_id.clone()),
+        extend_to,
+    );
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Request a two-phase early exit (owner or operator).
+    ///
+    /// Locks the commitment against allocations and migration export until
+    /// the request is executed or cancelled. It becomes executable once the
+    /// commitment type's cooling-off period has passed.
+    pub fn request_early_exit(e: Env, commitment_id: String, caller: Address) -> ExitRequest {
+        caller.require_auth();
+        let commitment = read_commitment(&e, &commitment_id).unwrap_or_else(|| {
+            fail(
+                &e,
+                CommitmentError::CommitmentNotFound,
+                "request_early_exit",
+            )
+        });
+        if !operators::is_owner_or_operator(&e, &commitment, &caller) {
+            fail(&e, CommitmentError::Unauthorized, "request_early_exit");
+        }
+        if commitment.status != CommitmentStatus::Active {
+            fail(&e, CommitmentError::NotActive, "request_early_exit");
+        }
+        if let Err(err) = disputes::check_not_frozen(&e, &commitment_id) {
+            fail(&e, err, "request_early_exit");
+        }
+        require_none(&e, &commitment_id, "request_early_exit");
+
+        let now = e.ledger().timestamp();
+        let cooloff = cooloff(&e, commitment.rules.commitment_type);
+        let request = ExitRequest {
+            requested_at: now,
+            executable_at: now.saturating_add(cooloff),
+        };
+        storage_layout::set(
+            &e,
+            &ExitDataKey::ExitRequest(commitment_id.clone()),
+            &request,
+        );
+        e.events().publish(
+            (symbol_short!("ExitReq"), commitment_id, caller),
+            (request.requested_at, request.executable_at),
+        );
+        request
+    }
+
+    /// Complete a requested early exit once its cooling-off period has passed
+    /// (owner or operator). A commitment that matured in the meantime is settled
+    /// normally instead, without a penalty.
+    pub fn execute_early_exit(e: Env, commitment_id: String, caller: Address) {
+        let request = Self::get_exit_request(e.clone(), commitment_id.clone())
+            .unwrap_or_else(|| fail(&e, CommitmentError::NothingPending, "execute_early_exit"));
+        let commitment = read_commitment(&e, &commitment_id).unwrap_or_else(|| {
+            fail(
+                &e,
+                CommitmentError::CommitmentNotFound,
+                "execute_early_exit",
+            )
+        });
+        let now = e.ledger().timestamp();
+        if now >= commitment.expires_at {
+            caller.require_auth();
+            if !operators::is_owner_or_operator(&e, &commitment, &caller) {
+                fail(&e, CommitmentError::Unauthorized, "execute_early_exit");
+            }
+            Self::settle(e, commitment_id);
+            return;
+        }
+        if now < request.executable_at {
+            fail(
+                &e,
+                CommitmentError::TimelockNotElapsed,
+                "execute_early_exit",
+            );
+        }
+        Self::early_exit_internal(e, commitment_id, caller);
+    }
+
+    /// Withdraw a pending exit request, returning the commitment to normal
+    /// (owner or operator)
+    pub fn cancel_exit_request(e: Env, commitment_id: String, caller: Address) {
+        caller.require_auth();
+        let commitment = read_commitment(&e, &commitment_id).unwrap_or_else(|| {
+            fail(
+                &e,
+                CommitmentError::CommitmentNotFound,
+                "cancel_exit_request",
+            )
+        });
+        if !operators::is_owner_or_operator(&e, &commitment, &caller) {
+            fail(&e, CommitmentError::Unauthorized, "cancel_exit_request");
+        }
+        if Self::get_exit_request(e.clone(), commitment_id.clone()).is_none() {
+            fail(&e, CommitmentError::NothingPending, "cancel_exit_request");
+        }
+        clear(&e, &commitment_id);
+        e.events()
+            .publish((symbol_short!("ExitCncl"), commitment_id, caller), ());
+    }
+
+    /// Get the pending exit request for a commitment, if any
+    pub fn get_exit_request(e: Env, commitment_id: String) -> Option<ExitRequest> {
+        storage_layout::get(&e, &ExitDataKey::ExitRequest(commitment_id))
+    }
+
+    /// Set the early-exit cooling-off period for a commitment type (admin
+    /// only). With `seconds > 0`, early exits of that type are two-phase.
+    pub fn set_exit_cooloff(e: Env, caller: Address, commitment_type: RiskProfile, seconds: u64) {
+        require_admin(&e, &caller);
+        e.storage()
+            .instance()
+            .set(&ExitDataKey::ExitCooloff(commitment_type), &seconds);
+        e.events()
+            .publish((symbol_short!("ExitCool"), commitment_type), seconds);
+    }
+
+    /// Get the early-exit cooling-off period for a commitment type (0 = none)
+    pub fn get_exit_cooloff(e: Env, commitment_type: RiskProfile) -> u64 {
+        cooloff(&e, commitment_type)
+    }
+}
@@ -0,0 +1,15 @@
+//! Exit request helpers used when the `exit_requests` feature is compiled
+//! out: no commitment type has a cooling-off period and none is ever locked.
+
+use shared_utils::RiskProfile;
+use soroban_sdk::{Env, String};
+
+pub(crate) fn require_none(_e: &Env, _commitment_id: &String, _context: &str) {}
+
+pub(crate) fn clear(_e: &Env, _commitment_id: &String) {}
+
+pub(crate) fn cooloff(_e: &Env, _commitment_type: RiskProfile) -> u64 {
+    0
+}
+
+pub(crate) fn extend_ttl(_e: &Env, _commitment_id: &String, _extend_to: u32) {}
@@ -0,0 +1,111 @@
+//! Commitment ids bucketed by the day they expire.
+//!
+//! Creation files each commitment under `expires_at / EXPIRY_BUCKET_SECONDS`
+//! so `get_expiring_commitments` reads only the buckets a window covers.
+//! Entries are not dropped when a commitment settles or exits; the query
+//! checks the compact status record instead. Commitments created before this
+//! index existed are not listed. `LastBucket` records the latest bucket
+//! written, so a long window stops scanning there.
+
+use crate::{
+    read_status_light, CommitmentCoreContract, CommitmentCoreContractClient, STATUS_CODE_ACTIVE,
+};
+use soroban_sdk::{contractimpl, contracttype, Env, String, Vec};
+
+/// Width of one expiry bucket
+pub const EXPIRY_BUCKET_SECONDS: u64 = 86400;
+
+#[contracttype]
+#[derive(Clone)]
+pub enum ExpiryDataKey {
+    Bucket(u64), // expires_at / EXPIRY_BUCKET_SECONDS -> Vec<commitment_id>
+    LastBucket,  // highest bucket ever written
+}
+
+fn read_bucket(e: &Env, bucket: u64) -> Vec<String> {
+    e.storage()
+        .persistent()
+        .get::<_, Vec<String>>(&ExpiryDataKey::Bucket(bucket))
+        .unwrap_or(Vec::new(e))
+}
+
+/// File a commitment under the bucket of `expires_at`.
+pub(crate) fn insert(e: &Env, commitment_id: &String, expires_at: u64) {
+    let bucket = expires_at / EXPIRY_BUCKET_SECONDS;
+    let mut ids = read_bucket(e, bucket);
+    ids.push_back(commitment_id.clone());
+    e.storage()
+        .persistent()
+        .set(&ExpiryDataKey::Bucket(bucket), &ids);
+    let last = e
+        .storage()
+        .persistent()
+        .get::<_, u64>(&ExpiryDataKey::LastBucket)
+        .unwrap_or(0);
+    if bucket > last {
+        e.storage()
+            .persistent()
+            .set(&ExpiryDataKey::LastBucket, &bucket);
+    }
+}
+
+/// Drop a commitment from the bucket of `expires_at`.
+#[cfg(any(feature = "amendments", feature = "recovery", feature = "renewal"))]
+pub(crate) fn remove(e: &Env, commitment_id: &String, expires_at: u64) {
+    let bucket = expires_at / EXPIRY_BUCKET_SECONDS;
+    let mut ids = read_bucket(e, bucket);
+    if let Some(pos) = ids.first_index_of(commitment_id) {
+        ids.remove(pos);
+        if ids.is_empty() {
+            e.storage()
+                .persistent()
+                .remove(&ExpiryDataKey::Bucket(bucket));
+        } else {
+            e.storage()
+                .persistent()
+                .set(&ExpiryDataKey::Bucket(bucket), &ids);
+        }
+    }
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Up to `limit` active commitments expiring between now and
+    /// now + `within_seconds`, from position `start`.
+    ///
+    /// Ordered by expiry day, then creation within a day. Reads one bucket
+    /// per day of the window, stopping at the latest bucket in use.
+    pub fn get_expiring_commitments(
+        e: Env,
+        within_seconds: u64,
+        start: u32,
+        limit: u32,
+    ) -> Vec<String> {
+        let now = e.ledger().timestamp();
+        let until = now.saturating_add(within_seconds);
+        let last = e
+            .storage()
+            .persistent()
+            .get::<_, u64>(&ExpiryDataKey::LastBucket)
+            .unwrap_or(0);
+        let end = start.saturating_add(limit);
+        let mut ids = Vec::new(&e);
+        let mut matched = 0u32;
+        for bucket in now / EXPIRY_BUCKET_SECONDS..=(until / EXPIRY_BUCKET_SECONDS).min(last) {
+            for commitment_id in read_bucket(&e, bucket).iter() {
+                if matched >= end {
+                    return ids;
+                }
+                let (code, expires_at, _) = read_status_light(&e, &commitment_id);
+                if code != STATUS_CODE_ACTIVE || expires_at < now || expires_at > until {
+                    continue;
+                }
+                if matched >= start {
+                    ids.push_back(commitment_id);
+                }
+                matched += 1;
+            }
+        }
+        ids
+    }
+}
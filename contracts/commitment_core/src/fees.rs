@@ -0,0 +1,282 @@
+//! Annual management fee, settlement fee pool and treasury (feature `fees`).
+//!
+//! Core only talks to this module through the `pub(crate)` helpers below;
+//! `fees_disabled.rs` provides the same helpers as no-ops when the feature
+//! is compiled out.
+
+use crate::{
+    audit, fail, read_commitment, require_admin, storage_layout, ttl, Commitment,
+    CommitmentCoreContract, CommitmentCoreContractClient, CommitmentError, CommitmentStatus,
+    MAX_SETTLEMENT_FEE_BPS,
+};
+use shared_utils::PercentMath;
+use soroban_sdk::{contractimpl, contracttype, symbol_short, token, Address, Env, String};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum FeeDataKey {
+    ManagementFeeBps,          // u32 — annual management fee for new commitments
+    CommitmentMgmtFee(String), // commitment_id -> annual fee bps fixed at creation
+    ProtocolFees(Address),     // asset -> management fees claimable by the treasury
+    Treasury,                  // Address allowed to claim protocol fees
+    SettlementFeeBps,          // u32 — fee on settlement payouts, at most MAX_SETTLEMENT_FEE_BPS
+    FeePool(Address),          // asset -> settlement fees withdrawable by the admin
+}
+
+/// Fix the current management fee rate on a newly created commitment.
+pub(crate) fn snapshot_management_fee(e: &Env, commitment_id: &String, is_virtual: bool) {
+    let management_fee_bps = new_commitment_fee_bps(e);
+    if !is_virtual && management_fee_bps > 0 {
+        storage_layout::set(
+            e,
+            &FeeDataKey::CommitmentMgmtFee(commitment_id.clone()),
+            &management_fee_bps,
+        );
+    }
+}
+
+/// Annual fee rate (bps) a commitment created now would have fixed.
+pub(crate) fn new_commitment_fee_bps(e: &Env) -> u32 {
+    e.storage()
+        .instance()
+        .get::<_, u32>(&FeeDataKey::ManagementFeeBps)
+        .unwrap_or(0)
+}
+
+/// Annual fee rate (bps) fixed on a commitment at creation.
+pub(crate) fn commitment_fee_bps(e: &Env, commitment: &Commitment) -> u32 {
+    if commitment.is_virtual {
+        return 0;
+    }
+    storage_layout::get::<_, u32>(
+        e,
+        &FeeDataKey::CommitmentMgmtFee(commitment.commitment_id.clone()),
+    )
+    .unwrap_or(0)
+}
+
+/// Management fee on `value` at `bps_per_year` from creation until `at`
+/// (or expiry, whichever is earlier).
+pub(crate) fn management_fee_at(
+    commitment: &Commitment,
+    bps_per_year: u32,
+    value: i128,
+    at: u64,
+) -> i128 {
+    let accrual_end = at.min(commitment.expires_at);
+    let elapsed = accrual_end.saturating_sub(commitment.created_at);
+    PercentMath::prorated_annual_fee(value, bps_per_year, elapsed)
+}
+
+/// Management fee accrued on `value` from creation until now (or expiry,
+/// whichever is earlier), at the rate fixed when the commitment was created.
+pub(crate) fn accrued_management_fee(e: &Env, commitment: &Commitment, value: i128) -> i128 {
+    let bps_per_year = commitment_fee_bps(e, commitment);
+    management_fee_at(commitment, bps_per_year, value, e.ledger().timestamp())
+}
+
+pub(crate) fn add_protocol_fees(e: &Env, asset: &Address, amount: i128) {
+    if amount == 0 {
+        return;
+    }
+    let key = FeeDataKey::ProtocolFees(asset.clone());
+    let fees = storage_layout::get::<_, i128>(e, &key).unwrap_or(0);
+    storage_layout::set(e, &key, &(fees + amount));
+}
+
+/// Credit a settlement fee to the asset's fee pool.
+pub(crate) fn add_to_fee_pool(e: &Env, asset: &Address, amount: i128) {
+    if amount == 0 {
+        return;
+    }
+    let key = FeeDataKey::FeePool(asset.clone());
+    let pool = storage_layout::get::<_, i128>(e, &key).unwrap_or(0);
+    storage_layout::set(e, &key, &(pool + amount));
+}
+
+pub(crate) fn fee_pool(e: &Env, asset: &Address) -> i128 {
+    storage_layout::get(e, &FeeDataKey::FeePool(asset.clone())).unwrap_or(0)
+}
+
+/// Settlement fee on `value`, what the owner would otherwise be paid.
+///
+/// Rounds down so any remainder stays with the owner, and is waived when it
+/// would be below the commitment's `min_fee_threshold`.
+pub(crate) fn settlement_fee(e: &Env, commitment: &Commitment, value: i128) -> i128 {
+    let bps = settlement_fee_bps(e);
+    if commitment.is_virtual || bps == 0 {
+        return 0;
+    }
+    let fee = PercentMath::apply_bps(value, bps);
+    if fee < commitment.rules.min_fee_threshold {
+        return 0;
+    }
+    fee
+}
+
+fn settlement_fee_bps(e: &Env) -> u32 {
+    e.storage()
+        .instance()
+        .get::<_, u32>(&FeeDataKey::SettlementFeeBps)
+        .unwrap_or(0)
+}
+
+pub(crate) fn protocol_fees(e: &Env, asset: &Address) -> i128 {
+    storage_layout::get(e, &FeeDataKey::ProtocolFees(asset.clone())).unwrap_or(0)
+}
+
+/// Drop per-commitment fee state (used when a commitment is voided).
+#[cfg(feature = "recovery")]
+pub(crate) fn clear_commitment_fees(e: &Env, commitment_id: &String) {
+    storage_layout::remove(e, &FeeDataKey::CommitmentMgmtFee(commitment_id.clone()));
+}
+
+/// Extend a commitment's fee entry (see `ttl::extend`).
+pub(crate) fn extend_ttl(e: &Env, commitment_id: &String, extend_to: u32) {
+    ttl::extend_persistent(
+        e,
+        &FeeDataKey::CommitmentMgmtFee(commitment_id.clone()),
+        extend_to,
+    );
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Set the annual management fee applied to new commitments (admin only).
+    ///
+    /// The rate is fixed per commitment at creation; `0` disables the fee.
+    pub fn set_management_fee(e: Env, caller: Address, fee_bps_per_year: u32) {
+        require_admin(&e, &caller);
+        if !PercentMath::is_valid_bps(fee_bps_per_year) {
+            fail(&e, CommitmentError::InvalidFee, "set_management_fee");
+        }
+        e.storage()
+            .instance()
+            .set(&FeeDataKey::ManagementFeeBps, &fee_bps_per_year);
+        audit::record(
+            &e,
+            &caller,
+            symbol_short!("MgmtFee"),
+            &e.current_contract_address(),
+        );
+        e.events().publish(
+            (symbol_short!("MgmtFee"),),
+            (fee_bps_per_year, e.ledger().timestamp()),
+        );
+    }
+
+    /// Get the annual management fee (bps) applied to new commitments
+    pub fn get_management_fee_bps(e: Env) -> u32 {
+        new_commitment_fee_bps(&e)
+    }
+
+    /// Management fee accrued to date on an active commitment
+    pub fn get_accrued_management_fee(e: Env, commitment_id: String) -> i128 {
+        let commitment = read_commitment(&e, &commitment_id).unwrap_or_else(|| {
+            fail(
+                &e,
+                CommitmentError::CommitmentNotFound,
+                "get_accrued_management_fee",
+            )
+        });
+        if commitment.status != CommitmentStatus::Active {
+            return 0;
+        }
+        accrued_management_fee(&e, &commitment, commitment.current_value)
+    }
+
+    /// Set the fee taken from settlement payouts (admin only).
+    ///
+    /// Applies to every settlement from now on, at most
+    /// `MAX_SETTLEMENT_FEE_BPS`; `0` (the default) disables it. Fees are
+    /// credited to the asset's fee pool (see `withdraw_fees`).
+    pub fn set_settlement_fee_bps(e: Env, caller: Address, bps: u32) {
+        require_admin(&e, &caller);
+        if bps > MAX_SETTLEMENT_FEE_BPS {
+            fail(&e, CommitmentError::InvalidFee, "set_settlement_fee_bps");
+        }
+        e.storage()
+            .instance()
+            .set(&FeeDataKey::SettlementFeeBps, &bps);
+        audit::record(
+            &e,
+            &caller,
+            symbol_short!("SettleFee"),
+            &e.current_contract_address(),
+        );
+        e.events()
+            .publish((symbol_short!("SettleFee"),), (bps, e.ledger().timestamp()));
+    }
+
+    /// Get the fee (bps) taken from settlement payouts
+    pub fn get_settlement_fee_bps(e: Env) -> u32 {
+        settlement_fee_bps(&e)
+    }
+
+    /// Get settlement fees held in the fee pool for an asset
+    pub fn get_fee_pool(e: Env, asset: Address) -> i128 {
+        fee_pool(&e, &asset)
+    }
+
+    /// Transfer `amount` of the settlement fees in `asset` to `to` (admin
+    /// only).
+    ///
+    /// Fails with InvalidAmount for a non-positive amount and
+    /// InsufficientBalance above the pool.
+    pub fn withdraw_fees(e: Env, caller: Address, asset: Address, to: Address, amount: i128) {
+        require_admin(&e, &caller);
+        let pool = fee_pool(&e, &asset);
+        if amount <= 0 {
+            fail(&e, CommitmentError::InvalidAmount, "withdraw_fees");
+        }
+        if amount > pool {
+            fail(&e, CommitmentError::InsufficientBalance, "withdraw_fees");
+        }
+        storage_layout::set(&e, &FeeDataKey::FeePool(asset.clone()), &(pool - amount));
+        token::Client::new(&e, &asset).transfer(&e.current_contract_address(), &to, &amount);
+        e.events().publish(
+            (symbol_short!("FeeWdraw"), asset),
+            (to, amount, pool - amount),
+        );
+    }
+
+    /// Set the treasury allowed to claim protocol fees (admin only)
+    pub fn set_treasury(e: Env, caller: Address, treasury: Address) {
+        require_admin(&e, &caller);
+        e.storage().instance().set(&FeeDataKey::Treasury, &treasury);
+        e.events().publish((symbol_short!("Treasury"),), treasury);
+    }
+
+    /// Get the treasury address, if set
+    pub fn get_treasury(e: Env) -> Option<Address> {
+        e.storage().instance().get(&FeeDataKey::Treasury)
+    }
+
+    /// Get management fees claimable by the treasury for an asset
+    pub fn get_protocol_fees(e: Env, asset: Address) -> i128 {
+        protocol_fees(&e, &asset)
+    }
+
+    /// Transfer all claimable protocol fees for an asset to the treasury
+    pub fn claim_protocol_fees(e: Env, caller: Address, asset: Address) -> i128 {
+        caller.require_auth();
+        let treasury = Self::get_treasury(e.clone())
+            .unwrap_or_else(|| fail(&e, CommitmentError::NotInitialized, "claim_protocol_fees"));
+        if caller != treasury {
+            fail(&e, CommitmentError::Unauthorized, "claim_protocol_fees");
+        }
+
+        let amount = protocol_fees(&e, &asset);
+        storage_layout::remove(&e, &FeeDataKey::ProtocolFees(asset.clone()));
+        if amount > 0 {
+            token::Client::new(&e, &asset).transfer(
+                &e.current_contract_address(),
+                &treasury,
+                &amount,
+            );
+        }
+        e.events()
+            .publish((symbol_short!("FeeClaim"), asset), (treasury, amount));
+        amount
+    }
+}
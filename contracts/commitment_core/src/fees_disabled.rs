@@ -0,0 +1,51 @@
+//! No-op fee helpers used when the `fees` feature is compiled out.
+
+use crate::Commitment;
+use soroban_sdk::{Address, Env, String};
+
+pub(crate) fn snapshot_management_fee(_e: &Env, _commitment_id: &String, _is_virtual: bool) {}
+
+#[cfg(feature = "quotes")]
+pub(crate) fn new_commitment_fee_bps(_e: &Env) -> u32 {
+    0
+}
+
+pub(crate) fn commitment_fee_bps(_e: &Env, _commitment: &Commitment) -> u32 {
+    0
+}
+
+pub(crate) fn management_fee_at(
+    _commitment: &Commitment,
+    _bps_per_year: u32,
+    _value: i128,
+    _at: u64,
+) -> i128 {
+    0
+}
+
+pub(crate) fn accrued_management_fee(_e: &Env, _commitment: &Commitment, _value: i128) -> i128 {
+    0
+}
+
+pub(crate) fn settlement_fee(_e: &Env, _commitment: &Commitment, _value: i128) -> i128 {
+    0
+}
+
+pub(crate) fn add_protocol_fees(_e: &Env, _asset: &Address, _amount: i128) {}
+
+pub(crate) fn add_to_fee_pool(_e: &Env, _asset: &Address, _amount: i128) {}
+
+#[cfg(feature = "sweep")]
+pub(crate) fn protocol_fees(_e: &Env, _asset: &Address) -> i128 {
+    0
+}
+
+#[cfg(feature = "sweep")]
+pub(crate) fn fee_pool(_e: &Env, _asset: &Address) -> i128 {
+    0
+}
+
+#[cfg(feature = "recovery")]
+pub(crate) fn clear_commitment_fees(_e: &Env, _commitment_id: &String) {}
+
+pub(crate) fn extend_ttl(_e: &Env, _commitment_id: &String, _extend_to: u32) {}
@@ -0,0 +1,169 @@
+//! Deterministic large-state fixtures for load tests (feature `fixtures`).
+//!
+//! `seed_fixture` writes commitments straight into storage, keeping the
+//! counters, TVL and owner indexes consistent with what `create_commitment`,
+//! `settle` and `early_exit` would have produced. It makes no token transfers
+//! or NFT mints. Release wasm builds with this feature are rejected at compile
+//! time in `lib.rs`.
+
+use crate::{
+    adjust_active_commitments, adjust_asset_tvl, commitment_index, commitments_created,
+    expiry_index, fail, read_owner_index, set_commitment, track_asset, write_owner_index,
+    Commitment, CommitmentCoreContract, CommitmentCoreContractClient, CommitmentError,
+    CommitmentList, CommitmentRules, CommitmentStatus, DataKey, OwnerCommitmentRef, RiskProfile,
+};
+use soroban_sdk::{contractimpl, Address, BytesN, Env, Map, String, Vec};
+
+/// Number of distinct owners commitments are spread across
+pub const FIXTURE_OWNERS: u32 = 50;
+
+/// splitmix64; good enough to spread fixtures and fully determined by the seed
+struct FixtureRng(u64);
+
+impl FixtureRng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+}
+
+/// Stable address for fixture participant `index` under `seed`.
+fn fixture_address(e: &Env, core: &Address, seed: u64, index: u32) -> Address {
+    let mut salt = [0u8; 32];
+    salt[..8].copy_from_slice(&seed.to_be_bytes());
+    salt[8..12].copy_from_slice(&index.to_be_bytes());
+    e.deployer()
+        .with_address(core.clone(), BytesN::from_array(e, &salt))
+        .deployed_address()
+}
+
+/// Owner `index` of the fixture generated from `seed` on core contract `core`.
+pub fn fixture_owner(e: &Env, core: &Address, seed: u64, index: u32) -> Address {
+    fixture_address(e, core, seed, index)
+}
+
+/// Asset every commitment of the fixture generated from `seed` is held in.
+pub fn fixture_asset(e: &Env, core: &Address, seed: u64) -> Address {
+    fixture_address(e, core, seed, u32::MAX)
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Generate `count` commitments from `seed` directly into storage (test only).
+    ///
+    /// Owners, amounts, durations and statuses are pseudo-random but fully
+    /// determined by `seed`. Ids continue from the current id counter, so
+    /// fixtures can be layered on existing state. Returns the first new id.
+    pub fn seed_fixture(e: Env, seed: u64, count: u32) -> u64 {
+        if !e.storage().instance().has(&DataKey::Admin) {
+            fail(&e, CommitmentError::NotInitialized, "seed_fixture");
+        }
+        let core = e.current_contract_address();
+        let mut rng = FixtureRng(seed);
+        let asset = fixture_asset(&e, &core, seed);
+        let now = e.ledger().timestamp();
+        let first_id = commitments_created(&e);
+        let total = e
+            .storage()
+            .instance()
+            .get::<_, u64>(&DataKey::TotalCommitments)
+            .unwrap_or(0);
+        let mut tvl = e
+            .storage()
+            .instance()
+            .get::<_, i128>(&DataKey::TotalValueLocked)
+            .unwrap_or(0);
+        let mut asset_tvl = 0i128;
+        let mut active = 0i64;
+        // Owner listings are written once per owner rather than per commitment
+        let mut owner_ids: Map<u32, Vec<String>> = Map::new(&e);
+        let mut all_ids = Vec::new(&e);
+
+        for i in 0..count {
+            let owner_index = rng.below(FIXTURE_OWNERS as u64) as u32;
+            let owner = fixture_owner(&e, &core, seed, owner_index);
+            let amount = 1_000 + rng.below(99_000) as i128;
+            let duration_days = 1 + rng.below(365) as u32;
+            let commitment_type = match rng.below(3) {
+                0 => RiskProfile::Safe,
+                1 => RiskProfile::Balanced,
+                _ => RiskProfile::Aggressive,
+            };
+            let created_at = now.saturating_sub(rng.below(duration_days as u64 * 86400));
+            let status = match rng.below(10) {
+                0..=5 => CommitmentStatus::Active,
+                6 | 7 => CommitmentStatus::Settled,
+                8 => CommitmentStatus::Violated,
+                _ => CommitmentStatus::EarlyExit,
+            };
+            // Current value within +/-10% of the amount
+            let current_value = amount - amount / 10 + (rng.below(21) as i128 * amount) / 100;
+
+            let commitment_id = Self::generate_commitment_id(&e, first_id + i as u64);
+            let commitment = Commitment {
+                commitment_id: commitment_id.clone(),
+                owner: owner.clone(),
+                nft_token_id: 0,
+                rules: CommitmentRules {
+                    duration_days,
+                    max_loss_percent: 5 + rng.below(46) as u32,
+                    commitment_type,
+                    early_exit_penalty: rng.below(21) as u32,
+                    min_fee_threshold: 0,
+                },
+                amount,
+                asset_address: asset.clone(),
+                created_at,
+                expires_at: created_at + duration_days as u64 * 86400,
+                current_value,
+                status,
+                is_virtual: false,
+            };
+            set_commitment(&e, &commitment);
+            expiry_index::insert(&e, &commitment_id, commitment.expires_at);
+            let mut ids = owner_ids.get(owner_index).unwrap_or(Vec::new(&e));
+            ids.push_back(commitment_id.clone());
+            all_ids.push_back(commitment_id);
+            owner_ids.set(owner_index, ids);
+            if status == CommitmentStatus::Active {
+                active += 1;
+                tvl += amount;
+                asset_tvl += amount;
+            }
+        }
+
+        for (owner_index, ids) in owner_ids.iter() {
+            let owner = fixture_owner(&e, &core, seed, owner_index);
+            let (mut index, mut next_seq) = read_owner_index(&e, &owner);
+            for commitment_id in ids.iter() {
+                index.push_back(OwnerCommitmentRef {
+                    seq: next_seq,
+                    commitment_id,
+                });
+                next_seq += 1;
+            }
+            write_owner_index(&e, &owner, &index, next_seq);
+            commitment_index::append(&e, &CommitmentList::Owner(owner), &ids);
+        }
+        commitment_index::append(&e, &CommitmentList::All, &all_ids);
+
+        e.storage()
+            .instance()
+            .set(&DataKey::TotalCommitments, &(total + count as u64));
+        e.storage()
+            .instance()
+            .set(&DataKey::CommitmentIdCounter, &(first_id + count as u64));
+        e.storage().instance().set(&DataKey::TotalValueLocked, &tvl);
+        adjust_active_commitments(&e, active);
+        track_asset(&e, &asset);
+        adjust_asset_tvl(&e, &asset, asset_tvl);
+        first_id
+    }
+}
@@ -0,0 +1,177 @@
+//! Temporary freezes of a commitment's value updates.
+//!
+//! The admin, or the owner once per commitment, may hold back oracle value
+//! updates, e.g. while a price feed is suspect. A freeze lapses on its own
+//! after `max_freeze_seconds`; the admin may lift it any time and the owner
+//! after `FREEZE_OWNER_COOLDOWN_SECONDS`. Unlike the admin freeze in
+//! `disputes`, settlement and exits are unaffected.
+
+use crate::{
+    fail, read_commitment, require_admin, storage_layout, ttl, CommitmentCoreContract,
+    CommitmentCoreContractClient, CommitmentError, CommitmentStatus, DataKey,
+};
+use soroban_sdk::{contractimpl, contracttype, symbol_short, Address, Env, String};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum FreezeDataKey {
+    UpdateFreeze(String),    // commitment_id -> UpdateFreeze
+    OwnerFreezeUsed(String), // commitment_id -> bool (owners may freeze once)
+    MaxFreezeSeconds,        // u64 — freeze auto-expiry (default 7 days)
+}
+
+/// Default time after which a value-update freeze lapses on its own
+pub const DEFAULT_MAX_FREEZE_SECONDS: u64 = 7 * 86400;
+
+/// Time an owner must wait before lifting their own freeze
+pub const FREEZE_OWNER_COOLDOWN_SECONDS: u64 = 86400;
+
+/// A freeze of value updates on a commitment, e.g. during a dispute.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UpdateFreeze {
+    pub frozen_by: Address,
+    pub frozen_at: u64,
+    pub expires_at: u64,
+}
+
+/// Active (unexpired) freeze on a commitment's value updates, if any.
+fn active(e: &Env, commitment_id: &String) -> Option<UpdateFreeze> {
+    storage_layout::get::<_, UpdateFreeze>(e, &FreezeDataKey::UpdateFreeze(commitment_id.clone()))
+        .filter(|freeze| e.ledger().timestamp() < freeze.expires_at)
+}
+
+/// Whether a value-update freeze is in force on the commitment.
+pub(crate) fn is_active(e: &Env, commitment_id: &String) -> bool {
+    active(e, commitment_id).is_some()
+}
+
+/// Drop a commitment's freeze and owner-freeze marker (used when a
+/// commitment is voided).
+#[cfg(feature = "recovery")]
+pub(crate) fn clear(e: &Env, commitment_id: &String) {
+    storage_layout::remove(e, &FreezeDataKey::UpdateFreeze(commitment_id.clone()));
+    storage_layout::remove(e, &FreezeDataKey::OwnerFreezeUsed(commitment_id.clone()));
+}
+
+/// Extend a commitment's freeze entries (see `ttl::extend`).
+pub(crate) fn extend_ttl(e: &Env, commitment_id: &String, extend_to: u32) {
+    ttl::extend_persistent(
+        e,
+        &FreezeDataKey::UpdateFreeze(commitment_id.clone()),
+        extend_to,
+    );
+    ttl::extend_persistent(
+        e,
+        &FreezeDataKey::OwnerFreezeUsed(commitment_id.clone()),
+        extend_to,
+    );
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Freeze value updates on an active commitment (owner or admin).
+    ///
+    /// The freeze lapses automatically after the configured maximum so it
+    /// cannot be used to dodge violation detection indefinitely; owners may
+    /// freeze a given commitment only once. Settlement is unaffected.
+    pub fn freeze_updates(e: Env, commitment_id: String, caller: Address) {
+        caller.require_auth();
+        let commitment = read_commitment(&e, &commitment_id)
+            .unwrap_or_else(|| fail(&e, CommitmentError::CommitmentNotFound, "freeze_updates"));
+        let is_admin =
+            e.storage().instance().get::<_, Address>(&DataKey::Admin) == Some(caller.clone());
+        if caller != commitment.owner && !is_admin {
+            fail(&e, CommitmentError::Unauthorized, "freeze_updates");
+        }
+        if commitment.status != CommitmentStatus::Active {
+            fail(&e, CommitmentError::NotActive, "freeze_updates");
+        }
+        if active(&e, &commitment_id).is_some() {
+            fail(&e, CommitmentError::CommitmentFrozen, "freeze_updates");
+        }
+        if !is_admin {
+            let used_key = FreezeDataKey::OwnerFreezeUsed(commitment_id.clone());
+            if storage_layout::has(&e, &used_key) {
+                fail(&e, CommitmentError::CommitmentFrozen, "freeze_updates");
+            }
+            storage_layout::set(&e, &used_key, &true);
+        }
+
+        let now = e.ledger().timestamp();
+        let freeze = UpdateFreeze {
+            frozen_by: caller.clone(),
+            frozen_at: now,
+            expires_at: now.saturating_add(Self::get_max_freeze_seconds(e.clone())),
+        };
+        storage_layout::set(
+            &e,
+            &FreezeDataKey::UpdateFreeze(commitment_id.clone()),
+            &freeze,
+        );
+        e.events().publish(
+            (symbol_short!("Frozen"), commitment_id, caller),
+            freeze.expires_at,
+        );
+    }
+
+    /// Lift a freeze (admin any time; owner after `FREEZE_OWNER_COOLDOWN_SECONDS`)
+    pub fn unfreeze_updates(e: Env, commitment_id: String, caller: Address) {
+        caller.require_auth();
+        let commitment = read_commitment(&e, &commitment_id)
+            .unwrap_or_else(|| fail(&e, CommitmentError::CommitmentNotFound, "unfreeze_updates"));
+        let freeze = storage_layout::get::<_, UpdateFreeze>(
+            &e,
+            &FreezeDataKey::UpdateFreeze(commitment_id.clone()),
+        )
+        .unwrap_or_else(|| fail(&e, CommitmentError::InvalidStatus, "unfreeze_updates"));
+
+        let is_admin =
+            e.storage().instance().get::<_, Address>(&DataKey::Admin) == Some(caller.clone());
+        let owner_may_unfreeze = caller == commitment.owner
+            && e.ledger().timestamp()
+                >= freeze
+                    .frozen_at
+                    .saturating_add(FREEZE_OWNER_COOLDOWN_SECONDS);
+        if !is_admin && !owner_may_unfreeze {
+            fail(&e, CommitmentError::Unauthorized, "unfreeze_updates");
+        }
+
+        storage_layout::remove(&e, &FreezeDataKey::UpdateFreeze(commitment_id.clone()));
+        e.events()
+            .publish((symbol_short!("Unfrozen"), commitment_id, caller), ());
+    }
+
+    /// Whether value updates on a commitment are currently frozen
+    pub fn is_updates_frozen(e: Env, commitment_id: String) -> bool {
+        active(&e, &commitment_id).is_some()
+    }
+
+    /// Get the active freeze on a commitment, if any
+    pub fn get_update_freeze(e: Env, commitment_id: String) -> Option<UpdateFreeze> {
+        active(&e, &commitment_id)
+    }
+
+    /// Set how long a freeze lasts before lapsing automatically (admin only)
+    pub fn set_max_freeze_seconds(e: Env, caller: Address, max_freeze_seconds: u64) {
+        require_admin(&e, &caller);
+        if max_freeze_seconds == 0 {
+            fail(
+                &e,
+                CommitmentError::InvalidDuration,
+                "set_max_freeze_seconds",
+            );
+        }
+        e.storage()
+            .instance()
+            .set(&FreezeDataKey::MaxFreezeSeconds, &max_freeze_seconds);
+    }
+
+    /// Get how long a freeze lasts before lapsing automatically
+    pub fn get_max_freeze_seconds(e: Env) -> u64 {
+        e.storage()
+            .instance()
+            .get::<_, u64>(&FreezeDataKey::MaxFreezeSeconds)
+            .unwrap_or(DEFAULT_MAX_FREEZE_SECONDS)
+    }
+}
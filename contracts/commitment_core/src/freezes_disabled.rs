@@ -0,0 +1,13 @@
+//! No-op value-update freeze helpers used when the `freezes` feature is
+//! compiled out.
+
+use soroban_sdk::{Env, String};
+
+pub(crate) fn is_active(_e: &Env, _commitment_id: &String) -> bool {
+    false
+}
+
+#[cfg(feature = "recovery")]
+pub(crate) fn clear(_e: &Env, _commitment_id: &String) {}
+
+pub(crate) fn extend_ttl(_e: &Env, _commitment_id: &String, _extend_to: u32) {}
@@ -0,0 +1,121 @@
+//! Retry-safe commitment creation.
+//!
+//! A wallet that resends a timed-out transaction could create the same
+//! commitment twice. `create_commitment_idempotent` takes a caller-chosen
+//! key: the first call with it creates the commitment and remembers the id
+//! under `(owner, key)` together with a hash of the amount, asset and
+//! rules; later calls with the same owner, key and parameters return that
+//! id without moving funds or minting again, while a retry with different
+//! parameters fails with IdempotencyKeyReused. Keys live in temporary
+//! storage, lapse after `IDEMPOTENCY_KEY_TTL_LEDGERS` and are dropped if
+//! the commitment is voided.
+
+use crate::{
+    fail, storage_layout::LEDGERS_PER_DAY, CommitmentCoreContract, CommitmentCoreContractClient,
+    CommitmentError, CommitmentRules,
+};
+use soroban_sdk::{contractimpl, contracttype, xdr::ToXdr, Address, BytesN, Env, String};
+
+/// How long an idempotency key is remembered: about 7 days of ledgers
+pub const IDEMPOTENCY_KEY_TTL_LEDGERS: u32 = 7 * LEDGERS_PER_DAY;
+
+#[contracttype]
+#[derive(Clone)]
+pub enum IdempotencyDataKey {
+    Idempotency(Address, BytesN<32>), // (owner, key) -> IdempotencyRecord, temporary
+    KeyOf(String),                    // commitment_id -> key, temporary
+}
+
+/// What an idempotency key resolves to
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IdempotencyRecord {
+    pub commitment_id: String,
+    pub params_hash: BytesN<32>, // sha256 of (amount, asset, rules) XDR
+}
+
+fn params_hash(e: &Env, amount: i128, asset: &Address, rules: &CommitmentRules) -> BytesN<32> {
+    let payload = (amount, asset.clone(), rules.clone());
+    e.crypto().sha256(&payload.to_xdr(e)).into()
+}
+
+/// Forget the key a voided commitment was created with, if any.
+#[cfg(feature = "recovery")]
+pub(crate) fn clear(e: &Env, owner: &Address, commitment_id: &String) {
+    let reverse = IdempotencyDataKey::KeyOf(commitment_id.clone());
+    if let Some(key) = e.storage().temporary().get::<_, BytesN<32>>(&reverse) {
+        e.storage()
+            .temporary()
+            .remove(&IdempotencyDataKey::Idempotency(owner.clone(), key));
+        e.storage().temporary().remove(&reverse);
+    }
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Create a commitment at most once per `(owner, idempotency_key)`.
+    ///
+    /// Otherwise like `create_commitment`. If the owner already created a
+    /// commitment with this key within `IDEMPOTENCY_KEY_TTL_LEDGERS`, its id
+    /// is returned and nothing is transferred or minted; if the amount,
+    /// asset or rules differ from that first call it fails with
+    /// IdempotencyKeyReused. A failed creation does not use up the key.
+    pub fn create_commitment_idempotent(
+        e: Env,
+        owner: Address,
+        amount: i128,
+        asset_address: Address,
+        rules: CommitmentRules,
+        idempotency_key: BytesN<32>,
+    ) -> Result<String, CommitmentError> {
+        let key = IdempotencyDataKey::Idempotency(owner.clone(), idempotency_key.clone());
+        let params_hash = params_hash(&e, amount, &asset_address, &rules);
+        if let Some(record) = e.storage().temporary().get::<_, IdempotencyRecord>(&key) {
+            owner.require_auth();
+            if record.params_hash != params_hash {
+                fail(
+                    &e,
+                    CommitmentError::IdempotencyKeyReused,
+                    "create_commitment_idempotent",
+                );
+            }
+            return Ok(record.commitment_id);
+        }
+        let commitment_id = Self::create_commitment_internal(
+            e.clone(),
+            owner,
+            amount,
+            asset_address,
+            rules,
+            false,
+            None,
+            None,
+            None,
+        )?;
+        let ttl = IDEMPOTENCY_KEY_TTL_LEDGERS.min(e.storage().max_ttl());
+        let reverse = IdempotencyDataKey::KeyOf(commitment_id.clone());
+        e.storage().temporary().set(
+            &key,
+            &IdempotencyRecord {
+                commitment_id: commitment_id.clone(),
+                params_hash,
+            },
+        );
+        e.storage().temporary().extend_ttl(&key, ttl, ttl);
+        e.storage().temporary().set(&reverse, &idempotency_key);
+        e.storage().temporary().extend_ttl(&reverse, ttl, ttl);
+        Ok(commitment_id)
+    }
+
+    /// Commitment an owner created with `idempotency_key`, while remembered
+    pub fn get_idempotent_commitment(
+        e: Env,
+        owner: Address,
+        idempotency_key: BytesN<32>,
+    ) -> Option<String> {
+        e.storage()
+            .temporary()
+            .get::<_, IdempotencyRecord>(&IdempotencyDataKey::Idempotency(owner, idempotency_key))
+            .map(|record| record.commitment_id)
+    }
+}
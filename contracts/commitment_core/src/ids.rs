@@ -0,0 +1,66 @@
+//! Commitment id scheme.
+//!
+//! Ids are `c_<salt>_<counter>`, where the salt is the ledger sequence the
+//! instance was initialized at. A deployment started over with fresh
+//! instance storage behind the same address therefore never reissues an
+//! earlier instance's ids, and creation skips any id that is still taken if
+//! the counter itself goes backwards. Instances initialized before ids were
+//! salted keep the plain `c_<counter>` form so their existing commitments
+//! stay enumerable.
+
+use soroban_sdk::{contracttype, Env, String};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum IdDataKey {
+    Salt, // u32 ledger sequence at initialization
+}
+
+/// Record this instance's id salt (called once, from `initialize`).
+pub(crate) fn init_salt(e: &Env) {
+    e.storage()
+        .instance()
+        .set(&IdDataKey::Salt, &e.ledger().sequence());
+}
+
+/// This instance's id salt; `None` for instances initialized before ids
+/// were salted.
+pub(crate) fn salt(e: &Env) -> Option<u32> {
+    e.storage().instance().get(&IdDataKey::Salt)
+}
+
+/// Append the decimal digits of `n` to `buf` at `i`; returns the new end.
+fn push_decimal(buf: &mut [u8], mut i: usize, mut n: u64) -> usize {
+    let mut digits = [0u8; 20];
+    let mut digit_count = 0;
+    loop {
+        digits[digit_count] = (n % 10) as u8 + b'0';
+        n /= 10;
+        digit_count += 1;
+        if n == 0 {
+            break;
+        }
+    }
+    for j in (0..digit_count).rev() {
+        buf[i] = digits[j];
+        i += 1;
+    }
+    i
+}
+
+/// Format the id for `counter`: `c_<salt>_<counter>`, or `c_<counter>`
+/// without a salt.
+pub(crate) fn format(e: &Env, salt: Option<u32>, counter: u64) -> String {
+    // "c_" + up to 10 salt digits + "_" + up to 20 counter digits
+    let mut buf = [0u8; 33];
+    buf[0] = b'c';
+    buf[1] = b'_';
+    let mut i = 2;
+    if let Some(salt) = salt {
+        i = push_decimal(&mut buf, i, salt as u64);
+        buf[i] = b'_';
+        i += 1;
+    }
+    i = push_decimal(&mut buf, i, counter);
+    String::from_str(e, core::str::from_utf8(&buf[..i]).unwrap_or("c_0"))
+}
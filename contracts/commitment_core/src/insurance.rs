@@ -0,0 +1,145 @@
+//! Protocol insurance fund, fed by a contribution taken at creation.
+//!
+//! With `insurance_bps` set, `create_commitment` keeps that share of the
+//! deposit (rounded down) out of the commitment: the owner locks the full
+//! amount, the commitment records the rest, and the contribution joins the
+//! asset's insurance fund. The fund is not TVL; the admin pays it out to
+//! cover shortfalls. Each commitment's contribution is remembered so an
+//! admin void can hand it back.
+
+use crate::{
+    audit, fail, require_admin, storage_layout, ttl, CommitmentCoreContract,
+    CommitmentCoreContractClient, CommitmentError,
+};
+use shared_utils::PercentMath;
+use soroban_sdk::{contractimpl, contracttype, symbol_short, token, Address, Env, String};
+
+/// Highest insurance contribution the admin can set (10%)
+pub const MAX_INSURANCE_BPS: u32 = 1_000;
+
+#[contracttype]
+#[derive(Clone)]
+pub enum InsuranceDataKey {
+    InsuranceBps, // u32 — share of new deposits contributed, at most MAX_INSURANCE_BPS
+    InsuranceFund(Address), // asset -> i128 contributions not yet paid out
+    Contribution(String), // commitment_id -> i128 it contributed, absent when 0
+}
+
+/// Contribution owed on a deposit of `amount`, rounded down.
+pub(crate) fn contribution(e: &Env, amount: i128) -> i128 {
+    PercentMath::apply_bps(amount, CommitmentCoreContract::get_insurance_bps(e.clone()))
+}
+
+/// Add `commitment_id`'s contribution of `amount` to the insurance fund in
+/// `asset`.
+pub(crate) fn add_to_fund(e: &Env, asset: &Address, commitment_id: &String, amount: i128) {
+    if amount == 0 {
+        return;
+    }
+    let key = InsuranceDataKey::InsuranceFund(asset.clone());
+    let fund = storage_layout::get::<_, i128>(e, &key).unwrap_or(0);
+    storage_layout::set(e, &key, &(fund + amount));
+    storage_layout::set(
+        e,
+        &InsuranceDataKey::Contribution(commitment_id.clone()),
+        &amount,
+    );
+}
+
+/// Insurance fund held for `asset`.
+pub(crate) fn fund(e: &Env, asset: &Address) -> i128 {
+    storage_layout::get(e, &InsuranceDataKey::InsuranceFund(asset.clone())).unwrap_or(0)
+}
+
+/// Take a voided commitment's contribution back out of the fund and return
+/// it, limited to what the fund still holds after payouts.
+#[cfg(feature = "recovery")]
+pub(crate) fn refund_contribution(e: &Env, asset: &Address, commitment_id: &String) -> i128 {
+    let contribution_key = InsuranceDataKey::Contribution(commitment_id.clone());
+    let Some(contribution) = storage_layout::get::<_, i128>(e, &contribution_key) else {
+        return 0;
+    };
+    storage_layout::remove(e, &contribution_key);
+    let held = fund(e, asset);
+    let refund = contribution.min(held);
+    storage_layout::set(
+        e,
+        &InsuranceDataKey::InsuranceFund(asset.clone()),
+        &(held - refund),
+    );
+    refund
+}
+
+/// Extend a commitment's contribution entry (see `ttl::extend`).
+pub(crate) fn extend_ttl(e: &Env, commitment_id: &String, extend_to: u32) {
+    ttl::extend_persistent(
+        e,
+        &InsuranceDataKey::Contribution(commitment_id.clone()),
+        extend_to,
+    );
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Set the share of each new deposit contributed to the insurance fund
+    /// (admin only).
+    ///
+    /// At most `MAX_INSURANCE_BPS`; `0` (the default) disables it. Existing
+    /// commitments are unaffected.
+    pub fn set_insurance_bps(e: Env, caller: Address, bps: u32) {
+        require_admin(&e, &caller);
+        if bps > MAX_INSURANCE_BPS {
+            fail(&e, CommitmentError::InvalidFee, "set_insurance_bps");
+        }
+        e.storage()
+            .instance()
+            .set(&InsuranceDataKey::InsuranceBps, &bps);
+        audit::record(
+            &e,
+            &caller,
+            symbol_short!("InsureBps"),
+            &e.current_contract_address(),
+        );
+        e.events()
+            .publish((symbol_short!("InsureBps"),), (bps, e.ledger().timestamp()));
+    }
+
+    /// Get the share (bps) of new deposits contributed to the insurance fund
+    pub fn get_insurance_bps(e: Env) -> u32 {
+        e.storage()
+            .instance()
+            .get::<_, u32>(&InsuranceDataKey::InsuranceBps)
+            .unwrap_or(0)
+    }
+
+    /// Get the insurance fund held for an asset
+    pub fn get_insurance_fund(e: Env, asset: Address) -> i128 {
+        fund(&e, &asset)
+    }
+
+    /// Transfer `amount` of the asset's insurance fund to `to` to cover a
+    /// shortfall (admin only).
+    ///
+    /// Fails with InvalidAmount for a non-positive amount and
+    /// InsufficientBalance beyond the fund.
+    pub fn payout_insurance(e: Env, caller: Address, asset: Address, to: Address, amount: i128) {
+        require_admin(&e, &caller);
+        if amount <= 0 {
+            fail(&e, CommitmentError::InvalidAmount, "payout_insurance");
+        }
+        let fund = Self::get_insurance_fund(e.clone(), asset.clone());
+        if amount > fund {
+            fail(&e, CommitmentError::InsufficientBalance, "payout_insurance");
+        }
+        storage_layout::set(
+            &e,
+            &InsuranceDataKey::InsuranceFund(asset.clone()),
+            &(fund - amount),
+        );
+        token::Client::new(&e, &asset).transfer(&e.current_contract_address(), &to, &amount);
+        e.events().publish(
+            (symbol_short!("InsPayout"), asset),
+            (to, amount, fund - amount),
+        );
+    }
+}
@@ -0,0 +1,21 @@
+//! No-op insurance helpers used when the `insurance` feature is compiled out.
+
+use soroban_sdk::{Address, Env, String};
+
+pub(crate) fn contribution(_e: &Env, _amount: i128) -> i128 {
+    0
+}
+
+pub(crate) fn add_to_fund(_e: &Env, _asset: &Address, _commitment_id: &String, _amount: i128) {}
+
+#[cfg(feature = "sweep")]
+pub(crate) fn fund(_e: &Env, _asset: &Address) -> i128 {
+    0
+}
+
+#[cfg(feature = "recovery")]
+pub(crate) fn refund_contribution(_e: &Env, _asset: &Address, _commitment_id: &String) -> i128 {
+    0
+}
+
+pub(crate) fn extend_ttl(_e: &Env, _commitment_id: &String, _extend_to: u32) {}
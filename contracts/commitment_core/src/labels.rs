@@ -0,0 +1,107 @@
+//! Free-text labels on commitments.
+//!
+//! Owners name their commitments ("emergency fund", "ETF ladder #3") at
+//! creation through `create_commitment_with_label`, or later with
+//! `set_label`. A label is at most `MAX_LABEL_BYTES` of UTF-8 and is purely
+//! informational: no rule reads it. An over-long label fails with
+//! LabelTooLong.
+//!
+//! The label is stored beside the commitment rather than as a field of
+//! `Commitment`: stored commitments decode by field layout, so a new field
+//! would make every existing record (and the interface crate's mirror of
+//! the struct) unreadable. Read it with `get_commitment_label`.
+
+use crate::{
+    fail, read_commitment, storage_layout, ttl, CommitmentCoreContract,
+    CommitmentCoreContractClient, CommitmentError, CommitmentRules,
+};
+use soroban_sdk::{contractimpl, contracttype, symbol_short, Address, Env, String};
+
+/// Longest label accepted, in bytes of UTF-8
+pub const MAX_LABEL_BYTES: u32 = 64;
+
+#[contracttype]
+#[derive(Clone)]
+pub enum LabelDataKey {
+    Label(String), // commitment_id -> String, absent when unlabeled
+}
+
+fn check_label(e: &Env, label: &String, context: &str) {
+    if label.len() > MAX_LABEL_BYTES {
+        fail(e, CommitmentError::LabelTooLong, context);
+    }
+}
+
+/// Store `label` on a commitment; an empty label removes it.
+fn write_label(e: &Env, commitment_id: &String, label: &String) {
+    let key = LabelDataKey::Label(commitment_id.clone());
+    if label.is_empty() {
+        storage_layout::remove(e, &key);
+    } else {
+        storage_layout::set(e, &key, label);
+    }
+}
+
+/// Drop a commitment's label (used when a commitment is voided).
+#[cfg(feature = "recovery")]
+pub(crate) fn clear(e: &Env, commitment_id: &String) {
+    storage_layout::remove(e, &LabelDataKey::Label(commitment_id.clone()));
+}
+
+/// Extend a commitment's label entry (see `ttl::extend`).
+pub(crate) fn extend_ttl(e: &Env, commitment_id: &String, extend_to: u32) {
+    ttl::extend_persistent(e, &LabelDataKey::Label(commitment_id.clone()), extend_to);
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Create a commitment carrying `label`.
+    ///
+    /// Otherwise like `create_commitment`. Fails with LabelTooLong beyond
+    /// `MAX_LABEL_BYTES`; an empty label leaves the commitment unlabeled.
+    pub fn create_commitment_with_label(
+        e: Env,
+        owner: Address,
+        amount: i128,
+        asset_address: Address,
+        rules: CommitmentRules,
+        label: String,
+    ) -> Result<String, CommitmentError> {
+        check_label(&e, &label, "create_commitment_with_label");
+        let commitment_id = Self::create_commitment_internal(
+            e.clone(),
+            owner,
+            amount,
+            asset_address,
+            rules,
+            false,
+            None,
+            None,
+            None,
+        )?;
+        write_label(&e, &commitment_id, &label);
+        Ok(commitment_id)
+    }
+
+    /// Replace a commitment's label (owner only); an empty label removes it.
+    ///
+    /// Allowed in any status, since labels have no effect on the commitment.
+    /// Fails with LabelTooLong beyond `MAX_LABEL_BYTES`.
+    pub fn set_label(e: Env, caller: Address, commitment_id: String, label: String) {
+        caller.require_auth();
+        let commitment = read_commitment(&e, &commitment_id)
+            .unwrap_or_else(|| fail(&e, CommitmentError::CommitmentNotFound, "set_label"));
+        if commitment.owner != caller {
+            fail(&e, CommitmentError::Unauthorized, "set_label");
+        }
+        check_label(&e, &label, "set_label");
+        write_label(&e, &commitment_id, &label);
+        e.events()
+            .publish((symbol_short!("Label"), commitment_id), label);
+    }
+
+    /// Get a commitment's label, if it has one
+    pub fn get_commitment_label(e: Env, commitment_id: String) -> Option<String> {
+        storage_layout::get(&e, &LabelDataKey::Label(commitment_id))
+    }
+}
@@ -0,0 +1,8 @@
+//! No-op label helpers used when the `labels` feature is compiled out.
+
+use soroban_sdk::{Env, String};
+
+#[cfg(feature = "recovery")]
+pub(crate) fn clear(_e: &Env, _commitment_id: &String) {}
+
+pub(crate) fn extend_ttl(_e: &Env, _commitment_id: &String, _extend_to: u32) {}
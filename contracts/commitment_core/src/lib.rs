@@ -1,13 +1,77 @@
 #![no_std]
 
+use commitlabs_interfaces::nft::CommitmentNftClient;
+pub use shared_utils::RiskProfile;
 use shared_utils::{
-    emit_error_event, EmergencyControl, RateLimiter, SafeMath, TimeUtils, Validation,
+    emit_error_event, AccessControl, EmergencyControl, PercentMath, TimeUtils, ALLOCATOR_ROLE,
+    ORACLE_ROLE,
 };
 use soroban_sdk::{
     contract, contracterror, contractimpl, contracttype, log, symbol_short, token, Address, Env,
-    IntoVal, String, Symbol, Vec,
+    IntoVal, Map, String, Symbol, TryFromVal, Val, Vec,
 };
 
+/// Errors raised by commitment_core.
+///
+/// This is the contract's only error type, with stable codes mirrored by
+/// `commitlabs_interfaces::core::CommitmentError`. Functions returning a
+/// `Result` surface it directly; the rest raise it through `fail`, which
+/// emits an error event with the code and then aborts with that code, so
+/// `try_*` client methods yield it either way. The enum is at the 50-variant
+/// `contracterror` limit, so some variants cover more than one condition.
+///
+/// | Code | Variant | Raised when |
+/// | --- | --- | --- |
+/// | 1 | `InvalidDuration` | Zero duration |
+/// | 2 | `InvalidMaxLossPercent` | max_loss_percent above 100 |
+/// | 3 | `InvalidCommitmentType` | Unknown commitment type |
+/// | 4 | `InvalidAmount` | Non-positive amount or bad limit setting |
+/// | 5 | `InsufficientBalance` | Not enough value or funds for the operation |
+/// | 6 | `TransferFailed` | Token transfer failed |
+/// | 7 | `MintingFailed` | NFT mint rejected |
+/// | 8 | `CommitmentNotFound` | Unknown commitment id |
+/// | 9 | `Unauthorized` | Caller lacks the required role or ownership |
+/// | 10 | `AlreadyInitialized` | `initialize` called twice |
+/// | 11 | `ReentrancyDetected` | Reentrant call |
+/// | 12 | `NotActive` | Commitment is not active |
+/// | 13 | `InvalidStatus` | Commitment status does not allow the operation |
+/// | 14 | `NotInitialized` | Contract not initialized |
+/// | 15 | `NotExpired` | Settlement before expiry |
+/// | 16 | `AssetNotSupported` | Asset not on the whitelist |
+/// | 17 | `InvalidTag` | Empty tag |
+/// | 18 | `TooManyTags` | Tag limit reached |
+/// | 19 | `DuplicateTag` | Tag already present |
+/// | 20 | `TagNotFound` | Tag not present |
+/// | 21 | `RateLimited` | Caller over the configured rate limit |
+/// | 22 | `VirtualNotAllowed` | Virtual commitments disabled, or operation not allowed on one |
+/// | 23 | `InvalidTwaWindow` | TWA window exceeds the value history |
+/// | 24 | `CollectionNotFound` | NFT collection not registered |
+/// | 25 | `VoidWindowExpired` | Admin void window over |
+/// | 26 | `HasAllocations` | Commitment has outstanding allocations |
+/// | 27 | `NothingPending` | No admin proposal, NFT contract change, NFT sync, settlement claim or exit request pending |
+/// | 28 | `TimelockNotElapsed` | Timelock or exit cooling-off period still running |
+/// | 29 | `DefaultCollectionTimelocked` | Default collection changed outside `set_nft_contract` |
+/// | 30 | `InvalidFee` | Fee above its maximum, or negative `min_fee_threshold` |
+/// | 31 | `DurationTooLong` | Duration above the protocol maximum, or expiration overflows |
+/// | 32 | `AmountBelowMinimum` | Amount below the asset's minimum |
+/// | 33 | `CommitmentFrozen` | Frozen by the admin or the owner, or owner freeze already used |
+/// | 34 | `AmountAboveMaximum` | Amount above the asset's maximum |
+/// | 35 | `GracePeriodExpired` | Cancellation grace period over |
+/// | 36 | `TooManyCommitments` | Owner at the per-owner commitment cap |
+/// | 37 | `MigrationRecordMismatch` | Migration record does not match its source |
+/// | 38 | `NotExported` | Commitment not exported for migration |
+/// | 39 | `AlreadyMigrated` | Commitment already migrated |
+/// | 40 | `MigrationFundsMissing` | Migration funds not released |
+/// | 41 | `AssetQuarantined` | Asset quarantined after failed transfers |
+/// | 42 | `LabelTooLong` | Commitment label over `MAX_LABEL_BYTES` |
+/// | 43 | `UpdateTooFrequent` | Value update or metrics heartbeat before its minimum interval |
+/// | 44 | `ExitRequestRequired` | Commitment type needs a requested early exit |
+/// | 45 | `ExitRequestPending` | Commitment locked by a pending exit request |
+/// | 46 | `IdempotencyKeyReused` | Idempotency key reused with a different amount, asset or rules |
+/// | 47 | `DepositCapExceeded` | Deposit over the owner's per-window cap for the asset |
+/// | 48 | `BatchTooLarge` | Too many ids in one batch |
+/// | 49 | `CommitmentExpired` | Commitment expired; settle it instead |
+/// | 50 | `AllocationExceeded` | Over the pool's allocation or the type's allocation cap |
 #[contracterror]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
 #[repr(u32)]
@@ -28,16 +92,50 @@ pub enum CommitmentError {
     NotInitialized = 14,
     NotExpired = 15,
     AssetNotSupported = 16,
+    InvalidTag = 17,
+    TooManyTags = 18,
+    DuplicateTag = 19,
+    TagNotFound = 20,
+    RateLimited = 21,
+    VirtualNotAllowed = 22,
+    InvalidTwaWindow = 23,
+    CollectionNotFound = 24,
+    VoidWindowExpired = 25,
+    HasAllocations = 26,
+    NothingPending = 27,
+    TimelockNotElapsed = 28,
+    DefaultCollectionTimelocked = 29,
+    InvalidFee = 30,
+    DurationTooLong = 31,
+    AmountBelowMinimum = 32,
+    CommitmentFrozen = 33,
+    AmountAboveMaximum = 34,
+    GracePeriodExpired = 35,
+    TooManyCommitments = 36,
+    MigrationRecordMismatch = 37,
+    NotExported = 38,
+    AlreadyMigrated = 39,
+    MigrationFundsMissing = 40,
+    AssetQuarantined = 41,
+    LabelTooLong = 42,
+    UpdateTooFrequent = 43,
+    ExitRequestRequired = 44,
+    ExitRequestPending = 45,
+    IdempotencyKeyReused = 46,
+    DepositCapExceeded = 47,
+    BatchTooLarge = 48,
+    CommitmentExpired = 49,
+    AllocationExceeded = 50,
 }
 
 impl CommitmentError {
-    /// Human-readable message for debugging and error events.
+    /// Human-readable message for debugging and client UIs.
     pub fn message(&self) -> &'static str {
         match self {
             CommitmentError::InvalidDuration => "Invalid duration: must be greater than zero",
             CommitmentError::InvalidMaxLossPercent => "Invalid max loss: must be 0-100",
             CommitmentError::InvalidCommitmentType => "Invalid commitment type",
-            CommitmentError::InvalidAmount => "Invalid amount: must be greater than zero",
+            CommitmentError::InvalidAmount => "Invalid amount: must be positive",
             CommitmentError::InsufficientBalance => "Insufficient balance",
             CommitmentError::TransferFailed => "Token transfer failed",
             CommitmentError::MintingFailed => "NFT minting failed",
@@ -50,26 +148,71 @@ impl CommitmentError {
             CommitmentError::NotInitialized => "Contract not initialized",
             CommitmentError::NotExpired => "Commitment has not expired yet",
             CommitmentError::AssetNotSupported => "Asset is not in the supported whitelist",
+            CommitmentError::InvalidTag => "Invalid tag: must not be empty",
+            CommitmentError::TooManyTags => "Commitment already has the maximum number of tags",
+            CommitmentError::DuplicateTag => "Tag already present on commitment",
+            CommitmentError::TagNotFound => "Tag not present on commitment",
+            CommitmentError::RateLimited => "Rate limit reached; retry after the window resets",
+            CommitmentError::VirtualNotAllowed => {
+                "Virtual commitments are disabled or not allowed here"
+            }
+            CommitmentError::InvalidTwaWindow => "Invalid TWA window: exceeds value history size",
+            CommitmentError::CollectionNotFound => "NFT collection not registered",
+            CommitmentError::VoidWindowExpired => "Void window has expired for this commitment",
+            CommitmentError::HasAllocations => "Commitment has outstanding allocations",
+            CommitmentError::NothingPending => "No pending request to act on",
+            CommitmentError::TimelockNotElapsed => "Timelock has not elapsed",
+            CommitmentError::DefaultCollectionTimelocked => {
+                "Default collection must be re-pointed via set_nft_contract"
+            }
+            CommitmentError::InvalidFee => {
+                "Invalid fee: must not exceed 10000 bps (or negative min_fee_threshold)"
+            }
+            CommitmentError::DurationTooLong => {
+                "Invalid duration: exceeds protocol maximum or overflows"
+            }
+            CommitmentError::AmountBelowMinimum => "Amount is below the asset's minimum",
+            CommitmentError::CommitmentFrozen => "Commitment is frozen",
+            CommitmentError::AmountAboveMaximum => "Amount is above the asset's maximum",
+            CommitmentError::GracePeriodExpired => {
+                "Cancellation grace period has expired for this commitment"
+            }
+            CommitmentError::TooManyCommitments => {
+                "Owner already holds the maximum number of active commitments"
+            }
+            CommitmentError::MigrationRecordMismatch => {
+                "Migration record does not match its source"
+            }
+            CommitmentError::NotExported => "Commitment has not been exported for migration",
+            CommitmentError::AlreadyMigrated => "Commitment already migrated",
+            CommitmentError::MigrationFundsMissing => "Migration funds have not been released",
+            CommitmentError::AssetQuarantined => "Asset is quarantined after failed transfers",
+            CommitmentError::LabelTooLong => "Label is longer than MAX_LABEL_BYTES",
+            CommitmentError::UpdateTooFrequent => "Update arrived before its minimum interval",
+            CommitmentError::ExitRequestRequired => {
+                "Early exit for this commitment type must be requested first"
+            }
+            CommitmentError::ExitRequestPending => "Commitment is locked by a pending exit request",
+            CommitmentError::IdempotencyKeyReused => {
+                "Idempotency key was already used with different parameters"
+            }
+            CommitmentError::DepositCapExceeded => {
+                "Deposit would exceed the per-window deposit cap"
+            }
+            CommitmentError::BatchTooLarge => "Too many ids in one batch",
+            CommitmentError::CommitmentExpired => "Commitment has expired; settle it instead",
+            CommitmentError::AllocationExceeded => {
+                "Amount exceeds the allocation to this pool (or the type's allocation cap)"
+            }
         }
     }
 }
 
-/// Emit error event and panic with standardized message (for indexers and UX).
+/// Emit an error event (for indexers and UX), then abort with the error's
+/// contract code so `try_*` callers receive it.
 fn fail(e: &Env, err: CommitmentError, context: &str) -> ! {
     emit_error_event(e, err as u32, context);
-    panic!("{}", err.message());
-}
-
-#[contracttype]
-#[derive(Clone)]
-pub struct CommitmentCreatedEvent {
-    pub commitment_id: String,
-    pub owner: Address,
-    pub amount: i128,
-    pub asset_address: Address,
-    pub nft_token_id: u32,
-    pub rules: CommitmentRules,
-    pub timestamp: u64,
+    e.panic_with_error(err)
 }
 
 #[contracttype]
@@ -77,17 +220,66 @@ pub struct CommitmentCreatedEvent {
 pub struct CommitmentRules {
     pub duration_days: u32,
     pub max_loss_percent: u32,
-    pub commitment_type: String, // "safe", "balanced", "aggressive"
+    pub commitment_type: RiskProfile,
     pub early_exit_penalty: u32,
     pub min_fee_threshold: i128,
 }
 
-/// Metadata for a supported asset (symbol, decimals).
+/// A commitment in an owner's listing, tagged with its per-owner creation
+/// sequence number.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OwnerCommitmentRef {
+    pub seq: u64,
+    pub commitment_id: String,
+}
+
+/// Transfer health of an asset as seen by settlements.
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct AssetMetadata {
-    pub symbol: String,
-    pub decimals: u32,
+pub struct AssetHealth {
+    pub consecutive_failures: u32,
+    pub quarantined: bool,
+    pub quarantined_at: u64, // 0 unless quarantined
+}
+
+/// A settlement payout that could not be transferred, claimable later.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SettlementClaim {
+    pub owner: Address,
+    pub asset: Address,
+    pub amount: i128,
+    pub recorded_at: u64,
+}
+
+/// How a commitment reached its terminal state.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ReceiptKind {
+    Settled,
+    EarlyExit,
+    Emergency,
+    Cancelled,
+    Liquidated,
+}
+
+/// Lifecycle status of a commitment.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CommitmentStatus {
+    Active,
+    Settled,
+    Violated,
+    EarlyExit,
+    /// Exported to another core deployment, funds not yet released
+    Migrating,
+    /// Funds released to another core deployment
+    Migrated,
+    /// Cancelled by the owner within the grace period, principal refunded
+    Cancelled,
+    /// Funded but not started; `activate` makes it active at its start time
+    Pending,
 }
 
 #[contracttype]
@@ -102,7 +294,9 @@ pub struct Commitment {
     pub created_at: u64,
     pub expires_at: u64,
     pub current_value: i128,
-    pub status: String, // "active", "settled", "violated", "early_exit"
+    pub status: CommitmentStatus,
+    /// Paper-trading commitment: no funds moved, excluded from TVL
+    pub is_virtual: bool,
 }
 
 #[contracttype]
@@ -110,75 +304,170 @@ pub struct Commitment {
 pub enum DataKey {
     Admin,
     NftContract,
-    Commitment(String),        // commitment_id -> Commitment
-    OwnerCommitments(Address), // owner -> Vec<commitment_id>
-    TotalCommitments,          // counter
-    ReentrancyGuard,           // reentrancy protection flag
-    TotalValueLocked,          // aggregate value locked across active commitments
-    SupportedAssets,          // Vec<Address> — whitelist; empty = allow all
-    AssetMetadata(Address),   // asset -> AssetMetadata (optional)
+    Commitment(String),               // commitment_id -> Commitment
+    OwnerCommitments(Address),        // owner -> Vec<commitment_id> (legacy, see commitment_index)
+    TotalCommitments,                 // counter
+    ReentrancyGuard,                  // reentrancy protection flag
+    TotalValueLocked,                 // principal locked across active commitments
+    SupportedAssets,                  // Vec<Address> — whitelist; empty = allow all
     TotalValueLockedByAsset(Address), // asset -> i128
+    PenaltyPool(Address),             // asset -> early-exit penalties held by the contract
+    CommitmentAllocated(String),      // commitment_id -> total amount ever allocated
+    CommitmentIdCounter,              // monotonic id source (TotalCommitments may decrease)
+    CommitmentNftContract(String),    // commitment_id -> NFT contract that minted its token
+    OwnerCommitmentIndex(Address),    // owner -> Vec<OwnerCommitmentRef> sorted by seq
+    OwnerCommitmentSeq(Address),      // owner -> next creation sequence number
+    ActiveCommitments,                // u64 — commitments currently in "active" status
+    TrackedAssets,                    // Vec<Address> of every asset ever committed
+    PendingNftSync(String),           // commitment_id -> bool (NFT settle call failed)
+    AssetTransferFailures(Address),   // asset -> consecutive failed payout transfers
+    AssetQuarantine(Address),         // asset -> timestamp it was quarantined
+    QuarantineThreshold,              // u32 — failures tolerated before quarantine
+    SettlementClaim(String),          // commitment_id -> SettlementClaim
+    StatusLight(String),              // commitment_id -> (status code, expires_at, current_value)
 }
 
+/// Status codes returned by `get_status_light`
+pub const STATUS_CODE_NOT_FOUND: u32 = 0;
+pub const STATUS_CODE_ACTIVE: u32 = 1;
+pub const STATUS_CODE_SETTLED: u32 = 2;
+pub const STATUS_CODE_VIOLATED: u32 = 3;
+pub const STATUS_CODE_EARLY_EXIT: u32 = 4;
+pub const STATUS_CODE_MIGRATING: u32 = 5;
+pub const STATUS_CODE_MIGRATED: u32 = 6;
+/// A status string outside the set above, recorded before statuses became
+/// `CommitmentStatus` (only through `emergency_update_commitment`)
+pub const STATUS_CODE_OTHER: u32 = 7;
+pub const STATUS_CODE_CANCELLED: u32 = 8;
+pub const STATUS_CODE_PENDING: u32 = 9;
+
+/// Maximum number of ids accepted by `get_status_light_batch` and
+/// `get_violation_details_batch`
+pub const MAX_STATUS_BATCH: u32 = 50;
+
+/// Maximum number of commitment ids `export_holders` and `repair_indexes`
+/// scan in one call
+pub const MAX_SCAN_PAGE: u64 = 100;
+
+/// Cap on the settlement fee (5%)
+pub const MAX_SETTLEMENT_FEE_BPS: u32 = 500;
+
+/// Default number of consecutive failed transfers tolerated per asset
+pub const DEFAULT_QUARANTINE_THRESHOLD: u32 = 3;
+
 /// Transfer assets from owner to contract
-fn transfer_assets(e: &Env, from: &Address, to: &Address, asset_address: &Address, amount: i128) {
+fn try_transfer_assets(
+    e: &Env,
+    from: &Address,
+    to: &Address,
+    asset_address: &Address,
+    amount: i128,
+) -> Result<(), CommitmentError> {
     let token_client = token::Client::new(e, asset_address);
 
     // Check balance first
     let balance = token_client.balance(from);
     if balance < amount {
         log!(e, "Insufficient balance: {} < {}", balance, amount);
-        fail(e, CommitmentError::InsufficientBalance, "transfer_assets");
+        return Err(CommitmentError::InsufficientBalance);
     }
 
-    // Transfer tokens (fails transaction if unsuccessful)
-    token_client.transfer(from, to, &amount);
+    // A reverting token surfaces as TransferFailed
+    if !matches!(token_client.try_transfer(from, to, &amount), Ok(Ok(()))) {
+        return Err(CommitmentError::TransferFailed);
+    }
+    Ok(())
 }
 
-/// Helper function to call NFT contract mint function
-fn call_nft_mint(
+/// Transfer `amount` from `from` to this contract and return how much
+/// arrived: less than `amount` for a token that takes a fee on transfer.
+/// Anything above `amount` is left unaccounted.
+fn try_pull_assets(
     e: &Env,
-    nft_contract: &Address,
-    owner: &Address,
-    commitment_id: &String,
-    duration_days: u32,
-    max_loss_percent: u32,
-    commitment_type: &String,
-    initial_amount: i128,
+    from: &Address,
     asset_address: &Address,
-) -> u32 {
-    let mut args = Vec::new(e);
-    args.push_back(owner.clone().into_val(e));
-    args.push_back(commitment_id.clone().into_val(e));
-    args.push_back(duration_days.into_val(e));
-    args.push_back(max_loss_percent.into_val(e));
-    args.push_back(commitment_type.clone().into_val(e));
-    args.push_back(initial_amount.into_val(e));
-    args.push_back(asset_address.clone().into_val(e));
-
-    // In Soroban, contract calls return the value directly
-    // Failures cause the entire transaction to fail
-    e.invoke_contract::<u32>(nft_contract, &Symbol::new(e, "mint"), args)
+    amount: i128,
+) -> Result<i128, CommitmentError> {
+    let token_client = token::Client::new(e, asset_address);
+    let contract_address = e.current_contract_address();
+    let balance_before = token_client.balance(&contract_address);
+    try_transfer_assets(e, from, &contract_address, asset_address, amount)?;
+    let received = token_client.balance(&contract_address) - balance_before;
+    if received <= 0 {
+        return Err(CommitmentError::TransferFailed);
+    }
+    Ok(received.min(amount))
+}
+
+fn transfer_assets(e: &Env, from: &Address, to: &Address, asset_address: &Address, amount: i128) {
+    if let Err(err) = try_transfer_assets(e, from, to, asset_address, amount) {
+        fail(e, err, "transfer_assets");
+    }
 }
 
 // Storage helpers
 fn read_commitment(e: &Env, commitment_id: &String) -> Option<Commitment> {
-    e.storage()
-        .instance()
-        .get::<_, Commitment>(&DataKey::Commitment(commitment_id.clone()))
+    storage_layout::get::<_, Commitment>(e, &DataKey::Commitment(commitment_id.clone()))
+}
+
+/// Decode a stored commitment record; `None` for one in the original
+/// string-status layout (see `migrate_status_strings`), which lacks
+/// `is_virtual` and cannot be unpacked as a `Commitment`.
+fn decode_commitment(e: &Env, raw: &Val) -> Option<Commitment> {
+    let fields = Map::<Symbol, Val>::try_from_val(e, raw).ok()?;
+    if !fields.contains_key(Symbol::new(e, "is_virtual")) {
+        return None;
+    }
+    Commitment::try_from_val(e, raw).ok()
 }
 
 fn set_commitment(e: &Env, commitment: &Commitment) {
-    e.storage().instance().set(
-        &DataKey::Commitment(commitment.commitment_id.clone()),
-        commitment,
+    let key = DataKey::Commitment(commitment.commitment_id.clone());
+    // Records in the legacy string-status layout count as new
+    let previous =
+        storage_layout::get::<_, Val>(e, &key).and_then(|raw| decode_commitment(e, &raw));
+    stats::record(e, previous.as_ref(), Some(commitment));
+    storage_layout::set(e, &key, commitment);
+    // Every status and value change goes through here, so the compact
+    // record read by get_status_light stays in step. It is kept in its own
+    // persistent entry so a status read loads only this tuple.
+    storage_layout::set(
+        e,
+        &DataKey::StatusLight(commitment.commitment_id.clone()),
+        &status_light(commitment),
     );
 }
 
+fn status_light(commitment: &Commitment) -> (u32, u64, i128) {
+    let code = match commitment.status {
+        CommitmentStatus::Active => STATUS_CODE_ACTIVE,
+        CommitmentStatus::Settled => STATUS_CODE_SETTLED,
+        CommitmentStatus::Violated => STATUS_CODE_VIOLATED,
+        CommitmentStatus::EarlyExit => STATUS_CODE_EARLY_EXIT,
+        CommitmentStatus::Migrating => STATUS_CODE_MIGRATING,
+        CommitmentStatus::Migrated => STATUS_CODE_MIGRATED,
+        CommitmentStatus::Cancelled => STATUS_CODE_CANCELLED,
+        CommitmentStatus::Pending => STATUS_CODE_PENDING,
+    };
+    (code, commitment.expires_at, commitment.current_value)
+}
+
+/// Compact status record; commitments written before it existed fall back
+/// to the full record.
+fn read_status_light(e: &Env, commitment_id: &String) -> (u32, u64, i128) {
+    if let Some(light) =
+        storage_layout::get::<_, (u32, u64, i128)>(e, &DataKey::StatusLight(commitment_id.clone()))
+    {
+        return light;
+    }
+    match read_commitment(e, commitment_id) {
+        Some(commitment) => status_light(&commitment),
+        None => (STATUS_CODE_NOT_FOUND, 0, 0),
+    }
+}
+
 fn has_commitment(e: &Env, commitment_id: &String) -> bool {
-    e.storage()
-        .instance()
-        .has(&DataKey::Commitment(commitment_id.clone()))
+    storage_layout::has(e, &DataKey::Commitment(commitment_id.clone()))
 }
 
 /// Reentrancy protection helpers
@@ -205,26 +494,6 @@ fn set_reentrancy_guard(e: &Env, value: bool) {
 }
 
 /// Require that the asset is in the supported whitelist (if whitelist is non-empty).
-fn require_asset_supported(e: &Env, asset_address: &Address) {
-    let supported = e
-        .storage()
-        .instance()
-        .get::<_, Vec<Address>>(&DataKey::SupportedAssets)
-        .unwrap_or(Vec::new(e));
-        if supported.len() > 0 {
-        let mut found = false;
-        for a in supported.iter() {
-            if a == *asset_address {
-                found = true;
-                break;
-            }
-        }
-        if !found {
-            fail(e, CommitmentError::AssetNotSupported, "require_asset_supported");
-        }
-    }
-}
-
 /// Require that the caller is the admin stored in this contract.
 fn require_admin(e: &Env, caller: &Address) {
     caller.require_auth();
@@ -238,56 +507,992 @@ fn require_admin(e: &Env, caller: &Address) {
     }
 }
 
+/// Gate fund deployment: the admin or holders of the allocator role.
+fn require_allocator(e: &Env, caller: &Address) {
+    caller.require_auth();
+    let admin = e.storage().instance().get::<_, Address>(&DataKey::Admin);
+    if admin.as_ref() == Some(caller) {
+        return;
+    }
+    if !AccessControl::has_role(e, &ALLOCATOR_ROLE, caller) {
+        fail(e, CommitmentError::Unauthorized, "allocate");
+    }
+}
+
+/// Gate value reports: the admin or holders of the oracle role.
+fn require_oracle(e: &Env, caller: &Address) {
+    caller.require_auth();
+    let admin = e.storage().instance().get::<_, Address>(&DataKey::Admin);
+    if admin.as_ref() == Some(caller) {
+        return;
+    }
+    if !AccessControl::has_role(e, &ORACLE_ROLE, caller) {
+        fail(e, CommitmentError::Unauthorized, "update_value");
+    }
+}
+
+/// Number of commitment ids ever issued (counters `0..n`, see `ids`).
+fn commitments_created(e: &Env) -> u64 {
+    e.storage()
+        .instance()
+        .get::<_, u64>(&DataKey::CommitmentIdCounter)
+        .unwrap_or_else(|| {
+            e.storage()
+                .instance()
+                .get::<_, u64>(&DataKey::TotalCommitments)
+                .unwrap_or(0)
+        })
+}
+
+/// Publish a commitment event. Virtual commitments get an extra trailing
+/// `virtual` topic so indexers can filter paper-trading activity.
+fn publish_event<T, D>(e: &Env, is_virtual: bool, topics: T, data: D)
+where
+    T: IntoVal<Env, Vec<Val>>,
+    D: IntoVal<Env, Val>,
+{
+    let mut topics: Vec<Val> = topics.into_val(e);
+    if is_virtual {
+        topics.push_back(symbol_short!("virtual").into_val(e));
+    }
+    e.events().publish(topics, data);
+}
+
+/// NFT contract holding the token for `commitment_id`.
+///
+/// Prefers the address recorded at mint time so re-pointing a collection
+/// never strands existing tokens; commitments created before that record
+/// existed resolve through their collection.
+fn commitment_nft_contract(e: &Env, commitment_id: &String) -> Option<Address> {
+    if let Some(nft_contract) =
+        storage_layout::get::<_, Address>(e, &DataKey::CommitmentNftContract(commitment_id.clone()))
+    {
+        return Some(nft_contract);
+    }
+    collections::nft_contract_of(e, commitment_id).or_else(|| {
+        e.storage()
+            .instance()
+            .get::<_, Address>(&DataKey::NftContract)
+    })
+}
+
+/// An owner's commitments in ascending creation sequence, and the next
+/// sequence number to assign.
+///
+/// Owners whose commitments predate the index are seeded from the
+/// insertion-ordered `OwnerCommitments` list.
+fn read_owner_index(e: &Env, owner: &Address) -> (Vec<OwnerCommitmentRef>, u64) {
+    if let Some(index) = storage_layout::get::<_, Vec<OwnerCommitmentRef>>(
+        e,
+        &DataKey::OwnerCommitmentIndex(owner.clone()),
+    ) {
+        let next_seq =
+            storage_layout::get::<_, u64>(e, &DataKey::OwnerCommitmentSeq(owner.clone()))
+                .unwrap_or(0);
+        return (index, next_seq);
+    }
+    let legacy = e
+        .storage()
+        .instance()
+        .get::<_, Vec<String>>(&DataKey::OwnerCommitments(owner.clone()))
+        .unwrap_or(Vec::new(e));
+    let mut index = Vec::new(e);
+    let mut next_seq = 0u64;
+    for commitment_id in legacy.iter() {
+        index.push_back(OwnerCommitmentRef {
+            seq: next_seq,
+            commitment_id,
+        });
+        next_seq += 1;
+    }
+    (index, next_seq)
+}
+
+fn write_owner_index(e: &Env, owner: &Address, index: &Vec<OwnerCommitmentRef>, next_seq: u64) {
+    storage_layout::set(e, &DataKey::OwnerCommitmentIndex(owner.clone()), index);
+    storage_layout::set(e, &DataKey::OwnerCommitmentSeq(owner.clone()), &next_seq);
+}
+
+/// Add a commitment to its owner's sorted index under the next sequence
+/// number. Must run before the id is appended to the owner's chunked list.
+/// Returns false (and changes nothing) if the id is already indexed.
+fn owner_index_insert(e: &Env, owner: &Address, commitment_id: &String) -> bool {
+    let (mut index, seq) = read_owner_index(e, owner);
+    if index
+        .iter()
+        .any(|entry| entry.commitment_id == *commitment_id)
+    {
+        return false;
+    }
+    // Sequence numbers only grow, so appending keeps the index sorted
+    index.push_back(OwnerCommitmentRef {
+        seq,
+        commitment_id: commitment_id.clone(),
+    });
+    write_owner_index(e, owner, &index, seq + 1);
+    true
+}
+
+/// Append a new commitment to its owner's listings and the protocol-wide
+/// list (no-op if already listed).
+fn add_owner_commitment(e: &Env, owner: &Address, commitment_id: &String) {
+    if owner_index_insert(e, owner, commitment_id) {
+        let ids = Vec::from_array(e, [commitment_id.clone()]);
+        commitment_index::append(e, &CommitmentList::Owner(owner.clone()), &ids);
+        commitment_index::append(e, &CommitmentList::All, &ids);
+    }
+}
+
+/// Remove every index entry for a commitment.
+fn owner_index_remove(e: &Env, owner: &Address, commitment_id: &String) {
+    let (index, next_seq) = read_owner_index(e, owner);
+    let mut kept = Vec::new(e);
+    for entry in index.iter() {
+        if entry.commitment_id != *commitment_id {
+            kept.push_back(entry);
+        }
+    }
+    write_owner_index(e, owner, &kept, next_seq);
+}
+
+/// Hand a commitment to `new_owner`: move it between the two owners'
+/// listings and tag indexes and rewrite `owner`. The caller persists the
+/// commitment.
+fn reassign_owner(e: &Env, commitment: &mut Commitment, new_owner: &Address) {
+    let commitment_id = &commitment.commitment_id;
+    let previous = commitment.owner.clone();
+    tags::move_owner(e, commitment_id, &previous, new_owner);
+    owner_index_remove(e, &previous, commitment_id);
+    commitment_index::remove(e, &CommitmentList::Owner(previous.clone()), commitment_id);
+    // The previous owner's operator does not act for the new one
+    operators::clear(e, commitment_id);
+    if owner_index_insert(e, new_owner, commitment_id) {
+        let ids = Vec::from_array(e, [commitment_id.clone()]);
+        commitment_index::append(e, &CommitmentList::Owner(new_owner.clone()), &ids);
+    }
+    e.events().publish(
+        (symbol_short!("OwnerChg"), commitment_id.clone()),
+        (previous, new_owner.clone()),
+    );
+    commitment.owner = new_owner.clone();
+}
+
+/// Current holder of a commitment's NFT, or `None` if it cannot be read
+/// (virtual commitment, NFT contract unreachable or token burned).
+fn nft_holder(e: &Env, commitment: &Commitment) -> Option<Address> {
+    if commitment.is_virtual {
+        return None;
+    }
+    let nft_contract = commitment_nft_contract(e, &commitment.commitment_id)?;
+    match CommitmentNftClient::new(e, &nft_contract).try_owner_of(&commitment.nft_token_id) {
+        Ok(Ok(holder)) => Some(holder),
+        _ => None,
+    }
+}
+
+/// Mark a commitment's NFT as settled without ever trapping.
+///
+/// Funds must move even if the NFT contract is missing, re-pointed or the
+/// token was burned: on failure the commitment is flagged for
+/// `retry_nft_sync` and an `NftPend` event is emitted. Returns whether the
+/// NFT side was updated.
+fn sync_nft_settled(e: &Env, commitment_id: &String, nft_token_id: u32) -> bool {
+    let synced = match commitment_nft_contract(e, commitment_id) {
+        Some(nft_contract) => {
+            let nft = CommitmentNftClient::new(e, &nft_contract);
+            // Already inactive (retry or race): nothing to notify
+            matches!(nft.try_is_active(&nft_token_id), Ok(Ok(false)))
+                || matches!(
+                    nft.try_settle(&e.current_contract_address(), &nft_token_id),
+                    Ok(Ok(()))
+                )
+        }
+        None => false,
+    };
+    let key = DataKey::PendingNftSync(commitment_id.clone());
+    if synced {
+        storage_layout::remove(e, &key);
+    } else {
+        storage_layout::set(e, &key, &true);
+        e.events().publish(
+            (symbol_short!("NftPend"), commitment_id.clone()),
+            nft_token_id,
+        );
+    }
+    synced
+}
+
+fn is_asset_quarantined(e: &Env, asset: &Address) -> bool {
+    e.storage()
+        .instance()
+        .has(&DataKey::AssetQuarantine(asset.clone()))
+}
+
+fn quarantine_threshold(e: &Env) -> u32 {
+    e.storage()
+        .instance()
+        .get::<_, u32>(&DataKey::QuarantineThreshold)
+        .unwrap_or(DEFAULT_QUARANTINE_THRESHOLD)
+}
+
+/// Count a failed payout transfer, quarantining the asset once failures
+/// exceed the threshold.
+fn record_transfer_failure(e: &Env, asset: &Address) {
+    let key = DataKey::AssetTransferFailures(asset.clone());
+    let failures = e.storage().instance().get::<_, u32>(&key).unwrap_or(0) + 1;
+    e.storage().instance().set(&key, &failures);
+    if failures > quarantine_threshold(e) && !is_asset_quarantined(e, asset) {
+        e.storage()
+            .instance()
+            .set(&DataKey::AssetQuarantine(asset.clone()), &e.ledger().timestamp());
+        e.events().publish(
+            (Symbol::new(e, "AssetQuarantined"), asset.clone()),
+            (failures, e.ledger().timestamp()),
+        );
+    }
+}
+
+/// Pay a settlement out to `to` without ever trapping.
+///
+/// A token that reverts would otherwise block every settlement in that
+/// asset. If the asset is quarantined or the transfer fails, the amount is
+/// recorded as a `SettlementClaim` for `claim_settlement` and a `ClaimRec`
+/// event is emitted. Returns whether the funds were transferred.
+fn pay_out(e: &Env, commitment_id: &String, asset: &Address, to: &Address, amount: i128) -> bool {
+    if amount <= 0 {
+        return true;
+    }
+    if !is_asset_quarantined(e, asset) {
+        let mut args = Vec::new(e);
+        args.push_back(e.current_contract_address().into_val(e));
+        args.push_back(to.into_val(e));
+        args.push_back(amount.into_val(e));
+        let transferred = matches!(
+            e.try_invoke_contract::<(), soroban_sdk::Error>(
+                asset,
+                &symbol_short!("transfer"),
+                args,
+            ),
+            Ok(Ok(()))
+        );
+        if transferred {
+            e.storage()
+                .instance()
+                .remove(&DataKey::AssetTransferFailures(asset.clone()));
+            return true;
+        }
+        record_transfer_failure(e, asset);
+    }
+
+    storage_layout::set(
+        e,
+        &DataKey::SettlementClaim(commitment_id.clone()),
+        &SettlementClaim {
+            owner: to.clone(),
+            asset: asset.clone(),
+            amount,
+            recorded_at: e.ledger().timestamp(),
+        },
+    );
+    sweep::adjust_pending_claims(e, asset, amount);
+    e.events().publish(
+        (symbol_short!("ClaimRec"), commitment_id.clone(), to.clone()),
+        (asset.clone(), amount),
+    );
+    false
+}
+
+/// Whether `commitment` can be settled now: expired and active, or
+/// violated. Returns whether it was active.
+fn check_settleable(e: &Env, commitment: &Commitment) -> Result<bool, CommitmentError> {
+    let was_active = commitment.status == CommitmentStatus::Active;
+    if !was_active && commitment.status != CommitmentStatus::Violated {
+        return Err(CommitmentError::NotActive);
+    }
+    if was_active && e.ledger().timestamp() < commitment.expires_at {
+        return Err(CommitmentError::NotExpired);
+    }
+    Ok(was_active)
+}
+
+/// Split settling `commitment` now into (penalty, management fee,
+/// settlement fee, payout). Only liquidation withholds the early-exit
+/// penalty; the fees accrue on what remains after it.
+fn settlement_split(
+    e: &Env,
+    commitment: &Commitment,
+    kind: ReceiptKind,
+) -> (i128, i128, i128, i128) {
+    let penalty_bps = match kind {
+        ReceiptKind::Liquidated => PercentMath::percent_to_bps(commitment.rules.early_exit_penalty),
+        _ => 0,
+    };
+    let (penalty, after_penalty) =
+        PercentMath::split_amount(commitment.current_value, penalty_bps);
+    let management_fee = fees::accrued_management_fee(e, commitment, after_penalty);
+    let settlement_fee = fees::settlement_fee(e, commitment, after_penalty - management_fee);
+    let payout = after_penalty - management_fee - settlement_fee;
+    (penalty, management_fee, settlement_fee, payout)
+}
+
+/// Settle a commitment at maturity, or a violated one at any time, paying
+/// the current NFT holder; `ReceiptKind::Liquidated` also retains the
+/// early-exit penalty as a violation fee.
+///
+/// Every check (status, expiry, freeze, outstanding allocations) runs
+/// before the NFT holder becomes the owner or the commitment renews, so an
+/// `Err` leaves storage and events untouched and `settle_batch` can skip
+/// the id. Returns the amount paid out (zero for virtual or renewed
+/// commitments). The caller holds the reentrancy guard.
+fn settle_commitment(
+    e: &Env,
+    commitment_id: &String,
+    kind: ReceiptKind,
+) -> Result<i128, CommitmentError> {
+    // CHECKS: Get and validate commitment
+    let mut commitment =
+        read_commitment(e, commitment_id).ok_or(CommitmentError::CommitmentNotFound)?;
+
+    // Verify commitment is expired and active, or violated, and not held
+    let was_active = check_settleable(e, &commitment)?;
+    disputes::check_not_frozen(e, commitment_id)?;
+
+    // Auto-renewing commitments start another term instead of paying out;
+    // otherwise deployed funds must come back before the owner is paid
+    let renew_until = if was_active && kind == ReceiptKind::Settled {
+        renewal::renewal_expiry(e, &commitment)
+    } else {
+        None
+    };
+    if renew_until.is_none() {
+        allocations::check_unallocated(e, commitment_id)?;
+    }
+
+    // EFFECTS: Update state before external calls
+    // The NFT is transferable: pay whoever holds it now. If the NFT cannot
+    // be read the recorded owner is paid, as settlement must not trap.
+    if let Some(holder) = nft_holder(e, &commitment) {
+        if holder != commitment.owner {
+            reassign_owner(e, &mut commitment, &holder);
+        }
+    }
+    if let Some(expires_at) = renew_until {
+        renewal::renew(e, &mut commitment, expires_at);
+        return Ok(0);
+    }
+
+    let settlement_amount = commitment.current_value;
+    let (penalty, management_fee, settlement_fee, payout) =
+        settlement_split(e, &commitment, kind);
+    commitment.status = CommitmentStatus::Settled;
+    set_commitment(e, &commitment);
+    tags::clear(e, &commitment);
+    clear_value_update_state(e, commitment_id);
+    exit_requests::clear(e, commitment_id);
+    if was_active {
+        adjust_active_commitments(e, -1);
+    }
+    receipts::record(
+        e,
+        &commitment,
+        kind,
+        settlement_amount,
+        penalty,
+        management_fee,
+        settlement_fee,
+    );
+
+    // Virtual commitments hold no funds: skip TVL, transfer and NFT
+    if !commitment.is_virtual {
+        // Release the principal from TVL and book the gain or loss on it
+        tvl::release(e, &commitment, settlement_amount);
+        pnl::book_owner_pnl(e, &commitment, payout);
+        tvl_history::maybe_snapshot(e);
+
+        // Any violation fee joins the early-exit penalties; management fees
+        // stay in the contract for the treasury and settlement fees go to
+        // the fee pool
+        add_to_penalty_pool(e, &commitment.asset_address, penalty);
+        statement::ledger_entry(e, &commitment, LedgerEntryKind::Penalty, -penalty);
+        fees::add_protocol_fees(e, &commitment.asset_address, management_fee);
+        fees::add_to_fee_pool(e, &commitment.asset_address, settlement_fee);
+        statement::ledger_entry(
+            e,
+            &commitment,
+            LedgerEntryKind::ManagementFee,
+            -management_fee,
+        );
+        statement::ledger_entry(
+            e,
+            &commitment,
+            LedgerEntryKind::SettlementFee,
+            -settlement_fee,
+        );
+
+        // INTERACTIONS: External calls (token transfer, NFT settlement)
+        // Transfer assets (net of management fee) back to owner, vest them
+        // (see vesting::pay_out) or record a claim if the asset is failing
+        // (see pay_out)
+        if vesting::pay_out(e, &commitment, payout) {
+            statement::ledger_entry(e, &commitment, LedgerEntryKind::Payout, -payout);
+        }
+
+        // Mark the NFT as settled (non-fatal; see sync_nft_settled)
+        sync_nft_settled(e, commitment_id, commitment.nft_token_id);
+    }
+
+    // Emit settlement event
+    publish_event(
+        e,
+        commitment.is_virtual,
+        (symbol_short!("Settled"), commitment_id.clone()),
+        CommitmentSettledEvent {
+            commitment_id: commitment_id.clone(),
+            owner: commitment.owner.clone(),
+            payout,
+            management_fee,
+            settlement_fee,
+            timestamp: e.ledger().timestamp(),
+        },
+    );
+    Ok(if commitment.is_virtual { 0 } else { payout })
+}
+
+/// Record a loss violation on an active commitment. The value stays locked
+/// until the commitment is settled.
+fn mark_violated(e: &Env, commitment: &mut Commitment, loss_bps: u32) {
+    commitment.status = CommitmentStatus::Violated;
+    set_commitment(e, commitment);
+    exit_requests::clear(e, &commitment.commitment_id);
+    adjust_active_commitments(e, -1);
+    publish_event(
+        e,
+        commitment.is_virtual,
+        (symbol_short!("Violated"), commitment.commitment_id.clone()),
+        CommitmentViolatedEvent {
+            commitment_id: commitment.commitment_id.clone(),
+            owner: commitment.owner.clone(),
+            reason: symbol_short!("LossViol"),
+            loss_bps,
+            timestamp: e.ledger().timestamp(),
+        },
+    );
+}
+
+/// Settle a commitment under emergency mode, paying its full current value
+/// to the owner with no maturity check, penalty or fee. Callers reject
+/// commitments with funds out at a pool first (HasAllocations), since a
+/// settled commitment can no longer be deallocated. Returns the payout.
+fn emergency_release(e: &Env, commitment: &mut Commitment) -> i128 {
+    let commitment_id = commitment.commitment_id.clone();
+    if matches!(
+        commitment.status,
+        CommitmentStatus::Active | CommitmentStatus::Pending
+    ) {
+        adjust_active_commitments(e, -1);
+    }
+    commitment.status = CommitmentStatus::Settled;
+    let settlement_amount = commitment.current_value;
+    commitment.current_value = 0;
+    set_commitment(e, commitment);
+    tags::clear(e, commitment);
+    clear_value_update_state(e, &commitment_id);
+    exit_requests::clear(e, &commitment_id);
+    receipts::record(
+        e,
+        commitment,
+        ReceiptKind::Emergency,
+        settlement_amount,
+        0,
+        0,
+        0,
+    );
+
+    // Virtual commitments hold no funds: skip TVL, transfer and NFT
+    if !commitment.is_virtual {
+        // Release the principal from TVL and book the gain or loss on it
+        tvl::release(e, commitment, settlement_amount);
+        pnl::book_owner_pnl(e, commitment, settlement_amount);
+
+        // Transfer funds back to owner, or record a claim if the asset is
+        // failing (see pay_out)
+        if pay_out(
+            e,
+            &commitment_id,
+            &commitment.asset_address,
+            &commitment.owner,
+            settlement_amount,
+        ) {
+            statement::ledger_entry(e, commitment, LedgerEntryKind::Payout, -settlement_amount);
+        }
+
+        // Update NFT (non-fatal)
+        sync_nft_settled(e, &commitment_id, commitment.nft_token_id);
+    }
+    settlement_amount
+}
+
+/// Drop a commitment's update-interval, pending-violation and operator
+/// state (terminal transitions).
+fn clear_value_update_state(e: &Env, commitment_id: &String) {
+    value_log::clear_last_update(e, commitment_id);
+    violations::clear_pending(e, commitment_id);
+    operators::clear(e, commitment_id);
+}
+
+/// Apply a signed delta to the active-commitment counter.
+fn adjust_active_commitments(e: &Env, delta: i64) {
+    let count = e
+        .storage()
+        .instance()
+        .get::<_, u64>(&DataKey::ActiveCommitments)
+        .unwrap_or(0);
+    e.storage()
+        .instance()
+        .set(&DataKey::ActiveCommitments, &count.saturating_add_signed(delta));
+}
+
+/// Add an asset to the tracked set the first time it is committed.
+fn track_asset(e: &Env, asset: &Address) {
+    let mut assets = e
+        .storage()
+        .instance()
+        .get::<_, Vec<Address>>(&DataKey::TrackedAssets)
+        .unwrap_or(Vec::new(e));
+    if !assets.contains(asset) {
+        assets.push_back(asset.clone());
+        e.storage().instance().set(&DataKey::TrackedAssets, &assets);
+    }
+}
+
+/// Apply a signed delta to the per-asset TVL counter, never below zero.
+fn adjust_asset_tvl(e: &Env, asset: &Address, delta: i128) {
+    let key = DataKey::TotalValueLockedByAsset(asset.clone());
+    let asset_tvl = storage_layout::get::<_, i128>(e, &key).unwrap_or(0);
+    storage_layout::set(e, &key, &asset_tvl.saturating_add(delta).max(0));
+}
+
+/// Move both the total and the per-asset TVL by `delta`, never below zero.
+fn adjust_tvl(e: &Env, asset: &Address, delta: i128) {
+    let tvl = e
+        .storage()
+        .instance()
+        .get::<_, i128>(&DataKey::TotalValueLocked)
+        .unwrap_or(0);
+    e.storage()
+        .instance()
+        .set(&DataKey::TotalValueLocked, &tvl.saturating_add(delta).max(0));
+    adjust_asset_tvl(e, asset, delta);
+}
+
+fn add_to_penalty_pool(e: &Env, asset: &Address, amount: i128) {
+    if amount == 0 {
+        return;
+    }
+    let key = DataKey::PenaltyPool(asset.clone());
+    let collected = storage_layout::get::<_, i128>(e, &key).unwrap_or(0);
+    storage_layout::set(e, &key, &(collected + amount));
+    stats::add_penalties(e, amount);
+    e.events().publish(
+        (Symbol::new(e, "PenaltyCollected"), asset.clone()),
+        (amount, collected + amount),
+    );
+}
+
+mod admin;
+
+mod allocations;
+pub use allocations::Allocation;
+
+mod commitment_index;
+pub use commitment_index::{CommitmentList, COMMITMENT_ID_CHUNK_SIZE};
+
+mod events;
+pub use events::{
+    CommitmentCreatedEvent, CommitmentSettledEvent, CommitmentViolatedEvent, EarlyExitEvent,
+    ValueUpdatedEvent,
+};
+
+mod expiry_index;
+pub use expiry_index::EXPIRY_BUCKET_SECONDS;
+
+mod ids;
+
+mod ownership;
+
+mod storage_layout;
+pub use storage_layout::STORAGE_VERSION;
+
+mod ttl;
+pub use ttl::TTL_GRACE_DAYS;
+
+mod tvl;
+
+#[cfg(feature = "analytics")]
+mod analytics;
+#[cfg(feature = "analytics")]
+pub use analytics::{
+    HolderSummary, MetricsHeartbeat, ProtocolHealth, ProtocolSnapshot,
+    DEFAULT_HEARTBEAT_INTERVAL_SECONDS, HEARTBEAT_TOP_ASSETS,
+};
+
+#[cfg(feature = "analytics")]
+mod tvl_history;
+#[cfg(feature = "analytics")]
+pub use tvl_history::{TvlSnapshot, MAX_TVL_SNAPSHOTS, TVL_SNAPSHOT_INTERVAL_SECONDS};
+#[cfg(not(feature = "analytics"))]
+#[path = "tvl_history_disabled.rs"]
+mod tvl_history;
+
+#[cfg(feature = "migration")]
+mod migration;
+#[cfg(feature = "migration")]
+pub use migration::MigrationRecord;
+
+#[cfg(feature = "fees")]
+mod fees;
+#[cfg(not(feature = "fees"))]
+#[path = "fees_disabled.rs"]
+mod fees;
+
+#[cfg(feature = "insurance")]
+mod insurance;
+#[cfg(feature = "insurance")]
+pub use insurance::MAX_INSURANCE_BPS;
+#[cfg(not(feature = "insurance"))]
+#[path = "insurance_disabled.rs"]
+mod insurance;
+
+#[cfg(feature = "labels")]
+mod labels;
+#[cfg(feature = "labels")]
+pub use labels::MAX_LABEL_BYTES;
+#[cfg(not(feature = "labels"))]
+#[path = "labels_disabled.rs"]
+mod labels;
+
+#[cfg(feature = "statement")]
+mod statement;
+#[cfg(feature = "statement")]
+pub use statement::{
+    CommitmentLedger, CommitmentStatement, LedgerEntry, LedgerEntryKind, MAX_LEDGER_ENTRIES,
+};
+#[cfg(not(feature = "statement"))]
+#[path = "statement_disabled.rs"]
+mod statement;
+#[cfg(not(feature = "statement"))]
+use statement::LedgerEntryKind;
+
+// Test fixtures write straight into storage and must never ship
+#[cfg(feature = "limits")]
+mod asset_limits;
+#[cfg(feature = "limits")]
+pub use asset_limits::AssetLimits;
+#[cfg(not(feature = "limits"))]
+#[path = "asset_limits_disabled.rs"]
+mod asset_limits;
+
+#[cfg(feature = "limits")]
+mod deposit_limits;
+#[cfg(feature = "limits")]
+pub use deposit_limits::{DepositCap, DepositWindow};
+#[cfg(not(feature = "limits"))]
+#[path = "deposit_limits_disabled.rs"]
+mod deposit_limits;
+
+#[cfg(feature = "limits")]
+mod duration_limits;
+#[cfg(not(feature = "limits"))]
+#[path = "duration_limits_disabled.rs"]
+mod duration_limits;
+
+#[cfg(feature = "limits")]
+mod owner_limits;
+#[cfg(not(feature = "limits"))]
+#[path = "owner_limits_disabled.rs"]
+mod owner_limits;
+
+#[cfg(feature = "operators")]
+mod operators;
+#[cfg(not(feature = "operators"))]
+#[path = "operators_disabled.rs"]
+mod operators;
+
+#[cfg(feature = "renewal")]
+mod renewal;
+#[cfg(not(feature = "renewal"))]
+#[path = "renewal_disabled.rs"]
+mod renewal;
+
+#[cfg(feature = "scheduling")]
+mod scheduling;
+#[cfg(feature = "scheduling")]
+pub use scheduling::MAX_START_DELAY_SECONDS;
+
+#[cfg(feature = "scheduling")]
+mod durations;
+#[cfg(feature = "scheduling")]
+pub use durations::MIN_DURATION_SECONDS;
+
+#[cfg(feature = "idempotency")]
+mod idempotency;
+#[cfg(feature = "idempotency")]
+pub use idempotency::IDEMPOTENCY_KEY_TTL_LEDGERS;
+#[cfg(all(feature = "recovery", not(feature = "idempotency")))]
+#[path = "idempotency_disabled.rs"]
+mod idempotency;
+
+#[cfg(feature = "sweep")]
+mod sweep;
+#[cfg(feature = "sweep")]
+pub use sweep::SolvencyEntry;
+#[cfg(not(feature = "sweep"))]
+#[path = "sweep_disabled.rs"]
+mod sweep;
+
+#[cfg(feature = "liquidation")]
+mod liquidation;
+
+#[cfg(feature = "cancellation")]
+mod cancellation;
+#[cfg(feature = "cancellation")]
+pub use cancellation::DEFAULT_GRACE_PERIOD_SECONDS;
+
+#[cfg(feature = "disputes")]
+mod disputes;
+#[cfg(feature = "disputes")]
+pub use disputes::{CommitmentFlags, CommitmentFreeze};
+#[cfg(not(feature = "disputes"))]
+#[path = "disputes_disabled.rs"]
+mod disputes;
+
+#[cfg(feature = "quotes")]
+mod quotes;
+#[cfg(feature = "quotes")]
+pub use quotes::{CommitmentQuote, EarlyExitQuote, ExitQuote, SettlementPreview, SettlementQuote};
+
+#[cfg(feature = "tags")]
+mod tags;
+#[cfg(feature = "tags")]
+pub use tags::MAX_TAGS_PER_COMMITMENT;
+#[cfg(not(feature = "tags"))]
+#[path = "tags_disabled.rs"]
+mod tags;
+
+#[cfg(feature = "freezes")]
+mod freezes;
+#[cfg(feature = "freezes")]
+pub use freezes::{UpdateFreeze, DEFAULT_MAX_FREEZE_SECONDS, FREEZE_OWNER_COOLDOWN_SECONDS};
+#[cfg(not(feature = "freezes"))]
+#[path = "freezes_disabled.rs"]
+mod freezes;
+
+#[cfg(feature = "exit_requests")]
+mod exit_requests;
+#[cfg(feature = "exit_requests")]
+pub use exit_requests::ExitRequest;
+#[cfg(not(feature = "exit_requests"))]
+#[path = "exit_requests_disabled.rs"]
+mod exit_requests;
+
+#[cfg(feature = "collections")]
+mod collections;
+#[cfg(feature = "collections")]
+pub use collections::DEFAULT_COLLECTION;
+#[cfg(not(feature = "collections"))]
+#[path = "collections_disabled.rs"]
+mod collections;
+
+#[cfg(feature = "nft_pointer")]
+mod nft_pointer;
+#[cfg(feature = "nft_pointer")]
+pub use nft_pointer::{PendingNftContract, NFT_CONTRACT_TIMELOCK_SECONDS};
+#[cfg(not(feature = "nft_pointer"))]
+#[path = "nft_pointer_disabled.rs"]
+mod nft_pointer;
+
+#[cfg(feature = "value_history")]
+mod value_log;
+#[cfg(feature = "value_history")]
+pub use value_log::{ValueUpdate, MAX_VALUE_HISTORY};
+#[cfg(not(feature = "value_history"))]
+#[path = "value_log_disabled.rs"]
+mod value_log;
+
+#[cfg(feature = "virtual_mode")]
+mod virtual_mode;
+
+#[cfg(feature = "amendments")]
+mod amendments;
+
+#[cfg(feature = "receipts")]
+mod receipts;
+#[cfg(feature = "receipts")]
+pub use receipts::SettlementReceipt;
+#[cfg(not(feature = "receipts"))]
+#[path = "receipts_disabled.rs"]
+mod receipts;
+
+#[cfg(feature = "recovery")]
+mod recovery;
+#[cfg(feature = "recovery")]
+pub use recovery::VOID_WINDOW_SECONDS;
+
+#[cfg(feature = "violation_grace")]
+mod violations;
+#[cfg(feature = "violation_grace")]
+pub use violations::ViolationDetails;
+#[cfg(not(feature = "violation_grace"))]
+#[path = "violations_disabled.rs"]
+mod violations;
+
+#[cfg(feature = "pnl")]
+mod pnl;
+#[cfg(not(feature = "pnl"))]
+#[path = "pnl_disabled.rs"]
+mod pnl;
+
+#[cfg(feature = "batch")]
+mod batch;
+#[cfg(feature = "batch")]
+pub use batch::MAX_SETTLE_BATCH;
+
+#[cfg(feature = "stats")]
+mod stats;
+#[cfg(feature = "stats")]
+pub use stats::{ContractStats, OwnerStats};
+#[cfg(not(feature = "stats"))]
+#[path = "stats_disabled.rs"]
+mod stats;
+
+#[cfg(feature = "allocation_controls")]
+mod allocation_controls;
+#[cfg(not(feature = "allocation_controls"))]
+#[path = "allocation_controls_disabled.rs"]
+mod allocation_controls;
+
+#[cfg(feature = "asset_metadata")]
+mod asset_metadata;
+#[cfg(feature = "asset_metadata")]
+pub use asset_metadata::AssetMetadata;
+
+#[cfg(feature = "rate_limits")]
+mod rate_limits;
+#[cfg(not(feature = "rate_limits"))]
+#[path = "rate_limits_disabled.rs"]
+mod rate_limits;
+
+#[cfg(feature = "audit_log")]
+mod audit;
+#[cfg(not(feature = "audit_log"))]
+#[path = "audit_disabled.rs"]
+mod audit;
+
+#[cfg(feature = "vesting")]
+mod vesting;
+#[cfg(feature = "vesting")]
+pub use vesting::{VestingSchedule, MAX_VESTING_PERIOD_SECONDS};
+#[cfg(not(feature = "vesting"))]
+#[path = "vesting_disabled.rs"]
+mod vesting;
+
+#[cfg(feature = "baskets")]
+mod baskets;
+#[cfg(feature = "baskets")]
+pub use baskets::{Basket, MAX_BASKET_SIZE};
+
+#[cfg(all(feature = "fixtures", target_family = "wasm", not(debug_assertions)))]
+compile_error!("the `fixtures` feature must not be enabled in release wasm builds");
+#[cfg(any(test, feature = "fixtures"))]
+pub mod fixtures;
+
 #[contract]
 pub struct CommitmentCoreContract;
 
 #[contractimpl]
 impl CommitmentCoreContract {
     /// Validate commitment rules using shared utilities
-    fn validate_rules(e: &Env, rules: &CommitmentRules) {
-        // Duration must be > 0
-        Validation::require_valid_duration(rules.duration_days);
+    fn validate_rules(rules: &CommitmentRules) -> Result<(), CommitmentError> {
+        if rules.duration_days == 0 {
+            Err(CommitmentError::InvalidDuration)
+        } else if rules.max_loss_percent > 100 {
+            Err(CommitmentError::InvalidMaxLossPercent)
+        } else if rules.min_fee_threshold < 0 {
+            Err(CommitmentError::InvalidFee)
+        } else {
+            Ok(())
+        }
+    }
 
-        // Max loss percent must be between 0 and 100
-        Validation::require_valid_percent(rules.max_loss_percent);
+    /// Error `amount` and `rules` would be rejected with at creation time.
+    fn creation_error(amount: i128, rules: &CommitmentRules) -> Result<(), CommitmentError> {
+        if amount <= 0 {
+            return Err(CommitmentError::InvalidAmount);
+        }
+        Self::validate_rules(rules)
+    }
+
+    /// Split `value` on early exit at `at` into (penalty, management fee,
+    /// payout). The penalty is rounded down so any rounding remainder is
+    /// returned to the owner; the management fee accrues on what remains, for
+    /// the time actually elapsed.
+    fn early_exit_split(
+        commitment: &Commitment,
+        penalty_bps: u32,
+        fee_bps: u32,
+        value: i128,
+        at: u64,
+    ) -> (i128, i128, i128) {
+        let (penalty, after_penalty) = PercentMath::split_amount(value, penalty_bps);
+        let management_fee = fees::management_fee_at(commitment, fee_bps, after_penalty, at);
+        (penalty, management_fee, after_penalty - management_fee)
+    }
+
+    /// Whether `commitment` can exit early now: active and not yet expired
+    /// (matured commitments settle without a penalty).
+    fn check_exitable(e: &Env, commitment: &Commitment) -> Result<(), CommitmentError> {
+        if commitment.status != CommitmentStatus::Active {
+            return Err(CommitmentError::NotActive);
+        }
+        if TimeUtils::now(e) >= commitment.expires_at {
+            return Err(CommitmentError::CommitmentExpired);
+        }
+        allocations::check_unallocated(e, &commitment.commitment_id)
+    }
 
-        // Commitment type must be valid
-        let valid_types = ["safe", "balanced", "aggressive"];
-        Validation::require_valid_commitment_type(e, &rules.commitment_type, &valid_types);
+    /// Split exiting `commitment` early now into (fee threshold met,
+    /// penalty, management fee, payout). The penalty is halved once
+    /// allocations have earned the min_fee_threshold.
+    fn early_exit_terms(e: &Env, commitment: &Commitment) -> (bool, i128, i128, i128) {
+        let fee_threshold_met = allocations::fee_threshold_met(e, commitment);
+        let mut penalty_bps = PercentMath::percent_to_bps(commitment.rules.early_exit_penalty);
+        if fee_threshold_met {
+            penalty_bps /= 2;
+        }
+        let (penalty, management_fee, payout) = Self::early_exit_split(
+            commitment,
+            penalty_bps,
+            fees::commitment_fee_bps(e, commitment),
+            commitment.current_value,
+            e.ledger().timestamp(),
+        );
+        (fee_threshold_met, penalty, management_fee, payout)
     }
 
-    /// Generate unique commitment ID
-    /// Optimized: Uses counter to create unique ID efficiently
+    /// Commitment id for `counter` on this instance (see `ids`)
     fn generate_commitment_id(e: &Env, counter: u64) -> String {
-        // OPTIMIZATION: Use counter directly as string to minimize allocations
-        // This is more gas-efficient than string concatenation
-        let mut buf = [0u8; 32];
-        let prefix = b"c_";
-        buf[0] = prefix[0];
-        buf[1] = prefix[1];
-
-        // Convert counter to string representation
-        let mut n = counter;
-        let mut i = 2;
-        if n == 0 {
-            buf[i] = b'0';
-            i += 1;
-        } else {
-            let mut digits = [0u8; 20];
-            let mut digit_count = 0;
-            while n > 0 {
-                digits[digit_count] = (n % 10) as u8 + b'0';
-                n /= 10;
-                digit_count += 1;
-            }
-            // Reverse digits
-            for j in 0..digit_count {
-                buf[i] = digits[digit_count - 1 - j];
-                i += 1;
-            }
-        }
+        ids::format(e, ids::salt(e), counter)
+    }
 
-        String::from_str(e, core::str::from_utf8(&buf[..i]).unwrap_or("c_0"))
+    /// First id from `counter` on that is not taken, with its counter.
+    /// Ids are never reused, even if the counter was reset.
+    fn next_free_commitment_id(e: &Env, mut counter: u64) -> (u64, String) {
+        let mut commitment_id = Self::generate_commitment_id(e, counter);
+        while has_commitment(e, &commitment_id) {
+            counter += 1;
+            commitment_id = Self::generate_commitment_id(e, counter);
+        }
+        (counter, commitment_id)
     }
 
     /// Initialize the core commitment contract
@@ -312,6 +1517,10 @@ impl CommitmentCoreContract {
         e.storage()
             .instance()
             .set(&DataKey::TotalValueLocked, &0i128);
+
+        // Salt commitment ids so a fresh instance never reissues old ones
+        ids::init_salt(&e);
+        storage_layout::init_version(&e);
     }
 
     /// Create a new commitment
@@ -328,7 +1537,6 @@ impl CommitmentCoreContract {
     /// - `amount > 0`
     /// - `rules.duration_days > 0`
     /// - `rules.max_loss_percent <= 100`
-    /// - `rules.commitment_type ∈ {"safe", "balanced", "aggressive"}`
     /// - Contract is initialized
     /// - `reentrancy_guard == false`
     ///
@@ -357,24 +1565,114 @@ impl CommitmentCoreContract {
         amount: i128,
         asset_address: Address,
         rules: CommitmentRules,
-    ) -> String {
-        // Reentrancy protection
+    ) -> Result<String, CommitmentError> {
+        Self::create_commitment_internal(
+            e,
+            owner,
+            amount,
+            asset_address,
+            rules,
+            false,
+            None,
+            None,
+            None,
+        )
+    }
+
+    /// With `start_at`, the commitment is created `Pending` and its term runs
+    /// from `start_at` (see `create_commitment_scheduled`). With
+    /// `duration_seconds`, the term is that many seconds rather than
+    /// `rules.duration_days` days (see `create_commitment_with_seconds`).
+    #[allow(clippy::too_many_arguments)]
+    fn create_commitment_internal(
+        e: Env,
+        owner: Address,
+        amount: i128,
+        asset_address: Address,
+        rules: CommitmentRules,
+        is_virtual: bool,
+        collection: Option<Symbol>,
+        start_at: Option<u64>,
+        duration_seconds: Option<u64>,
+    ) -> Result<String, CommitmentError> {
+        // The owner's funds are pulled, so the owner must sign. Checked before
+        // the reentrancy guard is set so a failed auth leaves nothing behind.
+        owner.require_auth();
+
+        // Reentrancy protection; every error path below returns through here
+        // so the guard is always cleared
         require_no_reentrancy(&e);
         set_reentrancy_guard(&e, true);
-        EmergencyControl::require_not_emergency(&e);
+        let result = Self::create_commitment_guarded(
+            e.clone(),
+            owner,
+            amount,
+            asset_address,
+            rules,
+            is_virtual,
+            collection,
+            start_at,
+            duration_seconds,
+        );
+        set_reentrancy_guard(&e, false);
+        result
+    }
 
-        // Rate limit: per-owner commitment creation
+    /// Body of `create_commitment_internal`, run under the reentrancy guard.
+    /// An `Err` rolls back everything written here.
+    #[allow(clippy::too_many_arguments)]
+    fn create_commitment_guarded(
+        e: Env,
+        owner: Address,
+        amount: i128,
+        asset_address: Address,
+        rules: CommitmentRules,
+        is_virtual: bool,
+        collection: Option<Symbol>,
+        start_at: Option<u64>,
+        duration_seconds: Option<u64>,
+    ) -> Result<String, CommitmentError> {
         let fn_symbol = symbol_short!("create");
-        RateLimiter::check(&e, &owner, &fn_symbol);
+        EmergencyControl::require_not_paused(&e, &fn_symbol);
 
-        // Validate amount > 0 using shared utilities
-        Validation::require_positive(amount);
-
-        // Validate rules
-        Self::validate_rules(&e, &rules);
+        // Rate limit: per-owner commitment creation
+        rate_limits::check(&e, &owner, &fn_symbol)?;
+
+        // Validate amount > 0 and rules
+        Self::creation_error(amount, &rules)?;
+        asset_limits::check_amount(&e, &asset_address, amount)?;
+        owner_limits::check(&e, &owner)?;
+        if !is_virtual {
+            deposit_limits::check(&e, &owner, &asset_address, amount)?;
+        }
 
         // Require asset is in supported whitelist (if whitelist is set)
-        require_asset_supported(&e, &asset_address);
+        if !Self::is_asset_supported(e.clone(), asset_address.clone()) {
+            return Err(CommitmentError::AssetNotSupported);
+        }
+        if is_asset_quarantined(&e, &asset_address) {
+            return Err(CommitmentError::AssetQuarantined);
+        }
+
+        // INTERACTIONS: Pull the funds before recording anything, so the
+        // commitment holds what actually arrived. A fee-on-transfer token
+        // delivers less than `amount`, and crediting the full amount would
+        // leave the last settlement in the asset short. The reentrancy guard
+        // is held throughout.
+        let requested_amount = amount;
+        let received_amount = if is_virtual {
+            amount
+        } else {
+            try_pull_assets(&e, &owner, &asset_address, amount)?
+        };
+        // The insurance contribution stays in the contract outside the
+        // commitment; virtual commitments hold nothing to contribute
+        let contribution = if is_virtual {
+            0
+        } else {
+            insurance::contribution(&e, received_amount)
+        };
+        let amount = received_amount - contribution;
 
         // OPTIMIZATION: Read both counters and NFT contract once to minimize storage operations
         let (current_total, current_tvl, nft_contract) = {
@@ -392,29 +1690,45 @@ impl CommitmentCoreContract {
                 .storage()
                 .instance()
                 .get::<_, Address>(&DataKey::NftContract)
-                .unwrap_or_else(|| {
-                    set_reentrancy_guard(&e, false);
-                    fail(&e, CommitmentError::NotInitialized, "create_commitment")
-                });
+                .ok_or(CommitmentError::NotInitialized)?;
             (total, tvl, nft)
         };
 
-        // Generate unique commitment ID using counter
-        let commitment_id = Self::generate_commitment_id(&e, current_total);
+        // Route the mint to the requested collection, if any
+        let collection = collections::route(&e, collection)?;
+        let nft_contract = match &collection {
+            Some((_, collection_nft)) => collection_nft.clone(),
+            None => nft_contract,
+        };
 
-        // CHECKS: Validate commitment doesn't already exist
-        if has_commitment(&e, &commitment_id) {
-            set_reentrancy_guard(&e, false);
-            fail(&e, CommitmentError::InvalidStatus, "create_commitment");
-        }
+        // Generate unique commitment ID using counter
+        // Ids come from a monotonic counter; fall back to TotalCommitments for
+        // deployments created before voiding could decrement it
+        let id_counter = e
+            .storage()
+            .instance()
+            .get::<_, u64>(&DataKey::CommitmentIdCounter)
+            .unwrap_or(current_total);
+        let (id_counter, commitment_id) = Self::next_free_commitment_id(&e, id_counter);
 
         // EFFECTS: Update state before external calls
-        // Calculate expiration timestamp using shared utilities
+        // Calculate expiration timestamp using shared utilities; a scheduled
+        // commitment's term runs from its start time
         let current_timestamp = TimeUtils::now(&e);
-        let expires_at = TimeUtils::calculate_expiration(&e, rules.duration_days);
+        let start = start_at.unwrap_or(current_timestamp);
+        let max_duration_days = duration_limits::max_days(&e);
+        let expires_at = match duration_seconds {
+            Some(seconds) => {
+                TimeUtils::checked_expiration_seconds_bounded(start, seconds, max_duration_days)
+            }
+            None => {
+                TimeUtils::checked_expiration_bounded(start, rules.duration_days, max_duration_days)
+            }
+        }
+        .map_err(|_| CommitmentError::DurationTooLong)?;
 
         // Create commitment data
-        let commitment = Commitment {
+        let mut commitment = Commitment {
             commitment_id: commitment_id.clone(),
             owner: owner.clone(),
             nft_token_id: 0, // Will be set after NFT mint
@@ -424,92 +1738,152 @@ impl CommitmentCoreContract {
             created_at: current_timestamp,
             expires_at,
             current_value: amount, // Initially same as amount
-            status: String::from_str(&e, "active"),
+            status: if start_at.is_some() {
+                CommitmentStatus::Pending
+            } else {
+                CommitmentStatus::Active
+            },
+            is_virtual,
         };
 
         // Store commitment data (before external calls)
         set_commitment(&e, &commitment);
+        value_log::record(&e, &commitment_id, &owner, 0, amount, amount);
+        statement::ledger_entry(&e, &commitment, LedgerEntryKind::Created, amount);
+        fees::snapshot_management_fee(&e, &commitment_id, is_virtual);
+        if let Some((id, _)) = &collection {
+            collections::record(&e, &commitment_id, id);
+        }
 
-        // Update owner's commitment list
-        let mut owner_commitments = e
-            .storage()
-            .instance()
-            .get::<_, Vec<String>>(&DataKey::OwnerCommitments(owner.clone()))
-            .unwrap_or(Vec::new(&e));
-        owner_commitments.push_back(commitment_id.clone());
-        e.storage().instance().set(
-            &DataKey::OwnerCommitments(owner.clone()),
-            &owner_commitments,
-        );
+        // Update owner's commitment list and creation-ordered index
+        add_owner_commitment(&e, &owner, &commitment_id);
+        expiry_index::insert(&e, &commitment_id, expires_at);
 
-        // OPTIMIZATION: Increment both counters using already-read values
         e.storage()
             .instance()
             .set(&DataKey::TotalCommitments, &(current_total + 1));
         e.storage()
             .instance()
-            .set(&DataKey::TotalValueLocked, &(current_tvl + amount));
-
-        // Per-asset TVL tracking
-        let asset_tvl = e
-            .storage()
-            .instance()
-            .get::<_, i128>(&DataKey::TotalValueLockedByAsset(asset_address.clone()))
-            .unwrap_or(0);
-        e.storage()
-            .instance()
-            .set(&DataKey::TotalValueLockedByAsset(asset_address.clone()), &(asset_tvl + amount));
-
-        // INTERACTIONS: External calls (token transfer, NFT mint)
-        // Transfer assets from owner to contract
-        let contract_address = e.current_contract_address();
-        transfer_assets(&e, &owner, &contract_address, &asset_address, amount);
+            .set(&DataKey::CommitmentIdCounter, &(id_counter + 1));
+        adjust_active_commitments(&e, 1);
+
+        // Virtual commitments hold no funds: no TVL, no transfer, no NFT
+        let mut nft_token_id = 0;
+        if !is_virtual {
+            // OPTIMIZATION: Increment TVL using already-read value
+            e.storage()
+                .instance()
+                .set(&DataKey::TotalValueLocked, &current_tvl.saturating_add(amount));
+
+            // Per-asset TVL tracking
+            adjust_asset_tvl(&e, &asset_address, amount);
+            track_asset(&e, &asset_address);
+            insurance::add_to_fund(&e, &asset_address, &commitment_id, contribution);
+            deposit_limits::record(&e, &owner, &asset_address, requested_amount);
+            tvl_history::maybe_snapshot(&e);
+
+            // INTERACTIONS: External calls (NFT mint)
+            let contract_address = e.current_contract_address();
+
+            // Mint NFT; a rejected mint reverts the whole creation rather
+            // than leaving the commitment without a token
+            nft_token_id = match CommitmentNftClient::new(&e, &nft_contract).try_mint(
+                &contract_address,
+                &owner,
+                &commitment_id,
+                &rules.duration_days,
+                &rules.max_loss_percent,
+                &rules.commitment_type,
+                &amount,
+                &asset_address,
+                &rules.early_exit_penalty,
+            ) {
+                Ok(Ok(token_id)) => token_id,
+                _ => return Err(CommitmentError::MintingFailed),
+            };
+            // The NFT dates its term from the mint in whole days; move it to
+            // the start time or the exact expiry
+            if start_at.is_some() || duration_seconds.is_some() {
+                CommitmentNftClient::new(&e, &nft_contract)
+                    .try_extend(&nft_token_id, &rules.duration_days, &expires_at)
+                    .map_err(|_| CommitmentError::MintingFailed)?
+                    .map_err(|_| CommitmentError::MintingFailed)?;
+            }
 
-        // Mint NFT
-        let nft_token_id = call_nft_mint(
-            &e,
-            &nft_contract,
-            &owner,
-            &commitment_id,
-            rules.duration_days,
-            rules.max_loss_percent,
-            &rules.commitment_type,
-            amount,
-            &asset_address,
-        );
+            storage_layout::set(
+                &e,
+                &DataKey::CommitmentNftContract(commitment_id.clone()),
+                &nft_contract,
+            );
+            ownership::record_token(&e, &nft_contract, nft_token_id, &commitment_id);
 
-        // Update commitment with NFT token ID
-        let mut updated_commitment = commitment;
-        updated_commitment.nft_token_id = nft_token_id;
-        set_commitment(&e, &updated_commitment);
+            // Update commitment with NFT token ID
+            commitment.nft_token_id = nft_token_id;
+            set_commitment(&e, &commitment);
+        }
 
-        // Clear reentrancy guard
-        set_reentrancy_guard(&e, false);
+        // Keep the entries alive through the term (from its start, if
+        // scheduled) and settlement
+        ttl::extend(&e, &commitment, ttl::ledgers_until(&e, expires_at));
 
         // Emit creation event
-        e.events().publish(
-            (
-                symbol_short!("Created"),
-                commitment_id.clone(),
-                owner.clone(),
-            ),
-            (amount, rules, nft_token_id, e.ledger().timestamp()),
+        publish_event(
+            &e,
+            is_virtual,
+            (symbol_short!("Created"), commitment_id.clone()),
+            CommitmentCreatedEvent {
+                commitment_id: commitment_id.clone(),
+                owner,
+                amount: requested_amount,
+                received_amount,
+                net_amount: amount,
+                asset_address,
+                nft_token_id,
+                rules,
+                timestamp: e.ledger().timestamp(),
+            },
         );
-        commitment_id
+        Ok(commitment_id)
     }
 
     /// Get commitment details
-    pub fn get_commitment(e: Env, commitment_id: String) -> Commitment {
-        read_commitment(&e, &commitment_id)
-            .unwrap_or_else(|| fail(&e, CommitmentError::CommitmentNotFound, "get_commitment"))
+    pub fn get_commitment(e: Env, commitment_id: String) -> Result<Commitment, CommitmentError> {
+        read_commitment(&e, &commitment_id).ok_or(CommitmentError::CommitmentNotFound)
+    }
+
+    /// Cheap status probe: `(status code, expires_at, current_value)`.
+    ///
+    /// Reads a compact record instead of the full commitment. Unknown ids
+    /// return `STATUS_CODE_NOT_FOUND` rather than failing.
+    pub fn get_status_light(e: Env, commitment_id: String) -> (u32, u64, i128) {
+        read_status_light(&e, &commitment_id)
     }
 
-    /// Get all commitments for an owner
+    /// Get all commitments for an owner (prefer `get_owner_commitments_page`)
     pub fn get_owner_commitments(e: Env, owner: Address) -> Vec<String> {
-        e.storage()
-            .instance()
-            .get::<_, Vec<String>>(&DataKey::OwnerCommitments(owner))
-            .unwrap_or(Vec::new(&e))
+        commitment_index::page(&e, &CommitmentList::Owner(owner), 0, u32::MAX)
+    }
+
+    /// Get a page of an owner's commitments in creation order.
+    ///
+    /// Reads the maintained index, so the order is stable regardless of how
+    /// commitments were later settled or removed.
+    pub fn get_owner_commitments_sorted(
+        e: Env,
+        owner: Address,
+        newest_first: bool,
+        offset: u32,
+        limit: u32,
+    ) -> Vec<OwnerCommitmentRef> {
+        let (index, _) = read_owner_index(&e, &owner);
+        let len = index.len();
+        let end = offset.saturating_add(limit).min(len);
+        let mut page = Vec::new(&e);
+        for i in offset..end {
+            let pos = if newest_first { len - 1 - i } else { i };
+            page.push_back(index.get(pos).unwrap());
+        }
+        page
     }
 
     /// Get total number of commitments
@@ -520,7 +1894,7 @@ impl CommitmentCoreContract {
             .unwrap_or(0)
     }
 
-    /// Get total value locked across all active commitments.
+    /// Get total principal locked across all active commitments.
     pub fn get_total_value_locked(e: Env) -> i128 {
         e.storage()
             .instance()
@@ -528,6 +1902,22 @@ impl CommitmentCoreContract {
             .unwrap_or(0)
     }
 
+    /// Get the number of commitments currently in "active" status
+    pub fn get_active_commitments(e: Env) -> u64 {
+        e.storage()
+            .instance()
+            .get::<_, u64>(&DataKey::ActiveCommitments)
+            .unwrap_or(0)
+    }
+
+    /// Get every asset that has ever been committed
+    pub fn get_tracked_assets(e: Env) -> Vec<Address> {
+        e.storage()
+            .instance()
+            .get::<_, Vec<Address>>(&DataKey::TrackedAssets)
+            .unwrap_or(Vec::new(&e))
+    }
+
     /// Get admin address
     pub fn get_admin(e: Env) -> Address {
         e.storage()
@@ -544,255 +1934,195 @@ impl CommitmentCoreContract {
             .unwrap_or_else(|| fail(&e, CommitmentError::NotInitialized, "get_nft_contract"))
     }
 
-    /// Update commitment value (called by allocation logic or oracle-fed keeper).
-    /// Persists new_value to commitment.current_value and updates TotalValueLocked.
-    pub fn update_value(e: Env, commitment_id: String, new_value: i128) {
+    /// Update commitment value (admin or oracle role only).
+    /// Persists new_value to commitment.current_value; TVL counts principal and is unchanged.
+    ///
+    /// If the loss now exceeds `max_loss_percent` the commitment is marked
+    /// `violated` and a `Violated` event is emitted. Returns the new status.
+    pub fn update_value(
+        e: Env,
+        caller: Address,
+        commitment_id: String,
+        new_value: i128,
+    ) -> CommitmentStatus {
+        require_oracle(&e, &caller);
         // Global per-function rate limit (per contract instance)
         let fn_symbol = symbol_short!("upd_val");
         let contract_address = e.current_contract_address();
-        RateLimiter::check(&e, &contract_address, &fn_symbol);
+        rate_limits::check(&e, &contract_address, &fn_symbol)
+            .unwrap_or_else(|err| fail(&e, err, "update_value"));
         EmergencyControl::require_not_emergency(&e);
 
-        Validation::require_non_negative(new_value);
+        if new_value < 0 {
+            fail(&e, CommitmentError::InvalidAmount, "update_value");
+        }
 
         let mut commitment = read_commitment(&e, &commitment_id)
             .unwrap_or_else(|| fail(&e, CommitmentError::CommitmentNotFound, "update_value"));
 
-        let active_status = String::from_str(&e, "active");
-        if commitment.status != active_status {
+        if commitment.status != CommitmentStatus::Active {
             fail(&e, CommitmentError::NotActive, "update_value");
         }
+        if freezes::is_active(&e, &commitment_id) {
+            fail(&e, CommitmentError::CommitmentFrozen, "update_value");
+        }
+
+        // A non-positive baseline makes every loss percentage meaningless
+        if commitment.amount <= 0 {
+            fail(&e, CommitmentError::InvalidAmount, "update_value");
+        }
+
+        // Updates inside the minimum interval are rejected or coalesced into
+        // the newest value history entry, depending on policy
+        let too_early = value_log::check_update_interval(&e, &commitment_id);
 
         let old_value = commitment.current_value;
-        let asset = commitment.asset_address.clone();
         commitment.current_value = new_value;
         set_commitment(&e, &commitment);
+        statement::ledger_entry(
+            &e,
+            &commitment,
+            LedgerEntryKind::Revalued,
+            new_value - old_value,
+        );
+        value_log::record_update(
+            &e,
+            &commitment_id,
+            &caller,
+            old_value,
+            new_value,
+            commitment.amount,
+            too_early,
+        );
 
-        // Adjust TotalValueLocked: TVL -= old_value, TVL += new_value
-        let current_tvl = e
-            .storage()
-            .instance()
-            .get::<_, i128>(&DataKey::TotalValueLocked)
-            .unwrap_or(0);
-        let new_tvl = current_tvl - old_value + new_value;
-        e.storage()
-            .instance()
-            .set(&DataKey::TotalValueLocked, &new_tvl);
-
-        // Per-asset TVL
-        let asset_tvl = e
-            .storage()
-            .instance()
-            .get::<_, i128>(&DataKey::TotalValueLockedByAsset(asset.clone()))
-            .unwrap_or(0);
-        e.storage()
-            .instance()
-            .set(&DataKey::TotalValueLockedByAsset(asset), &(asset_tvl - old_value + new_value));
+        publish_event(
+            &e,
+            commitment.is_virtual,
+            (symbol_short!("ValUpd"), commitment_id.clone()),
+            ValueUpdatedEvent {
+                commitment_id: commitment_id.clone(),
+                old_value,
+                new_value,
+                timestamp: e.ledger().timestamp(),
+            },
+        );
 
-        e.events().publish(
-            (symbol_short!("ValUpd"), commitment_id),
-            (new_value, e.ledger().timestamp()),
+        // Same loss test as check_violations (TWA value when enabled). The
+        // value stays locked until the owner settles the violated commitment.
+        let loss_bps = PercentMath::loss_bps(
+            commitment.amount,
+            value_log::effective_value(&e, &commitment),
         );
+        if PercentMath::exceeds_percent(loss_bps, commitment.rules.max_loss_percent) {
+            violations::loss_breach(&e, &mut commitment, loss_bps);
+        } else {
+            violations::clear_pending(&e, &commitment_id);
+        }
+        commitment.status
     }
 
     /// Check if commitment rules are violated
     /// Returns true if any rule violation is detected (loss limit or duration)
     ///
+    /// A loss violation marks the commitment `violated` and emits a
+    /// `Violated` event once; later calls return true without emitting.
+    /// With a violation grace period set, the breach must persist on a check
+    /// that long after it was first seen (see `get_pending_violation`).
+    /// Reaching expiry only reports true: the commitment stays active so it
+    /// can be settled normally.
+    ///
     /// # Formal Verification
     /// **Preconditions:**
     /// - `commitment_id` exists
     ///
     /// **Postconditions:**
-    /// - Returns `true` if `loss_percent > max_loss_percent OR current_time >= expires_at`
+    /// - Returns `true` if the status is `violated`, or it is `active` and
+    ///   `loss_percent > max_loss_percent OR current_time >= expires_at`
     /// - Returns `false` otherwise
-    /// - Pure function (no state changes)
+    /// - Only state change: `active` -> `violated` on a loss violation
     ///
     /// **Invariants Maintained:**
     /// - INV-2: Commitment balance conservation
     ///
     /// **Security Properties:**
-    /// - SP-4: State consistency (read-only)
+    /// - SP-4: State consistency (idempotent)
     pub fn check_violations(e: Env, commitment_id: String) -> bool {
-        let commitment = read_commitment(&e, &commitment_id)
+        let mut commitment = read_commitment(&e, &commitment_id)
             .unwrap_or_else(|| fail(&e, CommitmentError::CommitmentNotFound, "check_violations"));
 
-        // Skip check if already settled or violated
-        let active_status = String::from_str(&e, "active");
-        if commitment.status != active_status {
-            return false; // Already processed
+        // Already recorded; settled and exited commitments are done with
+        if commitment.status == CommitmentStatus::Violated {
+            return true;
         }
-
-        let current_time = e.ledger().timestamp();
-
-        // Check loss limit violation
-        // Calculate loss percentage using shared utilities, but handle zero-amount
-        // commitments gracefully to avoid panics. A zero-amount commitment cannot
-        // meaningfully violate a loss limit, so we treat its loss percent as 0.
-        let loss_percent = if commitment.amount > 0 {
-            SafeMath::loss_percent(commitment.amount, commitment.current_value)
-        } else {
-            0
-        };
-
-        // Convert max_loss_percent (u32) to i128 for comparison
-        let max_loss = commitment.rules.max_loss_percent as i128;
-        let loss_violated = loss_percent > max_loss;
-
-        // Check duration violation (expired)
-        let duration_violated = current_time >= commitment.expires_at;
-
-        let violated = loss_violated || duration_violated;
-
-        if violated {
-            // Emit violation event
-            e.events().publish(
-                (symbol_short!("Violated"), commitment_id),
-                (symbol_short!("RuleViol"), e.ledger().timestamp()),
-            );
+        if commitment.status != CommitmentStatus::Active {
+            return false;
         }
 
-        // Return true if any violation exists
-        violated
-    }
-
-    /// Get detailed violation information
-    /// Returns a tuple: (has_violations, loss_violated, duration_violated, loss_percent, time_remaining)
-    pub fn get_violation_details(e: Env, commitment_id: String) -> (bool, bool, bool, i128, u64) {
-        let commitment = read_commitment(&e, &commitment_id).unwrap_or_else(|| {
-            fail(
-                &e,
-                CommitmentError::CommitmentNotFound,
-                "get_violation_details",
-            )
-        });
-
-        let current_time = e.ledger().timestamp();
-
-        // Calculate loss percentage
-        let loss_amount = commitment.amount - commitment.current_value;
-        let loss_percent = if commitment.amount > 0 {
-            (loss_amount * 100) / commitment.amount
-        } else {
-            0
-        };
-
-        // Check loss limit violation
-        let max_loss = commitment.rules.max_loss_percent as i128;
-        let loss_violated = loss_percent > max_loss;
-
-        // Check duration violation
-        let duration_violated = current_time >= commitment.expires_at;
-
-        // Calculate time remaining (0 if expired)
-        let time_remaining = if current_time < commitment.expires_at {
-            commitment.expires_at - current_time
+        // Check loss limit violation in basis points. PercentMath treats a zero
+        // baseline as no loss, so zero-amount commitments never trip the limit.
+        // Uses the TWA value when enabled for this commitment type.
+        let loss_bps = PercentMath::loss_bps(
+            commitment.amount,
+            value_log::effective_value(&e, &commitment),
+        );
+        if PercentMath::exceeds_percent(loss_bps, commitment.rules.max_loss_percent) {
+            if violations::loss_breach(&e, &mut commitment, loss_bps) {
+                return true;
+            }
         } else {
-            0
-        };
-
-        let has_violations = loss_violated || duration_violated;
+            violations::clear_pending(&e, &commitment_id);
+        }
 
-        (
-            has_violations,
-            loss_violated,
-            duration_violated,
-            loss_percent,
-            time_remaining,
-        )
+        // Check duration violation (expired)
+        e.ledger().timestamp() >= commitment.expires_at
     }
 
     /// Settle commitment at maturity
     ///
+    /// Violated commitments can be settled at any time, paying out the value
+    /// they were marked violated at. The payout goes to the current holder of
+    /// the commitment's NFT, who becomes the commitment's owner. Expired
+    /// commitments with auto-renew on start another term instead (see
+    /// `set_auto_renew`). Commitments frozen by the admin fail with
+    /// CommitmentFrozen until unfrozen, and commitments with funds
+    /// still out at a pool fail with HasAllocations until deallocated.
+    ///
     /// # Reentrancy Protection
     /// Uses checks-effects-interactions pattern with reentrancy guard.
     pub fn settle(e: Env, commitment_id: String) {
         // Reentrancy protection
         require_no_reentrancy(&e);
         set_reentrancy_guard(&e, true);
-        EmergencyControl::require_not_emergency(&e);
-
-        // CHECKS: Get and validate commitment
-        let mut commitment = read_commitment(&e, &commitment_id).unwrap_or_else(|| {
-            set_reentrancy_guard(&e, false);
-            fail(&e, CommitmentError::CommitmentNotFound, "settle")
-        });
-
-        // Verify commitment is expired
-        let current_time = e.ledger().timestamp();
-        if current_time < commitment.expires_at {
-            set_reentrancy_guard(&e, false);
-            fail(&e, CommitmentError::NotExpired, "settle");
-        }
-
-        // Verify commitment is active
-        let active_status = String::from_str(&e, "active");
-        if commitment.status != active_status {
-            set_reentrancy_guard(&e, false);
-            fail(&e, CommitmentError::NotActive, "settle");
-        }
-
-        // EFFECTS: Update state before external calls
-        let settlement_amount = commitment.current_value;
-        commitment.status = String::from_str(&e, "settled");
-        set_commitment(&e, &commitment);
+        EmergencyControl::require_not_paused(&e, &symbol_short!("settle"));
 
-        // Decrease total value locked
-        let current_tvl = e
-            .storage()
-            .instance()
-            .get::<_, i128>(&DataKey::TotalValueLocked)
-            .unwrap_or(0);
-        let new_tvl = current_tvl - settlement_amount;
-        e.storage()
-            .instance()
-            .set(&DataKey::TotalValueLocked, &new_tvl);
-
-        // Per-asset TVL
-        let asset = commitment.asset_address.clone();
-        let asset_tvl = e
-            .storage()
-            .instance()
-            .get::<_, i128>(&DataKey::TotalValueLockedByAsset(asset.clone()))
-            .unwrap_or(0);
-        e.storage()
-            .instance()
-            .set(&DataKey::TotalValueLockedByAsset(asset), &(asset_tvl - settlement_amount));
-
-        // INTERACTIONS: External calls (token transfer, NFT settlement)
-        // Transfer assets back to owner
-        let contract_address = e.current_contract_address();
-        let token_client = token::Client::new(&e, &commitment.asset_address);
-        token_client.transfer(&contract_address, &commitment.owner, &settlement_amount);
-
-        // Call NFT contract to mark NFT as settled
-        let nft_contract = e
-            .storage()
-            .instance()
-            .get::<_, Address>(&DataKey::NftContract)
-            .unwrap_or_else(|| {
-                set_reentrancy_guard(&e, false);
-                fail(&e, CommitmentError::NotInitialized, "settle")
-            });
-
-        let mut args = Vec::new(&e);
-        args.push_back(commitment.nft_token_id.into_val(&e));
-        e.invoke_contract::<()>(&nft_contract, &Symbol::new(&e, "settle"), args);
+        let result = settle_commitment(&e, &commitment_id, ReceiptKind::Settled);
 
         // Clear reentrancy guard
         set_reentrancy_guard(&e, false);
-
-        // Emit settlement event
-        e.events().publish(
-            (symbol_short!("Settled"), commitment_id),
-            (settlement_amount, e.ledger().timestamp()),
-        );
+        if let Err(err) = result {
+            fail(&e, err, "settle");
+        }
     }
 
+    /// Exit an active commitment before expiry, paying the early-exit penalty.
+    ///
+    /// Commitment types with an exit cooling-off period must use
+    /// `request_early_exit` and `execute_early_exit` instead. Fails with
+    /// HasAllocations while funds are still out at a pool.
     pub fn early_exit(e: Env, commitment_id: String, caller: Address) {
+        if let Some(commitment) = read_commitment(&e, &commitment_id) {
+            if exit_requests::cooloff(&e, commitment.rules.commitment_type) > 0 {
+                fail(&e, CommitmentError::ExitRequestRequired, "early_exit");
+            }
+        }
+        Self::early_exit_internal(e, commitment_id, caller);
+    }
+
+    fn early_exit_internal(e: Env, commitment_id: String, caller: Address) {
         // Reentrancy protection
         require_no_reentrancy(&e);
         set_reentrancy_guard(&e, true);
-        EmergencyControl::require_not_emergency(&e);
+        EmergencyControl::require_not_paused(&e, &symbol_short!("exit"));
 
         // CHECKS: Get and validate commitment
         let mut commitment = read_commitment(&e, &commitment_id).unwrap_or_else(|| {
@@ -800,106 +2130,138 @@ impl CommitmentCoreContract {
             fail(&e, CommitmentError::CommitmentNotFound, "early_exit")
         });
 
-        // Verify caller is owner
+        // Verify caller is the owner or its operator
         caller.require_auth();
-        if commitment.owner != caller {
+        if !operators::is_owner_or_operator(&e, &commitment, &caller) {
             set_reentrancy_guard(&e, false);
             fail(&e, CommitmentError::Unauthorized, "early_exit");
         }
 
-        // Verify commitment is active
-        let active_status = String::from_str(&e, "active");
-        if commitment.status != active_status {
+        // Verify commitment is active and not yet matured
+        if let Err(err) = disputes::check_not_frozen(&e, &commitment_id) {
+            set_reentrancy_guard(&e, false);
+            fail(&e, err, "early_exit");
+        }
+        if let Err(err) = Self::check_exitable(&e, &commitment) {
             set_reentrancy_guard(&e, false);
-            fail(&e, CommitmentError::NotActive, "early_exit");
+            fail(&e, err, "early_exit");
         }
 
         // Save original current value before updating (for TVL and transfers)
         let original_current_value = commitment.current_value;
 
-        // EFFECTS: Calculate penalty using shared utilities
-        let penalty_amount =
-            SafeMath::penalty_amount(original_current_value, commitment.rules.early_exit_penalty);
-        let returned_amount = SafeMath::sub(original_current_value, penalty_amount);
+        // EFFECTS: Calculate penalty and management fee
+        let (fee_threshold_met, penalty_amount, management_fee, returned_amount) =
+            Self::early_exit_terms(&e, &commitment);
 
         // Update commitment status to early_exit
-        commitment.status = String::from_str(&e, "early_exit");
+        commitment.status = CommitmentStatus::EarlyExit;
         commitment.current_value = 0; // All value has been distributed
         set_commitment(&e, &commitment);
+        tags::clear(&e, &commitment);
+        clear_value_update_state(&e, &commitment_id);
+        exit_requests::clear(&e, &commitment_id);
+        adjust_active_commitments(&e, -1);
+        receipts::record(
+            &e,
+            &commitment,
+            ReceiptKind::EarlyExit,
+            original_current_value,
+            penalty_amount,
+            management_fee,
+            0,
+        );
 
-        // Decrease total value locked by full current value (no longer locked)
-        let current_tvl = e
-            .storage()
-            .instance()
-            .get::<_, i128>(&DataKey::TotalValueLocked)
-            .unwrap_or(0);
-        let new_tvl = current_tvl - original_current_value;
-        e.storage()
-            .instance()
-            .set(&DataKey::TotalValueLocked, &new_tvl);
-
-        // Per-asset TVL
-        let asset = commitment.asset_address.clone();
-        let asset_tvl = e
-            .storage()
-            .instance()
-            .get::<_, i128>(&DataKey::TotalValueLockedByAsset(asset.clone()))
-            .unwrap_or(0);
-        e.storage()
-            .instance()
-            .set(&DataKey::TotalValueLockedByAsset(asset), &(asset_tvl - original_current_value));
+        // Virtual commitments hold no funds: skip TVL, penalties, transfer and NFT
+        if !commitment.is_virtual {
+            // Release the principal from TVL and book the gain or loss on it
+            tvl::release(&e, &commitment, original_current_value);
+            pnl::book_owner_pnl(&e, &commitment, returned_amount);
+
+            // Penalty stays in the contract and is tracked per asset
+            add_to_penalty_pool(&e, &commitment.asset_address, penalty_amount);
+            fees::add_protocol_fees(&e, &commitment.asset_address, management_fee);
+            statement::ledger_entry(&e, &commitment, LedgerEntryKind::Penalty, -penalty_amount);
+            statement::ledger_entry(
+                &e,
+                &commitment,
+                LedgerEntryKind::ManagementFee,
+                -management_fee,
+            );
 
-        // INTERACTIONS: External calls (token transfer)
-        // Transfer remaining amount (after penalty) to owner
-        let contract_address = e.current_contract_address();
-        let token_client = token::Client::new(&e, &commitment.asset_address);
+            // INTERACTIONS: External calls (token transfer)
+            // Transfer remaining amount (after penalty) to owner, or record
+            // a claim if the asset is failing (see pay_out)
+            if pay_out(
+                &e,
+                &commitment_id,
+                &commitment.asset_address,
+                &commitment.owner,
+                returned_amount,
+            ) {
+                statement::ledger_entry(
+                    &e,
+                    &commitment,
+                    LedgerEntryKind::Payout,
+                    -returned_amount,
+                );
+            }
 
-        if returned_amount > 0 {
-            token_client.transfer(&contract_address, &commitment.owner, &returned_amount);
+            // Call settle on NFT to mark it as inactive (non-fatal)
+            sync_nft_settled(&e, &commitment_id, commitment.nft_token_id);
         }
 
-        // Call NFT contract to update NFT status (mark as inactive/early_exited)
-        let nft_contract = e
-            .storage()
-            .instance()
-            .get::<_, Address>(&DataKey::NftContract)
-            .unwrap_or_else(|| {
-                set_reentrancy_guard(&e, false);
-                fail(&e, CommitmentError::NotInitialized, "early_exit")
-            });
-
-        // Call settle on NFT to mark it as inactive
-        let mut args = Vec::new(&e);
-        args.push_back(commitment.nft_token_id.into_val(&e));
-        e.invoke_contract::<()>(&nft_contract, &Symbol::new(&e, "settle"), args);
-
         // Clear reentrancy guard
         set_reentrancy_guard(&e, false);
 
         // Emit early exit event with detailed information
-        e.events().publish(
-            (
-                symbol_short!("EarlyExt"),
-                commitment_id.clone(),
-                caller.clone(),
-            ),
-            (penalty_amount, returned_amount, e.ledger().timestamp()),
+        publish_event(
+            &e,
+            commitment.is_virtual,
+            (symbol_short!("EarlyExt"), commitment_id.clone()),
+            EarlyExitEvent {
+                commitment_id: commitment_id.clone(),
+                owner: commitment.owner.clone(),
+                caller,
+                penalty: penalty_amount,
+                returned_amount,
+                management_fee,
+                fee_threshold_met,
+                timestamp: e.ledger().timestamp(),
+            },
         );
     }
 
-    /// Allocate liquidity (called by allocation strategy)
+    /// Deploy `amount` of an active commitment's funds to `target_pool`
+    /// (admin or allocator role only).
+    ///
+    /// `amount` may not exceed the unallocated `current_value`, nor take the
+    /// outstanding allocations past the commitment type's allocation cap
+    /// (AllocationExceeded). Each allocation is recorded and listed by
+    /// `get_allocations`.
     ///
     /// # Reentrancy Protection
     /// Uses checks-effects-interactions pattern with reentrancy guard.
-    pub fn allocate(e: Env, commitment_id: String, target_pool: Address, amount: i128) {
+    pub fn allocate(
+        e: Env,
+        caller: Address,
+        commitment_id: String,
+        target_pool: Address,
+        amount: i128,
+    ) {
+        require_allocator(&e, &caller);
+
         // Reentrancy protection
         require_no_reentrancy(&e);
         set_reentrancy_guard(&e, true);
-        EmergencyControl::require_not_emergency(&e);
+        let fn_symbol = symbol_short!("alloc");
+        EmergencyControl::require_not_paused(&e, &fn_symbol);
 
         // Rate limit allocations per target pool address
-        let fn_symbol = symbol_short!("alloc");
-        RateLimiter::check(&e, &target_pool, &fn_symbol);
+        if let Err(err) = rate_limits::check(&e, &target_pool, &fn_symbol) {
+            set_reentrancy_guard(&e, false);
+            fail(&e, err, "allocate");
+        }
 
         // CHECKS: Validate inputs and commitment
         if amount <= 0 {
@@ -913,78 +2275,134 @@ impl CommitmentCoreContract {
         });
 
         // Verify commitment is active
-        let active_status = String::from_str(&e, "active");
-        if commitment.status != active_status {
+        if commitment.status != CommitmentStatus::Active {
             set_reentrancy_guard(&e, false);
             fail(&e, CommitmentError::NotActive, "allocate");
         }
+        if let Err(err) = disputes::check_not_frozen(&e, &commitment_id) {
+            set_reentrancy_guard(&e, false);
+            fail(&e, err, "allocate");
+        }
+        exit_requests::require_none(&e, &commitment_id, "allocate");
+
+        // Virtual commitments have no real funds to deploy
+        if commitment.is_virtual {
+            set_reentrancy_guard(&e, false);
+            fail(&e, CommitmentError::VirtualNotAllowed, "allocate");
+        }
 
         // Verify sufficient balance
         if commitment.current_value < amount {
             set_reentrancy_guard(&e, false);
             fail(&e, CommitmentError::InsufficientBalance, "allocate");
         }
+        if !allocations::within_cap(&e, &commitment, amount) {
+            set_reentrancy_guard(&e, false);
+            fail(&e, CommitmentError::AllocationExceeded, "allocate");
+        }
 
         // EFFECTS: Update commitment value before external call
         let mut updated_commitment = commitment;
-        let asset = updated_commitment.asset_address.clone();
-        updated_commitment.current_value = updated_commitment.current_value - amount;
+        updated_commitment.current_value -= amount;
         set_commitment(&e, &updated_commitment);
-
-        // Decrease total value locked and per-asset TVL
-        let current_tvl = e
-            .storage()
-            .instance()
-            .get::<_, i128>(&DataKey::TotalValueLocked)
-            .unwrap_or(0);
-        e.storage()
-            .instance()
-            .set(&DataKey::TotalValueLocked, &(current_tvl - amount));
-        let asset_tvl = e
-            .storage()
-            .instance()
-            .get::<_, i128>(&DataKey::TotalValueLockedByAsset(asset.clone()))
-            .unwrap_or(0);
-        e.storage()
-            .instance()
-            .set(&DataKey::TotalValueLockedByAsset(asset), &(asset_tvl - amount));
+        statement::ledger_entry(&e, &updated_commitment, LedgerEntryKind::Allocated, -amount);
+        let allocated = storage_layout::get::<_, i128>(
+            &e,
+            &DataKey::CommitmentAllocated(commitment_id.clone()),
+        )
+        .unwrap_or(0);
+        storage_layout::set(
+            &e,
+            &DataKey::CommitmentAllocated(commitment_id.clone()),
+            &(allocated + amount),
+        );
+        allocations::record_allocation(&e, &commitment_id, &target_pool, amount);
+        tvl::adjust_deployed(&e, &updated_commitment.asset_address, amount);
 
         // INTERACTIONS: External call (token transfer)
         // Transfer assets to target pool
-        let contract_address = e.current_contract_address();
-        let token_client = token::Client::new(&e, &updated_commitment.asset_address);
-        token_client.transfer(&contract_address, &target_pool, &amount);
+        transfer_assets(
+            &e,
+            &e.current_contract_address(),
+            &target_pool,
+            &updated_commitment.asset_address,
+            amount,
+        );
 
         // Clear reentrancy guard
         set_reentrancy_guard(&e, false);
 
         // Emit allocation event
         e.events().publish(
-            (symbol_short!("Alloc"), commitment_id, target_pool),
+            (symbol_short!("Allocated"), commitment_id, target_pool),
             (amount, e.ledger().timestamp()),
         );
     }
 
-    /// Configure rate limits for this contract's functions.
+    /// Return funds from `target_pool` to an active or violated commitment
+    /// (admin or allocator role only).
     ///
-    /// This function is restricted to the contract admin.
-    pub fn set_rate_limit(
+    /// `amount` of the pool's outstanding allocation is released and
+    /// `returned` tokens are transferred back from the pool; the difference
+    /// is the realized profit or loss and is reflected in `current_value`.
+    /// Violated commitments must be fully deallocated before they settle.
+    /// Returns the realized P&L.
+    pub fn deallocate(
         e: Env,
         caller: Address,
-        function: Symbol,
-        window_seconds: u64,
-        max_calls: u32,
-    ) {
-        require_admin(&e, &caller);
-        RateLimiter::set_limit(&e, &function, window_seconds, max_calls);
-    }
+        commitment_id: String,
+        target_pool: Address,
+        amount: i128,
+        returned: i128,
+    ) -> i128 {
+        require_allocator(&e, &caller);
 
-    /// Set or clear rate limit exemption for an address.
-    ///
-    /// This function is restricted to the contract admin.
-    pub fn set_rate_limit_exempt(e: Env, caller: Address, address: Address, exempt: bool) {
-        require_admin(&e, &caller);
-        RateLimiter::set_exempt(&e, &address, exempt);
+        require_no_reentrancy(&e);
+        set_reentrancy_guard(&e, true);
+
+        // CHECKS
+        if amount <= 0 || returned < 0 {
+            set_reentrancy_guard(&e, false);
+            fail(&e, CommitmentError::InvalidAmount, "deallocate");
+        }
+        let mut commitment = read_commitment(&e, &commitment_id).unwrap_or_else(|| {
+            set_reentrancy_guard(&e, false);
+            fail(&e, CommitmentError::CommitmentNotFound, "deallocate")
+        });
+        let open = matches!(
+            commitment.status,
+            CommitmentStatus::Active | CommitmentStatus::Violated
+        );
+        if !open {
+            set_reentrancy_guard(&e, false);
+            fail(&e, CommitmentError::NotActive, "deallocate");
+        }
+        if let Err(err) = disputes::check_not_frozen(&e, &commitment_id) {
+            set_reentrancy_guard(&e, false);
+            fail(&e, err, "deallocate");
+        }
+
+        // EFFECTS: release the pool's allocation and credit what came back
+        allocations::release_allocation(&e, &commitment_id, &target_pool, amount);
+        let asset = commitment.asset_address.clone();
+        tvl::adjust_deployed(&e, &asset, -amount);
+        commitment.current_value += returned;
+        set_commitment(&e, &commitment);
+        statement::ledger_entry(&e, &commitment, LedgerEntryKind::Deallocated, returned);
+
+        // INTERACTIONS: pull the returned tokens from the pool
+        if returned > 0 {
+            transfer_assets(&e, &target_pool, &e.current_contract_address(), &asset, returned);
+        }
+
+        set_reentrancy_guard(&e, false);
+
+        let pnl = returned - amount;
+        e.events().publish(
+            (Symbol::new(&e, "Deallocated"), commitment_id, target_pool),
+            (amount, returned, pnl, e.ledger().timestamp()),
+        );
+        pnl
     }
 
     // ========================================================================
@@ -995,6 +2413,12 @@ impl CommitmentCoreContract {
     pub fn set_emergency_mode(e: Env, caller: Address, enabled: bool) {
         require_admin(&e, &caller);
         EmergencyControl::set_emergency_mode(&e, enabled);
+        let action = if enabled {
+            symbol_short!("EmgOn")
+        } else {
+            symbol_short!("EmgOff")
+        };
+        audit::record(&e, &caller, action, &e.current_contract_address());
     }
 
     /// Check if in emergency mode
@@ -1002,6 +2426,25 @@ impl CommitmentCoreContract {
         EmergencyControl::is_emergency_mode(&e)
     }
 
+    /// Pause or unpause one function (admin only): `create` (all commitment
+    /// creation), `settle` (single and batch), `exit` (direct and requested
+    /// early exits) or `alloc`. Emergency mode still pauses everything.
+    pub fn set_function_paused(e: Env, caller: Address, fn_name: Symbol, paused: bool) {
+        require_admin(&e, &caller);
+        EmergencyControl::set_function_paused(&e, &fn_name, paused);
+        let action = if paused {
+            symbol_short!("Paused")
+        } else {
+            symbol_short!("Unpaused")
+        };
+        audit::record(&e, &caller, action, &e.current_contract_address());
+    }
+
+    /// Whether a function is paused, on its own or by emergency mode
+    pub fn is_function_paused(e: Env, fn_name: Symbol) -> bool {
+        EmergencyControl::is_paused(&e, &fn_name)
+    }
+
     /// Emergency withdrawal of funds (admin only)
     /// This allows rescuing funds from the contract to a safe address if needed.
     pub fn emergency_withdraw(
@@ -1023,93 +2466,78 @@ impl CommitmentCoreContract {
         );
     }
 
-    /// Force settle a commitment in emergency (admin only)
-    /// This bypasses maturity checks and fees.
-    pub fn emergency_settle(e: Env, caller: Address, commitment_id: String) {
-        require_admin(&e, &caller);
+    /// Withdraw a commitment's full current value while emergency mode is on
+    /// (owner or operator).
+    ///
+    /// The owners' escape hatch while `create_commitment` and `settle` are
+    /// blocked: no penalty or fee and no expiry check. Active, pending and
+    /// violated commitments only (InvalidStatus otherwise), with nothing
+    /// allocated to a pool (HasAllocations). Marks the commitment settled,
+    /// releases it from TVL and emits `EmergencyWithdrawal` with the time
+    /// emergency mode was turned on.
+    pub fn emergency_withdraw_commitment(e: Env, caller: Address, commitment_id: String) -> i128 {
+        require_no_reentrancy(&e);
+        set_reentrancy_guard(&e, true);
         EmergencyControl::require_emergency(&e);
-
-        let mut commitment =
-            read_commitment(&e, &commitment_id).unwrap_or_else(|| panic!("Commitment not found"));
-
-        // Mark as settled
-        commitment.status = String::from_str(&e, "settled");
-        let settlement_amount = commitment.current_value;
-        commitment.current_value = 0;
-        set_commitment(&e, &commitment);
-
-        // Adjust TVL
-        let current_tvl = e
-            .storage()
-            .instance()
-            .get::<_, i128>(&DataKey::TotalValueLocked)
-            .unwrap_or(0);
-        let new_tvl = current_tvl - settlement_amount;
-        e.storage()
-            .instance()
-            .set(&DataKey::TotalValueLocked, &new_tvl);
-
-        // Transfer funds back to owner
-        let token_client = token::Client::new(&e, &commitment.asset_address);
-        token_client.transfer(
-            &e.current_contract_address(),
-            &commitment.owner,
-            &settlement_amount,
+        caller.require_auth();
+        let context = "emergency_withdraw_commitment";
+        let mut commitment = read_commitment(&e, &commitment_id).unwrap_or_else(|| {
+            set_reentrancy_guard(&e, false);
+            fail(&e, CommitmentError::CommitmentNotFound, context)
+        });
+        if !operators::is_owner_or_operator(&e, &commitment, &caller) {
+            set_reentrancy_guard(&e, false);
+            fail(&e, CommitmentError::Unauthorized, context);
+        }
+        let open = matches!(
+            commitment.status,
+            CommitmentStatus::Active | CommitmentStatus::Pending | CommitmentStatus::Violated
         );
+        if !open {
+            set_reentrancy_guard(&e, false);
+            fail(&e, CommitmentError::InvalidStatus, context);
+        }
+        if let Err(err) = disputes::check_not_frozen(&e, &commitment_id)
+            .and_then(|_| allocations::check_unallocated(&e, &commitment_id))
+        {
+            set_reentrancy_guard(&e, false);
+            fail(&e, err, context);
+        }
 
-        // Update NFT
-        let nft_contract = e
-            .storage()
-            .instance()
-            .get::<_, Address>(&DataKey::NftContract)
-            .unwrap_or_else(|| panic!("NFT contract not initialized"));
-        let mut args = Vec::new(&e);
-        args.push_back(commitment.nft_token_id.into_val(&e));
-        e.invoke_contract::<()>(&nft_contract, &Symbol::new(&e, "settle"), args);
+        let amount = emergency_release(&e, &mut commitment);
+        set_reentrancy_guard(&e, false);
 
-        e.events().publish(
-            (symbol_short!("EmgSettl"), commitment_id),
-            (settlement_amount, e.ledger().timestamp()),
+        publish_event(
+            &e,
+            commitment.is_virtual,
+            (Symbol::new(&e, "EmergencyWithdrawal"), commitment_id, commitment.owner),
+            (
+                amount,
+                EmergencyControl::emergency_since(&e),
+                e.ledger().timestamp(),
+            ),
         );
+        amount
     }
 
-    /// Change commitment parameters in emergency (admin only)
-    /// This allows fixing stuck commitments or adjusting state during recovery.
-    pub fn emergency_update_commitment(
-        e: Env,
-        caller: Address,
-        commitment_id: String,
-        new_value: i128,
-        new_status: String,
-        new_expires_at: u64,
-    ) {
-        require_admin(&e, &caller);
-        EmergencyControl::require_emergency(&e);
-
-        let mut commitment =
-            read_commitment(&e, &commitment_id).unwrap_or_else(|| panic!("Commitment not found"));
-
-        // Adjust TVL first
-        let current_tvl = e
-            .storage()
-            .instance()
-            .get::<_, i128>(&DataKey::TotalValueLocked)
-            .unwrap_or(0);
-        let new_tvl = current_tvl - commitment.current_value + new_value;
-        e.storage()
-            .instance()
-            .set(&DataKey::TotalValueLocked, &new_tvl);
-
-        commitment.current_value = new_value;
-        commitment.status = new_status;
-        commitment.expires_at = new_expires_at;
-
-        set_commitment(&e, &commitment);
+    // ========== NFT collections ==========
 
-        e.events().publish(
-            (symbol_short!("EmgUpd"), commitment_id),
-            (e.ledger().timestamp(),),
-        );
+    /// Get the NFT contract that minted a commitment's token
+    pub fn get_commitment_nft_contract(e: Env, commitment_id: String) -> Address {
+        if !has_commitment(&e, &commitment_id) {
+            fail(
+                &e,
+                CommitmentError::CommitmentNotFound,
+                "get_commitment_nft_contract",
+            );
+        }
+        commitment_nft_contract(&e, &commitment_id).unwrap_or_else(|| {
+            fail(
+                &e,
+                CommitmentError::NotInitialized,
+                "get_commitment_nft_contract",
+            )
+        })
     }
 
     // ========== Multi-asset support ==========
@@ -1139,9 +2567,12 @@ impl CommitmentCoreContract {
             }
         }
         if !found {
-            supported.push_back(asset);
-            e.storage().instance().set(&DataKey::SupportedAssets, &supported);
+            supported.push_back(asset.clone());
+            e.storage()
+                .instance()
+                .set(&DataKey::SupportedAssets, &supported);
         }
+        audit::record(&e, &caller, symbol_short!("AddAsset"), &asset);
     }
 
     /// Remove an asset from the supported whitelist. Admin only.
@@ -1159,30 +2590,333 @@ impl CommitmentCoreContract {
             }
         }
         e.storage().instance().set(&DataKey::SupportedAssets, &out);
+        audit::record(&e, &caller, symbol_short!("RmAsset"), &asset);
+    }
+
+    /// Get total value locked for a specific asset.
+    pub fn get_total_value_locked_by_asset(e: Env, asset: Address) -> i128 {
+        storage_layout::get(&e, &DataKey::TotalValueLockedByAsset(asset)).unwrap_or(0)
+    }
+
+    /// Get the penalty pool for an asset: early-exit penalties collected and
+    /// still held.
+    pub fn get_penalty_pool(e: Env, asset: Address) -> i128 {
+        storage_layout::get(&e, &DataKey::PenaltyPool(asset)).unwrap_or(0)
+    }
+
+    /// Transfer `amount` of the collected penalties in `asset` to `to`
+    /// (admin only).
+    ///
+    /// Penalties are never part of TVL, so withdrawing them cannot touch
+    /// funds owed to open commitments. Fails with InvalidAmount for a
+    /// non-positive amount and InsufficientBalance above what was collected.
+    pub fn withdraw_penalties(
+        e: Env,
+        caller: Address,
+        asset: Address,
+        to: Address,
+        amount: i128,
+    ) {
+        require_admin(&e, &caller);
+        let collected = Self::get_penalty_pool(e.clone(), asset.clone());
+        if amount <= 0 {
+            fail(&e, CommitmentError::InvalidAmount, "withdraw_penalties");
+        }
+        if amount > collected {
+            fail(&e, CommitmentError::InsufficientBalance, "withdraw_penalties");
+        }
+        storage_layout::set(
+            &e,
+            &DataKey::PenaltyPool(asset.clone()),
+            &(collected - amount),
+        );
+        token::Client::new(&e, &asset).transfer(&e.current_contract_address(), &to, &amount);
+        e.events().publish(
+            (Symbol::new(&e, "PenaltyWithdrawn"), asset),
+            (to, amount, collected - amount),
+        );
     }
 
-    /// Set optional metadata for an asset (symbol, decimals). Admin only.
-    pub fn set_asset_metadata(e: Env, caller: Address, asset: Address, symbol: String, decimals: u32) {
+    /// Grant the allocator role to `allocator` (admin only)
+    pub fn add_allocator(e: Env, caller: Address, allocator: Address) {
         require_admin(&e, &caller);
-        let meta = AssetMetadata { symbol, decimals };
-        e.storage()
+        AccessControl::grant_role(&e, &ALLOCATOR_ROLE, &allocator);
+        audit::record(&e, &caller, symbol_short!("AddAlloc"), &allocator);
+        e.events()
+            .publish((symbol_short!("RoleGrant"), ALLOCATOR_ROLE), allocator);
+    }
+
+    /// Revoke the allocator role from `allocator` (admin only)
+    pub fn remove_allocator(e: Env, caller: Address, allocator: Address) {
+        require_admin(&e, &caller);
+        AccessControl::revoke_role(&e, &ALLOCATOR_ROLE, &allocator);
+        audit::record(&e, &caller, symbol_short!("RmAlloc"), &allocator);
+        e.events()
+            .publish((symbol_short!("RoleRevok"), ALLOCATOR_ROLE), allocator);
+    }
+
+    /// Check whether an address holds the allocator role
+    pub fn is_allocator(e: Env, address: Address) -> bool {
+        AccessControl::has_role(&e, &ALLOCATOR_ROLE, &address)
+    }
+
+    /// Grant the oracle role to `oracle` (admin only)
+    pub fn add_oracle(e: Env, caller: Address, oracle: Address) {
+        require_admin(&e, &caller);
+        AccessControl::grant_role(&e, &ORACLE_ROLE, &oracle);
+        audit::record(&e, &caller, symbol_short!("AddOracle"), &oracle);
+        e.events()
+            .publish((symbol_short!("RoleGrant"), ORACLE_ROLE), oracle);
+    }
+
+    /// Revoke the oracle role from `oracle` (admin only)
+    pub fn remove_oracle(e: Env, caller: Address, oracle: Address) {
+        require_admin(&e, &caller);
+        AccessControl::revoke_role(&e, &ORACLE_ROLE, &oracle);
+        audit::record(&e, &caller, symbol_short!("RmOracle"), &oracle);
+        e.events()
+            .publish((symbol_short!("RoleRevok"), ORACLE_ROLE), oracle);
+    }
+
+    /// Check whether an address holds the oracle role
+    pub fn is_oracle(e: Env, address: Address) -> bool {
+        AccessControl::has_role(&e, &ORACLE_ROLE, &address)
+    }
+
+    // ========== NFT sync ==========
+
+    /// Retry marking a closed commitment's NFT as settled after an earlier
+    /// failure. Callable by anyone; returns whether the NFT side is now in
+    /// sync (the pending flag is cleared only on success).
+    pub fn retry_nft_sync(e: Env, commitment_id: String) -> bool {
+        if !Self::is_nft_sync_pending(e.clone(), commitment_id.clone()) {
+            fail(&e, CommitmentError::NothingPending, "retry_nft_sync");
+        }
+        let commitment = read_commitment(&e, &commitment_id)
+            .unwrap_or_else(|| fail(&e, CommitmentError::CommitmentNotFound, "retry_nft_sync"));
+        let synced = sync_nft_settled(&e, &commitment_id, commitment.nft_token_id);
+        if synced {
+            e.events()
+                .publish((symbol_short!("NftSynced"), commitment_id), commitment.nft_token_id);
+        }
+        synced
+    }
+
+    /// Whether a commitment's NFT still needs to be marked settled
+    pub fn is_nft_sync_pending(e: Env, commitment_id: String) -> bool {
+        storage_layout::get::<_, bool>(&e, &DataKey::PendingNftSync(commitment_id)).unwrap_or(false)
+    }
+
+    // ========== Asset quarantine ==========
+
+    /// Consecutive payout failures and quarantine state of an asset
+    pub fn get_asset_health(e: Env, asset: Address) -> AssetHealth {
+        let quarantined_at = e
+            .storage()
             .instance()
-            .set(&DataKey::AssetMetadata(asset), &meta);
+            .get::<_, u64>(&DataKey::AssetQuarantine(asset.clone()));
+        AssetHealth {
+            consecutive_failures: e
+                .storage()
+                .instance()
+                .get::<_, u32>(&DataKey::AssetTransferFailures(asset))
+                .unwrap_or(0),
+            quarantined: quarantined_at.is_some(),
+            quarantined_at: quarantined_at.unwrap_or(0),
+        }
     }
 
-    /// Get metadata for an asset, if set.
-    pub fn get_asset_metadata(e: Env, asset: Address) -> Option<AssetMetadata> {
+    /// Set how many consecutive failed transfers an asset may have before it
+    /// is quarantined (admin only, must be non-zero)
+    pub fn set_quarantine_threshold(e: Env, caller: Address, threshold: u32) {
+        require_admin(&e, &caller);
+        if threshold == 0 {
+            fail(&e, CommitmentError::InvalidAmount, "set_quarantine_threshold");
+        }
         e.storage()
             .instance()
-            .get::<_, AssetMetadata>(&DataKey::AssetMetadata(asset))
+            .set(&DataKey::QuarantineThreshold, &threshold);
     }
 
-    /// Get total value locked for a specific asset.
-    pub fn get_total_value_locked_by_asset(e: Env, asset: Address) -> i128 {
+    /// Get the failed-transfer threshold for quarantining an asset
+    pub fn get_quarantine_threshold(e: Env) -> u32 {
+        quarantine_threshold(&e)
+    }
+
+    /// Lift an asset's quarantine and reset its failure count (admin only)
+    pub fn clear_asset_quarantine(e: Env, caller: Address, asset: Address) {
+        require_admin(&e, &caller);
         e.storage()
             .instance()
-            .get::<_, i128>(&DataKey::TotalValueLockedByAsset(asset))
-            .unwrap_or(0)
+            .remove(&DataKey::AssetQuarantine(asset.clone()));
+        e.storage()
+            .instance()
+            .remove(&DataKey::AssetTransferFailures(asset.clone()));
+        e.events()
+            .publish((symbol_short!("QuarClr"), asset), e.ledger().timestamp());
+    }
+
+    /// Get the payout recorded for a commitment whose settlement could not
+    /// transfer funds, if any
+    pub fn get_settlement_claim(e: Env, commitment_id: String) -> Option<SettlementClaim> {
+        storage_layout::get(&e, &DataKey::SettlementClaim(commitment_id))
+    }
+
+    /// Transfer a recorded settlement claim to its owner.
+    ///
+    /// Fails while the asset is still quarantined.
+    pub fn claim_settlement(e: Env, commitment_id: String) -> i128 {
+        let key = DataKey::SettlementClaim(commitment_id.clone());
+        let claim = storage_layout::get::<_, SettlementClaim>(&e, &key)
+            .unwrap_or_else(|| fail(&e, CommitmentError::NothingPending, "claim_settlement"));
+        claim.owner.require_auth();
+        if is_asset_quarantined(&e, &claim.asset) {
+            fail(&e, CommitmentError::AssetQuarantined, "claim_settlement");
+        }
+        if let Err(err) = disputes::check_not_frozen(&e, &commitment_id) {
+            fail(&e, err, "claim_settlement");
+        }
+        require_no_reentrancy(&e);
+        set_reentrancy_guard(&e, true);
+
+        storage_layout::remove(&e, &key);
+        sweep::adjust_pending_claims(&e, &claim.asset, -claim.amount);
+        token::Client::new(&e, &claim.asset).transfer(
+            &e.current_contract_address(),
+            &claim.owner,
+            &claim.amount,
+        );
+        if let Some(commitment) = read_commitment(&e, &commitment_id) {
+            statement::ledger_entry(&e, &commitment, LedgerEntryKind::Claimed, -claim.amount);
+        }
+
+        set_reentrancy_guard(&e, false);
+        e.events().publish(
+            (symbol_short!("Claimed"), commitment_id, claim.owner),
+            (claim.asset, claim.amount),
+        );
+        claim.amount
+    }
+
+    // ========== Compiled features ==========
+
+    /// Whether an optional module (e.g. `fees`) is compiled into this build
+    pub fn supports(e: Env, feature: Symbol) -> bool {
+        Self::get_supported_features(e).contains(&feature)
+    }
+
+    /// Optional modules compiled into this build
+    pub fn get_supported_features(e: Env) -> Vec<Symbol> {
+        let mut features = Vec::new(&e);
+        if cfg!(feature = "fees") {
+            features.push_back(symbol_short!("fees"));
+        }
+        if cfg!(feature = "insurance") {
+            features.push_back(symbol_short!("insurance"));
+        }
+        if cfg!(feature = "labels") {
+            features.push_back(symbol_short!("labels"));
+        }
+        if cfg!(feature = "statement") {
+            features.push_back(symbol_short!("statement"));
+        }
+        if cfg!(feature = "analytics") {
+            features.push_back(Symbol::new(&e, "analytics"));
+        }
+        if cfg!(feature = "migration") {
+            features.push_back(Symbol::new(&e, "migration"));
+        }
+        if cfg!(feature = "limits") {
+            features.push_back(symbol_short!("limits"));
+        }
+        if cfg!(feature = "operators") {
+            features.push_back(symbol_short!("operators"));
+        }
+        if cfg!(feature = "renewal") {
+            features.push_back(symbol_short!("renewal"));
+        }
+        if cfg!(feature = "scheduling") {
+            features.push_back(Symbol::new(&e, "scheduling"));
+        }
+        if cfg!(feature = "idempotency") {
+            features.push_back(Symbol::new(&e, "idempotency"));
+        }
+        if cfg!(feature = "sweep") {
+            features.push_back(symbol_short!("sweep"));
+        }
+        if cfg!(feature = "liquidation") {
+            features.push_back(Symbol::new(&e, "liquidation"));
+        }
+        if cfg!(feature = "cancellation") {
+            features.push_back(Symbol::new(&e, "cancellation"));
+        }
+        if cfg!(feature = "disputes") {
+            features.push_back(symbol_short!("disputes"));
+        }
+        if cfg!(feature = "vesting") {
+            features.push_back(symbol_short!("vesting"));
+        }
+        if cfg!(feature = "baskets") {
+            features.push_back(symbol_short!("baskets"));
+        }
+        if cfg!(feature = "quotes") {
+            features.push_back(symbol_short!("quotes"));
+        }
+        if cfg!(feature = "tags") {
+            features.push_back(symbol_short!("tags"));
+        }
+        if cfg!(feature = "freezes") {
+            features.push_back(symbol_short!("freezes"));
+        }
+        if cfg!(feature = "exit_requests") {
+            features.push_back(Symbol::new(&e, "exit_requests"));
+        }
+        if cfg!(feature = "collections") {
+            features.push_back(Symbol::new(&e, "collections"));
+        }
+        if cfg!(feature = "nft_pointer") {
+            features.push_back(Symbol::new(&e, "nft_pointer"));
+        }
+        if cfg!(feature = "value_history") {
+            features.push_back(Symbol::new(&e, "value_history"));
+        }
+        if cfg!(feature = "virtual_mode") {
+            features.push_back(Symbol::new(&e, "virtual_mode"));
+        }
+        if cfg!(feature = "amendments") {
+            features.push_back(Symbol::new(&e, "amendments"));
+        }
+        if cfg!(feature = "receipts") {
+            features.push_back(symbol_short!("receipts"));
+        }
+        if cfg!(feature = "recovery") {
+            features.push_back(symbol_short!("recovery"));
+        }
+        if cfg!(feature = "violation_grace") {
+            features.push_back(Symbol::new(&e, "violation_grace"));
+        }
+        if cfg!(feature = "pnl") {
+            features.push_back(symbol_short!("pnl"));
+        }
+        if cfg!(feature = "batch") {
+            features.push_back(symbol_short!("batch"));
+        }
+        if cfg!(feature = "stats") {
+            features.push_back(symbol_short!("stats"));
+        }
+        if cfg!(feature = "allocation_controls") {
+            features.push_back(Symbol::new(&e, "allocation_controls"));
+        }
+        if cfg!(feature = "asset_metadata") {
+            features.push_back(Symbol::new(&e, "asset_metadata"));
+        }
+        if cfg!(feature = "rate_limits") {
+            features.push_back(Symbol::new(&e, "rate_limits"));
+        }
+        if cfg!(feature = "audit_log") {
+            features.push_back(symbol_short!("audit_log"));
+        }
+        features
     }
 
     /// Check if an asset is supported (whitelist empty = all supported).
@@ -1192,7 +2926,7 @@ impl CommitmentCoreContract {
             .instance()
             .get::<_, Vec<Address>>(&DataKey::SupportedAssets)
             .unwrap_or(Vec::new(&e));
-        if supported.len() == 0 {
+        if supported.is_empty() {
             return true;
         }
         for a in supported.iter() {
@@ -1210,3 +2944,6 @@ mod tests;
 
 #[cfg(all(test, feature = "benchmark"))]
 mod benchmarks;
+
+#[cfg(test)]
+mod wasm_size_tests;
@@ -0,0 +1,49 @@
+//! Admin liquidation of violated commitments.
+//!
+//! A violated commitment otherwise waits for its owner to settle it.
+//! `liquidate` lets the admin or an allocator close it out: the remaining
+//! value, less the early-exit penalty kept as a violation fee, goes to the
+//! NFT holder exactly as `settle` would pay it.
+
+use crate::{
+    fail, read_commitment, require_allocator, require_no_reentrancy, set_reentrancy_guard,
+    settle_commitment, CommitmentCoreContract, CommitmentCoreContractClient, CommitmentError,
+    CommitmentStatus, ReceiptKind,
+};
+use shared_utils::EmergencyControl;
+use soroban_sdk::{contractimpl, Address, Env, String, Symbol};
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Force-settle a violated commitment (admin or allocator role only).
+    ///
+    /// Pays `current_value` less the early-exit penalty, management fee and
+    /// settlement fee to the NFT holder; the penalty is credited to the
+    /// collected penalties. The commitment ends `Settled` with a
+    /// `Liquidated` receipt. Fails with InvalidStatus unless the commitment
+    /// is violated, so liquidating twice fails. Returns the payout and
+    /// emits `Liquidated`.
+    pub fn liquidate(e: Env, caller: Address, commitment_id: String) -> i128 {
+        require_allocator(&e, &caller);
+        require_no_reentrancy(&e);
+        set_reentrancy_guard(&e, true);
+        EmergencyControl::require_not_emergency(&e);
+
+        let status = read_commitment(&e, &commitment_id).map(|commitment| commitment.status);
+        let result = match status {
+            None => Err(CommitmentError::CommitmentNotFound),
+            Some(CommitmentStatus::Violated) => {
+                settle_commitment(&e, &commitment_id, ReceiptKind::Liquidated)
+            }
+            Some(_) => Err(CommitmentError::InvalidStatus),
+        };
+        set_reentrancy_guard(&e, false);
+        let payout = result.unwrap_or_else(|err| fail(&e, err, "liquidate"));
+
+        e.events().publish(
+            (Symbol::new(&e, "Liquidated"), commitment_id, caller),
+            (payout, e.ledger().timestamp()),
+        );
+        payout
+    }
+}
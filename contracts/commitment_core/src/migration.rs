@@ -0,0 +1,464 @@
+//! Moving commitments between storage layouts and core deployments.
+//!
+//! `migrate_status_strings` rewrites commitments stored before statuses
+//! became `CommitmentStatus`. A commitment moves to another deployment in
+//! three steps: `export_for_migration` freezes it as `Migrating` and stores a
+//! hashed `MigrationRecord`, `release_for_migration` hands its funds to the
+//! new deployment, and the new deployment's `import_migrated` checks the
+//! record and the funds against the source and recreates the commitment
+//! under a fresh id, linked to the NFT it already has.
+
+use crate::{
+    add_owner_commitment, adjust_active_commitments, adjust_tvl, allocations,
+    clear_value_update_state, commitment_nft_contract, decode_commitment, disputes, exit_requests,
+    expiry_index, fail, ownership, read_commitment, require_admin, require_no_reentrancy,
+    set_commitment, set_reentrancy_guard, statement, storage_layout, tags, track_asset, ttl,
+    value_log, Commitment, CommitmentCoreContract, CommitmentCoreContractClient, CommitmentError,
+    CommitmentRules, CommitmentStatus, DataKey, LedgerEntryKind, RiskProfile,
+};
+use shared_utils::EmergencyControl;
+use soroban_sdk::{
+    contractimpl, contracttype, symbol_short, token, xdr::ToXdr, Address, BytesN, Env, IntoVal,
+    Map, String, Symbol, TryFromVal, Val, Vec,
+};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum MigrationDataKey {
+    MigrationExport(String),  // commitment_id -> MigrationRecord (source side)
+    MigrationRelease(String), // commitment_id -> Address funds were released to
+    MigratedFrom(Address, String), // (source contract, source id) -> imported id
+}
+
+/// A commitment exported from one core deployment for import into another.
+///
+/// `hash` covers every other field; the importing contract recomputes it and
+/// compares the record with the one stored by `source_contract`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MigrationRecord {
+    pub commitment: Commitment,
+    pub source_contract: Address,
+    pub nft_contract: Option<Address>,
+    pub exported_at: u64,
+    pub hash: BytesN<32>,
+}
+
+/// `CommitmentRules` as stored before commitment types became
+/// `RiskProfile`.
+#[contracttype(export = false)]
+#[derive(Clone)]
+pub(crate) struct LegacyCommitmentRules {
+    pub(crate) duration_days: u32,
+    pub(crate) max_loss_percent: u32,
+    pub(crate) commitment_type: String, // "safe", "balanced", "aggressive"
+    pub(crate) early_exit_penalty: u32,
+    pub(crate) min_fee_threshold: i128,
+}
+
+/// `Commitment` as stored before statuses became `CommitmentStatus`; read
+/// only by `migrate_status_strings`. Virtual commitments did not exist yet.
+#[contracttype(export = false)]
+#[derive(Clone)]
+pub(crate) struct LegacyCommitment {
+    pub(crate) commitment_id: String,
+    pub(crate) owner: Address,
+    pub(crate) nft_token_id: u32,
+    pub(crate) rules: LegacyCommitmentRules,
+    pub(crate) amount: i128,
+    pub(crate) asset_address: Address,
+    pub(crate) created_at: u64,
+    pub(crate) expires_at: u64,
+    pub(crate) current_value: i128,
+    pub(crate) status: String, // "active", "settled", "violated", "early_exit"
+}
+
+/// Number of fields in a `LegacyCommitment` record
+const LEGACY_COMMITMENT_FIELDS: u32 = 10;
+
+fn migration_record_hash(
+    e: &Env,
+    commitment: &Commitment,
+    source_contract: &Address,
+    nft_contract: &Option<Address>,
+    exported_at: u64,
+) -> BytesN<32> {
+    let payload = (
+        commitment.clone(),
+        source_contract.clone(),
+        nft_contract.clone(),
+        exported_at,
+    );
+    e.crypto().sha256(&payload.to_xdr(e)).into()
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Rewrite commitments stored in the original layout, with string
+    /// status and commitment type, as `Commitment` (admin only).
+    ///
+    /// For instances upgraded from a version that stored both as strings;
+    /// such commitments cannot be read until converted. Ids that are unknown
+    /// or already converted are skipped. Returns how many were converted.
+    /// Fails with `InvalidStatus` on a record that is in neither layout or a
+    /// status string outside the known set, and with `InvalidCommitmentType`
+    /// on an unknown commitment type.
+    pub fn migrate_status_strings(e: Env, caller: Address, commitment_ids: Vec<String>) -> u32 {
+        require_admin(&e, &caller);
+        let mut converted = 0;
+        for commitment_id in commitment_ids.iter() {
+            let Some(raw) = storage_layout::get::<_, Val>(&e, &DataKey::Commitment(commitment_id))
+            else {
+                continue;
+            };
+            if decode_commitment(&e, &raw).is_some() {
+                continue;
+            }
+            let legacy = Map::<Symbol, Val>::try_from_val(&e, &raw)
+                .ok()
+                .filter(|fields| fields.len() == LEGACY_COMMITMENT_FIELDS)
+                .and_then(|_| LegacyCommitment::try_from_val(&e, &raw).ok())
+                .unwrap_or_else(|| {
+                    fail(&e, CommitmentError::InvalidStatus, "migrate_status_strings")
+                });
+            let status = if legacy.status == String::from_str(&e, "active") {
+                CommitmentStatus::Active
+            } else if legacy.status == String::from_str(&e, "settled") {
+                CommitmentStatus::Settled
+            } else if legacy.status == String::from_str(&e, "violated") {
+                CommitmentStatus::Violated
+            } else if legacy.status == String::from_str(&e, "early_exit") {
+                CommitmentStatus::EarlyExit
+            } else {
+                fail(&e, CommitmentError::InvalidStatus, "migrate_status_strings")
+            };
+            let commitment_type = legacy.rules.commitment_type;
+            let commitment_type = if commitment_type == String::from_str(&e, "safe") {
+                RiskProfile::Safe
+            } else if commitment_type == String::from_str(&e, "balanced") {
+                RiskProfile::Balanced
+            } else if commitment_type == String::from_str(&e, "aggressive") {
+                RiskProfile::Aggressive
+            } else {
+                fail(
+                    &e,
+                    CommitmentError::InvalidCommitmentType,
+                    "migrate_status_strings",
+                )
+            };
+            set_commitment(
+                &e,
+                &Commitment {
+                    commitment_id: legacy.commitment_id,
+                    owner: legacy.owner,
+                    nft_token_id: legacy.nft_token_id,
+                    rules: CommitmentRules {
+                        duration_days: legacy.rules.duration_days,
+                        max_loss_percent: legacy.rules.max_loss_percent,
+                        commitment_type,
+                        early_exit_penalty: legacy.rules.early_exit_penalty,
+                        min_fee_threshold: legacy.rules.min_fee_threshold,
+                    },
+                    amount: legacy.amount,
+                    asset_address: legacy.asset_address,
+                    created_at: legacy.created_at,
+                    expires_at: legacy.expires_at,
+                    current_value: legacy.current_value,
+                    status,
+                    is_virtual: false,
+                },
+            );
+            converted += 1;
+        }
+        converted
+    }
+
+    /// Freeze an active commitment and export it for import into a new core
+    /// deployment (admin only). The commitment moves to `migrating` status,
+    /// which blocks settlement, exits and value updates here. Funds still
+    /// out at a pool must be deallocated first (HasAllocations).
+    pub fn export_for_migration(e: Env, caller: Address, commitment_id: String) -> MigrationRecord {
+        require_admin(&e, &caller);
+        let mut commitment = read_commitment(&e, &commitment_id).unwrap_or_else(|| {
+            fail(
+                &e,
+                CommitmentError::CommitmentNotFound,
+                "export_for_migration",
+            )
+        });
+        if commitment.is_virtual {
+            fail(&e, CommitmentError::InvalidStatus, "export_for_migration");
+        }
+        if commitment.status != CommitmentStatus::Active {
+            fail(&e, CommitmentError::NotActive, "export_for_migration");
+        }
+        exit_requests::require_none(&e, &commitment_id, "export_for_migration");
+        if let Err(err) = allocations::check_unallocated(&e, &commitment_id) {
+            fail(&e, err, "export_for_migration");
+        }
+
+        commitment.status = CommitmentStatus::Migrating;
+        set_commitment(&e, &commitment);
+        tags::clear(&e, &commitment);
+        clear_value_update_state(&e, &commitment_id);
+        exit_requests::clear(&e, &commitment_id);
+        adjust_active_commitments(&e, -1);
+
+        let source_contract = e.current_contract_address();
+        let nft_contract = commitment_nft_contract(&e, &commitment_id);
+        let exported_at = e.ledger().timestamp();
+        let hash = migration_record_hash(
+            &e,
+            &commitment,
+            &source_contract,
+            &nft_contract,
+            exported_at,
+        );
+        let record = MigrationRecord {
+            commitment,
+            source_contract,
+            nft_contract,
+            exported_at,
+            hash,
+        };
+        storage_layout::set(
+            &e,
+            &MigrationDataKey::MigrationExport(commitment_id.clone()),
+            &record,
+        );
+        e.events().publish(
+            (symbol_short!("MigExport"), commitment_id),
+            record.hash.clone(),
+        );
+        record
+    }
+
+    /// Transfer an exported commitment's funds to the new core deployment
+    /// (admin only) and mark it `migrated` here.
+    ///
+    /// Fails with `HasAllocations` if any of its funds are out at a pool.
+    /// The commitment's NFT stays active; the new deployment links it on
+    /// import.
+    pub fn release_for_migration(
+        e: Env,
+        caller: Address,
+        commitment_id: String,
+        new_core: Address,
+    ) {
+        require_admin(&e, &caller);
+        require_no_reentrancy(&e);
+        set_reentrancy_guard(&e, true);
+        if !storage_layout::has(
+            &e,
+            &MigrationDataKey::MigrationExport(commitment_id.clone()),
+        ) {
+            set_reentrancy_guard(&e, false);
+            fail(&e, CommitmentError::NotExported, "release_for_migration");
+        }
+        let release_key = MigrationDataKey::MigrationRelease(commitment_id.clone());
+        if storage_layout::has(&e, &release_key) {
+            set_reentrancy_guard(&e, false);
+            fail(
+                &e,
+                CommitmentError::AlreadyMigrated,
+                "release_for_migration",
+            );
+        }
+        let mut commitment = read_commitment(&e, &commitment_id).unwrap_or_else(|| {
+            set_reentrancy_guard(&e, false);
+            fail(
+                &e,
+                CommitmentError::CommitmentNotFound,
+                "release_for_migration",
+            )
+        });
+        if let Err(err) = disputes::check_not_frozen(&e, &commitment_id)
+            .and_then(|_| allocations::check_unallocated(&e, &commitment_id))
+        {
+            set_reentrancy_guard(&e, false);
+            fail(&e, err, "release_for_migration");
+        }
+
+        // EFFECTS: the principal leaves this deployment's TVL
+        let amount = commitment.current_value;
+        adjust_tvl(&e, &commitment.asset_address, -commitment.amount);
+        commitment.status = CommitmentStatus::Migrated;
+        set_commitment(&e, &commitment);
+        statement::ledger_entry(&e, &commitment, LedgerEntryKind::Migrated, -amount);
+        storage_layout::set(&e, &release_key, &new_core);
+
+        // INTERACTIONS: hand the funds to the new deployment
+        if amount > 0 {
+            token::Client::new(&e, &commitment.asset_address).transfer(
+                &e.current_contract_address(),
+                &new_core,
+                &amount,
+            );
+        }
+
+        set_reentrancy_guard(&e, false);
+        e.events().publish(
+            (symbol_short!("MigRlsd"), commitment_id, new_core),
+            (amount, e.ledger().timestamp()),
+        );
+    }
+
+    /// Recreate a commitment exported by another core deployment (admin only).
+    ///
+    /// The record must hash correctly and match the one stored by its source,
+    /// which must already have released the funds to this contract;
+    /// `funds_proof` is the amount released and must be held here unaccounted.
+    /// Original terms, timestamps and NFT are kept: the new id points at the
+    /// exported `nft_token_id` in the record's NFT contract, so it settles
+    /// against the token its holder already has (MigrationRecordMismatch if
+    /// the record names no NFT contract). Returns the new id.
+    pub fn import_migrated(
+        e: Env,
+        caller: Address,
+        record: MigrationRecord,
+        funds_proof: i128,
+    ) -> String {
+        require_admin(&e, &caller);
+        EmergencyControl::require_not_emergency(&e);
+        let source_id = record.commitment.commitment_id.clone();
+
+        let expected_hash = migration_record_hash(
+            &e,
+            &record.commitment,
+            &record.source_contract,
+            &record.nft_contract,
+            record.exported_at,
+        );
+        if expected_hash != record.hash {
+            fail(
+                &e,
+                CommitmentError::MigrationRecordMismatch,
+                "import_migrated",
+            );
+        }
+        let migrated_key =
+            MigrationDataKey::MigratedFrom(record.source_contract.clone(), source_id.clone());
+        if storage_layout::has(&e, &migrated_key) {
+            fail(&e, CommitmentError::AlreadyMigrated, "import_migrated");
+        }
+
+        // The source must hold the same record and have released to us
+        let mut args = Vec::new(&e);
+        args.push_back(source_id.clone().into_val(&e));
+        let source_record: Option<MigrationRecord> = e.invoke_contract(
+            &record.source_contract,
+            &Symbol::new(&e, "get_migration_record"),
+            args.clone(),
+        );
+        if source_record != Some(record.clone()) {
+            fail(
+                &e,
+                CommitmentError::MigrationRecordMismatch,
+                "import_migrated",
+            );
+        }
+        let released_to: Option<Address> = e.invoke_contract(
+            &record.source_contract,
+            &Symbol::new(&e, "get_migration_release"),
+            args,
+        );
+        let nft_contract = record.nft_contract.clone().unwrap_or_else(|| {
+            fail(
+                &e,
+                CommitmentError::MigrationRecordMismatch,
+                "import_migrated",
+            )
+        });
+        let asset = record.commitment.asset_address.clone();
+        if released_to != Some(e.current_contract_address())
+            || funds_proof != record.commitment.current_value
+            || Self::get_unaccounted_balance(e.clone(), asset.clone()) < funds_proof
+        {
+            fail(
+                &e,
+                CommitmentError::MigrationFundsMissing,
+                "import_migrated",
+            );
+        }
+
+        let total = Self::get_total_commitments(e.clone());
+        let id_counter = e
+            .storage()
+            .instance()
+            .get::<_, u64>(&DataKey::CommitmentIdCounter)
+            .unwrap_or(total);
+        let (id_counter, commitment_id) = Self::next_free_commitment_id(&e, id_counter);
+        let commitment = Commitment {
+            commitment_id: commitment_id.clone(),
+            status: CommitmentStatus::Active,
+            ..record.commitment
+        };
+
+        set_commitment(&e, &commitment);
+        value_log::record(
+            &e,
+            &commitment_id,
+            &caller,
+            commitment.current_value,
+            commitment.current_value,
+            commitment.amount,
+        );
+        statement::ledger_entry(
+            &e,
+            &commitment,
+            LedgerEntryKind::Created,
+            commitment.current_value,
+        );
+        add_owner_commitment(&e, &commitment.owner, &commitment_id);
+        expiry_index::insert(&e, &commitment_id, commitment.expires_at);
+        e.storage()
+            .instance()
+            .set(&DataKey::TotalCommitments, &(total + 1));
+        e.storage()
+            .instance()
+            .set(&DataKey::CommitmentIdCounter, &(id_counter + 1));
+        adjust_active_commitments(&e, 1);
+        adjust_tvl(&e, &asset, commitment.amount);
+        track_asset(&e, &asset);
+        storage_layout::set(&e, &migrated_key, &commitment_id);
+        storage_layout::set(
+            &e,
+            &DataKey::CommitmentNftContract(commitment_id.clone()),
+            &nft_contract,
+        );
+        ownership::record_token(&e, &nft_contract, commitment.nft_token_id, &commitment_id);
+        ttl::extend(
+            &e,
+            &commitment,
+            ttl::ledgers_for_term(commitment.rules.duration_days),
+        );
+
+        e.events().publish(
+            (symbol_short!("MigImport"), commitment_id.clone()),
+            (record.source_contract, source_id),
+        );
+        commitment_id
+    }
+
+    /// Get the export record of a commitment (source side)
+    pub fn get_migration_record(e: Env, commitment_id: String) -> Option<MigrationRecord> {
+        storage_layout::get(&e, &MigrationDataKey::MigrationExport(commitment_id))
+    }
+
+    /// Get the contract an exported commitment's funds were released to
+    pub fn get_migration_release(e: Env, commitment_id: String) -> Option<Address> {
+        storage_layout::get(&e, &MigrationDataKey::MigrationRelease(commitment_id))
+    }
+
+    /// Get the id a migrated commitment was imported under (importing side)
+    pub fn get_migrated_commitment_id(
+        e: Env,
+        source_contract: Address,
+        source_commitment_id: String,
+    ) -> Option<String> {
+        storage_layout::get(
+            &e,
+            &MigrationDataKey::MigratedFrom(source_contract, source_commitment_id),
+        )
+    }
+}
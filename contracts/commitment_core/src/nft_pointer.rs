@@ -0,0 +1,132 @@
+//! Timelocked changes of the default NFT contract.
+//!
+//! The admin queues a new default NFT contract with `set_nft_contract` and
+//! applies it after `NFT_CONTRACT_TIMELOCK_SECONDS`. Existing commitments
+//! keep the contract that minted their token; earlier defaults are kept in
+//! a history so their tokens still resolve to commitments.
+
+use crate::{
+    duration_limits, fail, require_admin, CommitmentCoreContract, CommitmentCoreContractClient,
+    CommitmentError, DataKey,
+};
+use soroban_sdk::{contractimpl, contracttype, symbol_short, Address, Env, Vec};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum NftPointerDataKey {
+    PendingNftContract, // PendingNftContract — queued default pointer change
+    NftContractHistory, // Vec<Address> of previous default NFT contracts
+}
+
+/// Delay between queueing and applying a default NFT contract change
+pub const NFT_CONTRACT_TIMELOCK_SECONDS: u64 = 2 * 86400;
+
+/// A queued change of the default NFT contract pointer.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingNftContract {
+    pub new_address: Address,
+    pub eta: u64,
+}
+
+/// Previous default NFT contracts, oldest first.
+pub(crate) fn history(e: &Env) -> Vec<Address> {
+    e.storage()
+        .instance()
+        .get::<_, Vec<Address>>(&NftPointerDataKey::NftContractHistory)
+        .unwrap_or(Vec::new(e))
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Queue a change of the default NFT contract (admin only).
+    ///
+    /// Takes effect via `apply_nft_contract` once
+    /// `NFT_CONTRACT_TIMELOCK_SECONDS` have passed. Queueing again replaces
+    /// the pending change and restarts the timelock.
+    pub fn set_nft_contract(e: Env, caller: Address, new_address: Address) {
+        require_admin(&e, &caller);
+        let eta = e
+            .ledger()
+            .timestamp()
+            .saturating_add(NFT_CONTRACT_TIMELOCK_SECONDS);
+        e.storage().instance().set(
+            &NftPointerDataKey::PendingNftContract,
+            &PendingNftContract {
+                new_address: new_address.clone(),
+                eta,
+            },
+        );
+        e.events()
+            .publish((symbol_short!("NftPtrQ"), new_address), eta);
+    }
+
+    /// Apply the queued NFT contract change (admin only).
+    ///
+    /// Existing commitments keep settling against the contract that minted
+    /// their token; only new commitments mint into the new contract, which
+    /// receives the current maximum duration if the admin has changed it.
+    pub fn apply_nft_contract(e: Env, caller: Address) {
+        require_admin(&e, &caller);
+        let pending = e
+            .storage()
+            .instance()
+            .get::<_, PendingNftContract>(&NftPointerDataKey::PendingNftContract)
+            .unwrap_or_else(|| fail(&e, CommitmentError::NothingPending, "apply_nft_contract"));
+        if e.ledger().timestamp() < pending.eta {
+            fail(
+                &e,
+                CommitmentError::TimelockNotElapsed,
+                "apply_nft_contract",
+            );
+        }
+
+        let old_address = Self::get_nft_contract(e.clone());
+        let mut previous = history(&e);
+        previous.push_back(old_address.clone());
+        e.storage()
+            .instance()
+            .set(&NftPointerDataKey::NftContractHistory, &previous);
+        duration_limits::sync_nft(&e, &pending.new_address, "apply_nft_contract");
+        e.storage()
+            .instance()
+            .set(&DataKey::NftContract, &pending.new_address);
+        e.storage()
+            .instance()
+            .remove(&NftPointerDataKey::PendingNftContract);
+
+        e.events().publish(
+            (symbol_short!("NftPtrSet"), old_address, pending.new_address),
+            e.ledger().timestamp(),
+        );
+    }
+
+    /// Cancel the queued NFT contract change (admin only)
+    pub fn cancel_nft_contract(e: Env, caller: Address) {
+        require_admin(&e, &caller);
+        if !e
+            .storage()
+            .instance()
+            .has(&NftPointerDataKey::PendingNftContract)
+        {
+            fail(&e, CommitmentError::NothingPending, "cancel_nft_contract");
+        }
+        e.storage()
+            .instance()
+            .remove(&NftPointerDataKey::PendingNftContract);
+        e.events()
+            .publish((symbol_short!("NftPtrCxl"),), e.ledger().timestamp());
+    }
+
+    /// Get the queued NFT contract change, if any
+    pub fn get_pending_nft_contract(e: Env) -> Option<PendingNftContract> {
+        e.storage()
+            .instance()
+            .get(&NftPointerDataKey::PendingNftContract)
+    }
+
+    /// Previous default NFT contracts, oldest first
+    pub fn get_nft_contract_history(e: Env) -> Vec<Address> {
+        history(&e)
+    }
+}
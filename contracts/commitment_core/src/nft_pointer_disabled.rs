@@ -0,0 +1,8 @@
+//! NFT pointer helpers used when the `nft_pointer` feature is compiled out:
+//! the default NFT contract set at initialization never changes.
+
+use soroban_sdk::{Address, Env, Vec};
+
+pub(crate) fn history(e: &Env) -> Vec<Address> {
+    Vec::new(e)
+}
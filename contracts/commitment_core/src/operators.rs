@@ -0,0 +1,82 @@
+//! Per-commitment operators acting for the owner.
+//!
+//! An owner may approve one operator per commitment, such as a bot or a
+//! custodial service, which can then exit early (directly or in two
+//! phases), cancel within the grace period and toggle auto-renew on the
+//! owner's behalf. Payouts still go to the owner. The approval is dropped
+//! when the commitment closes or changes hands.
+
+use crate::{
+    fail, read_commitment, storage_layout, ttl, Commitment, CommitmentCoreContract,
+    CommitmentCoreContractClient, CommitmentError,
+};
+use soroban_sdk::{contractimpl, contracttype, symbol_short, Address, Env, String};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum OperatorDataKey {
+    Operator(String), // commitment_id -> Address approved to act for the owner
+}
+
+/// Whether `caller` is the commitment's owner or its approved operator.
+/// The caller's auth is checked by the entrypoint.
+pub(crate) fn is_owner_or_operator(e: &Env, commitment: &Commitment, caller: &Address) -> bool {
+    commitment.owner == *caller
+        || CommitmentCoreContract::get_operator(e.clone(), commitment.commitment_id.clone())
+            .as_ref()
+            == Some(caller)
+}
+
+/// Drop the commitment's operator, if any.
+pub(crate) fn clear(e: &Env, commitment_id: &String) {
+    storage_layout::remove(e, &OperatorDataKey::Operator(commitment_id.clone()));
+}
+
+/// Extend a commitment's operator entry (see `ttl::extend`).
+pub(crate) fn extend_ttl(e: &Env, commitment_id: &String, extend_to: u32) {
+    ttl::extend_persistent(
+        e,
+        &OperatorDataKey::Operator(commitment_id.clone()),
+        extend_to,
+    );
+}
+
+/// Read a commitment for an owner-only operator change.
+fn owned_commitment(e: &Env, owner: &Address, commitment_id: &String, context: &str) -> Commitment {
+    owner.require_auth();
+    let commitment = read_commitment(e, commitment_id)
+        .unwrap_or_else(|| fail(e, CommitmentError::CommitmentNotFound, context));
+    if commitment.owner != *owner {
+        fail(e, CommitmentError::Unauthorized, context);
+    }
+    commitment
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Let `operator` act for the owner on a commitment (owner only),
+    /// replacing any earlier operator. Operators cannot approve others.
+    pub fn approve_operator(e: Env, owner: Address, operator: Address, commitment_id: String) {
+        owned_commitment(&e, &owner, &commitment_id, "approve_operator");
+        storage_layout::set(
+            &e,
+            &OperatorDataKey::Operator(commitment_id.clone()),
+            &operator,
+        );
+        e.events()
+            .publish((symbol_short!("OpApprove"), commitment_id, owner), operator);
+    }
+
+    /// Withdraw the commitment's operator approval (owner only)
+    pub fn revoke_operator(e: Env, owner: Address, commitment_id: String) {
+        owned_commitment(&e, &owner, &commitment_id, "revoke_operator");
+        clear(&e, &commitment_id);
+        e.events()
+            .publish((symbol_short!("OpRevoke"), commitment_id, owner), ());
+    }
+
+    /// Operator approved to act for the commitment's owner, if any
+    pub fn get_operator(e: Env, commitment_id: String) -> Option<Address> {
+        storage_layout::get(&e, &OperatorDataKey::Operator(commitment_id))
+    }
+}
@@ -0,0 +1,12 @@
+//! Owner-only helpers used when the `operators` feature is compiled out.
+
+use crate::Commitment;
+use soroban_sdk::{Address, Env, String};
+
+pub(crate) fn is_owner_or_operator(_e: &Env, commitment: &Commitment, caller: &Address) -> bool {
+    commitment.owner == *caller
+}
+
+pub(crate) fn clear(_e: &Env, _commitment_id: &String) {}
+
+pub(crate) fn extend_ttl(_e: &Env, _commitment_id: &String, _extend_to: u32) {}
@@ -0,0 +1,59 @@
+//! Cap on an owner's concurrent commitments.
+//!
+//! `create_commitment` (and `quote_commitment`) compare the owner's active
+//! and pending commitments, as counted by `stats`, against the cap, so
+//! settling, exiting or cancelling one frees a slot. No cap is set by
+//! default.
+
+use crate::{
+    require_admin, stats, CommitmentCoreContract, CommitmentCoreContractClient, CommitmentError,
+};
+use soroban_sdk::{contractimpl, contracttype, symbol_short, Address, Env};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum OwnerLimitsDataKey {
+    MaxCommitments, // u32 active commitments allowed per owner; unset = unlimited
+}
+
+/// `TooManyCommitments` if `owner` is already at the cap.
+pub(crate) fn check(e: &Env, owner: &Address) -> Result<(), CommitmentError> {
+    let max = CommitmentCoreContract::get_max_commitments_per_owner(e.clone());
+    if max > 0 && stats::owner_active_count(e, owner) >= max {
+        return Err(CommitmentError::TooManyCommitments);
+    }
+    Ok(())
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Cap each owner's active commitments at `max` (admin only); 0 lifts
+    /// the cap. Owners already above it keep their commitments.
+    pub fn set_max_commitments_per_owner(e: Env, caller: Address, max: u32) {
+        require_admin(&e, &caller);
+        if max == 0 {
+            e.storage()
+                .instance()
+                .remove(&OwnerLimitsDataKey::MaxCommitments);
+        } else {
+            e.storage()
+                .instance()
+                .set(&OwnerLimitsDataKey::MaxCommitments, &max);
+        }
+        e.events()
+            .publish((symbol_short!("OwnerCap"),), (max, e.ledger().timestamp()));
+    }
+
+    /// Active commitments allowed per owner (0 = unlimited)
+    pub fn get_max_commitments_per_owner(e: Env) -> u32 {
+        e.storage()
+            .instance()
+            .get(&OwnerLimitsDataKey::MaxCommitments)
+            .unwrap_or(0)
+    }
+
+    /// Owner's active or pending commitments, as checked against the cap
+    pub fn get_active_commitment_count(e: Env, owner: Address) -> u32 {
+        stats::owner_active_count(&e, &owner)
+    }
+}
@@ -0,0 +1,8 @@
+//! No-op owner limit check used when the `limits` feature is compiled out.
+
+use crate::CommitmentError;
+use soroban_sdk::{Address, Env};
+
+pub(crate) fn check(_e: &Env, _owner: &Address) -> Result<(), CommitmentError> {
+    Ok(())
+}
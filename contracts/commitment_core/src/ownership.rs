@@ -0,0 +1,117 @@
+//! Commitment ownership follows the position NFT.
+//!
+//! Creation (and migration import) records which commitment each minted
+//! token belongs to, keyed by the minting NFT contract since token ids are
+//! only unique per collection. The NFT contract calls `on_nft_transfer`
+//! after every transfer so `Commitment::owner` and the owner listings track
+//! the holder, and wallets can resolve a token id back to its commitment.
+//! Tokens minted before this index existed are picked up at settlement
+//! instead (see `settle`). Tokens are keyed by NFT contract as well as id,
+//! since collections mint from different contracts.
+
+use crate::{
+    fail, nft_pointer, read_commitment, reassign_owner, set_commitment, storage_layout, Commitment,
+    CommitmentCoreContract, CommitmentCoreContractClient, CommitmentError, CommitmentStatus,
+};
+use soroban_sdk::{contractimpl, contracttype, Address, Env, String};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum OwnershipDataKey {
+    CommitmentByToken(Address, u32), // (nft_contract, token_id) -> commitment_id
+}
+
+/// Commitment recorded for `token_id` of `nft_contract`, if any.
+fn commitment_for_token(e: &Env, nft_contract: &Address, token_id: u32) -> Option<String> {
+    storage_layout::get(
+        e,
+        &OwnershipDataKey::CommitmentByToken(nft_contract.clone(), token_id),
+    )
+}
+
+/// Commitment for `token_id` in the default collection, checking the
+/// current default NFT contract and then earlier ones, newest first.
+fn default_collection_commitment(e: &Env, token_id: u32) -> Option<String> {
+    let mut nft_contracts = nft_pointer::history(e);
+    nft_contracts.push_back(CommitmentCoreContract::get_nft_contract(e.clone()));
+    nft_contracts
+        .iter()
+        .rev()
+        .find_map(|nft_contract| commitment_for_token(e, &nft_contract, token_id))
+}
+
+/// Remember that `token_id` of `nft_contract` represents `commitment_id`.
+pub(crate) fn record_token(e: &Env, nft_contract: &Address, token_id: u32, commitment_id: &String) {
+    storage_layout::set(
+        e,
+        &OwnershipDataKey::CommitmentByToken(nft_contract.clone(), token_id),
+        commitment_id,
+    );
+}
+
+/// Forget which commitment `token_id` of `nft_contract` represents (used
+/// when a commitment is voided and its NFT burned).
+#[cfg(feature = "recovery")]
+pub(crate) fn clear_token(e: &Env, nft_contract: &Address, token_id: u32) {
+    storage_layout::remove(
+        e,
+        &OwnershipDataKey::CommitmentByToken(nft_contract.clone(), token_id),
+    );
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Id of the commitment whose NFT in the default collection is `token_id`.
+    ///
+    /// Looks in the current default NFT contract first, then in earlier
+    /// ones, newest first. Fails with CommitmentNotFound for tokens of
+    /// named collections and for commitments created before this mapping.
+    pub fn get_commitment_id_by_token(e: Env, token_id: u32) -> String {
+        default_collection_commitment(&e, token_id).unwrap_or_else(|| {
+            fail(
+                &e,
+                CommitmentError::CommitmentNotFound,
+                "get_commitment_id_by_token",
+            )
+        })
+    }
+
+    /// The commitment whose NFT in the default collection is `token_id`.
+    pub fn get_commitment_by_token(e: Env, token_id: u32) -> Result<Commitment, CommitmentError> {
+        default_collection_commitment(&e, token_id)
+            .and_then(|commitment_id| read_commitment(&e, &commitment_id))
+            .ok_or(CommitmentError::CommitmentNotFound)
+    }
+
+    /// Hand a commitment to the new holder of its NFT (NFT contract only).
+    ///
+    /// `caller` must be the NFT contract that minted `token_id`; tokens it
+    /// did not mint for this core are ignored, as are commitments that are
+    /// no longer active or violated. Returns whether the owner changed.
+    pub fn on_nft_transfer(
+        e: Env,
+        caller: Address,
+        token_id: u32,
+        from: Address,
+        to: Address,
+    ) -> bool {
+        caller.require_auth();
+        let Some(commitment_id) = commitment_for_token(&e, &caller, token_id) else {
+            return false;
+        };
+        let Some(mut commitment) = read_commitment(&e, &commitment_id) else {
+            return false;
+        };
+        let open = matches!(
+            commitment.status,
+            CommitmentStatus::Active | CommitmentStatus::Violated
+        );
+        // `from` is informational: the NFT is the source of truth
+        if !open || commitment.owner == to || from == to {
+            return false;
+        }
+        reassign_owner(&e, &mut commitment, &to);
+        set_commitment(&e, &commitment);
+        true
+    }
+}
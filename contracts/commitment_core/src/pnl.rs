@@ -0,0 +1,83 @@
+//! Realized profit and loss.
+//!
+//! The difference between what a commitment pays out and its principal is
+//! booked per asset in `RealizedPnl` when it settles or exits. What the
+//! owner is paid against that principal, net of penalties and fees, is
+//! booked per owner and protocol-wide.
+
+// `PnlDataKey` keeps the variant names (the storage keys) it took over from
+// `TvlDataKey`
+#![allow(clippy::enum_variant_names)]
+
+use crate::{
+    storage_layout, ttl, Commitment, CommitmentCoreContract, CommitmentCoreContractClient,
+};
+use soroban_sdk::{contractimpl, contracttype, Address, Env};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum PnlDataKey {
+    RealizedPnl(Address), // asset -> sum of (realized value - principal)
+    OwnerPnl(Address),    // owner -> sum of (paid out - principal), across assets
+    ProtocolPnl,          // i128 sum of every owner's OwnerPnl
+}
+
+/// Book `realized` (what a closing commitment was worth on the way out)
+/// against its principal.
+pub(crate) fn book_realized(e: &Env, commitment: &Commitment, realized: i128) {
+    let delta = realized.saturating_sub(commitment.amount);
+    if delta != 0 {
+        let key = PnlDataKey::RealizedPnl(commitment.asset_address.clone());
+        let pnl = storage_layout::get::<_, i128>(e, &key).unwrap_or(0);
+        storage_layout::set(e, &key, &pnl.saturating_add(delta));
+    }
+}
+
+/// Book what a closing commitment's owner was paid (`paid_out`, after any
+/// penalty and fees) against its principal.
+pub(crate) fn book_owner_pnl(e: &Env, commitment: &Commitment, paid_out: i128) {
+    let delta = paid_out.saturating_sub(commitment.amount);
+    if delta == 0 {
+        return;
+    }
+    let key = PnlDataKey::OwnerPnl(commitment.owner.clone());
+    let pnl = storage_layout::get::<_, i128>(e, &key).unwrap_or(0);
+    storage_layout::set(e, &key, &pnl.saturating_add(delta));
+    let pnl = e
+        .storage()
+        .instance()
+        .get::<_, i128>(&PnlDataKey::ProtocolPnl)
+        .unwrap_or(0);
+    e.storage()
+        .instance()
+        .set(&PnlDataKey::ProtocolPnl, &pnl.saturating_add(delta));
+}
+
+/// Extend an owner's realized P&L entry (see `ttl::extend`).
+pub(crate) fn extend_ttl(e: &Env, owner: &Address, extend_to: u32) {
+    ttl::extend_persistent(e, &PnlDataKey::OwnerPnl(owner.clone()), extend_to);
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Net gain (positive) or loss realized on commitments in `asset` when
+    /// they settled or exited, relative to their principal.
+    pub fn get_realized_pnl(e: Env, asset: Address) -> i128 {
+        storage_layout::get(&e, &PnlDataKey::RealizedPnl(asset)).unwrap_or(0)
+    }
+
+    /// Lifetime gain (positive) or loss an owner has realized: what they
+    /// were paid out on settlement and exits, after penalties and fees,
+    /// less the principal. Summed across assets in base units.
+    pub fn get_owner_realized_pnl(e: Env, owner: Address) -> i128 {
+        storage_layout::get(&e, &PnlDataKey::OwnerPnl(owner)).unwrap_or(0)
+    }
+
+    /// Sum of `get_owner_realized_pnl` over every owner
+    pub fn get_protocol_realized_pnl(e: Env) -> i128 {
+        e.storage()
+            .instance()
+            .get(&PnlDataKey::ProtocolPnl)
+            .unwrap_or(0)
+    }
+}
@@ -0,0 +1,10 @@
+//! No-op P&L bookkeeping used when the `pnl` feature is compiled out.
+
+use crate::Commitment;
+use soroban_sdk::{Address, Env};
+
+pub(crate) fn book_realized(_e: &Env, _commitment: &Commitment, _realized: i128) {}
+
+pub(crate) fn book_owner_pnl(_e: &Env, _commitment: &Commitment, _paid_out: i128) {}
+
+pub(crate) fn extend_ttl(_e: &Env, _owner: &Address, _extend_to: u32) {}
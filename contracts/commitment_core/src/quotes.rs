@@ -0,0 +1,259 @@
+//! Read-only quotes of what settling, exiting or creating a commitment
+//! would pay out.
+//!
+//! Each quote runs the same math as the flow it previews, so a quote taken
+//! in the same ledger matches what the call would do.
+
+use crate::{
+    allocations, asset_limits, check_settleable, deposit_limits, disputes, duration_limits, fail,
+    fees, is_asset_quarantined, owner_limits, rate_limits, read_commitment, settlement_split,
+    Commitment, CommitmentCoreContract, CommitmentCoreContractClient, CommitmentError,
+    CommitmentRules, CommitmentStatus, ReceiptKind,
+};
+use shared_utils::{EmergencyControl, PercentMath, TimeUtils};
+use soroban_sdk::{contractimpl, contracttype, symbol_short, Address, Env, String, Vec};
+
+/// What settling a commitment would pay out right now.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SettlementPreview {
+    pub commitment_id: String,
+    pub gross_value: i128,
+    pub accrued_management_fee: i128,
+    pub net_payout: i128,
+    pub expires_at: u64,
+    pub is_expired: bool,
+}
+
+/// What `settle` would pay out right now, from the same math it uses.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SettlementQuote {
+    pub commitment_id: String,
+    pub gross_value: i128,
+    pub penalty: i128,
+    pub protocol_fee: i128, // management plus settlement fee
+    pub net_payout: i128,
+}
+
+/// What `early_exit` would pay out right now, from the same math it uses.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EarlyExitQuote {
+    pub commitment_id: String,
+    pub gross_value: i128,
+    pub penalty: i128,
+    pub protocol_fee: i128, // management fee
+    pub net_payout: i128,
+    pub fee_threshold_met: bool, // penalty halved by allocation fees earned
+}
+
+/// Penalty, fee and payout of exiting a quoted commitment at one point in
+/// its term.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExitQuote {
+    pub elapsed_percent: u32,
+    pub exit_at: u64,
+    pub penalty: i128,
+    pub management_fee: i128,
+    pub payout: i128,
+}
+
+/// What creating a commitment would cost and pay out, computed with the same
+/// math as `create_commitment`, `early_exit` and `settle`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommitmentQuote {
+    pub amount: i128,
+    pub expires_at: u64,
+    pub management_fee_bps: u32,
+    pub early_exit_samples: Vec<ExitQuote>, // at 0%, 50% and 90% of the term
+    pub full_term_management_fee: i128,     // settling at expiry at full value
+    pub full_term_payout: i128,
+    pub worst_case_value: i128, // value after losing max_loss_percent
+    pub worst_case_payout: i128,
+    pub asset_supported: bool, // whitelisted and not quarantined
+    pub within_rate_limit: bool,
+    pub emergency_mode: bool,
+    pub validation_error: u32, // CommitmentError code creation would fail with, 0 if valid
+    pub can_create: bool,
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Preview the payout of settling a commitment at the current time
+    pub fn preview_settlement(e: Env, commitment_id: String) -> SettlementPreview {
+        let commitment = read_commitment(&e, &commitment_id).unwrap_or_else(|| {
+            fail(
+                &e,
+                CommitmentError::CommitmentNotFound,
+                "preview_settlement",
+            )
+        });
+        let gross_value = commitment.current_value;
+        let accrued_management_fee = fees::accrued_management_fee(&e, &commitment, gross_value);
+        let after_fee = gross_value - accrued_management_fee;
+        SettlementPreview {
+            commitment_id,
+            gross_value,
+            accrued_management_fee,
+            net_payout: after_fee - fees::settlement_fee(&e, &commitment, after_fee),
+            expires_at: commitment.expires_at,
+            is_expired: e.ledger().timestamp() >= commitment.expires_at,
+        }
+    }
+
+    /// Quote settling a commitment now: its value, the liquidation penalty
+    /// (zero for plain settlement), the protocol fees and the net payout.
+    ///
+    /// Fails like `settle` (NotActive, NotExpired, HasAllocations,
+    /// CommitmentFrozen, CommitmentNotFound) where settling would fail. An
+    /// auto-renewing commitment starts another term instead of paying out;
+    /// the quote is what settling it would pay.
+    pub fn quote_settlement(
+        e: Env,
+        commitment_id: String,
+    ) -> Result<SettlementQuote, CommitmentError> {
+        let commitment =
+            read_commitment(&e, &commitment_id).ok_or(CommitmentError::CommitmentNotFound)?;
+        check_settleable(&e, &commitment)?;
+        disputes::check_not_frozen(&e, &commitment_id)?;
+        allocations::check_unallocated(&e, &commitment_id)?;
+        let (penalty, management_fee, settlement_fee, net_payout) =
+            settlement_split(&e, &commitment, ReceiptKind::Settled);
+        Ok(SettlementQuote {
+            commitment_id,
+            gross_value: commitment.current_value,
+            penalty,
+            protocol_fee: management_fee + settlement_fee,
+            net_payout,
+        })
+    }
+
+    /// Quote exiting a commitment early now: its value, the early-exit
+    /// penalty, the management fee and the net payout.
+    ///
+    /// Fails like `early_exit` (NotActive, CommitmentExpired, HasAllocations,
+    /// CommitmentFrozen, CommitmentNotFound) where exiting would fail.
+    pub fn quote_early_exit(
+        e: Env,
+        commitment_id: String,
+    ) -> Result<EarlyExitQuote, CommitmentError> {
+        let commitment =
+            read_commitment(&e, &commitment_id).ok_or(CommitmentError::CommitmentNotFound)?;
+        disputes::check_not_frozen(&e, &commitment_id)?;
+        Self::check_exitable(&e, &commitment)?;
+        let (fee_threshold_met, penalty, protocol_fee, net_payout) =
+            Self::early_exit_terms(&e, &commitment);
+        Ok(EarlyExitQuote {
+            commitment_id,
+            gross_value: commitment.current_value,
+            penalty,
+            protocol_fee,
+            net_payout,
+            fee_threshold_met,
+        })
+    }
+
+    /// Quote creating a commitment of `amount` of `asset` under `rules` for
+    /// `owner` right now: early-exit outcomes at 0/50/90% of the term, the
+    /// full-term management fee and payout, the payout after losing
+    /// `max_loss_percent`, and whether creation is currently allowed.
+    pub fn quote_commitment(
+        e: Env,
+        owner: Address,
+        amount: i128,
+        asset: Address,
+        rules: CommitmentRules,
+    ) -> CommitmentQuote {
+        let now = TimeUtils::now(&e);
+        let mut validation_error = Self::creation_error(amount, &rules)
+            .and_then(|_| asset_limits::check_amount(&e, &asset, amount))
+            .and_then(|_| owner_limits::check(&e, &owner))
+            .and_then(|_| deposit_limits::check(&e, &owner, &asset, amount))
+            .err()
+            .map_or(0, |err| err as u32);
+        let expires_at = TimeUtils::checked_expiration_bounded(
+            now,
+            rules.duration_days,
+            duration_limits::max_days(&e),
+        )
+        .unwrap_or_else(|_| {
+            if validation_error == 0 {
+                validation_error = CommitmentError::DurationTooLong as u32;
+            }
+            now
+        });
+        let amount = amount.max(0);
+        let commitment = Commitment {
+            commitment_id: String::from_str(&e, ""),
+            owner: owner.clone(),
+            nft_token_id: 0,
+            rules: rules.clone(),
+            amount,
+            asset_address: asset.clone(),
+            created_at: now,
+            expires_at,
+            current_value: amount,
+            status: CommitmentStatus::Active,
+            is_virtual: false,
+        };
+        let fee_bps = fees::new_commitment_fee_bps(&e);
+        // A new commitment has earned no allocation fees yet: full penalty
+        let penalty_bps = PercentMath::percent_to_bps(rules.early_exit_penalty);
+
+        let term = expires_at - now;
+        let mut early_exit_samples = Vec::new(&e);
+        for elapsed_percent in [0u32, 50, 90] {
+            let exit_at = now + term * elapsed_percent as u64 / 100;
+            let (penalty, management_fee, payout) =
+                Self::early_exit_split(&commitment, penalty_bps, fee_bps, amount, exit_at);
+            early_exit_samples.push_back(ExitQuote {
+                elapsed_percent,
+                exit_at,
+                penalty,
+                management_fee,
+                payout,
+            });
+        }
+
+        // Settlement pays the current value less the fee accrued on it and
+        // the settlement fee on the rest
+        let full_term_management_fee =
+            fees::management_fee_at(&commitment, fee_bps, amount, expires_at);
+        let (_, worst_case_value) = PercentMath::split_amount(
+            amount,
+            PercentMath::percent_to_bps(rules.max_loss_percent.min(100)),
+        );
+        let worst_case_fee =
+            fees::management_fee_at(&commitment, fee_bps, worst_case_value, expires_at);
+        let full_term_payout = amount - full_term_management_fee;
+        let worst_case_payout = worst_case_value - worst_case_fee;
+
+        let asset_supported =
+            Self::is_asset_supported(e.clone(), asset.clone()) && !is_asset_quarantined(&e, &asset);
+        let within_rate_limit = rate_limits::would_allow(&e, &owner, &symbol_short!("create"));
+        let emergency_mode = EmergencyControl::is_emergency_mode(&e);
+        CommitmentQuote {
+            amount,
+            expires_at,
+            management_fee_bps: fee_bps,
+            early_exit_samples,
+            full_term_management_fee,
+            full_term_payout: full_term_payout
+                - fees::settlement_fee(&e, &commitment, full_term_payout),
+            worst_case_value,
+            worst_case_payout: worst_case_payout
+                - fees::settlement_fee(&e, &commitment, worst_case_payout),
+            asset_supported,
+            within_rate_limit,
+            emergency_mode,
+            validation_error,
+            can_create: validation_error == 0
+                && asset_supported
+                && within_rate_limit
+                && !emergency_mode,
+        }
+    }
+}
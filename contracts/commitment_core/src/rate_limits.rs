@@ -0,0 +1,72 @@
+//! Per-function call rate limits.
+//!
+//! `create` is limited per owner, `upd_val` per contract and `alloc` per
+//! target pool, through the shared `RateLimiter`. Functions are unlimited
+//! until the admin configures a window.
+
+use crate::{require_admin, CommitmentCoreContract, CommitmentCoreContractClient, CommitmentError};
+use shared_utils::RateLimiter;
+use soroban_sdk::{contractimpl, symbol_short, Address, Env, Symbol};
+
+/// Count a call against `function`'s rate limit for `address`; RateLimited
+/// once the window's calls are used up.
+pub(crate) fn check(e: &Env, address: &Address, function: &Symbol) -> Result<(), CommitmentError> {
+    if !RateLimiter::would_allow(e, address, function) {
+        return Err(CommitmentError::RateLimited);
+    }
+    RateLimiter::check(e, address, function);
+    Ok(())
+}
+
+/// Whether `address` has a call of `function` left in the current window.
+#[cfg(feature = "quotes")]
+pub(crate) fn would_allow(e: &Env, address: &Address, function: &Symbol) -> bool {
+    RateLimiter::would_allow(e, address, function)
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Configure rate limits for this contract's functions.
+    ///
+    /// `function` is `create` (per owner), `upd_val` (per contract) or
+    /// `alloc` (per target pool). `max_calls` of 0 removes the limit; functions
+    /// are unlimited until configured. This function is restricted to the
+    /// contract admin.
+    pub fn set_rate_limit(
+        e: Env,
+        caller: Address,
+        function: Symbol,
+        window_seconds: u64,
+        max_calls: u32,
+    ) {
+        require_admin(&e, &caller);
+        if max_calls == 0 {
+            RateLimiter::clear_limit(&e, &function);
+        } else {
+            RateLimiter::set_limit(&e, &function, window_seconds, max_calls);
+        }
+        e.events().publish(
+            (symbol_short!("RateLimit"), function),
+            (window_seconds, max_calls, e.ledger().timestamp()),
+        );
+    }
+
+    /// Get a function's `(window_seconds, max_calls)`; `(0, 0)` when unlimited
+    pub fn get_rate_limit(e: Env, function: Symbol) -> (u64, u32) {
+        RateLimiter::get_limit(&e, &function).unwrap_or((0, 0))
+    }
+
+    /// Calls `address` has left of `function` in the current window
+    /// (`u32::MAX` when unlimited or exempt)
+    pub fn get_remaining_calls(e: Env, address: Address, function: Symbol) -> u32 {
+        RateLimiter::remaining_calls(&e, &address, &function)
+    }
+
+    /// Set or clear rate limit exemption for an address.
+    ///
+    /// This function is restricted to the contract admin.
+    pub fn set_rate_limit_exempt(e: Env, caller: Address, address: Address, exempt: bool) {
+        require_admin(&e, &caller);
+        RateLimiter::set_exempt(&e, &address, exempt);
+    }
+}
@@ -0,0 +1,114 @@
+//! Settlement receipts.
+//!
+//! Each terminal transition of a non-virtual commitment (settlement, early
+//! exit, emergency settlement, cancellation or liquidation) writes one
+//! receipt with the payout breakdown, indexed by owner.
+
+use crate::{
+    storage_layout, ttl, Commitment, CommitmentCoreContract, CommitmentCoreContractClient,
+    ReceiptKind,
+};
+use soroban_sdk::{contractimpl, contracttype, Address, Env, String, Vec};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum ReceiptDataKey {
+    SettlementReceipt(String), // commitment_id -> SettlementReceipt (write-once)
+    OwnerReceipts(Address),    // owner -> Vec<commitment_id> with receipts, oldest first
+}
+
+/// Payout details of a commitment's terminal transition, written once.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SettlementReceipt {
+    pub commitment_id: String,
+    pub owner: Address,
+    pub asset: Address,
+    pub kind: ReceiptKind,
+    pub gross_amount: i128,   // value at the transition
+    pub penalty: i128,        // early-exit penalty (or violation fee) retained
+    pub management_fee: i128, // management fee retained
+    pub settlement_fee: i128, // settlement fee credited to the fee pool
+    pub net_amount: i128,     // paid (or owed as a claim) to the owner
+    pub ledger: u32,
+    pub timestamp: u64,
+}
+
+/// Record the receipt for a commitment's terminal transition. The first
+/// receipt is kept; later transitions of the same commitment are ignored.
+pub(crate) fn record(
+    e: &Env,
+    commitment: &Commitment,
+    kind: ReceiptKind,
+    gross_amount: i128,
+    penalty: i128,
+    management_fee: i128,
+    settlement_fee: i128,
+) {
+    // Virtual commitments pay nothing out
+    let key = ReceiptDataKey::SettlementReceipt(commitment.commitment_id.clone());
+    if commitment.is_virtual || storage_layout::has(e, &key) {
+        return;
+    }
+    let receipt = SettlementReceipt {
+        commitment_id: commitment.commitment_id.clone(),
+        owner: commitment.owner.clone(),
+        asset: commitment.asset_address.clone(),
+        kind,
+        gross_amount,
+        penalty,
+        management_fee,
+        settlement_fee,
+        net_amount: gross_amount - penalty - management_fee - settlement_fee,
+        ledger: e.ledger().sequence(),
+        timestamp: e.ledger().timestamp(),
+    };
+    storage_layout::set(e, &key, &receipt);
+
+    let index_key = ReceiptDataKey::OwnerReceipts(commitment.owner.clone());
+    let mut ids = storage_layout::get::<_, Vec<String>>(e, &index_key).unwrap_or(Vec::new(e));
+    ids.push_back(commitment.commitment_id.clone());
+    storage_layout::set(e, &index_key, &ids);
+}
+
+/// Extend a commitment's receipt and its owner's receipt index (see `ttl::extend`).
+pub(crate) fn extend_ttl(e: &Env, commitment: &Commitment, extend_to: u32) {
+    ttl::extend_persistent(
+        e,
+        &ReceiptDataKey::SettlementReceipt(commitment.commitment_id.clone()),
+        extend_to,
+    );
+    ttl::extend_persistent(
+        e,
+        &ReceiptDataKey::OwnerReceipts(commitment.owner.clone()),
+        extend_to,
+    );
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Get the receipt written when a commitment settled, exited early or
+    /// was emergency-settled, if any
+    pub fn get_receipt(e: Env, commitment_id: String) -> Option<SettlementReceipt> {
+        storage_layout::get(&e, &ReceiptDataKey::SettlementReceipt(commitment_id))
+    }
+
+    /// Get a page of an owner's receipts, oldest first
+    pub fn get_owner_receipts(
+        e: Env,
+        owner: Address,
+        offset: u32,
+        limit: u32,
+    ) -> Vec<SettlementReceipt> {
+        let ids = storage_layout::get::<_, Vec<String>>(&e, &ReceiptDataKey::OwnerReceipts(owner))
+            .unwrap_or(Vec::new(&e));
+        let end = offset.saturating_add(limit).min(ids.len());
+        let mut receipts = Vec::new(&e);
+        for i in offset..end {
+            if let Some(receipt) = Self::get_receipt(e.clone(), ids.get_unchecked(i)) {
+                receipts.push_back(receipt);
+            }
+        }
+        receipts
+    }
+}
@@ -0,0 +1,17 @@
+//! No-op receipt helpers used when the `receipts` feature is compiled out.
+
+use crate::{Commitment, ReceiptKind};
+use soroban_sdk::Env;
+
+pub(crate) fn record(
+    _e: &Env,
+    _commitment: &Commitment,
+    _kind: ReceiptKind,
+    _gross_amount: i128,
+    _penalty: i128,
+    _management_fee: i128,
+    _settlement_fee: i128,
+) {
+}
+
+pub(crate) fn extend_ttl(_e: &Env, _commitment: &Commitment, _extend_to: u32) {}
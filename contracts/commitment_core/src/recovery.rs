@@ -0,0 +1,312 @@
+//! Admin recovery tools.
+//!
+//! Entry points for repairing state after an incident: rebuilding an
+//! owner's indexes, force-settling or rewriting a commitment while
+//! emergency mode is on, voiding a commitment created in error and
+//! recomputing TVL.
+
+use crate::{
+    adjust_active_commitments, adjust_tvl, allocations, clear_value_update_state, collections,
+    commitment_index, commitment_nft_contract, commitments_created, deposit_limits, disputes,
+    emergency_release, exit_requests, expiry_index, fail, fees, freezes, idempotency, insurance,
+    labels, owner_index_remove, ownership, publish_event, read_commitment, read_owner_index,
+    require_admin, require_no_reentrancy, set_commitment, set_reentrancy_guard, statement, stats,
+    storage_layout, tags, track_asset, value_log, write_owner_index, CommitmentCoreContract,
+    CommitmentCoreContractClient, CommitmentError, CommitmentList, CommitmentNftClient,
+    CommitmentStatus, DataKey, LedgerEntryKind, OwnerCommitmentRef, MAX_SCAN_PAGE,
+};
+use shared_utils::{EmergencyControl, SafeIndex};
+use soroban_sdk::{contractimpl, symbol_short, token, Address, Env, Map, String, Vec};
+
+/// Seconds after creation during which the admin may void a commitment
+pub const VOID_WINDOW_SECONDS: u64 = 86400;
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Rebuild an owner's commitment listing and sorted index (admin only).
+    ///
+    /// Drops duplicate entries and ids that no longer belong to the owner,
+    /// then indexes any of the owner's commitments among ids
+    /// `c_{offset}..c_{offset + limit}` (at most `MAX_SCAN_PAGE`) that are
+    /// missing, under fresh sequence numbers; callers page through
+    /// `commitments_created`. Returns the number of entries removed or added.
+    pub fn repair_indexes(e: Env, caller: Address, owner: Address, offset: u64, limit: u64) -> u32 {
+        require_admin(&e, &caller);
+        let owned = |id: &String| read_commitment(&e, id).is_some_and(|c| c.owner == owner);
+
+        let (index, mut next_seq) = read_owner_index(&e, &owner);
+        let mut ids: Vec<String> = Vec::new(&e);
+        let mut repaired = Vec::new(&e);
+        let mut changes = 0u32;
+        for entry in index.iter() {
+            let commitment_id = entry.commitment_id.clone();
+            if owned(&commitment_id) && SafeIndex::insert(&mut ids, commitment_id) {
+                repaired.push_back(entry);
+            } else {
+                changes += 1;
+            }
+        }
+        let end = offset
+            .saturating_add(limit.min(MAX_SCAN_PAGE))
+            .min(commitments_created(&e));
+        for i in offset..end {
+            let commitment_id = Self::generate_commitment_id(&e, i);
+            if !ids.contains(&commitment_id) && owned(&commitment_id) {
+                ids.push_back(commitment_id.clone());
+                repaired.push_back(OwnerCommitmentRef {
+                    seq: next_seq,
+                    commitment_id,
+                });
+                next_seq += 1;
+                changes += 1;
+            }
+        }
+        write_owner_index(&e, &owner, &repaired, next_seq);
+
+        // The listing follows the repaired index
+        let listing = Self::get_owner_commitments(e.clone(), owner.clone());
+        let (unique, duplicates) = SafeIndex::dedup(&e, &listing);
+        changes += duplicates;
+        for commitment_id in unique.iter() {
+            if !ids.contains(&commitment_id) {
+                changes += 1;
+            }
+        }
+        for commitment_id in ids.iter() {
+            if !unique.contains(&commitment_id) {
+                changes += 1;
+            }
+        }
+        commitment_index::replace(&e, &CommitmentList::Owner(owner.clone()), &ids);
+
+        e.events()
+            .publish((symbol_short!("IdxRepr"), owner), changes);
+        changes
+    }
+
+    /// Force settle a commitment in emergency (admin only)
+    /// This bypasses maturity checks and fees. Fails with HasAllocations
+    /// while any of its funds are out at a pool.
+    pub fn emergency_settle(e: Env, caller: Address, commitment_id: String) {
+        require_admin(&e, &caller);
+        EmergencyControl::require_emergency(&e);
+
+        let mut commitment = read_commitment(&e, &commitment_id)
+            .unwrap_or_else(|| fail(&e, CommitmentError::CommitmentNotFound, "emergency_settle"));
+        if let Err(err) = allocations::check_unallocated(&e, &commitment_id) {
+            fail(&e, err, "emergency_settle");
+        }
+        let settlement_amount = emergency_release(&e, &mut commitment);
+
+        publish_event(
+            &e,
+            commitment.is_virtual,
+            (symbol_short!("EmgSettl"), commitment_id),
+            (settlement_amount, e.ledger().timestamp()),
+        );
+    }
+
+    /// Change commitment parameters in emergency (admin only)
+    /// This allows fixing stuck commitments or adjusting state during recovery.
+    pub fn emergency_update_commitment(
+        e: Env,
+        caller: Address,
+        commitment_id: String,
+        new_value: i128,
+        new_status: CommitmentStatus,
+        new_expires_at: u64,
+    ) {
+        require_admin(&e, &caller);
+        EmergencyControl::require_emergency(&e);
+
+        let mut commitment = read_commitment(&e, &commitment_id).unwrap_or_else(|| {
+            fail(
+                &e,
+                CommitmentError::CommitmentNotFound,
+                "emergency_update_commitment",
+            )
+        });
+
+        let active = CommitmentStatus::Active;
+        match (commitment.status == active, new_status == active) {
+            (true, false) => adjust_active_commitments(&e, -1),
+            (false, true) => adjust_active_commitments(&e, 1),
+            _ => {}
+        }
+        let value_change = new_value - commitment.current_value;
+        commitment.current_value = new_value;
+        commitment.status = new_status;
+        if new_expires_at != commitment.expires_at {
+            expiry_index::remove(&e, &commitment_id, commitment.expires_at);
+            expiry_index::insert(&e, &commitment_id, new_expires_at);
+            commitment.expires_at = new_expires_at;
+        }
+
+        set_commitment(&e, &commitment);
+        statement::ledger_entry(&e, &commitment, LedgerEntryKind::Adjusted, value_change);
+        if commitment.status != CommitmentStatus::Active {
+            tags::clear(&e, &commitment);
+            clear_value_update_state(&e, &commitment_id);
+            exit_requests::clear(&e, &commitment_id);
+        }
+
+        publish_event(
+            &e,
+            commitment.is_virtual,
+            (symbol_short!("EmgUpd"), commitment_id),
+            (e.ledger().timestamp(),),
+        );
+    }
+
+    /// Void a commitment created in error (admin only).
+    ///
+    /// Allowed only within `VOID_WINDOW_SECONDS` of creation and before any
+    /// allocation. Refunds the owner the full deposit, principal plus the
+    /// insurance contribution (as far as the fund still holds it), and takes
+    /// it back off the owner's deposit-cap window. Burns the NFT, removes the
+    /// commitment from every index, the token mapping and its idempotency
+    /// key, and decrements the counters. Unlike a user cancellation this does
+    /// not require the owner.
+    pub fn admin_void_commitment(e: Env, caller: Address, commitment_id: String, reason: String) {
+        require_admin(&e, &caller);
+        require_no_reentrancy(&e);
+        set_reentrancy_guard(&e, true);
+
+        let commitment = read_commitment(&e, &commitment_id).unwrap_or_else(|| {
+            set_reentrancy_guard(&e, false);
+            fail(
+                &e,
+                CommitmentError::CommitmentNotFound,
+                "admin_void_commitment",
+            )
+        });
+        if commitment.status != CommitmentStatus::Active {
+            set_reentrancy_guard(&e, false);
+            fail(&e, CommitmentError::NotActive, "admin_void_commitment");
+        }
+        if e.ledger().timestamp() > commitment.created_at.saturating_add(VOID_WINDOW_SECONDS) {
+            set_reentrancy_guard(&e, false);
+            fail(
+                &e,
+                CommitmentError::VoidWindowExpired,
+                "admin_void_commitment",
+            );
+        }
+        if let Err(err) = allocations::check_unallocated(&e, &commitment_id) {
+            set_reentrancy_guard(&e, false);
+            fail(&e, err, "admin_void_commitment");
+        }
+        let nft_contract = commitment_nft_contract(&e, &commitment_id);
+
+        // EFFECTS: remove the commitment from every index
+        tags::clear(&e, &commitment);
+        owner_index_remove(&e, &commitment.owner, &commitment_id);
+        let owner_list = CommitmentList::Owner(commitment.owner.clone());
+        commitment_index::remove(&e, &owner_list, &commitment_id);
+        commitment_index::remove(&e, &CommitmentList::All, &commitment_id);
+        expiry_index::remove(&e, &commitment_id, commitment.expires_at);
+        storage_layout::remove(&e, &DataKey::Commitment(commitment_id.clone()));
+        stats::record(&e, Some(&commitment), None);
+        storage_layout::remove(&e, &DataKey::StatusLight(commitment_id.clone()));
+        collections::clear(&e, &commitment_id);
+        storage_layout::remove(&e, &DataKey::CommitmentNftContract(commitment_id.clone()));
+        fees::clear_commitment_fees(&e, &commitment_id);
+        labels::clear(&e, &commitment_id);
+        idempotency::clear(&e, &commitment.owner, &commitment_id);
+        disputes::clear(&e, &commitment_id);
+        statement::clear_ledger(&e, &commitment_id);
+        value_log::clear(&e, &commitment_id);
+        freezes::clear(&e, &commitment_id);
+        clear_value_update_state(&e, &commitment_id);
+        exit_requests::clear(&e, &commitment_id);
+
+        let total = Self::get_total_commitments(e.clone());
+        e.storage()
+            .instance()
+            .set(&DataKey::TotalCommitments, &total.saturating_sub(1));
+        adjust_active_commitments(&e, -1);
+
+        // Virtual commitments hold no funds and have no NFT
+        if !commitment.is_virtual {
+            adjust_tvl(&e, &commitment.asset_address, -commitment.amount);
+            let contribution =
+                insurance::refund_contribution(&e, &commitment.asset_address, &commitment_id);
+            let refund = commitment.amount + contribution;
+            deposit_limits::release(&e, &commitment.owner, &commitment.asset_address, refund);
+            if let Some(nft_contract) = &nft_contract {
+                ownership::clear_token(&e, nft_contract, commitment.nft_token_id);
+            }
+
+            // INTERACTIONS: refund the deposit and burn the NFT
+            let token_client = token::Client::new(&e, &commitment.asset_address);
+            token_client.transfer(&e.current_contract_address(), &commitment.owner, &refund);
+            if let Some(nft_contract) = nft_contract {
+                CommitmentNftClient::new(&e, &nft_contract).burn(&commitment.nft_token_id);
+            }
+        }
+
+        set_reentrancy_guard(&e, false);
+
+        publish_event(
+            &e,
+            commitment.is_virtual,
+            (symbol_short!("Voided"), commitment_id, commitment.owner),
+            (commitment.amount, reason, e.ledger().timestamp()),
+        );
+    }
+
+    /// Recompute total and per-asset TVL from `commitment_ids` (admin only).
+    ///
+    /// For recovery: the list must name every open commitment, since TVL is
+    /// rebuilt from the active, pending and violated non-virtual ones listed;
+    /// these still hold principal that settlement or cancellation releases. Duplicates and
+    /// unknown ids are ignored; tracked assets with no listed commitment
+    /// drop to zero. Returns the new total.
+    pub fn reconcile_tvl(e: Env, caller: Address, commitment_ids: Vec<String>) -> i128 {
+        require_admin(&e, &caller);
+        let (commitment_ids, _) = SafeIndex::dedup(&e, &commitment_ids);
+        let mut by_asset: Map<Address, i128> = Map::new(&e);
+        let mut total = 0i128;
+        for commitment_id in commitment_ids.iter() {
+            let Some(commitment) = read_commitment(&e, &commitment_id) else {
+                continue;
+            };
+            let open = matches!(
+                commitment.status,
+                CommitmentStatus::Active | CommitmentStatus::Pending | CommitmentStatus::Violated
+            );
+            if !open || commitment.is_virtual {
+                continue;
+            }
+            let asset_tvl = by_asset.get(commitment.asset_address.clone()).unwrap_or(0);
+            by_asset.set(
+                commitment.asset_address,
+                asset_tvl.saturating_add(commitment.amount),
+            );
+            total = total.saturating_add(commitment.amount);
+        }
+
+        let previous = Self::get_total_value_locked(e.clone());
+        for asset in Self::get_tracked_assets(e.clone()).iter() {
+            if !by_asset.contains_key(asset.clone()) {
+                storage_layout::set(&e, &DataKey::TotalValueLockedByAsset(asset), &0i128);
+            }
+        }
+        for (asset, asset_tvl) in by_asset.iter() {
+            storage_layout::set(
+                &e,
+                &DataKey::TotalValueLockedByAsset(asset.clone()),
+                &asset_tvl,
+            );
+            track_asset(&e, &asset);
+        }
+        e.storage()
+            .instance()
+            .set(&DataKey::TotalValueLocked, &total);
+        e.events().publish(
+            (symbol_short!("TvlRecon"),),
+            (previous, total, e.ledger().timestamp()),
+        );
+        total
+    }
+}
@@ -0,0 +1,128 @@
+//! Auto-renewing commitments.
+//!
+//! With auto-renew on, `settle` on an expired, non-violated commitment
+//! starts another full term instead of paying out: `created_at` and
+//! `expires_at` are reset, the NFT is kept and its expiry moved, and the
+//! principal stays locked. The management fee accrued over the finished term
+//! is still charged, as settlement would have. Renewal re-validates the
+//! rules under the current protocol limits and falls back to a normal
+//! settlement if they no longer pass.
+
+use crate::{
+    adjust_tvl, commitment_nft_contract, duration_limits, expiry_index, fail, fees, operators,
+    publish_event, read_commitment, set_commitment, statement, storage_layout, ttl, Commitment,
+    CommitmentCoreContract, CommitmentCoreContractClient, CommitmentError, CommitmentStatus,
+    LedgerEntryKind,
+};
+use commitlabs_interfaces::nft::CommitmentNftClient;
+use shared_utils::TimeUtils;
+use soroban_sdk::{contractimpl, contracttype, symbol_short, Address, Env, String};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum RenewalDataKey {
+    AutoRenew(String), // commitment_id -> bool
+}
+
+/// Extend a commitment's auto-renew entry (see `ttl::extend`).
+pub(crate) fn extend_ttl(e: &Env, commitment_id: &String, extend_to: u32) {
+    ttl::extend_persistent(
+        e,
+        &RenewalDataKey::AutoRenew(commitment_id.clone()),
+        extend_to,
+    );
+}
+
+/// New expiry for an expired commitment with auto-renew on, or `None` if
+/// it is off or the rules no longer validate. Changes nothing.
+pub(crate) fn renewal_expiry(e: &Env, commitment: &Commitment) -> Option<u64> {
+    if !CommitmentCoreContract::is_auto_renew(e.clone(), commitment.commitment_id.clone()) {
+        return None;
+    }
+    CommitmentCoreContract::validate_rules(&commitment.rules).ok()?;
+    TimeUtils::checked_expiration_bounded(
+        TimeUtils::now(e),
+        commitment.rules.duration_days,
+        duration_limits::max_days(e),
+    )
+    .ok()
+}
+
+/// Start another term for a commitment ending at `expires_at` (see
+/// `renewal_expiry`).
+pub(crate) fn renew(e: &Env, commitment: &mut Commitment, expires_at: u64) {
+    let id = commitment.commitment_id.clone();
+    let now = TimeUtils::now(e);
+
+    // The finished term's management fee leaves the principal (and TVL)
+    let fee = fees::accrued_management_fee(e, commitment, commitment.current_value);
+    if fee > 0 && !commitment.is_virtual {
+        commitment.amount -= fee;
+        commitment.current_value -= fee;
+        adjust_tvl(e, &commitment.asset_address, -fee);
+        fees::add_protocol_fees(e, &commitment.asset_address, fee);
+        statement::ledger_entry(e, commitment, LedgerEntryKind::ManagementFee, -fee);
+    }
+
+    let old_expires_at = commitment.expires_at;
+    commitment.created_at = now;
+    commitment.expires_at = expires_at;
+    set_commitment(e, commitment);
+    expiry_index::remove(e, &id, old_expires_at);
+    expiry_index::insert(e, &id, expires_at);
+    ttl::extend(e, commitment, ttl::ledgers_until(e, expires_at));
+
+    // Keep the NFT's expiry in step (non-fatal, like the settlement sync)
+    if let Some(nft_contract) = commitment_nft_contract(e, &id) {
+        let _ = CommitmentNftClient::new(e, &nft_contract).try_extend(
+            &commitment.nft_token_id,
+            &commitment.rules.duration_days,
+            &expires_at,
+        );
+    }
+
+    publish_event(
+        e,
+        commitment.is_virtual,
+        (symbol_short!("Renewed"), id),
+        (now, expires_at, fee, e.ledger().timestamp()),
+    );
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Turn auto-renew on or off for an active commitment (owner or
+    /// operator).
+    ///
+    /// Must be called before expiry; afterwards fails with
+    /// CommitmentExpired. Emits `AutoRenew`.
+    pub fn set_auto_renew(e: Env, caller: Address, commitment_id: String, enabled: bool) {
+        caller.require_auth();
+        let commitment = read_commitment(&e, &commitment_id)
+            .unwrap_or_else(|| fail(&e, CommitmentError::CommitmentNotFound, "set_auto_renew"));
+        if !operators::is_owner_or_operator(&e, &commitment, &caller) {
+            fail(&e, CommitmentError::Unauthorized, "set_auto_renew");
+        }
+        if commitment.status != CommitmentStatus::Active {
+            fail(&e, CommitmentError::NotActive, "set_auto_renew");
+        }
+        if TimeUtils::now(&e) >= commitment.expires_at {
+            fail(&e, CommitmentError::CommitmentExpired, "set_auto_renew");
+        }
+        let key = RenewalDataKey::AutoRenew(commitment_id.clone());
+        if enabled {
+            storage_layout::set(&e, &key, &true);
+        } else {
+            storage_layout::remove(&e, &key);
+        }
+        e.events().publish(
+            (symbol_short!("AutoRenew"), commitment_id),
+            (enabled, e.ledger().timestamp()),
+        );
+    }
+
+    /// Whether the commitment renews instead of paying out at settlement
+    pub fn is_auto_renew(e: Env, commitment_id: String) -> bool {
+        storage_layout::has(&e, &RenewalDataKey::AutoRenew(commitment_id))
+    }
+}
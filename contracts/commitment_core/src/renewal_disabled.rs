@@ -0,0 +1,12 @@
+//! No-op auto-renew helpers used when the `renewal` feature is compiled out.
+
+use crate::Commitment;
+use soroban_sdk::{Env, String};
+
+pub(crate) fn renewal_expiry(_e: &Env, _commitment: &Commitment) -> Option<u64> {
+    None
+}
+
+pub(crate) fn renew(_e: &Env, _commitment: &mut Commitment, _expires_at: u64) {}
+
+pub(crate) fn extend_ttl(_e: &Env, _commitment_id: &String, _extend_to: u32) {}
@@ -0,0 +1,76 @@
+//! Commitments that start at a future time.
+//!
+//! `create_commitment_scheduled` takes the funds and mints the NFT now but
+//! leaves the commitment `Pending`, with its term running from `start_at`.
+//! Pending commitments cannot be valued, violated, settled or exited early;
+//! the owner may still cancel within the grace period. Once `start_at` has
+//! passed, anyone may `activate` them.
+
+use crate::{
+    fail, publish_event, read_commitment, set_commitment, CommitmentCoreContract,
+    CommitmentCoreContractClient, CommitmentError, CommitmentRules, CommitmentStatus,
+};
+use soroban_sdk::{contractimpl, symbol_short, Address, Env, String};
+
+/// How far ahead a scheduled commitment may start: 30 days
+pub const MAX_START_DELAY_SECONDS: u64 = 30 * 24 * 60 * 60;
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Create a commitment now whose term starts at `start_at`.
+    ///
+    /// Funds are transferred and the NFT minted immediately; the commitment
+    /// is `Pending` with `expires_at = start_at + duration` until
+    /// `activate`. `start_at` must be in the future and at most
+    /// `MAX_START_DELAY_SECONDS` away (InvalidDuration otherwise).
+    pub fn create_commitment_scheduled(
+        e: Env,
+        owner: Address,
+        amount: i128,
+        asset_address: Address,
+        rules: CommitmentRules,
+        start_at: u64,
+    ) -> Result<String, CommitmentError> {
+        let now = e.ledger().timestamp();
+        if start_at <= now || start_at - now > MAX_START_DELAY_SECONDS {
+            return Err(CommitmentError::InvalidDuration);
+        }
+        Self::create_commitment_internal(
+            e,
+            owner,
+            amount,
+            asset_address,
+            rules,
+            false,
+            None,
+            Some(start_at),
+            None,
+        )
+    }
+
+    /// Start a pending commitment once its start time has passed
+    /// (permissionless).
+    ///
+    /// Fails with InvalidStatus unless the commitment is pending and with
+    /// TimelockNotElapsed before its start time. Emits `Activated`.
+    pub fn activate(e: Env, commitment_id: String) {
+        let mut commitment = read_commitment(&e, &commitment_id)
+            .unwrap_or_else(|| fail(&e, CommitmentError::CommitmentNotFound, "activate"));
+        if commitment.status != CommitmentStatus::Pending {
+            fail(&e, CommitmentError::InvalidStatus, "activate");
+        }
+        // Pending commitments cannot be extended, so the term is intact
+        let start_at = commitment.expires_at - u64::from(commitment.rules.duration_days) * 86400;
+        if e.ledger().timestamp() < start_at {
+            fail(&e, CommitmentError::TimelockNotElapsed, "activate");
+        }
+        commitment.status = CommitmentStatus::Active;
+        set_commitment(&e, &commitment);
+        publish_event(
+            &e,
+            commitment.is_virtual,
+            (symbol_short!("Activated"), commitment_id),
+            (start_at, commitment.expires_at, e.ledger().timestamp()),
+        );
+    }
+}
@@ -0,0 +1,158 @@
+//! Per-commitment statement of balance-affecting events.
+//!
+//! Flows record entries through `ledger_entry`; `get_statement` pages them
+//! newest first. Past `MAX_LEDGER_ENTRIES` the oldest entries are folded into
+//! an opening balance, so the statement still reconciles to the current
+//! value.
+
+use crate::{
+    storage_layout, ttl, Commitment, CommitmentCoreContract, CommitmentCoreContractClient,
+};
+use soroban_sdk::{contractimpl, contracttype, Env, String, Vec};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum StatementDataKey {
+    Ledger(String), // commitment_id -> CommitmentLedger
+}
+
+/// Statement entries retained per commitment before rolling up the oldest
+pub const MAX_LEDGER_ENTRIES: u32 = 64;
+
+/// Kind of balance-affecting event on a commitment's statement.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LedgerEntryKind {
+    Created,
+    Revalued,
+    Allocated,
+    Deallocated,
+    ManagementFee,
+    Penalty,
+    Payout,
+    Claimed,
+    Adjusted,
+    Migrated,
+    TopUp,
+    SettlementFee,
+}
+
+/// One statement line; `balance` is the running balance after `amount`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LedgerEntry {
+    pub seq: u32,
+    pub kind: LedgerEntryKind,
+    pub amount: i128, // signed change to the commitment's balance
+    pub balance: i128,
+    pub timestamp: u64,
+}
+
+/// A commitment's statement: the newest `MAX_LEDGER_ENTRIES` entries, with
+/// older ones rolled up into the opening balance.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommitmentLedger {
+    pub opening_balance: i128, // balance before the oldest retained entry
+    pub rolled_up: u32,        // entries folded into `opening_balance`
+    pub entries: Vec<LedgerEntry>,
+}
+
+/// Page of a statement, entries newest first.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommitmentStatement {
+    pub commitment_id: String,
+    pub opening_balance: i128,
+    pub rolled_up: u32,
+    pub total_entries: u32, // retained entries
+    pub entries: Vec<LedgerEntry>,
+}
+
+fn read_ledger(e: &Env, commitment_id: &String) -> CommitmentLedger {
+    storage_layout::get::<_, CommitmentLedger>(e, &StatementDataKey::Ledger(commitment_id.clone()))
+        .unwrap_or(CommitmentLedger {
+            opening_balance: 0,
+            rolled_up: 0,
+            entries: Vec::new(e),
+        })
+}
+
+/// Append a balance-affecting event to a commitment's statement.
+///
+/// Every flow that moves value in or out of a commitment goes through here,
+/// so the running balance stays equal to `current_value` while active and
+/// to any unclaimed payout afterwards. Virtual commitments hold no funds
+/// and get no statement.
+pub(crate) fn ledger_entry(e: &Env, commitment: &Commitment, kind: LedgerEntryKind, amount: i128) {
+    if commitment.is_virtual || amount == 0 {
+        return;
+    }
+    let mut ledger = read_ledger(e, &commitment.commitment_id);
+    let (seq, balance) = match ledger.entries.last() {
+        Some(last) => (last.seq + 1, last.balance),
+        None => (ledger.rolled_up, ledger.opening_balance),
+    };
+    if ledger.entries.len() >= MAX_LEDGER_ENTRIES {
+        let oldest = ledger.entries.pop_front_unchecked();
+        ledger.opening_balance = oldest.balance;
+        ledger.rolled_up += 1;
+    }
+    ledger.entries.push_back(LedgerEntry {
+        seq,
+        kind,
+        amount,
+        balance: balance + amount,
+        timestamp: e.ledger().timestamp(),
+    });
+    storage_layout::set(
+        e,
+        &StatementDataKey::Ledger(commitment.commitment_id.clone()),
+        &ledger,
+    );
+}
+
+/// Drop a commitment's statement (used when a commitment is voided).
+#[cfg(feature = "recovery")]
+pub(crate) fn clear_ledger(e: &Env, commitment_id: &String) {
+    storage_layout::remove(e, &StatementDataKey::Ledger(commitment_id.clone()));
+}
+
+/// Extend a commitment's statement entry (see `ttl::extend`).
+pub(crate) fn extend_ttl(e: &Env, commitment_id: &String, extend_to: u32) {
+    ttl::extend_persistent(
+        e,
+        &StatementDataKey::Ledger(commitment_id.clone()),
+        extend_to,
+    );
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Get a page of a commitment's statement, newest entry first.
+    ///
+    /// Each entry carries the running balance after it. While the commitment
+    /// is active the newest balance equals `current_value`; after a terminal
+    /// transition it equals any payout still waiting in `claim_settlement`.
+    pub fn get_statement(
+        e: Env,
+        commitment_id: String,
+        offset: u32,
+        limit: u32,
+    ) -> CommitmentStatement {
+        let ledger = read_ledger(&e, &commitment_id);
+        let total = ledger.entries.len();
+        let end = offset.saturating_add(limit).min(total);
+        let mut entries = Vec::new(&e);
+        for i in offset..end {
+            entries.push_back(ledger.entries.get_unchecked(total - 1 - i));
+        }
+        CommitmentStatement {
+            commitment_id,
+            opening_balance: ledger.opening_balance,
+            rolled_up: ledger.rolled_up,
+            total_entries: total,
+            entries,
+        }
+    }
+}
@@ -0,0 +1,37 @@
+//! No-op statement helpers used when the `statement` feature is compiled
+//! out. Flows still name the entry they would record.
+
+use crate::Commitment;
+use soroban_sdk::{Env, String};
+
+// Mirrors every kind, including those of flows compiled out with their own
+// features
+#[allow(dead_code)]
+#[derive(Clone, Copy)]
+pub(crate) enum LedgerEntryKind {
+    Created,
+    Revalued,
+    Allocated,
+    Deallocated,
+    ManagementFee,
+    Penalty,
+    Payout,
+    Claimed,
+    Adjusted,
+    Migrated,
+    TopUp,
+    SettlementFee,
+}
+
+pub(crate) fn ledger_entry(
+    _e: &Env,
+    _commitment: &Commitment,
+    _kind: LedgerEntryKind,
+    _amount: i128,
+) {
+}
+
+#[cfg(feature = "recovery")]
+pub(crate) fn clear_ledger(_e: &Env, _commitment_id: &String) {}
+
+pub(crate) fn extend_ttl(_e: &Env, _commitment_id: &String, _extend_to: u32) {}
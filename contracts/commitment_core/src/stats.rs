@@ -0,0 +1,178 @@
+//! Commitment statistics maintained on every write.
+//!
+//! `set_commitment` hands each write here together with the record it
+//! replaces, so status counts and per-owner totals follow every transition
+//! (creation, settlement, exits, violations, cancellation, migration and
+//! owner changes) without a storage scan. Commitments written before the
+//! counters existed are counted from their next write. Status counts and the
+//! penalty total are instance entries; per-owner totals are persistent.
+
+use crate::{
+    storage_layout, ttl, Commitment, CommitmentCoreContract, CommitmentCoreContractClient,
+    CommitmentStatus,
+};
+use soroban_sdk::{contractimpl, contracttype, Address, Env};
+
+/// Protocol-wide commitment statistics.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContractStats {
+    pub total_commitments: u64,
+    pub active_count: u64, // active or pending
+    pub settled_count: u64,
+    pub violated_count: u64,
+    pub early_exit_count: u64, // cancellations are penalty-free early exits
+    pub total_value_locked: i128,
+    pub total_penalties_collected: i128, // ever collected, across assets
+}
+
+/// One owner's commitment statistics.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OwnerStats {
+    pub commitment_count: u32,
+    pub active_count: u32,  // active or pending
+    pub total_locked: i128, // principal still held, like TVL
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum StatsDataKey {
+    StatusCount(CommitmentStatus), // u64 commitments currently in the status
+    PenaltiesCollected,            // i128 penalties ever collected
+    OwnerActive(Address),          // u32 owner's active or pending commitments
+    OwnerLocked(Address),          // i128 principal the owner has locked
+}
+
+/// Whether a commitment counts as active for the owner.
+fn is_active(commitment: &Commitment) -> bool {
+    matches!(
+        commitment.status,
+        CommitmentStatus::Active | CommitmentStatus::Pending
+    )
+}
+
+/// Principal a commitment keeps in TVL (released on settlement, exit,
+/// cancellation and migration release).
+fn locked(commitment: &Commitment) -> i128 {
+    let held = matches!(
+        commitment.status,
+        CommitmentStatus::Active
+            | CommitmentStatus::Pending
+            | CommitmentStatus::Violated
+            | CommitmentStatus::Migrating
+    );
+    if held && !commitment.is_virtual {
+        commitment.amount
+    } else {
+        0
+    }
+}
+
+fn status_count(e: &Env, status: CommitmentStatus) -> u64 {
+    e.storage()
+        .instance()
+        .get::<_, u64>(&StatsDataKey::StatusCount(status))
+        .unwrap_or(0)
+}
+
+fn adjust_status(e: &Env, status: CommitmentStatus, delta: i64) {
+    let count = status_count(e, status).saturating_add_signed(delta);
+    e.storage()
+        .instance()
+        .set(&StatsDataKey::StatusCount(status), &count);
+}
+
+fn adjust_owner(e: &Env, commitment: &Commitment, sign: i32) {
+    let owner = &commitment.owner;
+    let active = if is_active(commitment) { sign } else { 0 };
+    if active != 0 {
+        let key = StatsDataKey::OwnerActive(owner.clone());
+        let count = storage_layout::get::<_, u32>(e, &key).unwrap_or(0);
+        storage_layout::set(e, &key, &count.saturating_add_signed(active));
+    }
+    let amount = locked(commitment) * sign as i128;
+    if amount != 0 {
+        let key = StatsDataKey::OwnerLocked(owner.clone());
+        let total = storage_layout::get::<_, i128>(e, &key).unwrap_or(0);
+        storage_layout::set(e, &key, &total.saturating_add(amount).max(0));
+    }
+}
+
+/// Account for `current` replacing `previous` (either is `None` on
+/// creation or removal).
+pub(crate) fn record(e: &Env, previous: Option<&Commitment>, current: Option<&Commitment>) {
+    let status = |c: Option<&Commitment>| c.map(|c| c.status);
+    if status(previous) != status(current) {
+        if let Some(status) = status(previous) {
+            adjust_status(e, status, -1);
+        }
+        if let Some(status) = status(current) {
+            adjust_status(e, status, 1);
+        }
+    }
+    let share = |c: Option<&Commitment>| c.map(|c| (c.owner.clone(), is_active(c), locked(c)));
+    if share(previous) != share(current) {
+        if let Some(previous) = previous {
+            adjust_owner(e, previous, -1);
+        }
+        if let Some(current) = current {
+            adjust_owner(e, current, 1);
+        }
+    }
+}
+
+/// Owner's active or pending commitments.
+pub(crate) fn owner_active_count(e: &Env, owner: &Address) -> u32 {
+    storage_layout::get(e, &StatsDataKey::OwnerActive(owner.clone())).unwrap_or(0)
+}
+
+/// Add collected penalties to the running total.
+pub(crate) fn add_penalties(e: &Env, amount: i128) {
+    let total = e
+        .storage()
+        .instance()
+        .get::<_, i128>(&StatsDataKey::PenaltiesCollected)
+        .unwrap_or(0);
+    e.storage().instance().set(
+        &StatsDataKey::PenaltiesCollected,
+        &total.saturating_add(amount),
+    );
+}
+
+/// Extend an owner's totals (see `ttl::extend`).
+pub(crate) fn extend_ttl(e: &Env, owner: &Address, extend_to: u32) {
+    ttl::extend_persistent(e, &StatsDataKey::OwnerActive(owner.clone()), extend_to);
+    ttl::extend_persistent(e, &StatsDataKey::OwnerLocked(owner.clone()), extend_to);
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Protocol-wide counts by status, TVL and penalties collected
+    pub fn get_stats(e: Env) -> ContractStats {
+        ContractStats {
+            total_commitments: Self::get_total_commitments(e.clone()),
+            active_count: status_count(&e, CommitmentStatus::Active)
+                + status_count(&e, CommitmentStatus::Pending),
+            settled_count: status_count(&e, CommitmentStatus::Settled),
+            violated_count: status_count(&e, CommitmentStatus::Violated),
+            early_exit_count: status_count(&e, CommitmentStatus::EarlyExit)
+                + status_count(&e, CommitmentStatus::Cancelled),
+            total_value_locked: Self::get_total_value_locked(e.clone()),
+            total_penalties_collected: e
+                .storage()
+                .instance()
+                .get::<_, i128>(&StatsDataKey::PenaltiesCollected)
+                .unwrap_or(0),
+        }
+    }
+
+    /// An owner's commitment count, active count and locked principal
+    pub fn get_owner_stats(e: Env, owner: Address) -> OwnerStats {
+        OwnerStats {
+            commitment_count: Self::get_owner_commitment_count(e.clone(), owner.clone()),
+            active_count: owner_active_count(&e, &owner),
+            total_locked: storage_layout::get(&e, &StatsDataKey::OwnerLocked(owner)).unwrap_or(0),
+        }
+    }
+}
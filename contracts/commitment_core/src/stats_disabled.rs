@@ -0,0 +1,10 @@
+//! No-op statistics hooks used when the `stats` feature is compiled out.
+
+use crate::Commitment;
+use soroban_sdk::{Address, Env};
+
+pub(crate) fn record(_e: &Env, _previous: Option<&Commitment>, _current: Option<&Commitment>) {}
+
+pub(crate) fn add_penalties(_e: &Env, _amount: i128) {}
+
+pub(crate) fn extend_ttl(_e: &Env, _owner: &Address, _extend_to: u32) {}
@@ -0,0 +1,102 @@
+//! Persistent storage for per-commitment, per-owner and per-asset entries.
+//!
+//! Instance storage is a single ledger entry shared by the whole contract,
+//! so entries that grow with the number of commitments, owners or assets
+//! (commitments and their side records, owner indexes, per-asset balances)
+//! live in persistent storage, with their TTL bumped on
+//! every write. Global singletons stay in instance storage. Instances
+//! initialized before this layout (storage version 1) may still hold such
+//! an entry in instance storage: reads fall back to it and the first write
+//! moves it to persistent storage.
+
+use crate::{CommitmentCoreContract, CommitmentCoreContractClient};
+use soroban_sdk::{contractimpl, contracttype, Env, IntoVal, TryFromVal, Val};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum LayoutDataKey {
+    Version, // u32 storage layout version; absent on instances from before versioning
+}
+
+/// Storage layout written by this build
+pub const STORAGE_VERSION: u32 = 2;
+
+/// Ledgers per day at ~5s close time
+pub(crate) const LEDGERS_PER_DAY: u32 = 17_280;
+/// TTL given to a per-owner or per-asset entry on write
+const ENTRY_TTL: u32 = 120 * LEDGERS_PER_DAY;
+/// Remaining TTL below which a write extends the entry
+const ENTRY_TTL_THRESHOLD: u32 = 30 * LEDGERS_PER_DAY;
+
+/// Record the current layout version (called once, from `initialize`).
+pub(crate) fn init_version(e: &Env) {
+    e.storage()
+        .instance()
+        .set(&LayoutDataKey::Version, &STORAGE_VERSION);
+}
+
+/// Whether entries may still sit in instance storage.
+fn is_legacy(e: &Env) -> bool {
+    CommitmentCoreContract::get_storage_version(e.clone()) < STORAGE_VERSION
+}
+
+/// Read an entry, falling back to instance storage on legacy instances.
+pub(crate) fn get<K, V>(e: &Env, key: &K) -> Option<V>
+where
+    K: IntoVal<Env, Val>,
+    V: TryFromVal<Env, Val>,
+{
+    let value = e.storage().persistent().get(key);
+    if value.is_none() && is_legacy(e) {
+        return e.storage().instance().get(key);
+    }
+    value
+}
+
+/// Whether an entry exists, checking instance storage on legacy instances.
+pub(crate) fn has<K>(e: &Env, key: &K) -> bool
+where
+    K: IntoVal<Env, Val>,
+{
+    e.storage().persistent().has(key) || (is_legacy(e) && e.storage().instance().has(key))
+}
+
+/// Write an entry to persistent storage, moving it out of instance storage
+/// on legacy instances.
+pub(crate) fn set<K, V>(e: &Env, key: &K, value: &V)
+where
+    K: IntoVal<Env, Val>,
+    V: IntoVal<Env, Val>,
+{
+    e.storage().persistent().set(key, value);
+    e.storage()
+        .persistent()
+        .extend_ttl(key, ENTRY_TTL_THRESHOLD, ENTRY_TTL);
+    if is_legacy(e) {
+        e.storage().instance().remove(key);
+    }
+}
+
+/// Remove an entry from both locations.
+pub(crate) fn remove<K>(e: &Env, key: &K)
+where
+    K: IntoVal<Env, Val>,
+{
+    e.storage().persistent().remove(key);
+    if is_legacy(e) {
+        e.storage().instance().remove(key);
+    }
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Storage layout version of this instance (1 for instances initialized
+    /// before versioning, whose entries move to persistent storage as they
+    /// are written)
+    pub fn get_storage_version(e: Env) -> u32 {
+        e.storage()
+            .instance()
+            .get(&LayoutDataKey::Version)
+            .unwrap_or(1)
+    }
+}
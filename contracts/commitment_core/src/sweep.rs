@@ -0,0 +1,126 @@
+//! Recovery of tokens sent to the contract outside any commitment.
+//!
+//! The contract should hold exactly its accounted balance: per-asset TVL
+//! not deployed to pools, collected penalties, claimable protocol fees,
+//! the insurance fund and pending settlement claims. Anything above that
+//! (direct transfers, dust) may be swept by the admin; nothing below it can
+//! be. Pending claims are totalled per asset here as they are recorded and
+//! paid. `get_solvency_report` breaks the same sum down per asset next to
+//! the actual balance.
+
+use crate::{
+    fail, fees, insurance, require_admin, storage_layout, tvl, CommitmentCoreContract,
+    CommitmentCoreContractClient, CommitmentError, MAX_STATUS_BATCH,
+};
+use soroban_sdk::{contractimpl, contracttype, symbol_short, token, Address, Env, Vec};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum SweepDataKey {
+    PendingClaims(Address), // asset -> i128 total of unpaid settlement claims
+}
+
+/// One asset's actual balance against the ledgers that account for it.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SolvencyEntry {
+    pub asset: Address,
+    pub balance: i128, // tokens the contract holds
+    pub total_value_locked: i128,
+    pub deployed: i128, // part of the TVL out at pools, not held
+    pub penalties: i128,
+    pub protocol_fees: i128,
+    pub fee_pool: i128,
+    pub insurance_fund: i128,
+    pub pending_claims: i128,
+    pub surplus: i128, // balance less the accounted balance; negative is a deficit
+}
+
+/// Add `delta` to the unpaid settlement claims in `asset`.
+pub(crate) fn adjust_pending_claims(e: &Env, asset: &Address, delta: i128) {
+    let key = SweepDataKey::PendingClaims(asset.clone());
+    let pending = storage_layout::get::<_, i128>(e, &key).unwrap_or(0);
+    storage_layout::set(e, &key, &pending.saturating_add(delta).max(0));
+}
+
+fn pending_claims(e: &Env, asset: &Address) -> i128 {
+    storage_layout::get(e, &SweepDataKey::PendingClaims(asset.clone())).unwrap_or(0)
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Amount of `asset` the contract should hold: per-asset TVL less what
+    /// is deployed to pools, plus collected penalties, claimable protocol
+    /// fees, the settlement fee pool, the insurance fund and pending
+    /// settlement claims.
+    pub fn get_accounted_balance(e: Env, asset: Address) -> i128 {
+        Self::get_total_value_locked_by_asset(e.clone(), asset.clone()) - tvl::deployed(&e, &asset)
+            + Self::get_penalty_pool(e.clone(), asset.clone())
+            + fees::protocol_fees(&e, &asset)
+            + fees::fee_pool(&e, &asset)
+            + insurance::fund(&e, &asset)
+            + pending_claims(&e, &asset)
+    }
+
+    /// Per-asset solvency for up to `MAX_STATUS_BATCH` assets, in order.
+    ///
+    /// Each entry sets the contract's token balance against the ledgers
+    /// making up `get_accounted_balance`; `surplus` is their difference.
+    /// Any token contract can be reported, whitelisted or not. Fails with
+    /// BatchTooLarge above the limit.
+    pub fn get_solvency_report(e: Env, assets: Vec<Address>) -> Vec<SolvencyEntry> {
+        if assets.len() > MAX_STATUS_BATCH {
+            fail(&e, CommitmentError::BatchTooLarge, "get_solvency_report");
+        }
+        let mut report = Vec::new(&e);
+        for asset in assets.iter() {
+            let balance = token::Client::new(&e, &asset).balance(&e.current_contract_address());
+            report.push_back(SolvencyEntry {
+                balance,
+                total_value_locked: Self::get_total_value_locked_by_asset(e.clone(), asset.clone()),
+                deployed: tvl::deployed(&e, &asset),
+                penalties: Self::get_penalty_pool(e.clone(), asset.clone()),
+                protocol_fees: fees::protocol_fees(&e, &asset),
+                fee_pool: fees::fee_pool(&e, &asset),
+                insurance_fund: insurance::fund(&e, &asset),
+                pending_claims: pending_claims(&e, &asset),
+                surplus: balance - Self::get_accounted_balance(e.clone(), asset.clone()),
+                asset,
+            });
+        }
+        report
+    }
+
+    /// Transfer `amount` of `asset` held above the accounted balance to `to`
+    /// (admin only).
+    ///
+    /// Fails with InvalidAmount for a non-positive amount and
+    /// InsufficientBalance when it would dip into accounted funds.
+    pub fn sweep(e: Env, caller: Address, asset: Address, to: Address, amount: i128) {
+        require_admin(&e, &caller);
+        if amount <= 0 {
+            fail(&e, CommitmentError::InvalidAmount, "sweep");
+        }
+        let excess = Self::get_unaccounted_balance(e.clone(), asset.clone());
+        if amount > excess {
+            fail(&e, CommitmentError::InsufficientBalance, "sweep");
+        }
+        token::Client::new(&e, &asset).transfer(&e.current_contract_address(), &to, &amount);
+        e.events().publish(
+            (symbol_short!("Swept"), asset),
+            (to, amount, excess - amount),
+        );
+    }
+
+    /// Diagnostic: contract token balance minus the accounted balance
+    /// (per-asset TVL not deployed to pools, collected penalties, claimable
+    /// protocol fees and pending settlement claims).
+    ///
+    /// Zero means every unit held is accounted for; a positive value is
+    /// untracked dust, direct transfers or pool returns above the amount
+    /// deployed, and can be swept; a negative value is a shortfall.
+    pub fn get_unaccounted_balance(e: Env, asset: Address) -> i128 {
+        let balance = token::Client::new(&e, &asset).balance(&e.current_contract_address());
+        balance - Self::get_accounted_balance(e, asset)
+    }
+}
@@ -0,0 +1,178 @@
+//! Owner-defined tags on commitments.
+//!
+//! An owner may put up to `MAX_TAGS_PER_COMMITMENT` distinct tags on an
+//! active commitment and look their commitments up by tag. Tags follow the
+//! commitment to a new owner and are dropped when it leaves the active
+//! state.
+
+use crate::{
+    fail, read_commitment, storage_layout, ttl, Commitment, CommitmentCoreContract,
+    CommitmentCoreContractClient, CommitmentError, CommitmentStatus,
+};
+use soroban_sdk::{contractimpl, contracttype, symbol_short, Address, Env, String, Symbol, Vec};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum TagDataKey {
+    CommitmentTags(String),         // commitment_id -> Vec<Symbol>
+    OwnerTagIndex(Address, Symbol), // (owner, tag) -> Vec<commitment_id>
+}
+
+/// Maximum number of tags a single commitment may carry
+pub const MAX_TAGS_PER_COMMITMENT: u32 = 5;
+
+/// A commitment's tags, oldest first.
+pub(crate) fn read(e: &Env, commitment_id: &String) -> Vec<Symbol> {
+    storage_layout::get::<_, Vec<Symbol>>(e, &TagDataKey::CommitmentTags(commitment_id.clone()))
+        .unwrap_or(Vec::new(e))
+}
+
+/// Add `commitment_id` to the owner's index for `tag`.
+fn add_to_index(e: &Env, owner: &Address, tag: &Symbol, commitment_id: &String) {
+    let key = TagDataKey::OwnerTagIndex(owner.clone(), tag.clone());
+    let mut ids = storage_layout::get::<_, Vec<String>>(e, &key).unwrap_or(Vec::new(e));
+    ids.push_back(commitment_id.clone());
+    storage_layout::set(e, &key, &ids);
+}
+
+/// Remove `commitment_id` from the owner's index for `tag`, dropping empty indexes.
+fn remove_from_index(e: &Env, owner: &Address, tag: &Symbol, commitment_id: &String) {
+    let key = TagDataKey::OwnerTagIndex(owner.clone(), tag.clone());
+    let mut ids = storage_layout::get::<_, Vec<String>>(e, &key).unwrap_or(Vec::new(e));
+    if let Some(idx) = ids.first_index_of(commitment_id) {
+        ids.remove(idx);
+    }
+    if ids.is_empty() {
+        storage_layout::remove(e, &key);
+    } else {
+        storage_layout::set(e, &key, &ids);
+    }
+}
+
+/// Move a commitment's tags from `previous`'s indexes to `new_owner`'s.
+pub(crate) fn move_owner(e: &Env, commitment_id: &String, previous: &Address, new_owner: &Address) {
+    for tag in read(e, commitment_id).iter() {
+        remove_from_index(e, previous, &tag, commitment_id);
+        add_to_index(e, new_owner, &tag, commitment_id);
+    }
+}
+
+/// Drop all tags of a commitment that has left the active state.
+pub(crate) fn clear(e: &Env, commitment: &Commitment) {
+    let tags = read(e, &commitment.commitment_id);
+    for tag in tags.iter() {
+        remove_from_index(e, &commitment.owner, &tag, &commitment.commitment_id);
+    }
+    storage_layout::remove(
+        e,
+        &TagDataKey::CommitmentTags(commitment.commitment_id.clone()),
+    );
+}
+
+/// Extend a commitment's tag list and its owner's tag indexes (see
+/// `ttl::extend`).
+pub(crate) fn extend_ttl(e: &Env, commitment: &Commitment, extend_to: u32) {
+    let id = &commitment.commitment_id;
+    ttl::extend_persistent(e, &TagDataKey::CommitmentTags(id.clone()), extend_to);
+    for tag in read(e, id).iter() {
+        ttl::extend_persistent(
+            e,
+            &TagDataKey::OwnerTagIndex(commitment.owner.clone(), tag),
+            extend_to,
+        );
+    }
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Attach an organizational tag to an active commitment (owner only).
+    ///
+    /// Tags have no effect on commitment behavior. A commitment may carry at
+    /// most `MAX_TAGS_PER_COMMITMENT` distinct tags; they are dropped when the
+    /// commitment is settled or exited.
+    pub fn add_tag(e: Env, commitment_id: String, caller: Address, tag: Symbol) {
+        caller.require_auth();
+        let commitment = read_commitment(&e, &commitment_id)
+            .unwrap_or_else(|| fail(&e, CommitmentError::CommitmentNotFound, "add_tag"));
+        if commitment.owner != caller {
+            fail(&e, CommitmentError::Unauthorized, "add_tag");
+        }
+        if commitment.status != CommitmentStatus::Active {
+            fail(&e, CommitmentError::NotActive, "add_tag");
+        }
+        if tag == Symbol::new(&e, "") {
+            fail(&e, CommitmentError::InvalidTag, "add_tag");
+        }
+
+        let mut tags = read(&e, &commitment_id);
+        if tags.contains(&tag) {
+            fail(&e, CommitmentError::DuplicateTag, "add_tag");
+        }
+        if tags.len() >= MAX_TAGS_PER_COMMITMENT {
+            fail(&e, CommitmentError::TooManyTags, "add_tag");
+        }
+        tags.push_back(tag.clone());
+        storage_layout::set(
+            &e,
+            &TagDataKey::CommitmentTags(commitment_id.clone()),
+            &tags,
+        );
+
+        add_to_index(&e, &caller, &tag, &commitment_id);
+
+        e.events()
+            .publish((symbol_short!("TagAdd"), commitment_id, caller), tag);
+    }
+
+    /// Remove a tag from a commitment (owner only).
+    pub fn remove_tag(e: Env, commitment_id: String, caller: Address, tag: Symbol) {
+        caller.require_auth();
+        let commitment = read_commitment(&e, &commitment_id)
+            .unwrap_or_else(|| fail(&e, CommitmentError::CommitmentNotFound, "remove_tag"));
+        if commitment.owner != caller {
+            fail(&e, CommitmentError::Unauthorized, "remove_tag");
+        }
+
+        let mut tags = read(&e, &commitment_id);
+        let idx = tags
+            .first_index_of(&tag)
+            .unwrap_or_else(|| fail(&e, CommitmentError::TagNotFound, "remove_tag"));
+        tags.remove(idx);
+        if tags.is_empty() {
+            storage_layout::remove(&e, &TagDataKey::CommitmentTags(commitment_id.clone()));
+        } else {
+            storage_layout::set(
+                &e,
+                &TagDataKey::CommitmentTags(commitment_id.clone()),
+                &tags,
+            );
+        }
+        remove_from_index(&e, &caller, &tag, &commitment_id);
+
+        e.events()
+            .publish((symbol_short!("TagRem"), commitment_id, caller), tag);
+    }
+
+    /// Get the tags attached to a commitment.
+    pub fn get_commitment_tags(e: Env, commitment_id: String) -> Vec<Symbol> {
+        read(&e, &commitment_id)
+    }
+
+    /// Get a page of an owner's active commitment IDs carrying `tag`.
+    pub fn get_commitments_by_tag(
+        e: Env,
+        owner: Address,
+        tag: Symbol,
+        offset: u32,
+        limit: u32,
+    ) -> Vec<String> {
+        let ids = storage_layout::get::<_, Vec<String>>(&e, &TagDataKey::OwnerTagIndex(owner, tag))
+            .unwrap_or(Vec::new(&e));
+        let mut page = Vec::new(&e);
+        let end = offset.saturating_add(limit).min(ids.len());
+        for i in offset..end {
+            page.push_back(ids.get_unchecked(i));
+        }
+        page
+    }
+}
@@ -0,0 +1,16 @@
+//! No-op tag helpers used when the `tags` feature is compiled out.
+
+use crate::Commitment;
+use soroban_sdk::{Address, Env, String};
+
+pub(crate) fn move_owner(
+    _e: &Env,
+    _commitment_id: &String,
+    _previous: &Address,
+    _new_owner: &Address,
+) {
+}
+
+pub(crate) fn clear(_e: &Env, _commitment: &Commitment) {}
+
+pub(crate) fn extend_ttl(_e: &Env, _commitment: &Commitment, _extend_to: u32) {}
@@ -0,0 +1,146 @@
+//! Ledger TTLs for a commitment's entries.
+//!
+//! A commitment, its side entries (tags, value history, freezes, NFT
+//! routing, allocations, ...), its owner's indexes and its expiry bucket are
+//! persistent entries that expire on their own. Writes only bump them to
+//! the storage layout's default TTL, so if any lapsed before maturity the
+//! commitment could not be settled. Creation, renewal and extension extend
+//! them to cover the remaining term plus `TTL_GRACE_DAYS`, and anyone may
+//! extend them further with
+//! `extend_commitment_ttl`, paying for the bump themselves. Requests beyond
+//! the network's maximum TTL are capped at it.
+
+use crate::{
+    allocation_controls,
+    allocations::AllocationDataKey,
+    collections,
+    commitment_index::{CommitmentList, IndexDataKey, COMMITMENT_ID_CHUNK_SIZE},
+    commitment_nft_contract, disputes, exit_requests,
+    expiry_index::{ExpiryDataKey, EXPIRY_BUCKET_SECONDS},
+    fail, fees, freezes, insurance, labels, operators,
+    ownership::OwnershipDataKey,
+    pnl, read_commitment, receipts, renewal, statement, stats,
+    storage_layout::LEDGERS_PER_DAY,
+    tags, value_log, vesting, violations, Commitment, CommitmentCoreContract,
+    CommitmentCoreContractClient, CommitmentError, DataKey,
+};
+use soroban_sdk::{contractimpl, Env, IntoVal, String, Val};
+
+/// Days past expiry a new commitment's entries are kept alive for settlement
+pub const TTL_GRACE_DAYS: u32 = 30;
+
+/// Ledgers covering a `duration_days` term plus the settlement grace.
+pub(crate) fn ledgers_for_term(duration_days: u32) -> u32 {
+    duration_days
+        .saturating_add(TTL_GRACE_DAYS)
+        .saturating_mul(LEDGERS_PER_DAY)
+}
+
+/// Ledgers covering the time left until `expires_at` (in whole days,
+/// rounded up) plus the settlement grace.
+pub(crate) fn ledgers_until(e: &Env, expires_at: u64) -> u32 {
+    let remaining = expires_at.saturating_sub(e.ledger().timestamp());
+    ledgers_for_term(u32::try_from(remaining.div_ceil(86400)).unwrap_or(u32::MAX))
+}
+
+pub(crate) fn extend_persistent<K: IntoVal<Env, Val>>(e: &Env, key: &K, extend_to: u32) {
+    if e.storage().persistent().has(key) {
+        e.storage()
+            .persistent()
+            .extend_ttl(key, extend_to, extend_to);
+    }
+}
+
+/// Extend the instance and the persistent entries of `commitment` and its
+/// owner to live at least `extend_to` more ledgers (capped at the network
+/// maximum).
+pub(crate) fn extend(e: &Env, commitment: &Commitment, extend_to: u32) {
+    let extend_to = extend_to.min(e.storage().max_ttl());
+    e.storage().instance().extend_ttl(extend_to, extend_to);
+    extend_commitment_entries(e, commitment, extend_to);
+
+    let owner = &commitment.owner;
+    let list = CommitmentList::Owner(owner.clone());
+    let count_key = IndexDataKey::Count(list.clone());
+    let len = e
+        .storage()
+        .persistent()
+        .get::<_, u32>(&count_key)
+        .unwrap_or(0);
+    extend_persistent(e, &count_key, extend_to);
+    for chunk in 0..len.div_ceil(COMMITMENT_ID_CHUNK_SIZE) {
+        extend_persistent(e, &IndexDataKey::Chunk(list.clone(), chunk), extend_to);
+    }
+    extend_persistent(e, &DataKey::OwnerCommitmentIndex(owner.clone()), extend_to);
+    extend_persistent(e, &DataKey::OwnerCommitmentSeq(owner.clone()), extend_to);
+    stats::extend_ttl(e, owner, extend_to);
+    pnl::extend_ttl(e, owner, extend_to);
+    extend_persistent(
+        e,
+        &ExpiryDataKey::Bucket(commitment.expires_at / EXPIRY_BUCKET_SECONDS),
+        extend_to,
+    );
+}
+
+/// Entries keyed by the commitment id (absent ones are skipped).
+fn extend_commitment_entries(e: &Env, commitment: &Commitment, extend_to: u32) {
+    let id = &commitment.commitment_id;
+    for key in [
+        DataKey::Commitment(id.clone()),
+        DataKey::StatusLight(id.clone()),
+        DataKey::CommitmentNftContract(id.clone()),
+        DataKey::CommitmentAllocated(id.clone()),
+        DataKey::PendingNftSync(id.clone()),
+        DataKey::SettlementClaim(id.clone()),
+    ] {
+        extend_persistent(e, &key, extend_to);
+    }
+    for key in [
+        AllocationDataKey::Allocations(id.clone()),
+        AllocationDataKey::FeesGenerated(id.clone()),
+    ] {
+        extend_persistent(e, &key, extend_to);
+    }
+    if let Some(nft_contract) = commitment_nft_contract(e, id) {
+        extend_persistent(
+            e,
+            &OwnershipDataKey::CommitmentByToken(nft_contract, commitment.nft_token_id),
+            extend_to,
+        );
+    }
+    fees::extend_ttl(e, id, extend_to);
+    insurance::extend_ttl(e, id, extend_to);
+    labels::extend_ttl(e, id, extend_to);
+    statement::extend_ttl(e, id, extend_to);
+    collections::extend_ttl(e, id, extend_to);
+    disputes::extend_ttl(e, id, extend_to);
+    freezes::extend_ttl(e, id, extend_to);
+    exit_requests::extend_ttl(e, id, extend_to);
+    operators::extend_ttl(e, id, extend_to);
+    renewal::extend_ttl(e, id, extend_to);
+    tags::extend_ttl(e, commitment, extend_to);
+    value_log::extend_ttl(e, id, extend_to);
+    receipts::extend_ttl(e, commitment, extend_to);
+    violations::extend_ttl(e, id, extend_to);
+    allocation_controls::extend_ttl(e, id, extend_to);
+    vesting::extend_ttl(e, id, extend_to);
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Keep a commitment's entries alive for at least `extend_to` more
+    /// ledgers (capped at the network maximum).
+    ///
+    /// Anyone may call this; the caller pays for the extension. Fails with
+    /// CommitmentNotFound for an unknown id.
+    pub fn extend_commitment_ttl(e: Env, commitment_id: String, extend_to: u32) {
+        let commitment = read_commitment(&e, &commitment_id).unwrap_or_else(|| {
+            fail(
+                &e,
+                CommitmentError::CommitmentNotFound,
+                "extend_commitment_ttl",
+            )
+        });
+        extend(&e, &commitment, extend_to);
+    }
+}
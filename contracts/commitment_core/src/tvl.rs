@@ -0,0 +1,38 @@
+//! Principal-based TVL.
+//!
+//! TVL counts the principal (`Commitment::amount`) of each open (active,
+//! pending or violated) non-virtual commitment: creation and migration import add it, and every
+//! path that closes a commitment removes exactly that amount again. Value
+//! updates and pool allocations leave TVL alone; the difference between what
+//! a commitment pays out and its principal is booked by `pnl` when it
+//! settles or exits. Funds out at pools are tracked in `Deployed` so the
+//! balance check can still account for them.
+
+use crate::{adjust_tvl, pnl, storage_layout, Commitment};
+use soroban_sdk::{contracttype, Address, Env};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum TvlDataKey {
+    Deployed(Address), // asset -> amount allocated to pools and not yet returned
+}
+
+/// Take a closing commitment's principal out of TVL and book `realized`
+/// (what it was worth on the way out) against it.
+pub(crate) fn release(e: &Env, commitment: &Commitment, realized: i128) {
+    adjust_tvl(e, &commitment.asset_address, -commitment.amount);
+    pnl::book_realized(e, commitment, realized);
+}
+
+/// Move the amount of `asset` deployed to pools by `delta`.
+pub(crate) fn adjust_deployed(e: &Env, asset: &Address, delta: i128) {
+    let key = TvlDataKey::Deployed(asset.clone());
+    let deployed = storage_layout::get::<_, i128>(e, &key).unwrap_or(0);
+    storage_layout::set(e, &key, &deployed.saturating_add(delta).max(0));
+}
+
+/// Amount of `asset` deployed to pools and not yet returned.
+#[cfg(feature = "sweep")]
+pub(crate) fn deployed(e: &Env, asset: &Address) -> i128 {
+    storage_layout::get(e, &TvlDataKey::Deployed(asset.clone())).unwrap_or(0)
+}
@@ -0,0 +1,81 @@
+//! Coarse on-chain history of total TVL.
+//!
+//! `snapshot_tvl` records `(timestamp, total TVL)` at most once every
+//! `TVL_SNAPSHOT_INTERVAL_SECONDS`, so the series holds about one point per
+//! day. Anyone may call it, and creation and settlement call it too, so the
+//! history stays fresh without a keeper. The newest `MAX_TVL_SNAPSHOTS` are
+//! kept.
+
+use crate::{CommitmentCoreContract, CommitmentCoreContractClient};
+use soroban_sdk::{contractimpl, contracttype, Env, Vec};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum TvlHistoryDataKey {
+    Snapshots, // Vec<TvlSnapshot>, oldest first
+}
+
+/// Snapshots retained before the oldest is evicted
+pub const MAX_TVL_SNAPSHOTS: u32 = 90;
+
+/// Minimum seconds between two snapshots
+pub const TVL_SNAPSHOT_INTERVAL_SECONDS: u64 = 24 * 60 * 60;
+
+/// Total TVL at one point in time.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TvlSnapshot {
+    pub timestamp: u64,
+    pub total_value_locked: i128,
+}
+
+fn read_snapshots(e: &Env) -> Vec<TvlSnapshot> {
+    e.storage()
+        .instance()
+        .get::<_, Vec<TvlSnapshot>>(&TvlHistoryDataKey::Snapshots)
+        .unwrap_or(Vec::new(e))
+}
+
+/// Record a snapshot if the interval has passed since the last one,
+/// dropping the oldest once the history is full. Returns whether one was
+/// recorded.
+pub(crate) fn maybe_snapshot(e: &Env) -> bool {
+    let now = e.ledger().timestamp();
+    let mut snapshots = read_snapshots(e);
+    if let Some(last) = snapshots.last() {
+        if now < last.timestamp.saturating_add(TVL_SNAPSHOT_INTERVAL_SECONDS) {
+            return false;
+        }
+    }
+    if snapshots.len() >= MAX_TVL_SNAPSHOTS {
+        snapshots.pop_front();
+    }
+    snapshots.push_back(TvlSnapshot {
+        timestamp: now,
+        total_value_locked: CommitmentCoreContract::get_total_value_locked(e.clone()),
+    });
+    e.storage()
+        .instance()
+        .set(&TvlHistoryDataKey::Snapshots, &snapshots);
+    true
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Record total TVL if `TVL_SNAPSHOT_INTERVAL_SECONDS` have passed since
+    /// the last snapshot (permissionless). Returns whether one was recorded.
+    pub fn snapshot_tvl(e: Env) -> bool {
+        maybe_snapshot(&e)
+    }
+
+    /// Up to `limit` retained TVL snapshots from position `start`, oldest
+    /// first.
+    pub fn get_tvl_history(e: Env, start: u32, limit: u32) -> Vec<TvlSnapshot> {
+        let snapshots = read_snapshots(&e);
+        let end = start.saturating_add(limit).min(snapshots.len());
+        if start >= end {
+            return Vec::new(&e);
+        }
+        snapshots.slice(start..end)
+    }
+}
@@ -0,0 +1,7 @@
+//! No-op TVL history helper used when the `analytics` feature is compiled out.
+
+use soroban_sdk::Env;
+
+pub(crate) fn maybe_snapshot(_e: &Env) -> bool {
+    false
+}
@@ -0,0 +1,314 @@
+//! Per-commitment value history.
+//!
+//! One entry is written at creation (or migration import) and one per
+//! accepted `update_value`, recording who reported the value, what it
+//! replaced and the principal it was measured against. The same entries feed
+//! TWA violation checks, which restate older values against the current
+//! principal so a top-up does not read as a gain or loss. An update inside
+//! the minimum interval under the coalescing policy overwrites the newest
+//! entry instead of appending. The newest `MAX_VALUE_HISTORY` entries are
+//! kept in persistent storage and survive settlement for post-mortem queries.
+
+use crate::{
+    fail, read_commitment, require_admin, storage_layout, ttl, Commitment, CommitmentCoreContract,
+    CommitmentCoreContractClient, CommitmentError, RiskProfile,
+};
+use soroban_sdk::{contractimpl, contracttype, symbol_short, Address, Env, String, Vec};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum ValueLogDataKey {
+    Updates(String),         // commitment_id -> Vec<ValueUpdate>, oldest first
+    TwaWindow(RiskProfile),  // commitment_type -> history entries averaged (0 = off)
+    MinUpdateInterval,       // u64 — seconds between accepted value updates (0 = off)
+    CoalesceEarlyUpdates,    // bool — fold early updates into the newest value history entry
+    LastValueUpdate(String), // commitment_id -> timestamp of the last accepted update
+}
+
+/// Value history entries retained per commitment before the oldest is evicted
+pub const MAX_VALUE_HISTORY: u32 = 50;
+
+/// One recorded commitment value.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ValueUpdate {
+    pub timestamp: u64,
+    /// Oracle or admin that reported the value; the owner for creation entries
+    pub caller: Address,
+    pub old_value: i128,
+    pub new_value: i128,
+    /// Commitment principal when the value was recorded
+    pub amount: i128,
+}
+
+pub(crate) fn read(e: &Env, commitment_id: &String) -> Vec<ValueUpdate> {
+    storage_layout::get(e, &ValueLogDataKey::Updates(commitment_id.clone())).unwrap_or(Vec::new(e))
+}
+
+fn write(e: &Env, commitment_id: &String, updates: &Vec<ValueUpdate>) {
+    storage_layout::set(e, &ValueLogDataKey::Updates(commitment_id.clone()), updates);
+}
+
+/// Append an entry, dropping the oldest once the log is full.
+pub(crate) fn record(
+    e: &Env,
+    commitment_id: &String,
+    caller: &Address,
+    old_value: i128,
+    new_value: i128,
+    amount: i128,
+) {
+    let mut updates = read(e, commitment_id);
+    if updates.len() >= MAX_VALUE_HISTORY {
+        updates.pop_front();
+    }
+    updates.push_back(ValueUpdate {
+        timestamp: e.ledger().timestamp(),
+        caller: caller.clone(),
+        old_value,
+        new_value,
+        amount,
+    });
+    write(e, commitment_id, &updates);
+}
+
+/// Overwrite the newest entry's value and caller, keeping its timestamp and
+/// the value it replaced. Appends if the log is empty.
+fn coalesce(
+    e: &Env,
+    commitment_id: &String,
+    caller: &Address,
+    old_value: i128,
+    new_value: i128,
+    amount: i128,
+) {
+    let mut updates = read(e, commitment_id);
+    match updates.pop_back() {
+        Some(last) => {
+            updates.push_back(ValueUpdate {
+                caller: caller.clone(),
+                new_value,
+                amount,
+                ..last
+            });
+            write(e, commitment_id, &updates);
+        }
+        None => record(e, commitment_id, caller, old_value, new_value, amount),
+    }
+}
+
+/// Drop a commitment's log (used when a commitment is voided).
+#[cfg(feature = "recovery")]
+pub(crate) fn clear(e: &Env, commitment_id: &String) {
+    storage_layout::remove(e, &ValueLogDataKey::Updates(commitment_id.clone()));
+}
+/// Whether an update to the commitment now falls inside the minimum update
+/// interval. Fails with UpdateTooFrequent unless early updates are
+/// coalesced.
+pub(crate) fn check_update_interval(e: &Env, commitment_id: &String) -> bool {
+    let min_interval = CommitmentCoreContract::get_min_update_interval(e.clone());
+    let too_early = min_interval > 0
+        && storage_layout::get::<_, u64>(
+            e,
+            &ValueLogDataKey::LastValueUpdate(commitment_id.clone()),
+        )
+        .is_some_and(|last| e.ledger().timestamp() < last.saturating_add(min_interval));
+    if too_early && !CommitmentCoreContract::is_coalescing_early_updates(e.clone()) {
+        fail(e, CommitmentError::UpdateTooFrequent, "update_value");
+    }
+    too_early
+}
+
+/// Log an accepted value update: coalesced into the newest entry when it
+/// came too early, otherwise appended and, while an interval is set,
+/// stamped as the last update.
+pub(crate) fn record_update(
+    e: &Env,
+    commitment_id: &String,
+    caller: &Address,
+    old_value: i128,
+    new_value: i128,
+    amount: i128,
+    too_early: bool,
+) {
+    if too_early {
+        coalesce(e, commitment_id, caller, old_value, new_value, amount);
+        return;
+    }
+    record(e, commitment_id, caller, old_value, new_value, amount);
+    if CommitmentCoreContract::get_min_update_interval(e.clone()) > 0 {
+        storage_layout::set(
+            e,
+            &ValueLogDataKey::LastValueUpdate(commitment_id.clone()),
+            &e.ledger().timestamp(),
+        );
+    }
+}
+
+/// Drop a commitment's last-update stamp (terminal transitions).
+pub(crate) fn clear_last_update(e: &Env, commitment_id: &String) {
+    storage_layout::remove(e, &ValueLogDataKey::LastValueUpdate(commitment_id.clone()));
+}
+
+/// Extend a commitment's log and last-update stamp (see `ttl::extend`).
+pub(crate) fn extend_ttl(e: &Env, commitment_id: &String, extend_to: u32) {
+    ttl::extend_persistent(
+        e,
+        &ValueLogDataKey::Updates(commitment_id.clone()),
+        extend_to,
+    );
+    ttl::extend_persistent(
+        e,
+        &ValueLogDataKey::LastValueUpdate(commitment_id.clone()),
+        extend_to,
+    );
+}
+
+/// Value used for violation checks.
+///
+/// When a TWA window is configured for the commitment type, this is the
+/// time-weighted average of the last `window` value history entries, each
+/// weighted by how long it was in effect (the newest until now) and restated
+/// against the current principal. Otherwise, or with no recorded history, it
+/// is the instantaneous `current_value`.
+pub(crate) fn effective_value(e: &Env, commitment: &Commitment) -> i128 {
+    let window = e
+        .storage()
+        .instance()
+        .get::<_, u32>(&ValueLogDataKey::TwaWindow(
+            commitment.rules.commitment_type,
+        ))
+        .unwrap_or(0);
+    let history = read(e, &commitment.commitment_id);
+    if window == 0 || history.is_empty() {
+        return commitment.current_value;
+    }
+
+    let len = history.len();
+    let start = len.saturating_sub(window);
+    let now = e.ledger().timestamp();
+    let mut weighted_sum: i128 = 0;
+    let mut total_weight: i128 = 0;
+    let mut plain_sum: i128 = 0;
+    for i in start..len {
+        let entry = history.get_unchecked(i);
+        let value = entry
+            .new_value
+            .saturating_add(commitment.amount.saturating_sub(entry.amount));
+        let until = if i + 1 < len {
+            history.get_unchecked(i + 1).timestamp
+        } else {
+            now
+        };
+        let weight = until.saturating_sub(entry.timestamp) as i128;
+        weighted_sum = weighted_sum.saturating_add(value.saturating_mul(weight));
+        total_weight += weight;
+        plain_sum = plain_sum.saturating_add(value);
+    }
+
+    if total_weight == 0 {
+        // All entries share a timestamp: fall back to a simple mean
+        plain_sum / (len - start) as i128
+    } else {
+        weighted_sum / total_weight
+    }
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Up to `limit` of a commitment's retained value history entries from
+    /// position `start`, oldest first.
+    pub fn get_value_history(
+        e: Env,
+        commitment_id: String,
+        start: u32,
+        limit: u32,
+    ) -> Vec<ValueUpdate> {
+        let updates = read(&e, &commitment_id);
+        let end = start.saturating_add(limit).min(updates.len());
+        if start >= end {
+            return Vec::new(&e);
+        }
+        updates.slice(start..end)
+    }
+
+    /// Number of value history entries retained for a commitment
+    pub fn get_value_history_count(e: Env, commitment_id: String) -> u32 {
+        read(&e, &commitment_id).len()
+    }
+
+    /// Configure the TWA window for a commitment type (admin only).
+    ///
+    /// With `window > 0`, violation checks for commitments of this type use
+    /// the time-weighted average of the last `window` value history entries
+    /// instead of the instantaneous value. `0` restores instantaneous checks.
+    pub fn set_twa_window(e: Env, caller: Address, commitment_type: RiskProfile, window: u32) {
+        require_admin(&e, &caller);
+        if window > MAX_VALUE_HISTORY {
+            fail(&e, CommitmentError::InvalidTwaWindow, "set_twa_window");
+        }
+        e.storage()
+            .instance()
+            .set(&ValueLogDataKey::TwaWindow(commitment_type), &window);
+        e.events()
+            .publish((symbol_short!("TwaWindow"), commitment_type), window);
+    }
+
+    /// Get the TWA window for a commitment type (0 = instantaneous)
+    pub fn get_twa_window(e: Env, commitment_type: RiskProfile) -> u32 {
+        e.storage()
+            .instance()
+            .get::<_, u32>(&ValueLogDataKey::TwaWindow(commitment_type))
+            .unwrap_or(0)
+    }
+
+    /// Get the value used for violation checks (TWA or instantaneous)
+    pub fn get_effective_value(e: Env, commitment_id: String) -> i128 {
+        let commitment = read_commitment(&e, &commitment_id).unwrap_or_else(|| {
+            fail(
+                &e,
+                CommitmentError::CommitmentNotFound,
+                "get_effective_value",
+            )
+        });
+        effective_value(&e, &commitment)
+    }
+
+    /// Set the minimum time between accepted value updates per commitment
+    /// (admin only). `0` disables the limit.
+    ///
+    /// Earlier updates fail with `UpdateTooFrequent`, or with `coalesce` set
+    /// replace the value of the newest value history entry instead.
+    pub fn set_min_update_interval(e: Env, caller: Address, seconds: u64, coalesce: bool) {
+        require_admin(&e, &caller);
+        e.storage()
+            .instance()
+            .set(&ValueLogDataKey::MinUpdateInterval, &seconds);
+        e.storage()
+            .instance()
+            .set(&ValueLogDataKey::CoalesceEarlyUpdates, &coalesce);
+        e.events()
+            .publish((symbol_short!("UpdIntvl"),), (seconds, coalesce));
+    }
+
+    /// Get the minimum seconds between accepted value updates (0 = no limit)
+    pub fn get_min_update_interval(e: Env) -> u64 {
+        e.storage()
+            .instance()
+            .get::<_, u64>(&ValueLogDataKey::MinUpdateInterval)
+            .unwrap_or(0)
+    }
+
+    /// Whether early value updates are coalesced rather than rejected
+    pub fn is_coalescing_early_updates(e: Env) -> bool {
+        e.storage()
+            .instance()
+            .get::<_, bool>(&ValueLogDataKey::CoalesceEarlyUpdates)
+            .unwrap_or(false)
+    }
+
+    /// Timestamp of the last accepted value update while an interval is set
+    pub fn get_last_value_update(e: Env, commitment_id: String) -> Option<u64> {
+        storage_layout::get(&e, &ValueLogDataKey::LastValueUpdate(commitment_id))
+    }
+}
@@ -0,0 +1,42 @@
+//! Value history helpers used when the `value_history` feature is compiled
+//! out: nothing is logged, updates are never rate limited and violation
+//! checks use the instantaneous value.
+
+use crate::Commitment;
+use soroban_sdk::{Address, Env, String};
+
+pub(crate) fn record(
+    _e: &Env,
+    _commitment_id: &String,
+    _caller: &Address,
+    _old_value: i128,
+    _new_value: i128,
+    _amount: i128,
+) {
+}
+
+pub(crate) fn check_update_interval(_e: &Env, _commitment_id: &String) -> bool {
+    false
+}
+
+pub(crate) fn record_update(
+    _e: &Env,
+    _commitment_id: &String,
+    _caller: &Address,
+    _old_value: i128,
+    _new_value: i128,
+    _amount: i128,
+    _too_early: bool,
+) {
+}
+
+#[cfg(feature = "recovery")]
+pub(crate) fn clear(_e: &Env, _commitment_id: &String) {}
+
+pub(crate) fn clear_last_update(_e: &Env, _commitment_id: &String) {}
+
+pub(crate) fn effective_value(_e: &Env, commitment: &Commitment) -> i128 {
+    commitment.current_value
+}
+
+pub(crate) fn extend_ttl(_e: &Env, _commitment_id: &String, _extend_to: u32) {}
@@ -0,0 +1,176 @@
+//! Vested settlement payouts.
+//!
+//! While the admin has set a vesting period, settling a commitment at
+//! maturity does not transfer the payout at once: it is released linearly
+//! to the owner over the period and withdrawn with `claim_vested`. Early
+//! exits and emergency paths still pay out immediately.
+
+use crate::{
+    disputes, fail, is_asset_quarantined, read_commitment, require_admin, require_no_reentrancy,
+    set_reentrancy_guard, statement, storage_layout, sweep, ttl, Commitment,
+    CommitmentCoreContract, CommitmentCoreContractClient, CommitmentError, LedgerEntryKind,
+};
+use soroban_sdk::{contractimpl, contracttype, symbol_short, token, Address, Env, String};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum VestingDataKey {
+    VestingPeriod,   // u64 seconds settlement payouts vest over; 0 or absent pays at once
+    Vesting(String), // commitment_id -> VestingSchedule until fully claimed
+}
+
+/// Longest vesting period the admin may set
+pub const MAX_VESTING_PERIOD_SECONDS: u64 = 4 * 365 * 86400;
+
+/// A settlement payout released linearly from `start` over `duration`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VestingSchedule {
+    pub beneficiary: Address,
+    pub asset: Address,
+    pub total: i128,
+    pub claimed: i128,
+    pub start: u64,
+    pub duration: u64,
+}
+
+/// Amount of `schedule` vested at `now`.
+fn vested(schedule: &VestingSchedule, now: u64) -> i128 {
+    let elapsed = now.saturating_sub(schedule.start).min(schedule.duration);
+    schedule.total * elapsed as i128 / schedule.duration as i128
+}
+
+/// Pay a settlement out to the commitment's owner, or put it on a vesting
+/// schedule while a vesting period is set. Returns whether the funds were
+/// transferred (see `pay_out`).
+pub(crate) fn pay_out(e: &Env, commitment: &Commitment, amount: i128) -> bool {
+    let duration = CommitmentCoreContract::get_vesting_period(e.clone());
+    if duration == 0 || amount <= 0 {
+        return crate::pay_out(
+            e,
+            &commitment.commitment_id,
+            &commitment.asset_address,
+            &commitment.owner,
+            amount,
+        );
+    }
+    let schedule = VestingSchedule {
+        beneficiary: commitment.owner.clone(),
+        asset: commitment.asset_address.clone(),
+        total: amount,
+        claimed: 0,
+        start: e.ledger().timestamp(),
+        duration,
+    };
+    storage_layout::set(
+        e,
+        &VestingDataKey::Vesting(commitment.commitment_id.clone()),
+        &schedule,
+    );
+    // Vesting funds stay reserved like claims until withdrawn
+    sweep::adjust_pending_claims(e, &commitment.asset_address, amount);
+    e.events().publish(
+        (
+            symbol_short!("Vesting"),
+            commitment.commitment_id.clone(),
+            commitment.owner.clone(),
+        ),
+        (commitment.asset_address.clone(), amount, duration),
+    );
+    false
+}
+
+/// Extend a commitment's vesting schedule (see `ttl::extend`).
+pub(crate) fn extend_ttl(e: &Env, commitment_id: &String, extend_to: u32) {
+    ttl::extend_persistent(
+        e,
+        &VestingDataKey::Vesting(commitment_id.clone()),
+        extend_to,
+    );
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Set the period settlement payouts vest over, in seconds; 0 pays them
+    /// out at once. Applies to commitments settled afterwards. Admin only.
+    pub fn set_vesting_period(e: Env, caller: Address, seconds: u64) {
+        require_admin(&e, &caller);
+        if seconds > MAX_VESTING_PERIOD_SECONDS {
+            fail(&e, CommitmentError::DurationTooLong, "set_vesting_period");
+        }
+        e.storage()
+            .instance()
+            .set(&VestingDataKey::VestingPeriod, &seconds);
+    }
+
+    /// Period settlement payouts vest over, in seconds (0 when unset)
+    pub fn get_vesting_period(e: Env) -> u64 {
+        e.storage()
+            .instance()
+            .get(&VestingDataKey::VestingPeriod)
+            .unwrap_or(0)
+    }
+
+    /// Get a settled commitment's vesting schedule, if it is not fully claimed
+    pub fn get_vesting_schedule(e: Env, commitment_id: String) -> Option<VestingSchedule> {
+        storage_layout::get(&e, &VestingDataKey::Vesting(commitment_id))
+    }
+
+    /// Amount `claim_vested` would transfer now
+    pub fn get_vested_claimable(e: Env, commitment_id: String) -> i128 {
+        Self::get_vesting_schedule(e.clone(), commitment_id)
+            .map(|s| vested(&s, e.ledger().timestamp()) - s.claimed)
+            .unwrap_or(0)
+    }
+
+    /// Transfer the vested, unclaimed part of a settlement payout to its
+    /// beneficiary. Returns the amount transferred.
+    ///
+    /// Fails with NothingPending when nothing has vested since the last
+    /// claim, and while the asset is quarantined.
+    pub fn claim_vested(e: Env, commitment_id: String) -> i128 {
+        let key = VestingDataKey::Vesting(commitment_id.clone());
+        let mut schedule = storage_layout::get::<_, VestingSchedule>(&e, &key)
+            .unwrap_or_else(|| fail(&e, CommitmentError::NothingPending, "claim_vested"));
+        schedule.beneficiary.require_auth();
+        let amount = vested(&schedule, e.ledger().timestamp()) - schedule.claimed;
+        if amount <= 0 {
+            fail(&e, CommitmentError::NothingPending, "claim_vested");
+        }
+        if is_asset_quarantined(&e, &schedule.asset) {
+            fail(&e, CommitmentError::AssetQuarantined, "claim_vested");
+        }
+        if let Err(err) = disputes::check_not_frozen(&e, &commitment_id) {
+            fail(&e, err, "claim_vested");
+        }
+        require_no_reentrancy(&e);
+        set_reentrancy_guard(&e, true);
+
+        schedule.claimed += amount;
+        if schedule.claimed == schedule.total {
+            storage_layout::remove(&e, &key);
+        } else {
+            storage_layout::set(&e, &key, &schedule);
+        }
+        sweep::adjust_pending_claims(&e, &schedule.asset, -amount);
+        token::Client::new(&e, &schedule.asset).transfer(
+            &e.current_contract_address(),
+            &schedule.beneficiary,
+            &amount,
+        );
+        if let Some(commitment) = read_commitment(&e, &commitment_id) {
+            statement::ledger_entry(&e, &commitment, LedgerEntryKind::Claimed, -amount);
+        }
+
+        set_reentrancy_guard(&e, false);
+        e.events().publish(
+            (
+                symbol_short!("VestClaim"),
+                commitment_id,
+                schedule.beneficiary,
+            ),
+            (schedule.asset, amount),
+        );
+        amount
+    }
+}
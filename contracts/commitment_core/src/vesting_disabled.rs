@@ -0,0 +1,17 @@
+//! Settlement payouts transfer at once when the `vesting` feature is
+//! compiled out.
+
+use crate::Commitment;
+use soroban_sdk::{Env, String};
+
+pub(crate) fn pay_out(e: &Env, commitment: &Commitment, amount: i128) -> bool {
+    crate::pay_out(
+        e,
+        &commitment.commitment_id,
+        &commitment.asset_address,
+        &commitment.owner,
+        amount,
+    )
+}
+
+pub(crate) fn extend_ttl(_e: &Env, _commitment_id: &String, _extend_to: u32) {}
@@ -0,0 +1,176 @@
+//! Grace period before a loss breach hard-violates a commitment.
+//!
+//! With `violation_grace_seconds` set, the first loss breach seen by
+//! `update_value` or `check_violations` only records a pending violation and
+//! emits `ViolWarn`. The commitment is marked violated if the breach is still
+//! there on a check at least the grace period later; a recovery in the
+//! meantime clears the marker. The default of 0 violates on the first breach.
+//! `get_violation_details` reports where a commitment stands against its
+//! limits without changing anything.
+
+use crate::{
+    fail, mark_violated, read_commitment, require_admin, storage_layout, ttl, value_log,
+    Commitment, CommitmentCoreContract, CommitmentCoreContractClient, CommitmentError,
+    MAX_STATUS_BATCH,
+};
+use shared_utils::PercentMath;
+use soroban_sdk::{contractimpl, contracttype, symbol_short, Address, Env, String, Vec};
+
+/// Where a commitment stands against its loss and duration limits.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ViolationDetails {
+    pub has_violations: bool, // loss_violated || duration_violated
+    pub loss_violated: bool,
+    pub duration_violated: bool, // expired
+    pub loss_percent: i128,      // whole percent, rounded down
+    pub time_remaining: u64,     // seconds until expiry, 0 once expired
+    pub checked_at: u64,
+    pub not_found: bool, // batch entry for an unknown id; everything else zero
+}
+
+/// Violation details of `commitment_id`, or None if it does not exist.
+fn details(e: &Env, commitment_id: &String) -> Option<ViolationDetails> {
+    let commitment = read_commitment(e, commitment_id)?;
+    let checked_at = e.ledger().timestamp();
+
+    // Calculate loss in basis points; report whole percent (rounded down)
+    let loss_bps = PercentMath::loss_bps(
+        commitment.amount,
+        value_log::effective_value(e, &commitment),
+    );
+    let loss_violated = PercentMath::exceeds_percent(loss_bps, commitment.rules.max_loss_percent);
+    let duration_violated = checked_at >= commitment.expires_at;
+
+    Some(ViolationDetails {
+        has_violations: loss_violated || duration_violated,
+        loss_violated,
+        duration_violated,
+        loss_percent: PercentMath::bps_to_percent(loss_bps) as i128,
+        time_remaining: commitment.expires_at.saturating_sub(checked_at),
+        checked_at,
+        not_found: false,
+    })
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum ViolationDataKey {
+    GraceSeconds,             // u64 seconds a loss breach must persist
+    ViolationPending(String), // commitment_id -> u64 timestamp of the first breach
+}
+
+/// Handle a loss breach of `loss_bps`; returns whether the commitment was
+/// marked violated.
+pub(crate) fn loss_breach(e: &Env, commitment: &mut Commitment, loss_bps: u32) -> bool {
+    let grace = CommitmentCoreContract::get_violation_grace_seconds(e.clone());
+    let now = e.ledger().timestamp();
+    let key = ViolationDataKey::ViolationPending(commitment.commitment_id.clone());
+    if grace > 0 {
+        match storage_layout::get::<_, u64>(e, &key) {
+            None => {
+                storage_layout::set(e, &key, &now);
+                e.events().publish(
+                    (symbol_short!("ViolWarn"), commitment.commitment_id.clone()),
+                    (loss_bps, now.saturating_add(grace), now),
+                );
+                return false;
+            }
+            Some(since) if now < since.saturating_add(grace) => return false,
+            Some(_) => {}
+        }
+    }
+    storage_layout::remove(e, &key);
+    mark_violated(e, commitment, loss_bps);
+    true
+}
+
+/// Drop the commitment's pending violation, if any.
+pub(crate) fn clear_pending(e: &Env, commitment_id: &String) {
+    storage_layout::remove(
+        e,
+        &ViolationDataKey::ViolationPending(commitment_id.clone()),
+    );
+}
+
+/// Extend a commitment's pending violation (see `ttl::extend`).
+pub(crate) fn extend_ttl(e: &Env, commitment_id: &String, extend_to: u32) {
+    ttl::extend_persistent(
+        e,
+        &ViolationDataKey::ViolationPending(commitment_id.clone()),
+        extend_to,
+    );
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Set how long a loss breach must persist before the commitment is
+    /// marked violated (admin only). `0` violates on the first breach.
+    pub fn set_violation_grace_seconds(e: Env, caller: Address, seconds: u64) {
+        require_admin(&e, &caller);
+        e.storage()
+            .instance()
+            .set(&ViolationDataKey::GraceSeconds, &seconds);
+        e.events().publish(
+            (symbol_short!("ViolGrace"),),
+            (seconds, e.ledger().timestamp()),
+        );
+    }
+
+    /// Seconds a loss breach must persist before the commitment is violated
+    pub fn get_violation_grace_seconds(e: Env) -> u64 {
+        e.storage()
+            .instance()
+            .get::<_, u64>(&ViolationDataKey::GraceSeconds)
+            .unwrap_or(0)
+    }
+
+    /// Where a commitment stands against its loss and duration limits.
+    ///
+    /// Fails with CommitmentNotFound for unknown ids.
+    pub fn get_violation_details(e: Env, commitment_id: String) -> ViolationDetails {
+        details(&e, &commitment_id).unwrap_or_else(|| {
+            fail(
+                &e,
+                CommitmentError::CommitmentNotFound,
+                "get_violation_details",
+            )
+        })
+    }
+
+    /// `get_violation_details` for up to `MAX_STATUS_BATCH` ids, in order.
+    ///
+    /// Unknown ids get an entry with `not_found` set instead of failing the
+    /// batch.
+    pub fn get_violation_details_batch(
+        e: Env,
+        commitment_ids: Vec<String>,
+    ) -> Vec<ViolationDetails> {
+        if commitment_ids.len() > MAX_STATUS_BATCH {
+            fail(
+                &e,
+                CommitmentError::BatchTooLarge,
+                "get_violation_details_batch",
+            );
+        }
+        let mut batch = Vec::new(&e);
+        for commitment_id in commitment_ids.iter() {
+            batch.push_back(details(&e, &commitment_id).unwrap_or(ViolationDetails {
+                has_violations: false,
+                loss_violated: false,
+                duration_violated: false,
+                loss_percent: 0,
+                time_remaining: 0,
+                checked_at: e.ledger().timestamp(),
+                not_found: true,
+            }));
+        }
+        batch
+    }
+
+    /// When the commitment's current, not yet violated, loss breach was
+    /// first seen, if any.
+    pub fn get_pending_violation(e: Env, commitment_id: String) -> Option<u64> {
+        storage_layout::get::<_, u64>(&e, &ViolationDataKey::ViolationPending(commitment_id))
+    }
+}
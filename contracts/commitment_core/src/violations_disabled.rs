@@ -0,0 +1,14 @@
+//! Loss breaches violate immediately when the `violation_grace` feature is
+//! compiled out.
+
+use crate::{mark_violated, Commitment};
+use soroban_sdk::{Env, String};
+
+pub(crate) fn loss_breach(e: &Env, commitment: &mut Commitment, loss_bps: u32) -> bool {
+    mark_violated(e, commitment, loss_bps);
+    true
+}
+
+pub(crate) fn clear_pending(_e: &Env, _commitment_id: &String) {}
+
+pub(crate) fn extend_ttl(_e: &Env, _commitment_id: &String, _extend_to: u32) {}
@@ -0,0 +1,69 @@
+//! Virtual (paper-trading) commitments.
+//!
+//! While the admin has virtual mode on, anyone may create a virtual
+//! commitment: it runs through the normal lifecycle but moves no tokens,
+//! mints no NFT, stays out of TVL and tags every event it emits with a
+//! trailing `virtual` topic.
+
+use crate::{
+    require_admin, CommitmentCoreContract, CommitmentCoreContractClient, CommitmentError,
+    CommitmentRules,
+};
+use soroban_sdk::{contractimpl, contracttype, symbol_short, Address, Env, String};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum VirtualDataKey {
+    VirtualModeEnabled, // bool — admin flag allowing virtual commitments
+}
+
+#[contractimpl]
+impl CommitmentCoreContract {
+    /// Create a virtual (paper-trading) commitment.
+    ///
+    /// Requires virtual mode to be enabled by the admin. No tokens are
+    /// transferred and no NFT is minted; the commitment is excluded from TVL
+    /// and every event it emits carries a trailing `virtual` topic.
+    pub fn create_virtual_commitment(
+        e: Env,
+        owner: Address,
+        amount: i128,
+        asset_address: Address,
+        rules: CommitmentRules,
+    ) -> Result<String, CommitmentError> {
+        if !Self::is_virtual_mode_enabled(e.clone()) {
+            return Err(CommitmentError::VirtualNotAllowed);
+        }
+        Self::create_commitment_internal(
+            e,
+            owner,
+            amount,
+            asset_address,
+            rules,
+            true,
+            None,
+            None,
+            None,
+        )
+    }
+
+    /// Enable or disable creation of virtual commitments (admin only)
+    pub fn set_virtual_mode(e: Env, caller: Address, enabled: bool) {
+        require_admin(&e, &caller);
+        e.storage()
+            .instance()
+            .set(&VirtualDataKey::VirtualModeEnabled, &enabled);
+        e.events().publish(
+            (symbol_short!("VirtMode"),),
+            (enabled, e.ledger().timestamp()),
+        );
+    }
+
+    /// Whether virtual commitments may currently be created
+    pub fn is_virtual_mode_enabled(e: Env) -> bool {
+        e.storage()
+            .instance()
+            .get::<_, bool>(&VirtualDataKey::VirtualModeEnabled)
+            .unwrap_or(false)
+    }
+}
@@ -0,0 +1,28 @@
+//! Wasm size budget for the default-features build.
+//!
+//! `build.rs` builds the default-features release wasm and passes its path
+//! as `COMMITMENT_CORE_WASM`; modules that do not fit the budget belong
+//! behind a cargo feature.
+
+extern crate std;
+
+/// Size budget for the optimized default-features contract: the core plus
+/// `fees` (see `default` in Cargo.toml), kept deployable under the network's
+/// 128 KiB contract size limit.
+const MAX_WASM_SIZE_BYTES: u64 = 128 * 1024;
+
+#[test]
+fn test_default_wasm_within_size_budget() {
+    let path = env!("COMMITMENT_CORE_WASM");
+    assert!(
+        !path.is_empty(),
+        "build.rs could not build the default-features wasm (is the wasm32v1-none target installed?)"
+    );
+    let size = std::fs::metadata(path).unwrap().len();
+    assert!(
+        size <= MAX_WASM_SIZE_BYTES,
+        "commitment_core.wasm is {} bytes, budget is {} bytes",
+        size,
+        MAX_WASM_SIZE_BYTES
+    );
+}
@@ -84,7 +84,7 @@ fn benchmark_mint() {
             String::from_str(&e, "commitment_1"),
             30,
             20,
-            String::from_str(&e, "balanced"),
+            RiskProfile::Balanced,
             1000_0000000,
             Address::generate(&e),
             10,
@@ -110,7 +110,7 @@ fn benchmark_get_metadata() {
             String::from_str(&e, "commitment_1"),
             30,
             20,
-            String::from_str(&e, "balanced"),
+            RiskProfile::Balanced,
             1000_0000000,
             Address::generate(&e),
             10,
@@ -143,7 +143,7 @@ fn benchmark_owner_of() {
             String::from_str(&e, "commitment_1"),
             30,
             20,
-            String::from_str(&e, "balanced"),
+            RiskProfile::Balanced,
             1000_0000000,
             Address::generate(&e),
             10,
@@ -176,7 +176,7 @@ fn benchmark_balance_of() {
             String::from_str(&e, "commitment_1"),
             30,
             20,
-            String::from_str(&e, "balanced"),
+            RiskProfile::Balanced,
             1000_0000000,
             Address::generate(&e),
             10,
@@ -213,7 +213,7 @@ fn benchmark_batch_mint() {
                 String::from_str(&e, &format!("commitment_{}", i)),
                 30,
                 20,
-                String::from_str(&e, "balanced"),
+                RiskProfile::Balanced,
                 1000_0000000,
                 Address::generate(&e),
                 10,
@@ -1,8 +1,8 @@
 #![no_std]
-use shared_utils::EmergencyControl;
+use shared_utils::{EmergencyControl, PercentMath, RiskProfile, TimeUtils};
 use soroban_sdk::{
-    contract, contracterror, contractimpl, contracttype, symbol_short, Address, Env, String,
-    Symbol, Vec,
+    contract, contracterror, contractimpl, contracttype, symbol_short, vec, Address, Env,
+    IntoVal, String, Symbol, Vec,
 };
 
 // ============================================================================
@@ -32,16 +32,26 @@ pub enum ContractError {
     AlreadySettled = 8,
     /// Commitment has not expired yet
     NotExpired = 9,
-    /// Invalid duration (must be > 0)
+    /// Invalid duration (must be > 0, within the maximum, and not overflow expiry)
     InvalidDuration = 10,
     /// Invalid max loss percent (must be 0-100)
     InvalidMaxLoss = 11,
-    /// Invalid commitment type (must be safe, balanced, or aggressive)
+    /// Invalid commitment type (no longer returned: the type is a `RiskProfile`)
     InvalidCommitmentType = 12,
     /// Invalid amount (must be > 0)
     InvalidAmount = 13,
     /// Reentrancy detected
     ReentrancyDetected = 14,
+    /// A badge was already minted for this commitment
+    BadgeAlreadyIssued = 15,
+    /// Badge with the given badge_id does not exist
+    BadgeNotFound = 16,
+    /// Asset is not on the core contract's supported-asset list
+    AssetNotSupported = 17,
+    /// Asset address is the owner or this contract
+    InvalidAsset = 18,
+    /// No admin transfer has been proposed
+    NoPendingAdmin = 19,
 }
 
 // ============================================================================
@@ -55,7 +65,7 @@ pub struct CommitmentMetadata {
     pub commitment_id: String,
     pub duration_days: u32,
     pub max_loss_percent: u32,
-    pub commitment_type: String, // "safe", "balanced", "aggressive"
+    pub commitment_type: RiskProfile,
     pub created_at: u64,
     pub expires_at: u64,
     pub initial_amount: i128,
@@ -73,29 +83,92 @@ pub struct CommitmentNFT {
     pub early_exit_penalty: u32,
 }
 
+/// Metadata recorded on a compliance badge
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BadgeMetadata {
+    pub commitment_id: String,
+    pub compliance_score: u32,
+    pub duration_days: u32,
+    pub issued_at: u64,
+}
+
+/// A soulbound compliance badge. Badges live in their own id space, are not
+/// counted by `balance_of` / `total_supply` and have no transfer path.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommitmentBadge {
+    pub owner: Address,
+    pub badge_id: u32,
+    pub metadata: BadgeMetadata,
+}
+
+/// An approval granted by a token owner, as listed by `approvals_of_owner`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ApprovalGrant {
+    /// `spender` may transfer the given token
+    Token(u32, Address),
+    /// `operator` may transfer any of the owner's tokens
+    Operator(Address),
+}
+
+/// Token ids per `DataKey::TokenIdPage` entry
+pub const TOKEN_ID_PAGE_SIZE: u32 = 100;
+
 /// Storage keys for the contract
 #[contracttype]
 pub enum DataKey {
     /// Admin address (singleton)
     Admin,
-    /// Counter for generating unique token IDs / Total supply
+    /// Last token id issued (ids start at 1) / Total supply
     TokenCounter,
     /// NFT data storage (token_id -> CommitmentNFT)
     NFT(u32),
     /// Owner balance count (Address -> u32)
     OwnerBalance(Address),
-    /// Owner tokens list (Address -> Vec<u32>)
+    /// Legacy owner tokens list (Address -> Vec<u32>), migrated lazily to
+    /// `OwnerTokenAt` / `OwnerTokenPos`
     OwnerTokens(Address),
-    /// List of all token IDs (Vec<u32>)
+    /// Legacy list of all token IDs (Vec<u32>), migrated to `TokenIdPage`
     TokenIds,
+    /// Page of the global token id list (page -> Vec<u32>, `TOKEN_ID_PAGE_SIZE`
+    /// ids each); burned ids stay in their page and are skipped on read
+    TokenIdPage(u32),
+    /// Number of ids appended to the token id pages (u32)
+    TokenIdCount,
     /// Authorized commitment_core contract address (for settlement)
     CoreContract,
-    /// Authorized minter addresses (from upstream)
+    /// Addresses allowed to mint besides the admin and core (Address -> bool)
     AuthorizedMinter(Address),
     /// Active status (token_id -> bool)
     ActiveStatus(u32),
     /// Reentrancy guard flag
     ReentrancyGuard,
+    /// Single-token approval (token_id -> approved Address)
+    Approved(u32),
+    /// Operator approval ((owner, operator) -> bool)
+    OperatorApproval(Address, Address),
+    /// Approvals granted by an owner (Address -> Vec<ApprovalGrant>)
+    OwnerApprovals(Address),
+    /// Owner's token at a position ((owner, index) -> token_id); the count is `OwnerBalance`
+    OwnerTokenAt(Address, u32),
+    /// Position of a token in its owner's index (token_id -> index)
+    OwnerTokenPos(u32),
+    /// Address allowed to mint compliance badges (attestation engine)
+    BadgeMinter,
+    /// Counter for badge ids
+    BadgeCounter,
+    /// Badge data (badge_id -> CommitmentBadge)
+    Badge(u32),
+    /// Badge minted for a commitment (commitment_id -> badge_id)
+    CommitmentBadge(String),
+    /// Badges held by an owner (Address -> Vec<u32>)
+    OwnerBadges(Address),
+    /// Maximum commitment duration in days, mirrored from core (u32, default 3650)
+    MaxDurationDays,
+    /// Address proposed as the next admin (two-step transfer)
+    PendingAdmin,
 }
 
 // Events
@@ -125,21 +198,9 @@ impl CommitmentNFTContract {
         // Initialize token counter to 0
         e.storage().instance().set(&DataKey::TokenCounter, &0u32);
 
-        // Initialize empty token IDs vector
-        let token_ids: Vec<u32> = Vec::new(&e);
-        e.storage().instance().set(&DataKey::TokenIds, &token_ids);
-
         Ok(())
     }
 
-    /// Validate commitment type
-    fn is_valid_commitment_type(e: &Env, commitment_type: &String) -> bool {
-        let safe = String::from_str(e, "safe");
-        let balanced = String::from_str(e, "balanced");
-        let aggressive = String::from_str(e, "aggressive");
-        *commitment_type == safe || *commitment_type == balanced || *commitment_type == aggressive
-    }
-
     /// Set the authorized commitment_core contract address for settlement
     /// Only the admin can call this function
     pub fn set_core_contract(e: Env, core_contract: Address) -> Result<(), ContractError> {
@@ -169,6 +230,36 @@ impl CommitmentNFTContract {
             .ok_or(ContractError::NotInitialized)
     }
 
+    /// Mirror core's maximum commitment duration in days (core contract only)
+    ///
+    /// Core owns the limit and pushes it here whenever it changes, so tokens
+    /// minted directly are bounded like commitments created through core.
+    pub fn set_max_duration_days(e: Env, max_duration_days: u32) -> Result<(), ContractError> {
+        let core: Address = e
+            .storage()
+            .instance()
+            .get(&DataKey::CoreContract)
+            .ok_or(ContractError::NotInitialized)?;
+        core.require_auth();
+
+        if max_duration_days == 0 {
+            return Err(ContractError::InvalidDuration);
+        }
+        e.storage()
+            .instance()
+            .set(&DataKey::MaxDurationDays, &max_duration_days);
+
+        Ok(())
+    }
+
+    /// Get the maximum commitment duration in days
+    pub fn get_max_duration_days(e: Env) -> u32 {
+        e.storage()
+            .instance()
+            .get(&DataKey::MaxDurationDays)
+            .unwrap_or(TimeUtils::DEFAULT_MAX_DURATION_DAYS)
+    }
+
     /// Get the admin address
     pub fn get_admin(e: Env) -> Result<Address, ContractError> {
         e.storage()
@@ -177,6 +268,65 @@ impl CommitmentNFTContract {
             .ok_or(ContractError::NotInitialized)
     }
 
+    /// Propose the next admin (admin only)
+    ///
+    /// The transfer completes only when `new_admin` calls `accept_admin`;
+    /// proposing again replaces the pending address.
+    pub fn propose_admin(e: Env, caller: Address, new_admin: Address) -> Result<(), ContractError> {
+        let admin = Self::get_admin(e.clone())?;
+        admin.require_auth();
+        if caller != admin {
+            return Err(ContractError::NotAuthorized);
+        }
+
+        e.storage()
+            .instance()
+            .set(&DataKey::PendingAdmin, &new_admin);
+        e.events().publish(
+            (Symbol::new(&e, "AdminProposed"), admin),
+            (new_admin, e.ledger().timestamp()),
+        );
+        Ok(())
+    }
+
+    /// Accept a pending admin proposal (the proposed address only)
+    pub fn accept_admin(e: Env, caller: Address) -> Result<(), ContractError> {
+        caller.require_auth();
+        let pending = Self::get_pending_admin(e.clone()).ok_or(ContractError::NoPendingAdmin)?;
+        if caller != pending {
+            return Err(ContractError::NotAuthorized);
+        }
+
+        let previous = Self::get_admin(e.clone())?;
+        e.storage().instance().set(&DataKey::Admin, &caller);
+        e.storage().instance().remove(&DataKey::PendingAdmin);
+        e.events().publish(
+            (Symbol::new(&e, "AdminChanged"), previous),
+            (caller, e.ledger().timestamp()),
+        );
+        Ok(())
+    }
+
+    /// Withdraw the pending admin proposal (admin only)
+    pub fn cancel_admin_proposal(e: Env, caller: Address) -> Result<(), ContractError> {
+        let admin = Self::get_admin(e.clone())?;
+        admin.require_auth();
+        if caller != admin {
+            return Err(ContractError::NotAuthorized);
+        }
+        if !e.storage().instance().has(&DataKey::PendingAdmin) {
+            return Err(ContractError::NoPendingAdmin);
+        }
+
+        e.storage().instance().remove(&DataKey::PendingAdmin);
+        Ok(())
+    }
+
+    /// Get the address proposed as the next admin, if any
+    pub fn get_pending_admin(e: Env) -> Option<Address> {
+        e.storage().instance().get(&DataKey::PendingAdmin)
+    }
+
     // ========================================================================
     // NFT Minting
     // ========================================================================
@@ -189,23 +339,29 @@ impl CommitmentNFTContract {
     /// * `commitment_id` - Unique identifier for the commitment
     /// * `duration_days` - Duration of the commitment in days
     /// * `max_loss_percent` - Maximum allowed loss percentage (0-100)
-    /// * `commitment_type` - Type of commitment ("safe", "balanced", "aggressive")
+    /// * `commitment_type` - Risk profile of the commitment
     /// * `initial_amount` - Initial amount committed
     /// * `asset_address` - Address of the asset contract
     ///
+    /// The core contract, the admin and authorized minters may mint. Input
+    /// from the core contract is trusted; for anyone else the asset is checked
+    /// against core's supported assets when a core contract is configured.
+    ///
     /// # Returns
     /// The token_id of the newly minted NFT
     ///
     /// # Reentrancy Protection
     /// Uses checks-effects-interactions pattern. This function only writes to storage
     /// and doesn't make external calls, but still protected for consistency.
+    #[allow(clippy::too_many_arguments)]
     pub fn mint(
         e: Env,
+        caller: Address,
         owner: Address,
         commitment_id: String,
         duration_days: u32,
         max_loss_percent: u32,
-        commitment_type: String,
+        commitment_type: RiskProfile,
         initial_amount: i128,
         asset_address: Address,
         early_exit_penalty: u32,
@@ -230,6 +386,13 @@ impl CommitmentNFTContract {
                 .set(&DataKey::ReentrancyGuard, &false);
             return Err(ContractError::NotInitialized);
         }
+        caller.require_auth();
+        if let Err(err) = Self::check_minter_and_asset(&e, &caller, &owner, &asset_address) {
+            e.storage()
+                .instance()
+                .set(&DataKey::ReentrancyGuard, &false);
+            return Err(err);
+        }
 
         // Validate inputs
         if duration_days == 0 {
@@ -238,18 +401,12 @@ impl CommitmentNFTContract {
                 .set(&DataKey::ReentrancyGuard, &false);
             return Err(ContractError::InvalidDuration);
         }
-        if max_loss_percent > 100 {
+        if !PercentMath::is_valid_percent(max_loss_percent) {
             e.storage()
                 .instance()
                 .set(&DataKey::ReentrancyGuard, &false);
             return Err(ContractError::InvalidMaxLoss);
         }
-        if !Self::is_valid_commitment_type(&e, &commitment_type) {
-            e.storage()
-                .instance()
-                .set(&DataKey::ReentrancyGuard, &false);
-            return Err(ContractError::InvalidCommitmentType);
-        }
         if initial_amount <= 0 {
             e.storage()
                 .instance()
@@ -258,21 +415,31 @@ impl CommitmentNFTContract {
         }
 
         // EFFECTS: Update state
-        // Generate unique token_id
+        // Generate unique token_id. Ids start at 1 so that 0 never names a
+        // real token (core stores 0 for commitments without an NFT)
         let token_id: u32 = e
             .storage()
             .instance()
-            .get(&DataKey::TokenCounter)
-            .unwrap_or(0);
-        let next_token_id = token_id + 1;
-        e.storage()
-            .instance()
-            .set(&DataKey::TokenCounter, &next_token_id);
+            .get::<_, u32>(&DataKey::TokenCounter)
+            .unwrap_or(0)
+            + 1;
+        e.storage().instance().set(&DataKey::TokenCounter, &token_id);
 
         // Calculate timestamps
         let created_at = e.ledger().timestamp();
-        let seconds_per_day: u64 = 86400;
-        let expires_at = created_at + (duration_days as u64 * seconds_per_day);
+        let expires_at = match TimeUtils::checked_expiration_bounded(
+            created_at,
+            duration_days,
+            Self::get_max_duration_days(e.clone()),
+        ) {
+            Ok(expires_at) => expires_at,
+            Err(_) => {
+                e.storage()
+                    .instance()
+                    .set(&DataKey::ReentrancyGuard, &false);
+                return Err(ContractError::InvalidDuration);
+            }
+        };
 
         // Create CommitmentMetadata
         let metadata = CommitmentMetadata {
@@ -298,36 +465,11 @@ impl CommitmentNFTContract {
         // Store NFT data
         e.storage().persistent().set(&DataKey::NFT(token_id), &nft);
 
-        // Update owner balance
-        let current_balance: u32 = e
-            .storage()
-            .persistent()
-            .get(&DataKey::OwnerBalance(owner.clone()))
-            .unwrap_or(0);
-        e.storage().persistent().set(
-            &DataKey::OwnerBalance(owner.clone()),
-            &(current_balance + 1),
-        );
-
-        // Update owner tokens list
-        let mut owner_tokens: Vec<u32> = e
-            .storage()
-            .persistent()
-            .get(&DataKey::OwnerTokens(owner.clone()))
-            .unwrap_or(Vec::new(&e));
-        owner_tokens.push_back(token_id);
-        e.storage()
-            .persistent()
-            .set(&DataKey::OwnerTokens(owner.clone()), &owner_tokens);
+        // Update owner balance and token index
+        Self::owner_index_add(&e, &owner, token_id);
 
         // Add token_id to the list of all tokens
-        let mut token_ids: Vec<u32> = e
-            .storage()
-            .instance()
-            .get(&DataKey::TokenIds)
-            .unwrap_or(Vec::new(&e));
-        token_ids.push_back(token_id);
-        e.storage().instance().set(&DataKey::TokenIds, &token_ids);
+        Self::token_ids_append(&e, token_id);
 
         // Clear reentrancy guard
         e.storage()
@@ -343,6 +485,77 @@ impl CommitmentNFTContract {
         Ok(token_id)
     }
 
+    /// Check `caller` may mint and `asset_address` is acceptable
+    fn check_minter_and_asset(
+        e: &Env,
+        caller: &Address,
+        owner: &Address,
+        asset_address: &Address,
+    ) -> Result<(), ContractError> {
+        if asset_address == owner || *asset_address == e.current_contract_address() {
+            return Err(ContractError::InvalidAsset);
+        }
+        let core: Option<Address> = e.storage().instance().get(&DataKey::CoreContract);
+        // Core validated the asset when it created the commitment
+        if core.as_ref() == Some(caller) {
+            return Ok(());
+        }
+        let admin: Address = e
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(ContractError::NotInitialized)?;
+        if *caller != admin && !Self::is_authorized_minter(e.clone(), caller.clone()) {
+            return Err(ContractError::NotAuthorized);
+        }
+        if let Some(core) = core {
+            let supported: bool = e.invoke_contract(
+                &core,
+                &Symbol::new(e, "is_asset_supported"),
+                vec![e, asset_address.into_val(e)],
+            );
+            if !supported {
+                return Err(ContractError::AssetNotSupported);
+            }
+        }
+        Ok(())
+    }
+
+    /// Allow or revoke an address to mint commitment NFTs (admin only)
+    pub fn set_authorized_minter(
+        e: Env,
+        minter: Address,
+        authorized: bool,
+    ) -> Result<(), ContractError> {
+        let admin: Address = e
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(ContractError::NotInitialized)?;
+        admin.require_auth();
+
+        let key = DataKey::AuthorizedMinter(minter.clone());
+        if authorized {
+            e.storage().instance().set(&key, &true);
+        } else {
+            e.storage().instance().remove(&key);
+        }
+        e.events().publish(
+            (Symbol::new(&e, "MinterSet"),),
+            (minter, authorized),
+        );
+
+        Ok(())
+    }
+
+    /// Whether `minter` was authorized by the admin to mint
+    pub fn is_authorized_minter(e: Env, minter: Address) -> bool {
+        e.storage()
+            .instance()
+            .get(&DataKey::AuthorizedMinter(minter))
+            .unwrap_or(false)
+    }
+
     // ========================================================================
     // NFT Query Functions
     // ========================================================================
@@ -366,150 +579,579 @@ impl CommitmentNFTContract {
         Ok(nft.owner)
     }
 
-    /// Transfer NFT to new owner
-    ///
-    /// # Reentrancy Protection
-    /// Uses checks-effects-interactions pattern. This function only writes to storage
-    /// and doesn't make external calls, but still protected for consistency.
-    pub fn transfer(
-        e: Env,
-        from: Address,
-        to: Address,
-        token_id: u32,
-    ) -> Result<(), ContractError> {
-        // Reentrancy protection
-        let guard: bool = e
-            .storage()
-            .instance()
-            .get(&DataKey::ReentrancyGuard)
-            .unwrap_or(false);
+    /// Move a token between owners, updating balances, owner lists and
+    /// clearing any single-token approval. Callers perform all checks.
+    fn move_token(e: &Env, nft: &mut CommitmentNFT, from: &Address, to: &Address) {
+        let token_id = nft.token_id;
 
-        if guard {
-            return Err(ContractError::ReentrancyDetected);
-        }
-        e.storage().instance().set(&DataKey::ReentrancyGuard, &true);
-        EmergencyControl::require_not_emergency(&e);
+        // Update owner
+        nft.owner = to.clone();
+        e.storage().persistent().set(&DataKey::NFT(token_id), nft);
 
-        // CHECKS: Require authorization from the sender
-        from.require_auth();
+        // A transfer revokes the previous owner's single-token approval
+        Self::clear_token_approval(e, from, token_id);
 
-        // Get the NFT
-        let mut nft: CommitmentNFT = e
-            .storage()
-            .persistent()
-            .get(&DataKey::NFT(token_id))
-            .ok_or_else(|| {
-                e.storage()
-                    .instance()
-                    .set(&DataKey::ReentrancyGuard, &false);
-                ContractError::TokenNotFound
-            })?;
+        // Update balances and owner token indexes in O(1)
+        Self::owner_index_remove(e, from, token_id);
+        Self::owner_index_add(e, to, token_id);
+    }
 
-        // Verify ownership
-        if nft.owner != from {
-            e.storage()
-                .instance()
-                .set(&DataKey::ReentrancyGuard, &false);
-            return Err(ContractError::NotOwner);
+    /// Tell core a token changed hands so the commitment follows it.
+    ///
+    /// Runs after the transfer's effects; core ignores tokens it did not
+    /// record, so this is a no-op for tokens minted outside core.
+    fn notify_core_of_transfer(e: &Env, token_id: u32, from: &Address, to: &Address) {
+        let core: Option<Address> = e.storage().instance().get(&DataKey::CoreContract);
+        if let Some(core) = core {
+            let _: bool = e.invoke_contract(
+                &core,
+                &Symbol::new(e, "on_nft_transfer"),
+                vec![
+                    e,
+                    e.current_contract_address().into_val(e),
+                    token_id.into_val(e),
+                    from.into_val(e),
+                    to.into_val(e),
+                ],
+            );
         }
+    }
 
-        // Check if NFT is still active (active NFTs may have transfer restrictions)
-        // For now, we allow transfers regardless of active status
-        // Uncomment below to restrict transfers of active NFTs:
-        // if nft.is_active {
-        //     e.storage().instance().set(&DataKey::ReentrancyGuard, &false);
-        //     return Err(ContractError::TransferNotAllowed);
-        // }
-
-        // EFFECTS: Update state
-        // Update owner
-        nft.owner = to.clone();
-        e.storage().persistent().set(&DataKey::NFT(token_id), &nft);
+    /// Ask core whether the token's commitment may change hands.
+    ///
+    /// Core refuses while the commitment has funds allocated to pools
+    /// (unless the admin overrides); with no core set, transfers are free.
+    fn core_allows_transfer(e: &Env, nft: &CommitmentNFT) -> bool {
+        let core: Option<Address> = e.storage().instance().get(&DataKey::CoreContract);
+        match core {
+            Some(core) => e.invoke_contract(
+                &core,
+                &Symbol::new(e, "is_transferable"),
+                vec![e, nft.metadata.commitment_id.into_val(e)],
+            ),
+            None => true,
+        }
+    }
 
-        // OPTIMIZATION: Batch read balances before updating
-        let (from_balance, to_balance) = {
-            let from_bal = e
-                .storage()
-                .persistent()
-                .get(&DataKey::OwnerBalance(from.clone()))
-                .unwrap_or(0u32);
-            let to_bal = e
-                .storage()
+    /// Move a legacy `OwnerTokens` Vec into the position-indexed layout.
+    ///
+    /// No-op once migrated. Runs before any index mutation so the two layouts
+    /// never coexist for an owner that has been written to.
+    fn migrate_owner_index(e: &Env, owner: &Address) {
+        let legacy_key = DataKey::OwnerTokens(owner.clone());
+        let legacy: Option<Vec<u32>> = e.storage().persistent().get(&legacy_key);
+        if let Some(tokens) = legacy {
+            for (index, token_id) in tokens.iter().enumerate() {
+                let index = index as u32;
+                e.storage()
+                    .persistent()
+                    .set(&DataKey::OwnerTokenAt(owner.clone(), index), &token_id);
+                e.storage()
+                    .persistent()
+                    .set(&DataKey::OwnerTokenPos(token_id), &index);
+            }
+            e.storage()
                 .persistent()
-                .get(&DataKey::OwnerBalance(to.clone()))
-                .unwrap_or(0u32);
-            (from_bal, to_bal)
-        };
+                .set(&DataKey::OwnerBalance(owner.clone()), &tokens.len());
+            e.storage().persistent().remove(&legacy_key);
+        }
+    }
 
-        // Update balance counts
-        if from_balance > 0 {
+    /// Move the legacy instance `TokenIds` Vec into persistent pages.
+    ///
+    /// No-op once migrated. Runs before any append so the two layouts never
+    /// coexist once the list has been written to.
+    fn migrate_token_id_list(e: &Env) -> u32 {
+        let legacy: Option<Vec<u32>> = e.storage().instance().get(&DataKey::TokenIds);
+        let Some(tokens) = legacy else {
+            return 0;
+        };
+        let count = tokens.len();
+        let mut page = 0;
+        while page * TOKEN_ID_PAGE_SIZE < count {
+            let start = page * TOKEN_ID_PAGE_SIZE;
+            let end = (start + TOKEN_ID_PAGE_SIZE).min(count);
             e.storage()
                 .persistent()
-                .set(&DataKey::OwnerBalance(from.clone()), &(from_balance - 1));
+                .set(&DataKey::TokenIdPage(page), &tokens.slice(start..end));
+            page += 1;
         }
+        e.storage().persistent().set(&DataKey::TokenIdCount, &count);
+        e.storage().instance().remove(&DataKey::TokenIds);
+        count
+    }
+
+    /// Append a token id to the last page of the global token list.
+    fn token_ids_append(e: &Env, token_id: u32) {
+        Self::migrate_token_id_list(e);
+        let count: u32 = e
+            .storage()
+            .persistent()
+            .get(&DataKey::TokenIdCount)
+            .unwrap_or(0);
+        let key = DataKey::TokenIdPage(count / TOKEN_ID_PAGE_SIZE);
+        let mut page: Vec<u32> = e.storage().persistent().get(&key).unwrap_or(Vec::new(e));
+        page.push_back(token_id);
+        e.storage().persistent().set(&key, &page);
         e.storage()
             .persistent()
-            .set(&DataKey::OwnerBalance(to.clone()), &(to_balance + 1));
+            .set(&DataKey::TokenIdCount, &(count + 1));
+    }
+
+    /// Up to `limit` ids of the global token list from `offset`, reading
+    /// only the pages that overlap the window.
+    fn token_id_range(e: &Env, offset: u32, limit: u32) -> Vec<u32> {
+        let mut ids = Vec::new(e);
+        let legacy: Option<Vec<u32>> = e.storage().instance().get(&DataKey::TokenIds);
+        if let Some(tokens) = legacy {
+            let end = offset.saturating_add(limit).min(tokens.len());
+            for i in offset..end {
+                ids.push_back(tokens.get_unchecked(i));
+            }
+            return ids;
+        }
 
-        // Update owner tokens lists
-        let mut from_tokens: Vec<u32> = e
+        let count: u32 = e
             .storage()
             .persistent()
-            .get(&DataKey::OwnerTokens(from.clone()))
-            .unwrap_or(Vec::new(&e));
-        if let Some(index) = from_tokens.iter().position(|id| id == token_id) {
-            from_tokens.remove(index as u32);
+            .get(&DataKey::TokenIdCount)
+            .unwrap_or(0);
+        let end = offset.saturating_add(limit).min(count);
+        let mut index = offset;
+        while index < end {
+            let page_start = index / TOKEN_ID_PAGE_SIZE * TOKEN_ID_PAGE_SIZE;
+            let page: Vec<u32> = e
+                .storage()
+                .persistent()
+                .get(&DataKey::TokenIdPage(index / TOKEN_ID_PAGE_SIZE))
+                .unwrap_or(Vec::new(e));
+            let page_end = (page_start + page.len()).min(end);
+            for i in index..page_end {
+                ids.push_back(page.get_unchecked(i - page_start));
+            }
+            index = page_start + TOKEN_ID_PAGE_SIZE;
         }
-        e.storage()
-            .persistent()
-            .set(&DataKey::OwnerTokens(from.clone()), &from_tokens);
+        ids
+    }
 
-        let mut to_tokens: Vec<u32> = e
+    /// Append a token to the owner's index and bump their balance.
+    fn owner_index_add(e: &Env, owner: &Address, token_id: u32) {
+        Self::migrate_owner_index(e, owner);
+        let count: u32 = e
             .storage()
             .persistent()
-            .get(&DataKey::OwnerTokens(to.clone()))
-            .unwrap_or(Vec::new(&e));
-        to_tokens.push_back(token_id);
+            .get(&DataKey::OwnerBalance(owner.clone()))
+            .unwrap_or(0);
         e.storage()
             .persistent()
-            .set(&DataKey::OwnerTokens(to.clone()), &to_tokens);
-
-        // Clear reentrancy guard
+            .set(&DataKey::OwnerTokenAt(owner.clone(), count), &token_id);
         e.storage()
-            .instance()
-            .set(&DataKey::ReentrancyGuard, &false);
-
-        // Emit transfer event
-        e.events().publish(
-            (symbol_short!("Transfer"), from, to),
-            (token_id, e.ledger().timestamp()),
-        );
-
-        Ok(())
-    }
-
-    /// Check if NFT is active
-    pub fn is_active(e: Env, token_id: u32) -> Result<bool, ContractError> {
-        let nft: CommitmentNFT = e
-            .storage()
             .persistent()
-            .get(&DataKey::NFT(token_id))
-            .ok_or(ContractError::TokenNotFound)?;
-
-        Ok(nft.is_active)
-    }
-
-    /// Get total supply of NFTs minted
-    pub fn total_supply(e: Env) -> u32 {
+            .set(&DataKey::OwnerTokenPos(token_id), &count);
         e.storage()
-            .instance()
-            .get(&DataKey::TokenCounter)
-            .unwrap_or(0)
+            .persistent()
+            .set(&DataKey::OwnerBalance(owner.clone()), &(count + 1));
     }
 
-    /// Get NFT count for a specific owner
-    pub fn balance_of(e: Env, owner: Address) -> u32 {
-        e.storage()
+    /// Remove a token from the owner's index by swapping in the last entry.
+    fn owner_index_remove(e: &Env, owner: &Address, token_id: u32) {
+        Self::migrate_owner_index(e, owner);
+        let count: u32 = e
+            .storage()
+            .persistent()
+            .get(&DataKey::OwnerBalance(owner.clone()))
+            .unwrap_or(0);
+        let pos: Option<u32> = e.storage().persistent().get(&DataKey::OwnerTokenPos(token_id));
+        let (Some(pos), true) = (pos, count > 0) else {
+            return;
+        };
+
+        let last = count - 1;
+        if pos != last {
+            let last_token: u32 = e
+                .storage()
+                .persistent()
+                .get(&DataKey::OwnerTokenAt(owner.clone(), last))
+                .unwrap_or(token_id);
+            e.storage()
+                .persistent()
+                .set(&DataKey::OwnerTokenAt(owner.clone(), pos), &last_token);
+            e.storage()
+                .persistent()
+                .set(&DataKey::OwnerTokenPos(last_token), &pos);
+        }
+        e.storage()
+            .persistent()
+            .remove(&DataKey::OwnerTokenAt(owner.clone(), last));
+        e.storage()
+            .persistent()
+            .remove(&DataKey::OwnerTokenPos(token_id));
+        e.storage()
+            .persistent()
+            .set(&DataKey::OwnerBalance(owner.clone()), &last);
+    }
+
+    /// Read a page of an owner's token ids from either storage layout.
+    fn owner_token_page(e: &Env, owner: &Address, offset: u32, limit: u32) -> Vec<u32> {
+        let mut page = Vec::new(e);
+        let legacy: Option<Vec<u32>> = e
+            .storage()
+            .persistent()
+            .get(&DataKey::OwnerTokens(owner.clone()));
+        if let Some(tokens) = legacy {
+            let end = offset.saturating_add(limit).min(tokens.len());
+            for i in offset..end {
+                page.push_back(tokens.get_unchecked(i));
+            }
+            return page;
+        }
+
+        let count: u32 = e
+            .storage()
+            .persistent()
+            .get(&DataKey::OwnerBalance(owner.clone()))
+            .unwrap_or(0);
+        let end = offset.saturating_add(limit).min(count);
+        for i in offset..end {
+            if let Some(token_id) = e
+                .storage()
+                .persistent()
+                .get::<_, u32>(&DataKey::OwnerTokenAt(owner.clone(), i))
+            {
+                page.push_back(token_id);
+            }
+        }
+        page
+    }
+
+    /// Remove the single-token approval for `token_id` and its index entry.
+    fn clear_token_approval(e: &Env, owner: &Address, token_id: u32) {
+        if let Some(spender) = e
+            .storage()
+            .persistent()
+            .get::<_, Address>(&DataKey::Approved(token_id))
+        {
+            e.storage().persistent().remove(&DataKey::Approved(token_id));
+            Self::remove_grant(e, owner, &ApprovalGrant::Token(token_id, spender));
+        }
+    }
+
+    fn read_grants(e: &Env, owner: &Address) -> Vec<ApprovalGrant> {
+        e.storage()
+            .persistent()
+            .get(&DataKey::OwnerApprovals(owner.clone()))
+            .unwrap_or(Vec::new(e))
+    }
+
+    fn write_grants(e: &Env, owner: &Address, grants: &Vec<ApprovalGrant>) {
+        if grants.is_empty() {
+            e.storage()
+                .persistent()
+                .remove(&DataKey::OwnerApprovals(owner.clone()));
+        } else {
+            e.storage()
+                .persistent()
+                .set(&DataKey::OwnerApprovals(owner.clone()), grants);
+        }
+    }
+
+    fn remove_grant(e: &Env, owner: &Address, grant: &ApprovalGrant) {
+        let mut grants = Self::read_grants(e, owner);
+        if let Some(index) = grants.first_index_of(grant) {
+            grants.remove(index);
+            Self::write_grants(e, owner, &grants);
+        }
+    }
+
+    /// Transfer NFT to new owner
+    ///
+    /// # Reentrancy Protection
+    /// Uses checks-effects-interactions pattern. The only external call is
+    /// the ownership hook on core, made after the token has moved.
+    pub fn transfer(
+        e: Env,
+        from: Address,
+        to: Address,
+        token_id: u32,
+    ) -> Result<(), ContractError> {
+        // Reentrancy protection
+        let guard: bool = e
+            .storage()
+            .instance()
+            .get(&DataKey::ReentrancyGuard)
+            .unwrap_or(false);
+
+        if guard {
+            return Err(ContractError::ReentrancyDetected);
+        }
+        e.storage().instance().set(&DataKey::ReentrancyGuard, &true);
+        EmergencyControl::require_not_emergency(&e);
+
+        // CHECKS: Require authorization from the sender
+        from.require_auth();
+
+        // Get the NFT
+        let mut nft: CommitmentNFT = e
+            .storage()
+            .persistent()
+            .get(&DataKey::NFT(token_id))
+            .ok_or_else(|| {
+                e.storage()
+                    .instance()
+                    .set(&DataKey::ReentrancyGuard, &false);
+                ContractError::TokenNotFound
+            })?;
+
+        // Verify ownership
+        if nft.owner != from {
+            e.storage()
+                .instance()
+                .set(&DataKey::ReentrancyGuard, &false);
+            return Err(ContractError::NotOwner);
+        }
+
+        // Core locks commitments with funds still deployed to pools
+        if !Self::core_allows_transfer(&e, &nft) {
+            e.storage()
+                .instance()
+                .set(&DataKey::ReentrancyGuard, &false);
+            return Err(ContractError::TransferNotAllowed);
+        }
+
+        // EFFECTS: Update state
+        Self::move_token(&e, &mut nft, &from, &to);
+
+        // INTERACTIONS: keep core's commitment owner in sync
+        Self::notify_core_of_transfer(&e, token_id, &from, &to);
+
+        // Clear reentrancy guard
+        e.storage()
+            .instance()
+            .set(&DataKey::ReentrancyGuard, &false);
+
+        // Emit transfer event
+        e.events().publish(
+            (symbol_short!("Transfer"), from, to),
+            (token_id, e.ledger().timestamp()),
+        );
+
+        Ok(())
+    }
+
+    // ========================================================================
+    // Approvals
+    // ========================================================================
+
+    /// Approve `spender` to transfer `token_id` on the owner's behalf.
+    ///
+    /// Replaces any existing approval for the token. Emits
+    /// `("Approval", owner, spender)` with `token_id` as data.
+    pub fn approve(
+        e: Env,
+        owner: Address,
+        spender: Address,
+        token_id: u32,
+    ) -> Result<(), ContractError> {
+        owner.require_auth();
+        let nft: CommitmentNFT = e
+            .storage()
+            .persistent()
+            .get(&DataKey::NFT(token_id))
+            .ok_or(ContractError::TokenNotFound)?;
+        if nft.owner != owner {
+            return Err(ContractError::NotOwner);
+        }
+
+        Self::clear_token_approval(&e, &owner, token_id);
+        e.storage()
+            .persistent()
+            .set(&DataKey::Approved(token_id), &spender);
+        let mut grants = Self::read_grants(&e, &owner);
+        grants.push_back(ApprovalGrant::Token(token_id, spender.clone()));
+        Self::write_grants(&e, &owner, &grants);
+
+        e.events()
+            .publish((symbol_short!("Approval"), owner, spender), token_id);
+        Ok(())
+    }
+
+    /// Revoke the single-token approval for `token_id`, if any.
+    pub fn revoke_approval(e: Env, owner: Address, token_id: u32) -> Result<(), ContractError> {
+        owner.require_auth();
+        let nft: CommitmentNFT = e
+            .storage()
+            .persistent()
+            .get(&DataKey::NFT(token_id))
+            .ok_or(ContractError::TokenNotFound)?;
+        if nft.owner != owner {
+            return Err(ContractError::NotOwner);
+        }
+
+        if let Some(spender) = Self::get_approved(e.clone(), token_id) {
+            Self::clear_token_approval(&e, &owner, token_id);
+            e.events()
+                .publish((Symbol::new(&e, "ApprovalRevoked"), owner, spender), token_id);
+        }
+        Ok(())
+    }
+
+    /// Grant or revoke `operator` permission to transfer all of the owner's tokens.
+    ///
+    /// Emits `("ApprovalForAll", owner, operator)` with `approved` as data.
+    pub fn set_approval_for_all(e: Env, owner: Address, operator: Address, approved: bool) {
+        owner.require_auth();
+        let key = DataKey::OperatorApproval(owner.clone(), operator.clone());
+        let grant = ApprovalGrant::Operator(operator.clone());
+        let currently = e.storage().persistent().get(&key).unwrap_or(false);
+
+        if approved && !currently {
+            e.storage().persistent().set(&key, &true);
+            let mut grants = Self::read_grants(&e, &owner);
+            grants.push_back(grant);
+            Self::write_grants(&e, &owner, &grants);
+        } else if !approved && currently {
+            e.storage().persistent().remove(&key);
+            Self::remove_grant(&e, &owner, &grant);
+        }
+
+        e.events().publish(
+            (Symbol::new(&e, "ApprovalForAll"), owner, operator),
+            approved,
+        );
+    }
+
+    /// Get the address approved for `token_id`, if any
+    pub fn get_approved(e: Env, token_id: u32) -> Option<Address> {
+        e.storage().persistent().get(&DataKey::Approved(token_id))
+    }
+
+    /// Check whether `operator` may transfer all of `owner`'s tokens
+    pub fn is_approved_for_all(e: Env, owner: Address, operator: Address) -> bool {
+        e.storage()
+            .persistent()
+            .get(&DataKey::OperatorApproval(owner, operator))
+            .unwrap_or(false)
+    }
+
+    /// List a page of the approvals currently granted by `owner`
+    pub fn approvals_of_owner(
+        e: Env,
+        owner: Address,
+        offset: u32,
+        limit: u32,
+    ) -> Vec<ApprovalGrant> {
+        let grants = Self::read_grants(&e, &owner);
+        let mut page = Vec::new(&e);
+        let end = offset.saturating_add(limit).min(grants.len());
+        for i in offset..end {
+            page.push_back(grants.get_unchecked(i));
+        }
+        page
+    }
+
+    /// Transfer `token_id` from `from` to `to` as an approved spender or operator.
+    ///
+    /// The owner may also call this directly.
+    pub fn transfer_from(
+        e: Env,
+        spender: Address,
+        from: Address,
+        to: Address,
+        token_id: u32,
+    ) -> Result<(), ContractError> {
+        // Reentrancy protection
+        let guard: bool = e
+            .storage()
+            .instance()
+            .get(&DataKey::ReentrancyGuard)
+            .unwrap_or(false);
+
+        if guard {
+            return Err(ContractError::ReentrancyDetected);
+        }
+        e.storage().instance().set(&DataKey::ReentrancyGuard, &true);
+        EmergencyControl::require_not_emergency(&e);
+
+        // CHECKS: Require authorization from the spender
+        spender.require_auth();
+
+        let mut nft: CommitmentNFT = e
+            .storage()
+            .persistent()
+            .get(&DataKey::NFT(token_id))
+            .ok_or_else(|| {
+                e.storage()
+                    .instance()
+                    .set(&DataKey::ReentrancyGuard, &false);
+                ContractError::TokenNotFound
+            })?;
+
+        if nft.owner != from {
+            e.storage()
+                .instance()
+                .set(&DataKey::ReentrancyGuard, &false);
+            return Err(ContractError::NotOwner);
+        }
+
+        let authorized = spender == from
+            || Self::get_approved(e.clone(), token_id) == Some(spender.clone())
+            || Self::is_approved_for_all(e.clone(), from.clone(), spender.clone());
+        if !authorized {
+            e.storage()
+                .instance()
+                .set(&DataKey::ReentrancyGuard, &false);
+            return Err(ContractError::NotAuthorized);
+        }
+
+        if !Self::core_allows_transfer(&e, &nft) {
+            e.storage()
+                .instance()
+                .set(&DataKey::ReentrancyGuard, &false);
+            return Err(ContractError::TransferNotAllowed);
+        }
+
+        // EFFECTS: Update state
+        Self::move_token(&e, &mut nft, &from, &to);
+
+        // INTERACTIONS: keep core's commitment owner in sync
+        Self::notify_core_of_transfer(&e, token_id, &from, &to);
+
+        // Clear reentrancy guard
+        e.storage()
+            .instance()
+            .set(&DataKey::ReentrancyGuard, &false);
+
+        // Emit transfer event
+        e.events().publish(
+            (symbol_short!("Transfer"), from, to),
+            (token_id, e.ledger().timestamp()),
+        );
+
+        Ok(())
+    }
+
+    /// Check if NFT is active
+    pub fn is_active(e: Env, token_id: u32) -> Result<bool, ContractError> {
+        let nft: CommitmentNFT = e
+            .storage()
+            .persistent()
+            .get(&DataKey::NFT(token_id))
+            .ok_or(ContractError::TokenNotFound)?;
+
+        Ok(nft.is_active)
+    }
+
+    /// Get total supply of NFTs minted
+    pub fn total_supply(e: Env) -> u32 {
+        e.storage()
+            .instance()
+            .get(&DataKey::TokenCounter)
+            .unwrap_or(0)
+    }
+
+    /// Get NFT count for a specific owner
+    pub fn balance_of(e: Env, owner: Address) -> u32 {
+        e.storage()
             .persistent()
             .get(&DataKey::OwnerBalance(owner))
             .unwrap_or(0)
@@ -517,11 +1159,13 @@ impl CommitmentNFTContract {
 
     /// Get all NFTs metadata (for frontend)
     pub fn get_all_metadata(e: Env) -> Vec<CommitmentNFT> {
-        let token_ids: Vec<u32> = e
-            .storage()
-            .instance()
-            .get(&DataKey::TokenIds)
-            .unwrap_or(Vec::new(&e));
+        Self::get_all_metadata_page(e, 0, u32::MAX)
+    }
+
+    /// Get NFTs metadata for `limit` entries of the global token list from
+    /// `offset`, in mint order. Burned tokens in the window are skipped.
+    pub fn get_all_metadata_page(e: Env, offset: u32, limit: u32) -> Vec<CommitmentNFT> {
+        let token_ids = Self::token_id_range(&e, offset, limit);
 
         let mut nfts: Vec<CommitmentNFT> = Vec::new(&e);
 
@@ -538,13 +1182,22 @@ impl CommitmentNFTContract {
         nfts
     }
 
+    /// Length of the global token list, including burned ids
+    pub fn get_token_id_count(e: Env) -> u32 {
+        let legacy: Option<Vec<u32>> = e.storage().instance().get(&DataKey::TokenIds);
+        match legacy {
+            Some(tokens) => tokens.len(),
+            None => e
+                .storage()
+                .persistent()
+                .get(&DataKey::TokenIdCount)
+                .unwrap_or(0),
+        }
+    }
+
     /// Get all NFTs owned by a specific address
     pub fn get_nfts_by_owner(e: Env, owner: Address) -> Vec<CommitmentNFT> {
-        let token_ids: Vec<u32> = e
-            .storage()
-            .persistent()
-            .get(&DataKey::OwnerTokens(owner))
-            .unwrap_or(Vec::new(&e));
+        let token_ids = Self::owner_token_page(&e, &owner, 0, u32::MAX);
 
         let mut owned_nfts: Vec<CommitmentNFT> = Vec::new(&e);
 
@@ -561,16 +1214,44 @@ impl CommitmentNFTContract {
         owned_nfts
     }
 
+    /// Get a page of token ids owned by `owner`
+    pub fn get_owner_tokens(e: Env, owner: Address, offset: u32, limit: u32) -> Vec<u32> {
+        Self::owner_token_page(&e, &owner, offset, limit)
+    }
+
+    /// Migrate the legacy global `TokenIds` Vec into token id pages.
+    ///
+    /// Permissionless and idempotent; also runs automatically on the next
+    /// mint or burn. Returns the number of ids moved.
+    pub fn migrate_token_ids(e: Env) -> u32 {
+        Self::migrate_token_id_list(&e)
+    }
+
+    /// Migrate an owner's legacy Vec-based token list to the indexed layout.
+    ///
+    /// Permissionless and idempotent; owners are also migrated automatically
+    /// the first time they mint, send or receive a token.
+    pub fn migrate_owner_tokens(e: Env, owner: Address) {
+        Self::migrate_owner_index(&e, &owner);
+    }
+
     // ========================================================================
     // Settlement (Issue #5 - Main Implementation)
     // ========================================================================
 
     /// Mark NFT as settled (after maturity)
     ///
+    /// Idempotent for the core contract: settling an already-settled token
+    /// returns Ok and emits nothing, so a retried or racing core settlement
+    /// cannot fail on the NFT. Other callers get `AlreadySettled`. The core
+    /// contract may also settle before maturity (early exit).
+    ///
     /// # Reentrancy Protection
     /// Uses checks-effects-interactions pattern. This function only writes to storage
     /// and doesn't make external calls, but still protected for consistency.
-    pub fn settle(e: Env, token_id: u32) -> Result<(), ContractError> {
+    pub fn settle(e: Env, caller: Address, token_id: u32) -> Result<(), ContractError> {
+        caller.require_auth();
+
         // Reentrancy protection
         let guard: bool = e
             .storage()
@@ -596,17 +1277,23 @@ impl CommitmentNFTContract {
                 ContractError::TokenNotFound
             })?;
 
+        let core: Option<Address> = e.storage().instance().get(&DataKey::CoreContract);
+        let is_core = core == Some(caller);
+
         // Check if already settled
         if !nft.is_active {
             e.storage()
                 .instance()
                 .set(&DataKey::ReentrancyGuard, &false);
+            if is_core {
+                return Ok(());
+            }
             return Err(ContractError::AlreadySettled);
         }
 
         // Verify the commitment has expired
         let current_time = e.ledger().timestamp();
-        if current_time < nft.metadata.expires_at {
+        if current_time < nft.metadata.expires_at && !is_core {
             e.storage()
                 .instance()
                 .set(&DataKey::ReentrancyGuard, &false);
@@ -618,6 +1305,9 @@ impl CommitmentNFTContract {
         nft.is_active = false;
         e.storage().persistent().set(&DataKey::NFT(token_id), &nft);
 
+        // Settled tokens no longer carry a single-token approval
+        Self::clear_token_approval(&e, &nft.owner, token_id);
+
         // Clear reentrancy guard
         e.storage()
             .instance()
@@ -630,6 +1320,190 @@ impl CommitmentNFTContract {
         Ok(())
     }
 
+    /// Burn an NFT (core contract only)
+    ///
+    /// Used when core voids a commitment. Removes the token from its owner's
+    /// balance and token list and any approvals.
+    pub fn burn(e: Env, token_id: u32) -> Result<(), ContractError> {
+        let core: Address = e
+            .storage()
+            .instance()
+            .get(&DataKey::CoreContract)
+            .ok_or(ContractError::NotInitialized)?;
+        core.require_auth();
+
+        let nft: CommitmentNFT = e
+            .storage()
+            .persistent()
+            .get(&DataKey::NFT(token_id))
+            .ok_or(ContractError::TokenNotFound)?;
+        let owner = nft.owner;
+
+        Self::clear_token_approval(&e, &owner, token_id);
+        e.storage().persistent().remove(&DataKey::NFT(token_id));
+
+        Self::owner_index_remove(&e, &owner, token_id);
+
+        // The id stays in its page; readers skip tokens with no NFT record
+        Self::migrate_token_id_list(&e);
+
+        e.events().publish(
+            (symbol_short!("Burn"), token_id, owner),
+            e.ledger().timestamp(),
+        );
+
+        Ok(())
+    }
+
+    /// Push an active token's expiry out (core contract only)
+    ///
+    /// Used when core extends a commitment; mirrors its new duration and
+    /// expiry so the token does not become settleable early.
+    pub fn extend(
+        e: Env,
+        token_id: u32,
+        duration_days: u32,
+        expires_at: u64,
+    ) -> Result<(), ContractError> {
+        let core: Address = e
+            .storage()
+            .instance()
+            .get(&DataKey::CoreContract)
+            .ok_or(ContractError::NotInitialized)?;
+        core.require_auth();
+
+        let mut nft: CommitmentNFT = e
+            .storage()
+            .persistent()
+            .get(&DataKey::NFT(token_id))
+            .ok_or(ContractError::TokenNotFound)?;
+        if !nft.is_active {
+            return Err(ContractError::AlreadySettled);
+        }
+        let previous = nft.metadata.expires_at;
+        nft.metadata.duration_days = duration_days;
+        nft.metadata.expires_at = expires_at;
+        e.storage().persistent().set(&DataKey::NFT(token_id), &nft);
+
+        e.events().publish(
+            (symbol_short!("Extended"), token_id),
+            (previous, expires_at),
+        );
+
+        Ok(())
+    }
+
+    // ========================================================================
+    // Compliance Badges
+    // ========================================================================
+
+    /// Set the address allowed to mint badges (admin only)
+    pub fn set_badge_minter(e: Env, minter: Address) -> Result<(), ContractError> {
+        let admin: Address = e
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(ContractError::NotInitialized)?;
+        admin.require_auth();
+
+        e.storage().instance().set(&DataKey::BadgeMinter, &minter);
+        e.events()
+            .publish((Symbol::new(&e, "BadgeMinterSet"),), (minter,));
+
+        Ok(())
+    }
+
+    /// Get the badge minter address
+    pub fn get_badge_minter(e: Env) -> Result<Address, ContractError> {
+        e.storage()
+            .instance()
+            .get(&DataKey::BadgeMinter)
+            .ok_or(ContractError::NotInitialized)
+    }
+
+    /// Mint a soulbound compliance badge (badge minter only)
+    ///
+    /// At most one badge is minted per commitment.
+    pub fn mint_badge(
+        e: Env,
+        owner: Address,
+        commitment_id: String,
+        compliance_score: u32,
+        duration_days: u32,
+    ) -> Result<u32, ContractError> {
+        let minter = Self::get_badge_minter(e.clone())?;
+        minter.require_auth();
+        EmergencyControl::require_not_emergency(&e);
+
+        if e
+            .storage()
+            .persistent()
+            .has(&DataKey::CommitmentBadge(commitment_id.clone()))
+        {
+            return Err(ContractError::BadgeAlreadyIssued);
+        }
+
+        let badge_id: u32 = e
+            .storage()
+            .instance()
+            .get(&DataKey::BadgeCounter)
+            .unwrap_or(0);
+        e.storage()
+            .instance()
+            .set(&DataKey::BadgeCounter, &(badge_id + 1));
+
+        let badge = CommitmentBadge {
+            owner: owner.clone(),
+            badge_id,
+            metadata: BadgeMetadata {
+                commitment_id: commitment_id.clone(),
+                compliance_score,
+                duration_days,
+                issued_at: e.ledger().timestamp(),
+            },
+        };
+        e.storage().persistent().set(&DataKey::Badge(badge_id), &badge);
+        e.storage()
+            .persistent()
+            .set(&DataKey::CommitmentBadge(commitment_id.clone()), &badge_id);
+
+        let mut owner_badges = Self::badges_of_owner(e.clone(), owner.clone());
+        owner_badges.push_back(badge_id);
+        e.storage()
+            .persistent()
+            .set(&DataKey::OwnerBadges(owner.clone()), &owner_badges);
+
+        e.events().publish(
+            (symbol_short!("BadgeMint"), badge_id, owner),
+            (commitment_id, compliance_score),
+        );
+
+        Ok(badge_id)
+    }
+
+    /// Get a badge by id
+    pub fn get_badge(e: Env, badge_id: u32) -> Result<CommitmentBadge, ContractError> {
+        e.storage()
+            .persistent()
+            .get(&DataKey::Badge(badge_id))
+            .ok_or(ContractError::BadgeNotFound)
+    }
+
+    /// Get the badge minted for a commitment, if any
+    pub fn badge_of_commitment(e: Env, commitment_id: String) -> Option<u32> {
+        e.storage()
+            .persistent()
+            .get(&DataKey::CommitmentBadge(commitment_id))
+    }
+
+    /// Get the badge ids held by an owner
+    pub fn badges_of_owner(e: Env, owner: Address) -> Vec<u32> {
+        e.storage()
+            .persistent()
+            .get(&DataKey::OwnerBadges(owner))
+            .unwrap_or(Vec::new(&e))
+    }
+
     /// Check if an NFT has expired (based on time)
     pub fn is_expired(e: Env, token_id: u32) -> Result<bool, ContractError> {
         let nft: CommitmentNFT = e
@@ -6,7 +6,7 @@ use crate::*;
 use soroban_sdk::{
     symbol_short,
     testutils::{Address as _, Events, Ledger},
-    vec, Address, Env, IntoVal, String,
+    vec, Address, Env, IntoVal, String, Vec,
 };
 
 fn setup_contract(e: &Env) -> (Address, CommitmentNFTContractClient<'_>) {
@@ -19,12 +19,12 @@ fn setup_contract(e: &Env) -> (Address, CommitmentNFTContractClient<'_>) {
 fn create_test_metadata(
     e: &Env,
     asset_address: &Address,
-) -> (String, u32, u32, String, i128, Address, u32) {
+) -> (String, u32, u32, RiskProfile, i128, Address, u32) {
     (
         String::from_str(e, "commitment_001"),
         30, // duration_days
         10, // max_loss_percent
-        String::from_str(e, "balanced"),
+        RiskProfile::Balanced,
         1000, // initial_amount
         asset_address.clone(),
         5, // early_exit_penalty
@@ -81,6 +81,7 @@ fn test_initialize_twice_fails() {
 #[test]
 fn test_mint() {
     let e = Env::default();
+    e.mock_all_auths();
     let (admin, client) = setup_contract(&e);
     let owner = Address::generate(&e);
     let asset_address = Address::generate(&e);
@@ -91,6 +92,7 @@ fn test_mint() {
         create_test_metadata(&e, &asset_address);
 
     let token_id = client.mint(
+        &admin,
         &owner,
         &commitment_id,
         &duration,
@@ -101,7 +103,7 @@ fn test_mint() {
         &penalty,
     );
 
-    assert_eq!(token_id, 0);
+    assert_eq!(token_id, 1);
     assert_eq!(client.total_supply(), 1);
     assert_eq!(client.balance_of(&owner), 1);
 
@@ -126,6 +128,7 @@ fn test_mint() {
 #[test]
 fn test_mint_multiple() {
     let e = Env::default();
+    e.mock_all_auths();
     let (admin, client) = setup_contract(&e);
     let owner = Address::generate(&e);
     let asset_address = Address::generate(&e);
@@ -134,40 +137,43 @@ fn test_mint_multiple() {
 
     // Mint 3 NFTs
     let token_id_0 = client.mint(
+        &admin,
         &owner,
         &String::from_str(&e, "commitment_0"),
         &30,
         &10,
-        &String::from_str(&e, "balanced"),
+        &RiskProfile::Balanced,
         &1000,
         &asset_address,
         &5,
     );
-    assert_eq!(token_id_0, 0);
+    assert_eq!(token_id_0, 1);
 
     let token_id_1 = client.mint(
+        &admin,
         &owner,
         &String::from_str(&e, "commitment_1"),
         &30,
         &10,
-        &String::from_str(&e, "balanced"),
+        &RiskProfile::Balanced,
         &1000,
         &asset_address,
         &5,
     );
-    assert_eq!(token_id_1, 1);
+    assert_eq!(token_id_1, 2);
 
     let token_id_2 = client.mint(
+        &admin,
         &owner,
         &String::from_str(&e, "commitment_2"),
         &30,
         &10,
-        &String::from_str(&e, "balanced"),
+        &RiskProfile::Balanced,
         &1000,
         &asset_address,
         &5,
     );
-    assert_eq!(token_id_2, 2);
+    assert_eq!(token_id_2, 3);
 
     assert_eq!(client.total_supply(), 3);
     assert_eq!(client.balance_of(&owner), 3);
@@ -177,7 +183,8 @@ fn test_mint_multiple() {
 #[should_panic(expected = "Error(Contract, #1)")] // NotInitialized
 fn test_mint_without_initialize_fails() {
     let e = Env::default();
-    let (_admin, client) = setup_contract(&e);
+    e.mock_all_auths();
+    let (admin, client) = setup_contract(&e);
     let owner = Address::generate(&e);
     let asset_address = Address::generate(&e);
 
@@ -185,6 +192,7 @@ fn test_mint_without_initialize_fails() {
         create_test_metadata(&e, &asset_address);
 
     client.mint(
+        &admin,
         &owner,
         &commitment_id,
         &duration,
@@ -203,6 +211,7 @@ fn test_mint_without_initialize_fails() {
 #[test]
 fn test_get_metadata() {
     let e = Env::default();
+    e.mock_all_auths();
     let (admin, client) = setup_contract(&e);
     let owner = Address::generate(&e);
     let asset_address = Address::generate(&e);
@@ -212,10 +221,11 @@ fn test_get_metadata() {
     let commitment_id = String::from_str(&e, "test_commitment");
     let duration = 30u32;
     let max_loss = 15u32;
-    let commitment_type = String::from_str(&e, "aggressive");
+    let commitment_type = RiskProfile::Aggressive;
     let amount = 5000i128;
 
     let token_id = client.mint(
+        &admin,
         &owner,
         &commitment_id,
         &duration,
@@ -257,6 +267,7 @@ fn test_get_metadata_nonexistent_token() {
 #[test]
 fn test_owner_of() {
     let e = Env::default();
+    e.mock_all_auths();
     let (admin, client) = setup_contract(&e);
     let owner = Address::generate(&e);
     let asset_address = Address::generate(&e);
@@ -267,6 +278,7 @@ fn test_owner_of() {
         create_test_metadata(&e, &asset_address);
 
     let token_id = client.mint(
+        &admin,
         &owner,
         &commitment_id,
         &duration,
@@ -299,6 +311,7 @@ fn test_owner_of_nonexistent_token() {
 #[test]
 fn test_is_active() {
     let e = Env::default();
+    e.mock_all_auths();
     let (admin, client) = setup_contract(&e);
     let owner = Address::generate(&e);
     let asset_address = Address::generate(&e);
@@ -309,6 +322,7 @@ fn test_is_active() {
         create_test_metadata(&e, &asset_address);
 
     let token_id = client.mint(
+        &admin,
         &owner,
         &commitment_id,
         &duration,
@@ -320,7 +334,7 @@ fn test_is_active() {
     );
 
     // Newly minted NFT should be active
-    assert_eq!(client.is_active(&token_id), true);
+    assert!(client.is_active(&token_id));
 }
 
 #[test]
@@ -351,6 +365,7 @@ fn test_total_supply_initial() {
 #[test]
 fn test_total_supply_after_minting() {
     let e = Env::default();
+    e.mock_all_auths();
     let (admin, client) = setup_contract(&e);
     let owner = Address::generate(&e);
     let asset_address = Address::generate(&e);
@@ -360,11 +375,12 @@ fn test_total_supply_after_minting() {
     // Mint 5 NFTs
     for _ in 0..5 {
         client.mint(
+            &admin,
             &owner,
             &String::from_str(&e, "commitment"),
             &30,
             &10,
-            &String::from_str(&e, "safe"),
+            &RiskProfile::Safe,
             &1000,
             &asset_address,
             &5,
@@ -393,6 +409,7 @@ fn test_balance_of_initial() {
 #[test]
 fn test_balance_of_after_minting() {
     let e = Env::default();
+    e.mock_all_auths();
     let (admin, client) = setup_contract(&e);
     let owner1 = Address::generate(&e);
     let owner2 = Address::generate(&e);
@@ -403,11 +420,12 @@ fn test_balance_of_after_minting() {
     // Mint 3 NFTs for owner1
     for _ in 0..3 {
         client.mint(
+            &admin,
             &owner1,
             &String::from_str(&e, "owner1_commitment"),
             &30,
             &10,
-            &String::from_str(&e, "safe"),
+            &RiskProfile::Safe,
             &1000,
             &asset_address,
             &5,
@@ -417,11 +435,12 @@ fn test_balance_of_after_minting() {
     // Mint 2 NFTs for owner2
     for _ in 0..2 {
         client.mint(
+            &admin,
             &owner2,
             &String::from_str(&e, "owner2_commitment"),
             &30,
             &10,
-            &String::from_str(&e, "safe"),
+            &RiskProfile::Safe,
             &1000,
             &asset_address,
             &5,
@@ -450,6 +469,7 @@ fn test_get_all_metadata_empty() {
 #[test]
 fn test_get_all_metadata() {
     let e = Env::default();
+    e.mock_all_auths();
     let (admin, client) = setup_contract(&e);
     let owner = Address::generate(&e);
     let asset_address = Address::generate(&e);
@@ -459,11 +479,12 @@ fn test_get_all_metadata() {
     // Mint 3 NFTs
     for _ in 0..3 {
         client.mint(
+            &admin,
             &owner,
             &String::from_str(&e, "commitment"),
             &30,
             &10,
-            &String::from_str(&e, "balanced"),
+            &RiskProfile::Balanced,
             &1000,
             &asset_address,
             &5,
@@ -498,6 +519,7 @@ fn test_get_nfts_by_owner_empty() {
 #[test]
 fn test_get_nfts_by_owner() {
     let e = Env::default();
+    e.mock_all_auths();
     let (admin, client) = setup_contract(&e);
     let owner1 = Address::generate(&e);
     let owner2 = Address::generate(&e);
@@ -508,11 +530,12 @@ fn test_get_nfts_by_owner() {
     // Mint 2 NFTs for owner1
     for _ in 0..2 {
         client.mint(
+            &admin,
             &owner1,
             &String::from_str(&e, "owner1"),
             &30,
             &10,
-            &String::from_str(&e, "safe"),
+            &RiskProfile::Safe,
             &1000,
             &asset_address,
             &5,
@@ -522,11 +545,12 @@ fn test_get_nfts_by_owner() {
     // Mint 3 NFTs for owner2
     for _ in 0..3 {
         client.mint(
+            &admin,
             &owner2,
             &String::from_str(&e, "owner2"),
             &30,
             &10,
-            &String::from_str(&e, "safe"),
+            &RiskProfile::Safe,
             &1000,
             &asset_address,
             &5,
@@ -578,6 +602,7 @@ fn test_transfer() {
         create_test_metadata(&e, &asset_address);
 
     let token_id = client.mint(
+        &admin,
         &owner1,
         &commitment_id,
         &duration,
@@ -637,6 +662,7 @@ fn test_transfer_not_owner() {
         create_test_metadata(&e, &asset_address);
 
     let token_id = client.mint(
+        &admin,
         &owner,
         &commitment_id,
         &duration,
@@ -673,6 +699,7 @@ fn test_transfer_nonexistent_token() {
 #[test]
 fn test_settle() {
     let e = Env::default();
+    e.mock_all_auths();
     let (admin, client) = setup_contract(&e);
     let owner = Address::generate(&e);
     let asset_address = Address::generate(&e);
@@ -681,18 +708,19 @@ fn test_settle() {
 
     // Mint with 1 day duration
     let token_id = client.mint(
+        &admin,
         &owner,
         &String::from_str(&e, "test_commitment"),
         &1, // 1 day duration
         &10,
-        &String::from_str(&e, "safe"),
+        &RiskProfile::Safe,
         &1000,
         &asset_address,
         &5,
     );
 
     // NFT should be active initially
-    assert_eq!(client.is_active(&token_id), true);
+    assert!(client.is_active(&token_id));
 
     // Fast forward time past expiration (2 days = 172800 seconds)
     e.ledger().with_mut(|li| {
@@ -700,13 +728,13 @@ fn test_settle() {
     });
 
     // Verify it's expired
-    assert_eq!(client.is_expired(&token_id), true);
+    assert!(client.is_expired(&token_id));
 
     // Settle the NFT
-    client.settle(&token_id);
+    client.settle(&admin, &token_id);
 
     // NFT should now be inactive
-    assert_eq!(client.is_active(&token_id), false);
+    assert!(!client.is_active(&token_id));
 
     // Verify Settle event
     let events = e.events().all();
@@ -729,6 +757,7 @@ fn test_settle() {
 #[should_panic(expected = "Error(Contract, #9)")] // NotExpired
 fn test_settle_not_expired() {
     let e = Env::default();
+    e.mock_all_auths();
     let (admin, client) = setup_contract(&e);
     let owner = Address::generate(&e);
     let asset_address = Address::generate(&e);
@@ -736,24 +765,26 @@ fn test_settle_not_expired() {
     client.initialize(&admin);
 
     let token_id = client.mint(
+        &admin,
         &owner,
         &String::from_str(&e, "test_commitment"),
         &30, // 30 days duration
         &10,
-        &String::from_str(&e, "safe"),
+        &RiskProfile::Safe,
         &1000,
         &asset_address,
         &5,
     );
 
     // Try to settle before expiration (should fail)
-    client.settle(&token_id);
+    client.settle(&admin, &token_id);
 }
 
 #[test]
 #[should_panic(expected = "Error(Contract, #8)")] // AlreadySettled
 fn test_settle_already_settled() {
     let e = Env::default();
+    e.mock_all_auths();
     let (admin, client) = setup_contract(&e);
     let owner = Address::generate(&e);
     let asset_address = Address::generate(&e);
@@ -761,11 +792,12 @@ fn test_settle_already_settled() {
     client.initialize(&admin);
 
     let token_id = client.mint(
+        &admin,
         &owner,
         &String::from_str(&e, "test_commitment"),
         &1,
         &10,
-        &String::from_str(&e, "safe"),
+        &RiskProfile::Safe,
         &1000,
         &asset_address,
         &5,
@@ -776,8 +808,56 @@ fn test_settle_already_settled() {
         li.timestamp = 172800;
     });
 
-    client.settle(&token_id);
-    client.settle(&token_id); // Should fail
+    client.settle(&admin, &token_id);
+    client.settle(&admin, &token_id); // Should fail
+}
+
+#[test]
+fn test_core_contract_settles_before_expiry() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (admin, client) = setup_contract(&e);
+    client.initialize(&admin);
+    let owner = Address::generate(&e);
+    let token_id = mint_for(&e, &client, &owner);
+    let core = Address::generate(&e);
+    client.set_core_contract(&core);
+
+    assert_eq!(
+        client.try_settle(&admin, &token_id),
+        Err(Ok(ContractError::NotExpired))
+    );
+    client.settle(&core, &token_id);
+    assert!(!client.is_active(&token_id));
+}
+
+#[test]
+fn test_settle_idempotent_for_core_contract() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (admin, client) = setup_contract(&e);
+    client.initialize(&admin);
+    let owner = Address::generate(&e);
+    let token_id = mint_for(&e, &client, &owner);
+    let core = Address::generate(&e);
+    client.set_core_contract(&core);
+
+    e.ledger().with_mut(|l| {
+        l.timestamp += 31 * 86400;
+    });
+    client.settle(&core, &token_id);
+    assert!(!client.is_active(&token_id));
+
+    // A repeated core notification is a silent no-op
+    let events_before = e.events().all().len();
+    client.settle(&core, &token_id);
+    assert_eq!(e.events().all().len(), events_before);
+
+    // Anyone else still gets the strict error
+    assert_eq!(
+        client.try_settle(&admin, &token_id),
+        Err(Ok(ContractError::AlreadySettled))
+    );
 }
 
 // ============================================
@@ -787,6 +867,7 @@ fn test_settle_already_settled() {
 #[test]
 fn test_is_expired() {
     let e = Env::default();
+    e.mock_all_auths();
     let (admin, client) = setup_contract(&e);
     let owner = Address::generate(&e);
     let asset_address = Address::generate(&e);
@@ -794,18 +875,19 @@ fn test_is_expired() {
     client.initialize(&admin);
 
     let token_id = client.mint(
+        &admin,
         &owner,
         &String::from_str(&e, "test_commitment"),
         &1, // 1 day
         &10,
-        &String::from_str(&e, "safe"),
+        &RiskProfile::Safe,
         &1000,
         &asset_address,
         &5,
     );
 
     // Should not be expired initially
-    assert_eq!(client.is_expired(&token_id), false);
+    assert!(!client.is_expired(&token_id));
 
     // Fast forward 2 days
     e.ledger().with_mut(|li| {
@@ -813,7 +895,7 @@ fn test_is_expired() {
     });
 
     // Should now be expired
-    assert_eq!(client.is_expired(&token_id), true);
+    assert!(client.is_expired(&token_id));
 }
 
 #[test]
@@ -834,6 +916,7 @@ fn test_is_expired_nonexistent_token() {
 #[test]
 fn test_token_exists() {
     let e = Env::default();
+    e.mock_all_auths();
     let (admin, client) = setup_contract(&e);
     let owner = Address::generate(&e);
     let asset_address = Address::generate(&e);
@@ -841,12 +924,13 @@ fn test_token_exists() {
     client.initialize(&admin);
 
     // Token 0 should not exist yet
-    assert_eq!(client.token_exists(&0), false);
+    assert!(!client.token_exists(&0));
 
     let (commitment_id, duration, max_loss, commitment_type, amount, asset, penalty) =
         create_test_metadata(&e, &asset_address);
 
     let token_id = client.mint(
+        &admin,
         &owner,
         &commitment_id,
         &duration,
@@ -858,10 +942,10 @@ fn test_token_exists() {
     );
 
     // Token should now exist
-    assert_eq!(client.token_exists(&token_id), true);
+    assert!(client.token_exists(&token_id));
 
     // Non-existent token should return false
-    assert_eq!(client.token_exists(&999), false);
+    assert!(!client.token_exists(&999));
 }
 
 // ============================================
@@ -894,6 +978,7 @@ fn test_get_admin_not_initialized() {
 #[test]
 fn test_metadata_timestamps() {
     let e = Env::default();
+    e.mock_all_auths();
 
     // Set initial ledger timestamp
     e.ledger().with_mut(|li| {
@@ -907,11 +992,12 @@ fn test_metadata_timestamps() {
     client.initialize(&admin);
 
     let token_id = client.mint(
+        &admin,
         &owner,
         &String::from_str(&e, "test"),
         &30, // 30 days
         &10,
-        &String::from_str(&e, "safe"),
+        &RiskProfile::Safe,
         &1000,
         &asset_address,
         &5,
@@ -939,31 +1025,34 @@ fn test_balance_updates_after_transfer() {
 
     // Mint multiple NFTs for owner1
     client.mint(
+        &admin,
         &owner1,
         &String::from_str(&e, "commitment_0"),
         &30,
         &10,
-        &String::from_str(&e, "safe"),
+        &RiskProfile::Safe,
         &1000,
         &asset_address,
         &5,
     );
     client.mint(
+        &admin,
         &owner1,
         &String::from_str(&e, "commitment_1"),
         &30,
         &10,
-        &String::from_str(&e, "safe"),
+        &RiskProfile::Safe,
         &1000,
         &asset_address,
         &5,
     );
     client.mint(
+        &admin,
         &owner1,
         &String::from_str(&e, "commitment_2"),
         &30,
         &10,
-        &String::from_str(&e, "safe"),
+        &RiskProfile::Safe,
         &1000,
         &asset_address,
         &5,
@@ -973,13 +1062,13 @@ fn test_balance_updates_after_transfer() {
     assert_eq!(client.balance_of(&owner2), 0);
 
     // Transfer one NFT
-    client.transfer(&owner1, &owner2, &0);
+    client.transfer(&owner1, &owner2, &1);
 
     assert_eq!(client.balance_of(&owner1), 2);
     assert_eq!(client.balance_of(&owner2), 1);
 
     // Transfer another
-    client.transfer(&owner1, &owner2, &1);
+    client.transfer(&owner1, &owner2, &2);
 
     assert_eq!(client.balance_of(&owner1), 1);
     assert_eq!(client.balance_of(&owner2), 2);
@@ -991,3 +1080,856 @@ fn test_balance_updates_after_transfer() {
     assert_eq!(owner1_nfts.len(), 1);
     assert_eq!(owner2_nfts.len(), 2);
 }
+
+// ============================================================================
+// Approval Tests
+// ============================================================================
+
+fn mint_for(e: &Env, client: &CommitmentNFTContractClient, owner: &Address) -> u32 {
+    let asset_address = Address::generate(e);
+    let (commitment_id, duration, max_loss, commitment_type, amount, asset, penalty) =
+        create_test_metadata(e, &asset_address);
+    client.mint(
+        &client.get_admin(),
+        owner,
+        &commitment_id,
+        &duration,
+        &max_loss,
+        &commitment_type,
+        &amount,
+        &asset,
+        &penalty,
+    )
+}
+
+#[test]
+fn test_approve_emits_event_and_getter() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (admin, client) = setup_contract(&e);
+    client.initialize(&admin);
+    let owner = Address::generate(&e);
+    let spender = Address::generate(&e);
+    let token_id = mint_for(&e, &client, &owner);
+
+    assert_eq!(client.get_approved(&token_id), None);
+    client.approve(&owner, &spender, &token_id);
+    assert_eq!(client.get_approved(&token_id), Some(spender.clone()));
+
+    let events = e.events().all();
+    let last_event = events.last().unwrap();
+    assert_eq!(
+        last_event.1,
+        vec![
+            &e,
+            symbol_short!("Approval").into_val(&e),
+            owner.into_val(&e),
+            spender.into_val(&e)
+        ]
+    );
+    let data: u32 = last_event.2.into_val(&e);
+    assert_eq!(data, token_id);
+}
+
+#[test]
+fn test_set_approval_for_all() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (admin, client) = setup_contract(&e);
+    client.initialize(&admin);
+    let owner = Address::generate(&e);
+    let operator = Address::generate(&e);
+
+    assert!(!client.is_approved_for_all(&owner, &operator));
+    client.set_approval_for_all(&owner, &operator, &true);
+    assert!(client.is_approved_for_all(&owner, &operator));
+
+    let events = e.events().all();
+    let last_event = events.last().unwrap();
+    assert_eq!(
+        last_event.1,
+        vec![
+            &e,
+            Symbol::new(&e, "ApprovalForAll").into_val(&e),
+            owner.into_val(&e),
+            operator.into_val(&e)
+        ]
+    );
+
+    client.set_approval_for_all(&owner, &operator, &false);
+    assert!(!client.is_approved_for_all(&owner, &operator));
+    assert_eq!(client.approvals_of_owner(&owner, &0, &10).len(), 0);
+}
+
+#[test]
+fn test_approvals_index_revoked_on_transfer() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (admin, client) = setup_contract(&e);
+    client.initialize(&admin);
+    let owner = Address::generate(&e);
+    let market = Address::generate(&e);
+    let other = Address::generate(&e);
+    let operator = Address::generate(&e);
+    let buyer = Address::generate(&e);
+
+    let t0 = mint_for(&e, &client, &owner);
+    let t1 = mint_for(&e, &client, &owner);
+    let t2 = mint_for(&e, &client, &owner);
+    client.approve(&owner, &market, &t0);
+    client.approve(&owner, &other, &t1);
+    client.approve(&owner, &market, &t2);
+    client.set_approval_for_all(&owner, &operator, &true);
+
+    assert_eq!(
+        client.approvals_of_owner(&owner, &0, &10),
+        vec![
+            &e,
+            ApprovalGrant::Token(t0, market.clone()),
+            ApprovalGrant::Token(t1, other.clone()),
+            ApprovalGrant::Token(t2, market.clone()),
+            ApprovalGrant::Operator(operator.clone()),
+        ]
+    );
+    assert_eq!(
+        client.approvals_of_owner(&owner, &1, &2),
+        vec![
+            &e,
+            ApprovalGrant::Token(t1, other.clone()),
+            ApprovalGrant::Token(t2, market.clone()),
+        ]
+    );
+
+    // Marketplace sells t0: its approval is revoked automatically
+    client.transfer_from(&market, &owner, &buyer, &t0);
+    assert_eq!(client.owner_of(&t0), buyer);
+    assert_eq!(client.get_approved(&t0), None);
+    assert_eq!(
+        client.approvals_of_owner(&owner, &0, &10),
+        vec![
+            &e,
+            ApprovalGrant::Token(t1, other.clone()),
+            ApprovalGrant::Token(t2, market.clone()),
+            ApprovalGrant::Operator(operator.clone()),
+        ]
+    );
+    assert_eq!(client.approvals_of_owner(&buyer, &0, &10).len(), 0);
+
+    // Operator can move any remaining token
+    client.transfer_from(&operator, &owner, &buyer, &t1);
+    assert_eq!(
+        client.approvals_of_owner(&owner, &0, &10),
+        vec![
+            &e,
+            ApprovalGrant::Token(t2, market),
+            ApprovalGrant::Operator(operator),
+        ]
+    );
+
+    // Explicit revoke
+    client.revoke_approval(&owner, &t2);
+    assert_eq!(client.get_approved(&t2), None);
+    assert_eq!(client.approvals_of_owner(&owner, &0, &10).len(), 1);
+}
+
+#[test]
+fn test_settle_clears_approval() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (admin, client) = setup_contract(&e);
+    client.initialize(&admin);
+    let owner = Address::generate(&e);
+    let spender = Address::generate(&e);
+    let token_id = mint_for(&e, &client, &owner);
+    client.approve(&owner, &spender, &token_id);
+
+    e.ledger().with_mut(|l| {
+        l.timestamp += 31 * 86400;
+    });
+    client.settle(&admin, &token_id);
+
+    assert_eq!(client.get_approved(&token_id), None);
+    assert_eq!(client.approvals_of_owner(&owner, &0, &10).len(), 0);
+}
+
+#[test]
+fn test_transfer_from_unapproved_spender_fails() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (admin, client) = setup_contract(&e);
+    client.initialize(&admin);
+    let owner = Address::generate(&e);
+    let stranger = Address::generate(&e);
+    let token_id = mint_for(&e, &client, &owner);
+
+    let result = client.try_transfer_from(&stranger, &owner, &stranger, &token_id);
+    assert_eq!(result, Err(Ok(ContractError::NotAuthorized)));
+}
+
+#[test]
+fn test_approve_requires_owner() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (admin, client) = setup_contract(&e);
+    client.initialize(&admin);
+    let owner = Address::generate(&e);
+    let stranger = Address::generate(&e);
+    let token_id = mint_for(&e, &client, &owner);
+
+    let result = client.try_approve(&stranger, &stranger, &token_id);
+    assert_eq!(result, Err(Ok(ContractError::NotOwner)));
+}
+
+// ============================================================================
+// Burn Tests
+// ============================================================================
+
+#[test]
+fn test_burn_by_core_contract() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (admin, client) = setup_contract(&e);
+    client.initialize(&admin);
+    let owner = Address::generate(&e);
+    let spender = Address::generate(&e);
+    let token_id = mint_for(&e, &client, &owner);
+    let kept = mint_for(&e, &client, &owner);
+    let core = Address::generate(&e);
+    client.set_core_contract(&core);
+    client.approve(&owner, &spender, &token_id);
+
+    client.burn(&token_id);
+
+    assert!(!client.token_exists(&token_id));
+    assert_eq!(client.balance_of(&owner), 1);
+    assert_eq!(client.get_nfts_by_owner(&owner).len(), 1);
+    assert_eq!(client.get_all_metadata().len(), 1);
+    assert_eq!(client.get_approved(&token_id), None);
+    assert_eq!(client.approvals_of_owner(&owner, &0, &10).len(), 0);
+    assert!(client.token_exists(&kept));
+}
+
+#[test]
+fn test_extend_by_core_contract() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (admin, client) = setup_contract(&e);
+    client.initialize(&admin);
+    let owner = Address::generate(&e);
+    let token_id = mint_for(&e, &client, &owner);
+    client.set_core_contract(&Address::generate(&e));
+
+    let expires_at = client.get_metadata(&token_id).metadata.expires_at;
+    client.extend(&token_id, &60, &(expires_at + 30 * 86400));
+    let metadata = client.get_metadata(&token_id).metadata;
+    assert_eq!(metadata.duration_days, 60);
+    assert_eq!(metadata.expires_at, expires_at + 30 * 86400);
+
+    e.ledger().with_mut(|l| l.timestamp = expires_at);
+    assert!(!client.is_expired(&token_id));
+    assert_eq!(
+        client.try_extend(&99, &60, &expires_at),
+        Err(Ok(ContractError::TokenNotFound))
+    );
+}
+
+#[test]
+fn test_burn_requires_core_contract() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (admin, client) = setup_contract(&e);
+    client.initialize(&admin);
+    let owner = Address::generate(&e);
+    let token_id = mint_for(&e, &client, &owner);
+
+    assert_eq!(
+        client.try_burn(&token_id),
+        Err(Ok(ContractError::NotInitialized))
+    );
+}
+
+// ============================================
+// Owner token index
+// ============================================
+
+#[test]
+fn test_get_owner_tokens_paginated() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (admin, client) = setup_contract(&e);
+    client.initialize(&admin);
+    let owner = Address::generate(&e);
+    let first = mint_for(&e, &client, &owner);
+    let second = mint_for(&e, &client, &owner);
+    let third = mint_for(&e, &client, &owner);
+
+    assert_eq!(client.get_owner_tokens(&owner, &0, &2), vec![&e, first, second]);
+    assert_eq!(client.get_owner_tokens(&owner, &2, &2), vec![&e, third]);
+    assert_eq!(client.get_owner_tokens(&owner, &5, &2).len(), 0);
+
+    // Removing from the middle swaps the last token into the hole
+    client.transfer(&owner, &Address::generate(&e), &first);
+    assert_eq!(client.get_owner_tokens(&owner, &0, &10), vec![&e, third, second]);
+    assert_eq!(client.balance_of(&owner), 2);
+}
+
+#[test]
+fn test_migrate_legacy_owner_tokens() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (admin, client) = setup_contract(&e);
+    client.initialize(&admin);
+    let owner = Address::generate(&e);
+    let recipient = Address::generate(&e);
+    let first = mint_for(&e, &client, &owner);
+    let second = mint_for(&e, &client, &owner);
+
+    // Rewrite the owner's entries into the pre-index Vec layout
+    e.as_contract(&client.address, || {
+        let storage = e.storage().persistent();
+        for i in 0..2u32 {
+            storage.remove(&DataKey::OwnerTokenAt(owner.clone(), i));
+        }
+        storage.remove(&DataKey::OwnerTokenPos(first));
+        storage.remove(&DataKey::OwnerTokenPos(second));
+        storage.set(&DataKey::OwnerTokens(owner.clone()), &vec![&e, first, second]);
+    });
+
+    // Views read the legacy layout before migration
+    assert_eq!(client.get_owner_tokens(&owner, &0, &10), vec![&e, first, second]);
+
+    // First write migrates lazily
+    client.transfer(&owner, &recipient, &first);
+    assert_eq!(client.get_owner_tokens(&owner, &0, &10), vec![&e, second]);
+    assert_eq!(client.balance_of(&owner), 1);
+    e.as_contract(&client.address, || {
+        assert!(!e
+            .storage()
+            .persistent()
+            .has(&DataKey::OwnerTokens(owner.clone())));
+    });
+
+    // Explicit migration is idempotent
+    client.migrate_owner_tokens(&owner);
+    assert_eq!(client.get_owner_tokens(&owner, &0, &10), vec![&e, second]);
+}
+
+#[test]
+fn test_transfer_cost_independent_of_owner_balance() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (admin, client) = setup_contract(&e);
+    client.initialize(&admin);
+    let whale = Address::generate(&e);
+    let minnow = Address::generate(&e);
+    let recipient = Address::generate(&e);
+
+    let mut whale_token = 0;
+    for i in 0..100 {
+        let token_id = mint_for(&e, &client, &whale);
+        if i == 0 {
+            whale_token = token_id;
+        }
+    }
+    let minnow_token = mint_for(&e, &client, &minnow);
+    mint_for(&e, &client, &minnow);
+
+    e.budget().reset_default();
+    client.transfer(&minnow, &recipient, &minnow_token);
+    let small_cost = e.budget().cpu_instruction_cost();
+
+    e.budget().reset_default();
+    client.transfer(&whale, &recipient, &whale_token);
+    let large_cost = e.budget().cpu_instruction_cost();
+
+    // Removing the first of 100 tokens must not scan the owner's list
+    assert!(
+        large_cost < small_cost + small_cost / 4,
+        "transfer cost grew with balance: {} vs {}",
+        large_cost,
+        small_cost
+    );
+}
+
+// ============================================
+// Compliance badges
+// ============================================
+
+#[test]
+fn test_mint_badge() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (admin, client) = setup_contract(&e);
+    client.initialize(&admin);
+    let minter = Address::generate(&e);
+    let owner = Address::generate(&e);
+    client.set_badge_minter(&minter);
+
+    let commitment_id = String::from_str(&e, "commitment_001");
+    let badge_id = client.mint_badge(&owner, &commitment_id, &97, &90);
+
+    let badge = client.get_badge(&badge_id);
+    assert_eq!(badge.owner, owner);
+    assert_eq!(badge.metadata.commitment_id, commitment_id);
+    assert_eq!(badge.metadata.compliance_score, 97);
+    assert_eq!(badge.metadata.duration_days, 90);
+    assert_eq!(client.badge_of_commitment(&commitment_id), Some(badge_id));
+    assert_eq!(client.badges_of_owner(&owner), vec![&e, badge_id]);
+
+    // Badges are kept apart from transferable commitment NFTs
+    assert_eq!(client.balance_of(&owner), 0);
+    assert_eq!(client.total_supply(), 0);
+}
+
+#[test]
+fn test_mint_badge_twice_for_commitment_fails() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (admin, client) = setup_contract(&e);
+    client.initialize(&admin);
+    client.set_badge_minter(&Address::generate(&e));
+    let owner = Address::generate(&e);
+    let commitment_id = String::from_str(&e, "commitment_001");
+    client.mint_badge(&owner, &commitment_id, &97, &90);
+
+    assert_eq!(
+        client.try_mint_badge(&owner, &commitment_id, &97, &90),
+        Err(Ok(ContractError::BadgeAlreadyIssued))
+    );
+}
+
+#[test]
+fn test_mint_badge_requires_minter() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (admin, client) = setup_contract(&e);
+    client.initialize(&admin);
+
+    assert_eq!(
+        client.try_mint_badge(
+            &Address::generate(&e),
+            &String::from_str(&e, "commitment_001"),
+            &97,
+            &90
+        ),
+        Err(Ok(ContractError::NotInitialized))
+    );
+}
+
+// ============================================
+// Duration limits
+// ============================================
+
+#[test]
+fn test_mint_rejects_duration_above_max() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (admin, client) = setup_contract(&e);
+    client.initialize(&admin);
+    let owner = Address::generate(&e);
+    let asset_address = Address::generate(&e);
+    let (commitment_id, _, max_loss, commitment_type, amount, asset, penalty) =
+        create_test_metadata(&e, &asset_address);
+
+    for duration in [TimeUtils::DEFAULT_MAX_DURATION_DAYS + 1, u32::MAX] {
+        let result = client.try_mint(
+            &admin,
+            &owner,
+            &commitment_id,
+            &duration,
+            &max_loss,
+            &commitment_type,
+            &amount,
+            &asset,
+            &penalty,
+        );
+        assert_eq!(result, Err(Ok(ContractError::InvalidDuration)));
+    }
+    assert_eq!(client.total_supply(), 0);
+}
+
+#[test]
+fn test_mint_accepts_one_day_and_max_duration() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (admin, client) = setup_contract(&e);
+    client.initialize(&admin);
+    let owner = Address::generate(&e);
+    let asset_address = Address::generate(&e);
+    let (_, _, max_loss, commitment_type, amount, asset, penalty) =
+        create_test_metadata(&e, &asset_address);
+
+    for (commitment_id, duration) in [
+        ("one_day", 1),
+        ("max_days", TimeUtils::DEFAULT_MAX_DURATION_DAYS),
+    ] {
+        let token_id = client.mint(
+            &admin,
+            &owner,
+            &String::from_str(&e, commitment_id),
+            &duration,
+            &max_loss,
+            &commitment_type,
+            &amount,
+            &asset,
+            &penalty,
+        );
+        let metadata = client.get_metadata(&token_id).metadata;
+        assert_eq!(
+            metadata.expires_at - metadata.created_at,
+            duration as u64 * 86400
+        );
+    }
+    assert_eq!(client.total_supply(), 2);
+}
+
+#[test]
+fn test_mint_rejects_expiration_overflow() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (admin, client) = setup_contract(&e);
+    client.initialize(&admin);
+    // The limit is core's; let the admin stand in for it
+    client.set_core_contract(&admin);
+    client.set_max_duration_days(&u32::MAX);
+    e.ledger().with_mut(|l| l.timestamp = u64::MAX - 10);
+    let owner = Address::generate(&e);
+    let asset_address = Address::generate(&e);
+    let (commitment_id, _, max_loss, commitment_type, amount, asset, penalty) =
+        create_test_metadata(&e, &asset_address);
+
+    let result = client.try_mint(
+        &admin,
+        &owner,
+        &commitment_id,
+        &u32::MAX,
+        &max_loss,
+        &commitment_type,
+        &amount,
+        &asset,
+        &penalty,
+    );
+    assert_eq!(result, Err(Ok(ContractError::InvalidDuration)));
+
+    // The reentrancy guard was released: a valid mint at an early timestamp works
+    e.ledger().with_mut(|l| l.timestamp = 1000);
+    mint_for(&e, &client, &owner);
+}
+
+// ============================================
+// Minter and asset checks
+// ============================================
+
+/// Stand-in for core's supported-asset whitelist
+#[soroban_sdk::contract]
+pub struct MockCoreWhitelist;
+
+#[soroban_sdk::contractimpl]
+impl MockCoreWhitelist {
+    pub fn allow(e: Env, asset: Address) {
+        e.storage().instance().set(&asset, &true);
+    }
+
+    pub fn is_asset_supported(e: Env, asset: Address) -> bool {
+        e.storage().instance().get(&asset).unwrap_or(false)
+    }
+}
+
+fn mint_as(
+    client: &CommitmentNFTContractClient,
+    caller: &Address,
+    owner: &Address,
+    asset: &Address,
+) -> Result<u32, ContractError> {
+    let e = &client.env;
+    client
+        .try_mint(
+            caller,
+            owner,
+            &String::from_str(e, "commitment_001"),
+            &30,
+            &10,
+            &RiskProfile::Balanced,
+            &1000,
+            asset,
+            &5,
+        )
+        .map(|token_id| token_id.unwrap())
+        .map_err(|err| err.unwrap())
+}
+
+#[test]
+fn test_mint_from_core_trusts_asset() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (admin, client) = setup_contract(&e);
+    client.initialize(&admin);
+    let core = e.register_contract(None, MockCoreWhitelist);
+    client.set_core_contract(&core);
+    let owner = Address::generate(&e);
+
+    // Not on the whitelist, but core already validated it
+    let token_id = mint_as(&client, &core, &owner, &Address::generate(&e)).unwrap();
+    assert!(client.token_exists(&token_id));
+}
+
+#[test]
+fn test_authorized_minter_needs_whitelisted_asset() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (admin, client) = setup_contract(&e);
+    client.initialize(&admin);
+    let core = e.register_contract(None, MockCoreWhitelist);
+    let whitelist = MockCoreWhitelistClient::new(&e, &core);
+    client.set_core_contract(&core);
+    let minter = Address::generate(&e);
+    let owner = Address::generate(&e);
+    let listed = Address::generate(&e);
+    whitelist.allow(&listed);
+
+    assert_eq!(
+        mint_as(&client, &minter, &owner, &listed),
+        Err(ContractError::NotAuthorized)
+    );
+    client.set_authorized_minter(&minter, &true);
+    assert!(client.is_authorized_minter(&minter));
+
+    let token_id = mint_as(&client, &minter, &owner, &listed).unwrap();
+    assert_eq!(client.get_metadata(&token_id).metadata.asset_address, listed);
+    assert_eq!(
+        mint_as(&client, &minter, &owner, &Address::generate(&e)),
+        Err(ContractError::AssetNotSupported)
+    );
+    assert_eq!(client.total_supply(), 1);
+
+    client.set_authorized_minter(&minter, &false);
+    assert_eq!(
+        mint_as(&client, &minter, &owner, &listed),
+        Err(ContractError::NotAuthorized)
+    );
+}
+
+#[test]
+fn test_mint_rejects_owner_or_self_as_asset() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (admin, client) = setup_contract(&e);
+    client.initialize(&admin);
+    let owner = Address::generate(&e);
+
+    assert_eq!(
+        mint_as(&client, &admin, &owner, &owner),
+        Err(ContractError::InvalidAsset)
+    );
+    assert_eq!(
+        mint_as(&client, &admin, &owner, &client.address),
+        Err(ContractError::InvalidAsset)
+    );
+    // Without a core contract there is no whitelist to check
+    assert!(mint_as(&client, &admin, &owner, &Address::generate(&e)).is_ok());
+}
+
+// ============================================
+// Interface crate drift
+// ============================================
+
+#[test]
+fn test_interface_crate_matches_nft_abi() {
+    use commitlabs_interfaces::nft as iface;
+    use std::format;
+
+    for code in 1..=19u32 {
+        let error = soroban_sdk::Error::from_contract_error(code);
+        let nft_error = ContractError::try_from(error).expect("nft error code");
+        let iface_error = iface::ContractError::try_from(error).expect("interface error code");
+        assert_eq!(format!("{:?}", nft_error), format!("{:?}", iface_error));
+    }
+    assert!(ContractError::try_from(soroban_sdk::Error::from_contract_error(20)).is_err());
+
+    // The typed client mints and reads tokens on the real contract
+    let e = Env::default();
+    e.mock_all_auths();
+    let (admin, client) = setup_contract(&e);
+    client.initialize(&admin);
+    let nft = iface::CommitmentNftClient::new(&e, &client.address);
+    let owner = Address::generate(&e);
+    let token_id = nft.mint(
+        &admin,
+        &owner,
+        &String::from_str(&e, "c_0"),
+        &30,
+        &10,
+        &RiskProfile::Safe,
+        &1000,
+        &Address::generate(&e),
+        &5,
+    );
+    let nft_data = nft.get_metadata(&token_id);
+    assert_eq!(nft_data.owner, owner);
+    assert_eq!(nft_data.early_exit_penalty, 5);
+    assert_eq!(nft.owner_of(&token_id), owner);
+}
+
+// ============================================
+// Global token id pages
+// ============================================
+
+#[test]
+fn test_token_id_pages_cross_boundaries() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.budget().reset_unlimited();
+    let (admin, client) = setup_contract(&e);
+    client.initialize(&admin);
+    let owner = Address::generate(&e);
+    let mut minted = Vec::new(&e);
+    for _ in 0..205 {
+        minted.push_back(mint_for(&e, &client, &owner));
+    }
+
+    assert_eq!(client.get_token_id_count(), 205);
+    e.as_contract(&client.address, || {
+        let page_len = |page: u32| {
+            e.storage()
+                .persistent()
+                .get::<_, Vec<u32>>(&DataKey::TokenIdPage(page))
+                .map(|ids| ids.len())
+        };
+        assert_eq!(page_len(0), Some(TOKEN_ID_PAGE_SIZE));
+        assert_eq!(page_len(1), Some(TOKEN_ID_PAGE_SIZE));
+        assert_eq!(page_len(2), Some(5));
+        assert_eq!(page_len(3), None);
+    });
+
+    // A window spanning pages 0 and 1 keeps mint order
+    let window = client.get_all_metadata_page(&95, &10);
+    assert_eq!(window.len(), 10);
+    for (i, nft) in window.iter().enumerate() {
+        assert_eq!(nft.token_id, minted.get(95 + i as u32).unwrap());
+    }
+    assert_eq!(client.get_all_metadata_page(&200, &50).len(), 5);
+    assert_eq!(client.get_all_metadata_page(&205, &10).len(), 0);
+    assert_eq!(client.get_all_metadata().len(), 205);
+}
+
+#[test]
+fn test_migrate_legacy_token_ids_into_pages() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (admin, client) = setup_contract(&e);
+    client.initialize(&admin);
+    e.as_contract(&client.address, || {
+        let mut legacy = Vec::new(&e);
+        for token_id in 1000..1250u32 {
+            legacy.push_back(token_id);
+        }
+        e.storage().instance().set(&DataKey::TokenIds, &legacy);
+    });
+    assert_eq!(client.get_token_id_count(), 250);
+
+    assert_eq!(client.migrate_token_ids(), 250);
+    assert_eq!(client.migrate_token_ids(), 0);
+    assert_eq!(client.get_token_id_count(), 250);
+    e.as_contract(&client.address, || {
+        assert!(!e.storage().instance().has(&DataKey::TokenIds));
+        let page = |page: u32| {
+            e.storage()
+                .persistent()
+                .get::<_, Vec<u32>>(&DataKey::TokenIdPage(page))
+                .unwrap()
+        };
+        assert_eq!(page(0).len(), 100);
+        assert_eq!(page(0).get(0), Some(1000));
+        assert_eq!(page(1).get(0), Some(1100));
+        assert_eq!(page(2).len(), 50);
+        assert_eq!(page(2).last(), Some(1249));
+    });
+
+    // New mints continue on the last page
+    let token_id = mint_for(&e, &client, &Address::generate(&e));
+    assert_eq!(client.get_token_id_count(), 251);
+    let last = client.get_all_metadata_page(&250, &1);
+    assert_eq!(last.get(0).unwrap().token_id, token_id);
+}
+
+#[test]
+fn test_mint_cost_flat_with_supply() {
+    // Cost of one mint on a fresh contract whose token list already holds
+    // `supply` ids, so test-env overhead is the same for every run
+    let mint_cost = |supply: u32| {
+        let e = Env::default();
+        e.mock_all_auths();
+        let (admin, client) = setup_contract(&e);
+        client.initialize(&admin);
+        e.as_contract(&client.address, || {
+            let mut last_page = Vec::new(&e);
+            for token_id in supply / TOKEN_ID_PAGE_SIZE * TOKEN_ID_PAGE_SIZE..supply {
+                last_page.push_back(token_id);
+            }
+            let storage = e.storage().persistent();
+            storage.set(&DataKey::TokenIdPage(supply / TOKEN_ID_PAGE_SIZE), &last_page);
+            storage.set(&DataKey::TokenIdCount, &supply);
+            e.storage().instance().set(&DataKey::TokenCounter, &supply);
+        });
+        let owner = Address::generate(&e);
+        e.budget().reset_unlimited();
+        mint_for(&e, &client, &owner);
+        e.budget().cpu_instruction_cost()
+    };
+
+    let small = mint_cost(50);
+    let large = mint_cost(1_000_050);
+    // Only the last page is rewritten, so cost does not track supply
+    assert!(large < small + small / 20, "small {small}, large {large}");
+}
+
+#[test]
+fn test_two_step_admin_transfer() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (admin, client) = setup_contract(&e);
+    client.initialize(&admin);
+    let new_admin = Address::generate(&e);
+    let stranger = Address::generate(&e);
+    assert_eq!(
+        client.try_accept_admin(&new_admin),
+        Err(Ok(ContractError::NoPendingAdmin))
+    );
+
+    client.propose_admin(&admin, &new_admin);
+    assert_eq!(client.get_pending_admin(), Some(new_admin.clone()));
+    assert_eq!(
+        client.try_accept_admin(&stranger),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+    assert_eq!(client.get_admin(), admin);
+
+    client.accept_admin(&new_admin);
+    assert_eq!(client.get_admin(), new_admin);
+    assert_eq!(client.get_pending_admin(), None);
+    assert_eq!(
+        client.try_propose_admin(&admin, &stranger),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+}
+
+#[test]
+fn test_cancel_admin_proposal() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (admin, client) = setup_contract(&e);
+    client.initialize(&admin);
+    let new_admin = Address::generate(&e);
+    client.propose_admin(&admin, &new_admin);
+
+    client.cancel_admin_proposal(&admin);
+    assert_eq!(client.get_pending_admin(), None);
+    assert_eq!(
+        client.try_accept_admin(&new_admin),
+        Err(Ok(ContractError::NoPendingAdmin))
+    );
+    assert_eq!(
+        client.try_cancel_admin_proposal(&admin),
+        Err(Ok(ContractError::NoPendingAdmin))
+    );
+}